@@ -0,0 +1,5 @@
+-- Leaves the vector extension installed; other schemas may use it.
+DROP TABLE IF EXISTS web_memory;
+DROP TABLE IF EXISTS signals;
+DROP TABLE IF EXISTS agents;
+DROP TABLE IF EXISTS webs;
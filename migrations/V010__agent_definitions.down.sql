@@ -0,0 +1,2 @@
+ALTER TABLE agents DROP COLUMN IF EXISTS definition_id;
+DROP TABLE IF EXISTS agent_definitions;
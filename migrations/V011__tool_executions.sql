@@ -0,0 +1,14 @@
+-- Audit log of tool calls made by agents
+CREATE TABLE tool_executions (
+    id UUID PRIMARY KEY,
+    agent_id UUID NOT NULL REFERENCES agents(id),
+    web_id UUID NOT NULL REFERENCES webs(id),
+    tool_type VARCHAR(50) NOT NULL,
+    params JSONB NOT NULL,
+    output JSONB NOT NULL,
+    success BOOLEAN NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_tool_executions_agent_id ON tool_executions(agent_id, created_at);
+CREATE INDEX idx_tool_executions_web_id ON tool_executions(web_id);
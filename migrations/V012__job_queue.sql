@@ -0,0 +1,17 @@
+-- Durable queue of web runs shared by serve workers
+CREATE TABLE job_queue (
+    id UUID PRIMARY KEY,
+    web_id UUID NOT NULL REFERENCES webs(id),
+    status VARCHAR(20) NOT NULL DEFAULT 'queued',
+    attempts INTEGER NOT NULL DEFAULT 0,
+    claimed_by VARCHAR(255),
+    -- When a queued job becomes claimable, or when a claim's lease expires
+    visible_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    last_error TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_job_queue_claimable ON job_queue(visible_at, created_at)
+    WHERE status IN ('queued', 'claimed');
+CREATE INDEX idx_job_queue_web_id ON job_queue(web_id);
@@ -0,0 +1,5 @@
+DROP INDEX IF EXISTS idx_job_queue_owner;
+ALTER TABLE job_queue DROP COLUMN IF EXISTS owner;
+ALTER TABLE job_queue DROP COLUMN IF EXISTS priority;
+ALTER TABLE webs DROP COLUMN IF EXISTS metadata;
+ALTER TABLE webs DROP COLUMN IF EXISTS priority;
@@ -0,0 +1,9 @@
+-- Scheduling priority and owner labels for webs
+ALTER TABLE webs ADD COLUMN priority INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE webs ADD COLUMN metadata JSONB NOT NULL DEFAULT '{}';
+
+-- Copied onto queued runs so claims can order and cap without a join
+ALTER TABLE job_queue ADD COLUMN priority INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE job_queue ADD COLUMN owner VARCHAR(255);
+
+CREATE INDEX idx_job_queue_owner ON job_queue(owner) WHERE status = 'claimed';
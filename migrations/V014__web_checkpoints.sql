@@ -0,0 +1,8 @@
+-- Latest snapshot of each web's in-flight state, for resuming after a crash.
+-- No foreign key: webs run on in-memory storage are only persisted here.
+CREATE TABLE web_checkpoints (
+    web_id UUID PRIMARY KEY,
+    iteration BIGINT NOT NULL,
+    snapshot JSONB NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
@@ -0,0 +1,7 @@
+-- Run analytics of finished webs, written once the coordination loop ends.
+-- No foreign key, for the same reason as web_checkpoints.
+CREATE TABLE web_analytics (
+    web_id UUID PRIMARY KEY,
+    analytics JSONB NOT NULL,
+    completed_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
@@ -0,0 +1,8 @@
+-- Back to the ivfflat indexes of V001 and V010.
+DROP INDEX IF EXISTS idx_agents_tuning;
+DROP INDEX IF EXISTS idx_definitions_tuning;
+
+CREATE INDEX idx_agents_tuning ON agents
+    USING ivfflat (tuning vector_cosine_ops) WITH (lists = 100);
+CREATE INDEX idx_definitions_tuning ON agent_definitions
+    USING ivfflat (tuning_embedding vector_cosine_ops) WITH (lists = 50);
@@ -0,0 +1,22 @@
+-- Replace the ivfflat indexes with HNSW.
+--
+-- ivfflat clusters the rows present when the index is built into `lists`
+-- buckets. Ours were built on empty tables, so every row lands in arbitrary
+-- buckets and recall is poor until someone runs REINDEX. HNSW needs no
+-- training data and gives better recall at the same speed, at the cost of
+-- slower inserts and a larger index. Needs pgvector 0.5.0 or newer.
+--
+-- m and ef_construction are pgvector's defaults: raise them for better
+-- recall on large tables, at the cost of build time and memory. Query-time
+-- accuracy is set with ARACHNID_HNSW_EF_SEARCH (see VectorSearchConfig).
+--
+-- Only `ORDER BY <column> <=> $1 LIMIT n` queries can use these indexes.
+-- find_definitions_by_similarity is written that way; agent lookups are
+-- scoped to one web and served by idx_agents_web_id instead.
+DROP INDEX IF EXISTS idx_agents_tuning;
+DROP INDEX IF EXISTS idx_definitions_tuning;
+
+CREATE INDEX idx_agents_tuning ON agents
+    USING hnsw (tuning vector_cosine_ops) WITH (m = 16, ef_construction = 64);
+CREATE INDEX idx_definitions_tuning ON agent_definitions
+    USING hnsw (tuning_embedding vector_cosine_ops) WITH (m = 16, ef_construction = 64);
@@ -0,0 +1,2 @@
+-- Soft-deleted definitions become visible again.
+ALTER TABLE agent_definitions DROP COLUMN IF EXISTS deleted_at;
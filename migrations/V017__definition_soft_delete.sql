@@ -0,0 +1,3 @@
+-- Soft-delete definitions: agents keep their definition_id reference, but
+-- deleted definitions are left out of listings and matching.
+ALTER TABLE agent_definitions ADD COLUMN deleted_at TIMESTAMPTZ;
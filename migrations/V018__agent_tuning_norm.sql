@@ -0,0 +1,3 @@
+-- Cached magnitude of agents.tuning, so resonance checks skip recomputing it.
+-- Left NULL for existing rows; readers compute it from the tuning instead.
+ALTER TABLE agents ADD COLUMN tuning_norm REAL;
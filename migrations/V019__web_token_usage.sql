@@ -0,0 +1,9 @@
+-- Running LLM token totals per web, added to after every signal.
+-- No foreign key, for the same reason as web_checkpoints.
+CREATE TABLE web_token_usage (
+    web_id UUID PRIMARY KEY,
+    input_tokens BIGINT NOT NULL DEFAULT 0,
+    output_tokens BIGINT NOT NULL DEFAULT 0,
+    cost_usd DOUBLE PRECISION NOT NULL DEFAULT 0,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
@@ -0,0 +1 @@
+ALTER TABLE agent_definitions DROP COLUMN IF EXISTS max_tokens;
@@ -0,0 +1,2 @@
+-- Per-definition cap on response length; NULL uses the provider default.
+ALTER TABLE agent_definitions ADD COLUMN max_tokens INTEGER;
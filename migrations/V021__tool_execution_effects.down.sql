@@ -0,0 +1,3 @@
+ALTER TABLE tool_executions
+    DROP COLUMN IF EXISTS artifacts,
+    DROP COLUMN IF EXISTS side_effects;
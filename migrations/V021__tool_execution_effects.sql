@@ -0,0 +1,4 @@
+-- Summaries of the artifacts and side effects of each tool call.
+ALTER TABLE tool_executions
+    ADD COLUMN artifacts JSONB NOT NULL DEFAULT '[]',
+    ADD COLUMN side_effects JSONB NOT NULL DEFAULT '[]';
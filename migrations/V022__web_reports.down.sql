@@ -0,0 +1 @@
+ALTER TABLE webs DROP COLUMN IF EXISTS report;
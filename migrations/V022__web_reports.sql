@@ -0,0 +1,2 @@
+-- Final report synthesized when a web converges.
+ALTER TABLE webs ADD COLUMN report JSONB;
@@ -0,0 +1,92 @@
+use axum::http::{HeaderName, HeaderValue, Method};
+use tower_http::cors::{AllowHeaders, AllowMethods, AllowOrigin, CorsLayer};
+
+/// Cross-origin policy for the API.
+///
+/// Nothing is allowed cross-origin unless origins are configured; the fully
+/// permissive policy has to be opted into with `ARACHNID_CORS=*`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CorsConfig {
+    pub permissive: bool,
+    pub allowed_origins: Vec<String>,
+    /// Empty means the default set (GET, POST, PATCH, DELETE).
+    pub allowed_methods: Vec<String>,
+    /// Empty means just `content-type`.
+    pub allowed_headers: Vec<String>,
+}
+
+impl CorsConfig {
+    /// Reads `ARACHNID_CORS` (`*` or comma-separated origins),
+    /// `ARACHNID_CORS_METHODS` and `ARACHNID_CORS_HEADERS`.
+    pub fn from_env() -> Self {
+        let origins = std::env::var("ARACHNID_CORS").unwrap_or_default();
+        let list = |var: &str| split_list(&std::env::var(var).unwrap_or_default());
+
+        Self {
+            permissive: origins.trim() == "*",
+            allowed_origins: split_list(&origins)
+                .into_iter()
+                .filter(|o| o != "*")
+                .collect(),
+            allowed_methods: list("ARACHNID_CORS_METHODS"),
+            allowed_headers: list("ARACHNID_CORS_HEADERS"),
+        }
+    }
+
+    pub fn layer(&self) -> CorsLayer {
+        if self.permissive {
+            return CorsLayer::permissive();
+        }
+
+        let origins: Vec<HeaderValue> = self
+            .allowed_origins
+            .iter()
+            .filter_map(|o| HeaderValue::from_str(o).ok())
+            .collect();
+
+        let methods: Vec<Method> = if self.allowed_methods.is_empty() {
+            vec![Method::GET, Method::POST, Method::PATCH, Method::DELETE]
+        } else {
+            self.allowed_methods
+                .iter()
+                .filter_map(|m| Method::from_bytes(m.to_uppercase().as_bytes()).ok())
+                .collect()
+        };
+
+        let headers: Vec<HeaderName> = if self.allowed_headers.is_empty() {
+            vec![axum::http::header::CONTENT_TYPE]
+        } else {
+            self.allowed_headers
+                .iter()
+                .filter_map(|h| HeaderName::from_bytes(h.as_bytes()).ok())
+                .collect()
+        };
+
+        CorsLayer::new()
+            .allow_origin(AllowOrigin::list(origins))
+            .allow_methods(AllowMethods::list(methods))
+            .allow_headers(AllowHeaders::list(headers))
+    }
+}
+
+fn split_list(value: &str) -> Vec<String> {
+    value
+        .split(',')
+        .map(|s| s.trim().to_string())
+        .filter(|s| !s.is_empty())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_list() {
+        assert_eq!(
+            split_list(" https://a.example , ,https://b.example"),
+            vec!["https://a.example", "https://b.example"]
+        );
+        assert!(split_list("").is_empty());
+    }
+}
@@ -1,20 +1,64 @@
 use axum::{
-    extract::{Path, Query, State},
+    extract::{rejection::JsonRejection, Path, Query, State},
+    http::{HeaderMap, StatusCode},
     response::sse::{Event, KeepAlive, Sse},
     Json,
 };
 use futures::stream::Stream;
 use serde::{Deserialize, Serialize};
-use std::{convert::Infallible, sync::Arc, time::Duration};
+use std::{
+    collections::{HashMap, HashSet},
+    convert::Infallible,
+    sync::Arc,
+    time::Duration,
+};
+use tokio::sync::broadcast;
 use uuid::Uuid;
 
-use crate::api::error::ApiError;
+use crate::api::error::{ApiError, FieldError};
+use crate::api::server::{AppState, SharedConfig};
+use crate::definitions::DefinitionSource;
+use crate::engine::events::EventBus;
+use crate::engine::metrics::{MetricsRegistry, WebMetrics};
+use crate::lifecycle::{AgentStateMachine, LifecycleEvent};
+use crate::providers::usage::Usage;
+use crate::storage::traits::{ToolExecution, WebAnalytics};
 use crate::storage::Storage;
-use crate::types::{Agent, AgentContext, Signal, Web, WebConfig, WebState};
+use crate::types::{
+    Agent, AgentContext, ContextItem, FinalReport, Signal, Web, WebConfig, WebState,
+};
 
 #[derive(Deserialize)]
 pub struct CreateWebRequest {
     pub task: String,
+    /// Higher runs first when webs are queued.
+    #[serde(default)]
+    pub priority: i32,
+    /// Free-form labels; `owner` caps how many webs one owner runs at once.
+    #[serde(default)]
+    pub metadata: HashMap<String, String>,
+}
+
+impl CreateWebRequest {
+    const MAX_TASK_LEN: usize = 10_000;
+
+    fn validate(&self) -> Result<(), ApiError> {
+        let mut errors = Vec::new();
+        if self.task.trim().is_empty() {
+            errors.push(FieldError::new("task", "must not be empty"));
+        } else if self.task.len() > Self::MAX_TASK_LEN {
+            errors.push(FieldError::new(
+                "task",
+                format!("must be at most {} characters", Self::MAX_TASK_LEN),
+            ));
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(ApiError::Validation(errors))
+        }
+    }
 }
 
 #[derive(Serialize)]
@@ -23,6 +67,8 @@ pub struct WebResponse {
     pub task: String,
     pub state: String,
     pub root_agent_id: String,
+    pub priority: i32,
+    pub metadata: HashMap<String, String>,
 }
 
 impl From<Web> for WebResponse {
@@ -32,6 +78,8 @@ impl From<Web> for WebResponse {
             task: web.task,
             state: format!("{:?}", web.state),
             root_agent_id: web.root_agent.to_string(),
+            priority: web.priority,
+            metadata: web.metadata,
         }
     }
 }
@@ -44,6 +92,8 @@ pub struct WebDetailResponse {
     pub root_agent_id: String,
     pub agent_count: usize,
     pub pending_signal_count: usize,
+    /// LLM tokens (and dollars, for priced models) spent so far.
+    pub usage: Usage,
 }
 
 #[derive(Serialize)]
@@ -112,17 +162,71 @@ impl From<Signal> for SignalResponse {
     }
 }
 
+#[derive(Serialize)]
+pub struct ToolExecutionResponse {
+    pub id: String,
+    pub tool: String,
+    pub params: serde_json::Value,
+    pub output: serde_json::Value,
+    pub success: bool,
+    pub artifacts: Vec<serde_json::Value>,
+    pub side_effects: Vec<serde_json::Value>,
+    pub created_at: String,
+}
+
+impl From<ToolExecution> for ToolExecutionResponse {
+    fn from(execution: ToolExecution) -> Self {
+        Self {
+            id: execution.id.to_string(),
+            tool: execution.tool_type.as_str().to_string(),
+            params: execution.params,
+            output: execution.output,
+            success: execution.success,
+            artifacts: execution.artifacts,
+            side_effects: execution.side_effects,
+            created_at: execution.created_at.to_rfc3339(),
+        }
+    }
+}
+
 #[derive(Serialize)]
 pub struct WebResultsResponse {
     pub web_id: String,
     pub state: String,
     pub accumulated_knowledge: Vec<KnowledgeItem>,
+    /// Set once the web has converged.
+    pub report: Option<FinalReport>,
 }
 
 #[derive(Serialize)]
 pub struct KnowledgeItem {
     pub source_agent: String,
     pub content: String,
+    /// Agent holding the item, with its capability. Only set on results
+    /// gathered with `?include=all`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub agent_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub capability: Option<String>,
+}
+
+impl From<ContextItem> for KnowledgeItem {
+    fn from(item: ContextItem) -> Self {
+        Self {
+            source_agent: item.source_agent.to_string(),
+            content: item.content,
+            agent_id: None,
+            capability: None,
+        }
+    }
+}
+
+impl KnowledgeItem {
+    fn held_by(mut self, agent: &Agent) -> Self {
+        self.agent_id = Some(agent.id.to_string());
+        self.capability = Some(format!("{:?}", agent.capability));
+        self
+    }
 }
 
 #[derive(Serialize)]
@@ -138,34 +242,218 @@ impl From<AgentContext> for ContextResponse {
             accumulated_knowledge: ctx
                 .accumulated_knowledge
                 .into_iter()
-                .map(|item| KnowledgeItem {
-                    source_agent: item.source_agent.to_string(),
-                    content: item.content,
-                })
+                .map(KnowledgeItem::from)
                 .collect(),
         }
     }
 }
 
+#[derive(Serialize)]
+pub struct AgentTreeNode {
+    pub id: String,
+    pub purpose: String,
+    pub capability: String,
+    pub state: String,
+    pub health: f32,
+    pub children: Vec<AgentTreeNode>,
+}
+
+impl AgentTreeNode {
+    fn build(
+        agent: &Agent,
+        children_of: &HashMap<Uuid, Vec<&Agent>>,
+        remaining_depth: Option<usize>,
+    ) -> Self {
+        let children = match remaining_depth {
+            Some(0) => Vec::new(),
+            _ => children_of
+                .get(&agent.id)
+                .map(|children| {
+                    children
+                        .iter()
+                        .map(|child| {
+                            Self::build(child, children_of, remaining_depth.map(|d| d - 1))
+                        })
+                        .collect()
+                })
+                .unwrap_or_default(),
+        };
+
+        Self {
+            id: agent.id.to_string(),
+            purpose: agent.purpose.clone(),
+            capability: format!("{:?}", agent.capability),
+            state: format!("{:?}", agent.state),
+            health: agent.health,
+            children,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct UpdateWebConfigRequest {
+    pub attenuation_factor: Option<f32>,
+    pub min_amplitude: Option<f32>,
+    pub default_threshold: Option<f32>,
+    pub max_agents: Option<usize>,
+    pub max_depth: Option<usize>,
+    pub idle_timeout_secs: Option<u64>,
+    pub dormant_ttl_secs: Option<u64>,
+}
+
+impl UpdateWebConfigRequest {
+    fn validate(&self) -> Result<(), ApiError> {
+        let mut errors = Vec::new();
+
+        let unit_range = [
+            ("attenuation_factor", self.attenuation_factor),
+            ("min_amplitude", self.min_amplitude),
+            ("default_threshold", self.default_threshold),
+        ];
+        for (field, value) in unit_range {
+            if let Some(v) = value {
+                if !(0.0..=1.0).contains(&v) {
+                    errors.push(FieldError::new(field, "must be between 0.0 and 1.0"));
+                }
+            }
+        }
+        if self.attenuation_factor == Some(0.0) {
+            errors.push(FieldError::new(
+                "attenuation_factor",
+                "must be greater than 0.0",
+            ));
+        }
+        for (field, value) in [
+            ("max_agents", self.max_agents),
+            ("max_depth", self.max_depth),
+        ] {
+            if value == Some(0) {
+                errors.push(FieldError::new(field, "must be at least 1"));
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(ApiError::Validation(errors))
+        }
+    }
+
+    fn apply(&self, config: &mut WebConfig) {
+        if let Some(v) = self.attenuation_factor {
+            config.attenuation_factor = v;
+        }
+        if let Some(v) = self.min_amplitude {
+            config.min_amplitude = v;
+        }
+        if let Some(v) = self.default_threshold {
+            config.default_threshold = v;
+        }
+        if let Some(v) = self.max_agents {
+            config.max_agents = v;
+        }
+        if let Some(v) = self.max_depth {
+            config.max_depth = v;
+        }
+        if let Some(v) = self.idle_timeout_secs {
+            config.idle_timeout_secs = v;
+        }
+        if let Some(v) = self.dormant_ttl_secs {
+            config.dormant_ttl_secs = v;
+        }
+    }
+}
+
+#[derive(Deserialize)]
+pub struct TreeQuery {
+    pub depth: Option<usize>,
+}
+
+#[derive(Deserialize)]
+pub struct ResultsQuery {
+    /// `root` (the default) for the root agent's knowledge, or `all` to
+    /// gather it from every agent in the web.
+    pub include: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct EventsQuery {
+    /// Only stream events of this agent and its descendants.
+    pub agent: Option<Uuid>,
+}
+
 #[derive(Deserialize)]
 pub struct ListWebsQuery {
     pub state: Option<String>,
     pub limit: Option<usize>,
 }
 
-pub async fn health_check() -> Json<serde_json::Value> {
+/// How long `/health` waits for storage before reporting it unreachable.
+const HEALTH_PING_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Readiness: healthy only while storage answers, `503` otherwise.
+pub async fn health_check(
+    State(storage): State<Arc<dyn Storage>>,
+) -> (StatusCode, Json<serde_json::Value>) {
+    let storage_error = match tokio::time::timeout(HEALTH_PING_TIMEOUT, storage.ping()).await {
+        Ok(Ok(())) => None,
+        Ok(Err(e)) => Some(format!("{:#}", e)),
+        Err(_) => Some(format!(
+            "no response within {}s",
+            HEALTH_PING_TIMEOUT.as_secs()
+        )),
+    };
+
+    match storage_error {
+        None => (
+            StatusCode::OK,
+            Json(serde_json::json!({
+                "status": "healthy",
+                "version": env!("CARGO_PKG_VERSION"),
+                "storage": { "status": "ok" },
+            })),
+        ),
+        Some(error) => (
+            StatusCode::SERVICE_UNAVAILABLE,
+            Json(serde_json::json!({
+                "status": "unhealthy",
+                "version": env!("CARGO_PKG_VERSION"),
+                "storage": { "status": "unreachable", "error": error },
+            })),
+        ),
+    }
+}
+
+/// Liveness: the process is serving requests, whatever the state of its
+/// dependencies.
+pub async fn liveness_check() -> Json<serde_json::Value> {
     Json(serde_json::json!({
-        "status": "healthy",
+        "status": "alive",
         "version": env!("CARGO_PKG_VERSION"),
     }))
 }
 
+/// Work counters summed over every web this server has run.
+pub async fn get_metrics(State(metrics): State<MetricsRegistry>) -> Json<WebMetrics> {
+    Json(metrics.total())
+}
+
 pub async fn create_web(
     State(storage): State<Arc<dyn Storage>>,
-    Json(request): Json<CreateWebRequest>,
+    State(config): State<SharedConfig>,
+    payload: Result<Json<CreateWebRequest>, JsonRejection>,
 ) -> Result<Json<WebResponse>, ApiError> {
+    let Json(request) = payload?;
+    request.validate()?;
+
+    // The web keeps this snapshot; later config reloads don't affect it.
+    let web_config = config.read().unwrap().web_config();
+
     let root_agent_id = uuid::Uuid::new_v4();
-    let web = Web::new(root_agent_id, request.task, WebConfig::default());
+    let web = Web::new(root_agent_id, request.task, web_config)
+        .with_priority(request.priority)
+        .with_metadata(request.metadata);
 
     storage.create_web(&web).await?;
 
@@ -208,6 +496,7 @@ pub async fn get_web(
 
     let agents = storage.get_web_agents(id).await?;
     let signals = storage.get_pending_signals(id).await?;
+    let usage = storage.get_token_usage(id).await?;
 
     Ok(Json(WebDetailResponse {
         id: web.id.to_string(),
@@ -216,38 +505,174 @@ pub async fn get_web(
         root_agent_id: web.root_agent.to_string(),
         agent_count: agents.len(),
         pending_signal_count: signals.len(),
+        usage,
     }))
 }
 
 pub async fn get_web_results(
     State(storage): State<Arc<dyn Storage>>,
     Path(id): Path<Uuid>,
+    Query(query): Query<ResultsQuery>,
 ) -> Result<Json<WebResultsResponse>, ApiError> {
+    let include_all = match query.include.as_deref() {
+        None | Some("root") => false,
+        Some("all") => true,
+        Some(other) => {
+            return Err(ApiError::BadRequest(format!(
+                "include must be 'root' or 'all', got '{}'",
+                other
+            )))
+        }
+    };
+
     let web = storage
         .get_web(id)
         .await?
         .ok_or_else(|| ApiError::NotFound(format!("Web {} not found", id)))?;
 
-    let root_agent = storage
-        .get_agent(web.root_agent)
-        .await?
-        .ok_or_else(|| ApiError::NotFound("Root agent not found".to_string()))?;
+    let accumulated_knowledge = if include_all {
+        gather_web_knowledge(storage.get_web_agents(id).await?, web.root_agent)
+    } else {
+        let root_agent = storage
+            .get_agent(web.root_agent)
+            .await?
+            .ok_or_else(|| ApiError::NotFound("Root agent not found".to_string()))?;
+        root_agent
+            .context
+            .accumulated_knowledge
+            .into_iter()
+            .map(KnowledgeItem::from)
+            .collect()
+    };
 
     Ok(Json(WebResultsResponse {
         web_id: web.id.to_string(),
         state: format!("{:?}", web.state),
-        accumulated_knowledge: root_agent
-            .context
-            .accumulated_knowledge
-            .into_iter()
-            .map(|item| KnowledgeItem {
-                source_agent: item.source_agent.to_string(),
-                content: item.content,
-            })
-            .collect(),
+        accumulated_knowledge,
+        report: web.report,
     }))
 }
 
+/// Knowledge held by every agent, root first and then oldest first. Items
+/// that propagated upward are held several times; only the first holder
+/// is kept.
+fn gather_web_knowledge(mut agents: Vec<Agent>, root: Uuid) -> Vec<KnowledgeItem> {
+    agents.sort_by_key(|a| (a.id != root, a.created_at, a.id));
+
+    let mut seen = HashSet::new();
+    let mut items = Vec::new();
+    for agent in &agents {
+        for item in &agent.context.accumulated_knowledge {
+            if seen.insert(item.content.trim().to_string()) {
+                items.push(KnowledgeItem::from(item.clone()).held_by(agent));
+            }
+        }
+    }
+    items
+}
+
+pub async fn get_web_tree(
+    State(storage): State<Arc<dyn Storage>>,
+    Path(id): Path<Uuid>,
+    Query(query): Query<TreeQuery>,
+) -> Result<Json<AgentTreeNode>, ApiError> {
+    let web = storage
+        .get_web(id)
+        .await?
+        .ok_or_else(|| ApiError::NotFound(format!("Web {} not found", id)))?;
+
+    let mut agents = storage.get_web_agents(id).await?;
+    agents.sort_by_key(|a| (a.created_at, a.id));
+
+    let mut children_of: HashMap<Uuid, Vec<&Agent>> = HashMap::new();
+    for agent in &agents {
+        if let Some(parent_id) = agent.parent_id {
+            children_of.entry(parent_id).or_default().push(agent);
+        }
+    }
+
+    let root = agents
+        .iter()
+        .find(|a| a.id == web.root_agent)
+        .ok_or_else(|| ApiError::NotFound("Root agent not found".to_string()))?;
+
+    Ok(Json(AgentTreeNode::build(root, &children_of, query.depth)))
+}
+
+pub async fn get_web_config(
+    State(storage): State<Arc<dyn Storage>>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<WebConfig>, ApiError> {
+    let web = storage
+        .get_web(id)
+        .await?
+        .ok_or_else(|| ApiError::NotFound(format!("Web {} not found", id)))?;
+
+    Ok(Json(web.config))
+}
+
+/// Available once the web has finished running.
+pub async fn get_web_analytics(
+    State(storage): State<Arc<dyn Storage>>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<WebAnalytics>, ApiError> {
+    storage
+        .get_web(id)
+        .await?
+        .ok_or_else(|| ApiError::NotFound(format!("Web {} not found", id)))?;
+
+    let analytics = storage
+        .get_web_analytics(id)
+        .await?
+        .ok_or_else(|| ApiError::NotFound(format!("Web {} has no analytics yet", id)))?;
+    Ok(Json(analytics))
+}
+
+pub async fn get_web_metrics(
+    State(storage): State<Arc<dyn Storage>>,
+    State(metrics): State<MetricsRegistry>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<WebMetrics>, ApiError> {
+    storage
+        .get_web(id)
+        .await?
+        .ok_or_else(|| ApiError::NotFound(format!("Web {} not found", id)))?;
+
+    Ok(Json(metrics.web(&id)))
+}
+
+pub async fn update_web_config(
+    State(storage): State<Arc<dyn Storage>>,
+    Path(id): Path<Uuid>,
+    payload: Result<Json<UpdateWebConfigRequest>, JsonRejection>,
+) -> Result<Json<WebConfig>, ApiError> {
+    let Json(request) = payload?;
+    request.validate()?;
+
+    let mut web = storage
+        .get_web(id)
+        .await?
+        .ok_or_else(|| ApiError::NotFound(format!("Web {} not found", id)))?;
+
+    if web.state.is_terminal() {
+        return Err(ApiError::BadRequest(format!(
+            "Web {} is {:?}; its config can no longer be changed",
+            id, web.state
+        )));
+    }
+
+    request.apply(&mut web.config);
+    // Only the config column, so a run finishing meanwhile keeps its state.
+    if !storage.update_web_config(id, &web.config).await? {
+        return Err(ApiError::BadRequest(format!(
+            "Web {} finished; its config can no longer be changed",
+            id
+        )));
+    }
+
+    Ok(Json(web.config))
+}
+
 pub async fn get_web_agents(
     State(storage): State<Arc<dyn Storage>>,
     Path(id): Path<Uuid>,
@@ -279,16 +704,47 @@ pub async fn get_web_signals(
 }
 
 pub async fn terminate_web(
-    State(storage): State<Arc<dyn Storage>>,
+    State(state): State<AppState>,
     Path(id): Path<Uuid>,
 ) -> Result<Json<WebResponse>, ApiError> {
-    let mut web = storage
+    let mut web = state
+        .storage
         .get_web(id)
         .await?
         .ok_or_else(|| ApiError::NotFound(format!("Web {} not found", id)))?;
 
     web.state = WebState::Failed;
-    storage.update_web(&web).await?;
+    state.storage.update_web(&web).await?;
+
+    if let Some(scheduler) = &state.scheduler {
+        scheduler.cancel_web(&id);
+    }
+
+    Ok(Json(WebResponse::from(web)))
+}
+
+pub async fn run_web(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<WebResponse>, ApiError> {
+    let scheduler = state.scheduler.as_ref().ok_or_else(|| {
+        ApiError::Unavailable("Web execution is not enabled on this server".to_string())
+    })?;
+
+    let web = state
+        .storage
+        .get_web(id)
+        .await?
+        .ok_or_else(|| ApiError::NotFound(format!("Web {} not found", id)))?;
+
+    if web.state.is_terminal() {
+        return Err(ApiError::BadRequest(format!(
+            "Web {} is {:?} and can't be run",
+            id, web.state
+        )));
+    }
+
+    scheduler.start_web(&id).await?;
 
     Ok(Json(WebResponse::from(web)))
 }
@@ -320,6 +776,37 @@ pub async fn get_agent(
     }))
 }
 
+pub async fn pause_agent(
+    State(storage): State<Arc<dyn Storage>>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<AgentResponse>, ApiError> {
+    transition_agent(&*storage, id, LifecycleEvent::Paused).await
+}
+
+pub async fn resume_agent(
+    State(storage): State<Arc<dyn Storage>>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<AgentResponse>, ApiError> {
+    transition_agent(&*storage, id, LifecycleEvent::Resumed).await
+}
+
+async fn transition_agent(
+    storage: &dyn Storage,
+    id: Uuid,
+    event: LifecycleEvent,
+) -> Result<Json<AgentResponse>, ApiError> {
+    let mut agent = storage
+        .get_agent(id)
+        .await?
+        .ok_or_else(|| ApiError::NotFound(format!("Agent {} not found", id)))?;
+
+    AgentStateMachine::transition(&mut agent, event)
+        .map_err(|e| ApiError::BadRequest(e.to_string()))?;
+    storage.update_agent(&agent).await?;
+
+    Ok(Json(AgentResponse::from(agent)))
+}
+
 pub async fn get_agent_context(
     State(storage): State<Arc<dyn Storage>>,
     Path(id): Path<Uuid>,
@@ -332,17 +819,134 @@ pub async fn get_agent_context(
     Ok(Json(ContextResponse::from(agent.context)))
 }
 
+pub async fn get_agent_tool_executions(
+    State(storage): State<Arc<dyn Storage>>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<Vec<ToolExecutionResponse>>, ApiError> {
+    let _agent = storage
+        .get_agent(id)
+        .await?
+        .ok_or_else(|| ApiError::NotFound(format!("Agent {} not found", id)))?;
+
+    let executions = storage.get_tool_executions(id).await?;
+    Ok(Json(executions.into_iter().map(Into::into).collect()))
+}
+
+/// Built-in definitions ship with the binary and can't be deleted.
+pub async fn delete_definition(
+    State(storage): State<Arc<dyn Storage>>,
+    Path(id): Path<Uuid>,
+) -> Result<StatusCode, ApiError> {
+    let definition = storage
+        .get_definition(id)
+        .await?
+        .ok_or_else(|| ApiError::NotFound(format!("Definition {} not found", id)))?;
+    if definition.source == DefinitionSource::BuiltIn {
+        return Err(ApiError::BadRequest(format!(
+            "Definition {} is built in and can't be deleted",
+            definition.name
+        )));
+    }
+
+    if !storage.delete_definition(id).await? {
+        return Err(ApiError::NotFound(format!("Definition {} not found", id)));
+    }
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Position in a web's event stream, encoded into each SSE event id.
+///
+/// Agent spawns are replayable from storage, so the cursor records how many
+/// have been delivered; other events are snapshots and only need counting.
+/// Every event advances exactly one of the two counts, and the id pairs them
+/// into one sequence number (the Cantor pairing): it grows with each event
+/// and decodes back to both counts, neither of which is capped.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct EventCursor {
+    pub agents: u64,
+    pub others: u64,
+}
+
+impl EventCursor {
+    pub fn id(&self) -> u128 {
+        let events = self.agents as u128 + self.others as u128;
+        events * (events + 1) / 2 + self.agents as u128
+    }
+
+    /// The cursor `id` encodes, or `None` if no pair of counts gives it.
+    pub fn from_id(id: u128) -> Option<Self> {
+        // The number of events delivered is the largest n with
+        // triangular(n) <= id; both counts fit in a u64, so n is below 2^65.
+        let (mut events, mut above) = (0u128, 1u128 << 65);
+        while above - events > 1 {
+            let mid = events + (above - events) / 2;
+            if triangular(mid).is_some_and(|t| t <= id) {
+                events = mid;
+            } else {
+                above = mid;
+            }
+        }
+        let agents = id - triangular(events)?;
+        Some(Self {
+            agents: u64::try_from(agents).ok()?,
+            others: u64::try_from(events - agents).ok()?,
+        })
+    }
+
+    fn from_headers(headers: &HeaderMap) -> Self {
+        headers
+            .get("last-event-id")
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.trim().parse::<u128>().ok())
+            .and_then(Self::from_id)
+            .unwrap_or_default()
+    }
+}
+
+fn triangular(n: u128) -> Option<u128> {
+    n.checked_mul(n.checked_add(1)?).map(|product| product / 2)
+}
+
+/// `root` and everything below it.
+async fn subtree(storage: &dyn Storage, root: Uuid) -> anyhow::Result<HashSet<Uuid>> {
+    let mut members = HashSet::from([root]);
+    let mut frontier = vec![root];
+    while let Some(parent) = frontier.pop() {
+        for child in storage.get_children(parent).await? {
+            if members.insert(child.id) {
+                frontier.push(child.id);
+            }
+        }
+    }
+    Ok(members)
+}
+
 pub async fn stream_web_events(
     State(storage): State<Arc<dyn Storage>>,
+    State(events): State<EventBus>,
     Path(id): Path<Uuid>,
+    Query(query): Query<EventsQuery>,
+    headers: HeaderMap,
 ) -> Result<Sse<impl Stream<Item = Result<Event, Infallible>>>, ApiError> {
     let _web = storage
         .get_web(id)
         .await?
         .ok_or_else(|| ApiError::NotFound(format!("Web {} not found", id)))?;
 
+    if let Some(agent_id) = query.agent {
+        storage
+            .get_agent(agent_id)
+            .await?
+            .filter(|agent| agent.web_id == id)
+            .ok_or_else(|| {
+                ApiError::NotFound(format!("Agent {} not found in web {}", agent_id, id))
+            })?;
+    }
+
+    let mut cursor = EventCursor::from_headers(&headers);
+    let mut engine_events = events.subscribe();
+
     let stream = async_stream::stream! {
-        let mut last_agent_count = 0;
         let mut last_signal_count = 0;
         let mut iteration = 0;
 
@@ -350,9 +954,25 @@ pub async fn stream_web_events(
             iteration += 1;
 
             if let Ok(Some(web)) = storage.get_web(id).await {
-                if let Ok(agents) = storage.get_web_agents(id).await {
-                    if agents.len() > last_agent_count {
-                        for agent in agents.iter().skip(last_agent_count) {
+                // Recomputed each round so newly spawned descendants are included.
+                let watched = match query.agent {
+                    Some(agent_id) => match subtree(storage.as_ref(), agent_id).await {
+                        Ok(members) => Some(members),
+                        Err(_) => break,
+                    },
+                    None => None,
+                };
+                let in_scope = |agent_id: &Uuid| watched.as_ref().is_none_or(|w| w.contains(agent_id));
+
+                if let Ok(mut agents) = storage.get_web_agents(id).await {
+                    // Spawn order is what the cursor counts, so keep it stable across backends.
+                    agents.sort_by_key(|a| (a.created_at, a.id));
+                    if agents.len() as u64 > cursor.agents {
+                        for agent in agents.iter().skip(cursor.agents as usize) {
+                            cursor.agents += 1;
+                            if !in_scope(&agent.id) {
+                                continue;
+                            }
                             let event_data = serde_json::json!({
                                 "type": "agent_spawned",
                                 "agent_id": agent.id.to_string(),
@@ -360,32 +980,51 @@ pub async fn stream_web_events(
                                 "capability": format!("{:?}", agent.capability),
                             });
                             yield Ok(Event::default()
+                                .id(cursor.id().to_string())
                                 .event("agent_spawned")
                                 .data(event_data.to_string()));
                         }
-                        last_agent_count = agents.len();
                     }
                 }
 
-                if let Ok(signals) = storage.get_pending_signals(id).await {
+                if let Ok(mut signals) = storage.get_pending_signals(id).await {
+                    signals.retain(|s| in_scope(&s.origin));
                     if signals.len() != last_signal_count {
+                        cursor.others += 1;
                         let event_data = serde_json::json!({
                             "type": "signals_updated",
                             "pending_count": signals.len(),
                         });
                         yield Ok(Event::default()
+                            .id(cursor.id().to_string())
                             .event("signals_updated")
                             .data(event_data.to_string()));
                         last_signal_count = signals.len();
                     }
                 }
 
+                loop {
+                    match engine_events.try_recv() {
+                        Ok(event) if event.web_id() == id && in_scope(&event.agent_id()) => {
+                            cursor.others += 1;
+                            yield Ok(Event::default()
+                                .id(cursor.id().to_string())
+                                .event(event.name())
+                                .data(serde_json::to_string(&event).unwrap_or_default()));
+                        }
+                        Ok(_) | Err(broadcast::error::TryRecvError::Lagged(_)) => continue,
+                        Err(_) => break,
+                    }
+                }
+
                 if web.state != WebState::Running {
+                    cursor.others += 1;
                     let event_data = serde_json::json!({
                         "type": "web_state_changed",
                         "state": format!("{:?}", web.state),
                     });
                     yield Ok(Event::default()
+                        .id(cursor.id().to_string())
                         .event("web_state_changed")
                         .data(event_data.to_string()));
                     break;
@@ -405,12 +1044,68 @@ pub async fn stream_web_events(
     Ok(Sse::new(stream).keep_alive(KeepAlive::new().interval(Duration::from_secs(15))))
 }
 
-pub async fn get_config() -> Json<serde_json::Value> {
+pub async fn get_config(State(config): State<SharedConfig>) -> Json<serde_json::Value> {
+    let config = config.read().unwrap();
     Json(serde_json::json!({
         "version": env!("CARGO_PKG_VERSION"),
-        "default_activation_threshold": 0.6,
-        "default_attenuation": 0.8,
-        "max_agents": 50,
+        "default_activation_threshold": config.default_threshold,
+        "default_attenuation": config.attenuation_factor,
+        "max_agents": config.max_agents,
         "max_signal_hops": 10,
     }))
 }
+
+/// Constant-time comparison so the token can't be guessed byte by byte.
+fn token_matches(expected: &str, given: &str) -> bool {
+    expected.len() == given.len()
+        && expected
+            .bytes()
+            .zip(given.bytes())
+            .fold(0u8, |acc, (a, b)| acc | (a ^ b))
+            == 0
+}
+
+fn require_admin(state: &AppState, headers: &HeaderMap) -> Result<(), ApiError> {
+    let expected = state.admin_token.as_deref().ok_or_else(|| {
+        ApiError::Unauthorized(
+            "Admin endpoints are disabled; set ARACHNID_ADMIN_TOKEN to enable them".to_string(),
+        )
+    })?;
+
+    let given = headers
+        .get(axum::http::header::AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "))
+        .unwrap_or("");
+
+    if token_matches(expected, given) {
+        Ok(())
+    } else {
+        Err(ApiError::Unauthorized(
+            "Invalid or missing admin token".to_string(),
+        ))
+    }
+}
+
+/// Re-read the config sources and swap the result in. Only webs created
+/// afterwards see the new defaults; with a reload hook, e.g. the one `serve`
+/// uses to rebuild providers from new API keys, only webs started afterwards
+/// see the new providers.
+pub async fn reload_config(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    require_admin(&state, &headers)?;
+
+    let config = (state.config_loader)().map_err(|e| {
+        tracing::error!("Failed to reload config: {:#}", e);
+        ApiError::Internal("Failed to reload config".to_string())
+    })?;
+    if let Some(hook) = &state.reload_hook {
+        hook(&config);
+    }
+    *state.config.write().unwrap() = config;
+
+    tracing::info!("Configuration reloaded");
+    Ok(Json(serde_json::json!({ "reloaded": true })))
+}
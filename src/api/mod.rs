@@ -1,6 +1,9 @@
+pub mod cors;
 pub mod error;
 pub mod handlers;
+pub mod middleware;
 pub mod server;
 
-pub use error::ApiError;
+pub use cors::CorsConfig;
+pub use error::{ApiError, FieldError};
 pub use server::{serve, AppState};
@@ -1,35 +1,169 @@
 use anyhow::Result;
 use axum::{
-    routing::{delete, get, post},
+    extract::FromRef,
+    routing::{delete, get, patch, post},
     Router,
 };
-use std::sync::Arc;
-use tower_http::cors::CorsLayer;
+use std::sync::{Arc, RwLock};
 
+use crate::api::cors::CorsConfig;
 use crate::api::handlers;
+use crate::api::middleware::request_context;
+use crate::config::{Config, ConfigLayer};
+use crate::engine::events::EventBus;
+use crate::engine::metrics::MetricsRegistry;
+use crate::engine::scheduler::WebScheduler;
 use crate::storage::Storage;
 
+/// Server-wide config, swapped in place by `POST /admin/reload-config`.
+pub type SharedConfig = Arc<RwLock<Config>>;
+
+/// Re-reads the config sources on reload.
+pub type ConfigLoader = Arc<dyn Fn() -> Result<Config> + Send + Sync>;
+
+/// Applies a reloaded config beyond the server's copy, e.g. rebuilding the
+/// engine's providers.
+pub type ReloadHook = Arc<dyn Fn(&Config) + Send + Sync>;
+
 #[derive(Clone)]
 pub struct AppState {
     pub storage: Arc<dyn Storage>,
+    pub events: EventBus,
+    pub metrics: MetricsRegistry,
+    pub cors: CorsConfig,
+    pub config: SharedConfig,
+    pub config_loader: ConfigLoader,
+    pub reload_hook: Option<ReloadHook>,
+    /// Bearer token required by `/admin` endpoints; they are disabled when unset.
+    pub admin_token: Option<String>,
+    /// Runs webs started with `POST /webs/:id/run`; that endpoint is
+    /// unavailable when unset.
+    pub scheduler: Option<WebScheduler>,
+}
+
+impl AppState {
+    pub fn new(storage: Arc<dyn Storage>) -> Self {
+        Self {
+            storage,
+            events: EventBus::default(),
+            metrics: MetricsRegistry::default(),
+            cors: CorsConfig::default(),
+            config: Arc::new(RwLock::new(Config::default())),
+            config_loader: Arc::new(|| Config::load(&ConfigLayer::default(), None)),
+            reload_hook: None,
+            admin_token: None,
+            scheduler: None,
+        }
+    }
+
+    /// Share `events` with the engine so its events reach SSE streams.
+    pub fn with_events(mut self, events: EventBus) -> Self {
+        self.events = events;
+        self
+    }
+
+    /// Share `metrics` with the engine so its counters are served.
+    pub fn with_metrics(mut self, metrics: MetricsRegistry) -> Self {
+        self.metrics = metrics;
+        self
+    }
+
+    pub fn with_cors(mut self, cors: CorsConfig) -> Self {
+        self.cors = cors;
+        self
+    }
+
+    /// Start from `config` and rebuild it with `loader` on reload.
+    pub fn with_config(
+        mut self,
+        config: Config,
+        loader: impl Fn() -> Result<Config> + Send + Sync + 'static,
+    ) -> Self {
+        self.config = Arc::new(RwLock::new(config));
+        self.config_loader = Arc::new(loader);
+        self
+    }
+
+    /// Run `hook` with each config a reload swaps in.
+    pub fn with_reload_hook(mut self, hook: impl Fn(&Config) + Send + Sync + 'static) -> Self {
+        self.reload_hook = Some(Arc::new(hook));
+        self
+    }
+
+    pub fn with_admin_token(mut self, token: Option<String>) -> Self {
+        self.admin_token = token.filter(|t| !t.is_empty());
+        self
+    }
+
+    pub fn with_scheduler(mut self, scheduler: WebScheduler) -> Self {
+        self.scheduler = Some(scheduler);
+        self
+    }
+}
+
+impl FromRef<AppState> for Arc<dyn Storage> {
+    fn from_ref(state: &AppState) -> Self {
+        state.storage.clone()
+    }
+}
+
+impl FromRef<AppState> for SharedConfig {
+    fn from_ref(state: &AppState) -> Self {
+        state.config.clone()
+    }
+}
+
+impl FromRef<AppState> for EventBus {
+    fn from_ref(state: &AppState) -> Self {
+        state.events.clone()
+    }
+}
+
+impl FromRef<AppState> for MetricsRegistry {
+    fn from_ref(state: &AppState) -> Self {
+        state.metrics.clone()
+    }
 }
 
 pub fn create_router(state: AppState) -> Router {
+    let cors = state.cors.layer();
+
     Router::new()
         .route("/health", get(handlers::health_check))
+        .route("/health/live", get(handlers::liveness_check))
+        .route("/metrics", get(handlers::get_metrics))
         .route("/config", get(handlers::get_config))
+        .route("/admin/reload-config", post(handlers::reload_config))
         .route("/webs", post(handlers::create_web))
         .route("/webs", get(handlers::list_webs))
         .route("/webs/:id", get(handlers::get_web))
         .route("/webs/:id", delete(handlers::terminate_web))
+        .route("/webs/:id/run", post(handlers::run_web))
         .route("/webs/:id/results", get(handlers::get_web_results))
         .route("/webs/:id/agents", get(handlers::get_web_agents))
+        .route("/webs/:id/tree", get(handlers::get_web_tree))
+        .route("/webs/:id/config", get(handlers::get_web_config))
+        .route("/webs/:id/config", patch(handlers::update_web_config))
         .route("/webs/:id/signals", get(handlers::get_web_signals))
         .route("/webs/:id/events", get(handlers::stream_web_events))
+        .route("/webs/:id/metrics", get(handlers::get_web_metrics))
+        .route("/webs/:id/analytics", get(handlers::get_web_analytics))
         .route("/agents/:id", get(handlers::get_agent))
         .route("/agents/:id/context", get(handlers::get_agent_context))
-        .layer(CorsLayer::permissive())
-        .with_state(state.storage)
+        .route("/agents/:id/pause", post(handlers::pause_agent))
+        .route("/agents/:id/resume", post(handlers::resume_agent))
+        .route(
+            "/agents/:id/tools",
+            get(handlers::get_agent_tool_executions),
+        )
+        .route(
+            "/agents/:id/tool-executions",
+            get(handlers::get_agent_tool_executions),
+        )
+        .route("/definitions/:id", delete(handlers::delete_definition))
+        .layer(cors)
+        .layer(axum::middleware::from_fn(request_context))
+        .with_state(state)
 }
 
 pub async fn serve(state: AppState, port: u16) -> Result<()> {
@@ -52,23 +186,21 @@ mod tests {
     use http_body_util::BodyExt;
     use tower::ServiceExt;
 
+    use crate::providers::usage::Usage;
     use crate::storage::memory::InMemoryStore;
-    use crate::types::{Agent, CapabilityType, Web, WebConfig};
+    use crate::storage::traits::WebAnalytics;
+    use crate::types::{Agent, CapabilityType, Web, WebConfig, WebState};
 
     fn create_test_app() -> (Router, Arc<InMemoryStore>) {
         let storage = Arc::new(InMemoryStore::new());
-        let state = AppState {
-            storage: storage.clone() as Arc<dyn Storage>,
-        };
+        let state = AppState::new(storage.clone() as Arc<dyn Storage>);
         (create_router(state), storage)
     }
 
     #[tokio::test]
     async fn test_create_router() {
         let storage = Arc::new(InMemoryStore::new());
-        let state = AppState {
-            storage: storage as Arc<dyn Storage>,
-        };
+        let state = AppState::new(storage as Arc<dyn Storage>);
         let _router = create_router(state);
     }
 
@@ -92,6 +224,49 @@ mod tests {
         let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
 
         assert_eq!(json["status"], "healthy");
+        assert_eq!(json["storage"]["status"], "ok");
+    }
+
+    #[tokio::test]
+    async fn test_health_check_reports_unreachable_storage() {
+        // Nothing listens on a port we bound and released.
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        drop(listener);
+        let pool = sqlx::postgres::PgPoolOptions::new()
+            .acquire_timeout(std::time::Duration::from_millis(500))
+            .connect_lazy(&format!("postgres://arachnid@127.0.0.1:{}/arachnid", port))
+            .unwrap();
+        let storage = Arc::new(crate::storage::postgres::PostgresStorage::from_pool(pool));
+        let app = create_router(AppState::new(storage as Arc<dyn Storage>));
+
+        let response = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .uri("/health")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(json["status"], "unhealthy");
+        assert_eq!(json["storage"]["status"], "unreachable");
+        assert!(!json["storage"]["error"].as_str().unwrap().is_empty());
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri("/health/live")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
     }
 
     #[tokio::test]
@@ -140,6 +315,37 @@ mod tests {
 
         assert_eq!(json["task"], "Test task");
         assert_eq!(json["state"], "Running");
+        assert_eq!(json["priority"], 0);
+    }
+
+    #[tokio::test]
+    async fn test_create_web_with_priority() {
+        let (app, storage) = create_test_app();
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri("/webs")
+                    .header("content-type", "application/json")
+                    .body(Body::from(
+                        r#"{"task": "Urgent task", "priority": 5, "metadata": {"owner": "team-a"}}"#,
+                    ))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(json["priority"], 5);
+
+        let id = json["id"].as_str().unwrap().parse().unwrap();
+        let web = storage.get_web(id).await.unwrap().unwrap();
+        assert_eq!(web.priority, 5);
+        assert_eq!(web.owner(), Some("team-a"));
     }
 
     #[tokio::test]
@@ -184,6 +390,9 @@ mod tests {
         let web = Web::new(root_agent_id, "Test task".to_string(), WebConfig::default());
         let web_id = web.id;
         storage.create_web(&web).await.unwrap();
+        for usage in [Usage::new(100, 20), Usage::new(50, 10)] {
+            storage.record_token_usage(web_id, &usage).await.unwrap();
+        }
 
         let response = app
             .oneshot(
@@ -201,6 +410,8 @@ mod tests {
         let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
 
         assert_eq!(json["task"], "Test task");
+        assert_eq!(json["usage"]["input_tokens"], 150);
+        assert_eq!(json["usage"]["output_tokens"], 30);
     }
 
     #[tokio::test]
@@ -231,6 +442,141 @@ mod tests {
         assert_eq!(json["state"], "Failed");
     }
 
+    #[tokio::test]
+    async fn test_delete_definition() {
+        use crate::definitions::builtin::task_coordinator_definition;
+        use crate::definitions::{AgentDefinition, DefinitionSource};
+
+        let (app, storage) = create_test_app();
+        let generated = AgentDefinition {
+            name: "generated".to_string(),
+            source: DefinitionSource::Generated,
+            ..AgentDefinition::default()
+        };
+        let builtin = task_coordinator_definition();
+        Storage::create_definition(&*storage, &generated)
+            .await
+            .unwrap();
+        Storage::create_definition(&*storage, &builtin)
+            .await
+            .unwrap();
+
+        let delete = |id: uuid::Uuid| {
+            Request::builder()
+                .method("DELETE")
+                .uri(format!("/definitions/{}", id))
+                .body(Body::empty())
+                .unwrap()
+        };
+
+        let response = app.clone().oneshot(delete(generated.id)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::NO_CONTENT);
+        assert!(Storage::get_definition(&*storage, generated.id)
+            .await
+            .unwrap()
+            .is_none());
+
+        let response = app.clone().oneshot(delete(generated.id)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+        let response = app.oneshot(delete(builtin.id)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        assert!(Storage::get_definition(&*storage, builtin.id)
+            .await
+            .unwrap()
+            .is_some());
+    }
+
+    #[tokio::test]
+    async fn test_run_web() {
+        use crate::capabilities::Providers;
+        use crate::engine::coordination::CoordinationEngine;
+        use crate::engine::scheduler::{SchedulerConfig, WebScheduler};
+        use crate::types::WebState;
+        use std::collections::HashMap;
+
+        let storage = Arc::new(InMemoryStore::new());
+        let engine = CoordinationEngine::new(
+            storage.clone(),
+            HashMap::new(),
+            Providers {
+                embedding: None,
+                llm: None,
+                search: None,
+            },
+        );
+        let scheduler =
+            WebScheduler::new(storage.clone(), Arc::new(engine)).with_config(SchedulerConfig {
+                poll_interval: std::time::Duration::from_millis(10),
+                ..SchedulerConfig::default()
+            });
+        let handle = scheduler.spawn();
+        let app = create_router(
+            AppState::new(storage.clone() as Arc<dyn Storage>).with_scheduler(scheduler),
+        );
+
+        let web = Web::new(
+            uuid::Uuid::new_v4(),
+            "Test task".to_string(),
+            WebConfig::default(),
+        );
+        storage.create_web(&web).await.unwrap();
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri(format!("/webs/{}/run", web.id))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let state = tokio::time::timeout(std::time::Duration::from_secs(5), async {
+            loop {
+                let web = storage.get_web(web.id).await.unwrap().unwrap();
+                if web.state != WebState::Running {
+                    return web.state;
+                }
+                tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .expect("web did not finish");
+        handle.abort();
+
+        // With no capabilities the root gathers nothing, so the run fails
+        // rather than converging empty.
+        assert_eq!(state, WebState::Failed);
+        assert!(storage.get_agent(web.root_agent).await.unwrap().is_some());
+    }
+
+    #[tokio::test]
+    async fn test_run_web_unavailable_without_scheduler() {
+        let (app, storage) = create_test_app();
+
+        let web = Web::new(
+            uuid::Uuid::new_v4(),
+            "Test task".to_string(),
+            WebConfig::default(),
+        );
+        storage.create_web(&web).await.unwrap();
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri(format!("/webs/{}/run", web.id))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+    }
+
     #[tokio::test]
     async fn test_get_web_agents() {
         let (app, storage) = create_test_app();
@@ -370,4 +716,1149 @@ mod tests {
         assert_eq!(json["purpose"], "Test agent");
         assert!(json["accumulated_knowledge"].as_array().unwrap().is_empty());
     }
+
+    #[tokio::test]
+    async fn test_get_agent_tool_executions() {
+        use crate::definitions::ToolType;
+        use crate::storage::traits::ToolExecution;
+
+        let (app, storage) = create_test_app();
+
+        let agent = Agent::new(
+            uuid::Uuid::new_v4(),
+            None,
+            "Test agent".to_string(),
+            vec![1.0; 1536],
+            CapabilityType::Search,
+            0.6,
+        );
+        storage.create_agent(&agent).await.unwrap();
+
+        for (tool, success) in [(ToolType::ReadFile, true), (ToolType::WriteFile, false)] {
+            let execution = ToolExecution::new(
+                agent.id,
+                agent.web_id,
+                tool,
+                serde_json::json!({ "path": "notes.txt" }),
+                serde_json::json!({ "size": 5 }),
+                success,
+            )
+            .with_effects(
+                vec![serde_json::json!({ "type": "file", "path": "notes.txt", "size": 5 })],
+                Vec::new(),
+            );
+            storage.record_tool_execution(&execution).await.unwrap();
+        }
+
+        let response = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .uri(format!("/agents/{}/tool-executions", agent.id))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        let executions = json.as_array().unwrap();
+        assert_eq!(executions.len(), 2);
+        assert_eq!(executions[0]["tool"], "read_file");
+        assert_eq!(executions[0]["params"]["path"], "notes.txt");
+        assert_eq!(executions[1]["tool"], "write_file");
+        assert_eq!(executions[1]["success"], false);
+        assert_eq!(executions[0]["artifacts"][0]["path"], "notes.txt");
+        assert!(executions[0]["side_effects"].as_array().unwrap().is_empty());
+
+        let response = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .uri(format!("/agents/{}/tools", agent.id))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        let tools: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(tools, json);
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri(format!("/agents/{}/tool-executions", uuid::Uuid::new_v4()))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_pause_and_resume_agent() {
+        let (app, storage) = create_test_app();
+
+        let agent = Agent::new(
+            uuid::Uuid::new_v4(),
+            None,
+            "Test agent".to_string(),
+            vec![1.0; 1536],
+            CapabilityType::Search,
+            0.6,
+        );
+        storage.create_agent(&agent).await.unwrap();
+
+        let post = |path: String| {
+            Request::builder()
+                .method("POST")
+                .uri(path)
+                .body(Body::empty())
+                .unwrap()
+        };
+
+        let response = app
+            .clone()
+            .oneshot(post(format!("/agents/{}/pause", agent.id)))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(json["state"], "Paused");
+
+        // A paused agent no longer resonates.
+        let resonating = storage
+            .find_resonating_agents(agent.web_id, &agent.tuning, 0.5)
+            .await
+            .unwrap();
+        assert!(resonating.is_empty());
+
+        let response = app
+            .clone()
+            .oneshot(post(format!("/agents/{}/pause", agent.id)))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+        let response = app
+            .clone()
+            .oneshot(post(format!("/agents/{}/resume", agent.id)))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let stored = storage.get_agent(agent.id).await.unwrap().unwrap();
+        assert_eq!(stored.state, crate::types::AgentState::Listening);
+
+        let response = app
+            .oneshot(post(format!("/agents/{}/resume", uuid::Uuid::new_v4())))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+
+    fn parse_sse(body: &str) -> Vec<(u64, String, serde_json::Value)> {
+        body.split("\n\n")
+            .filter_map(|block| {
+                let mut id = None;
+                let mut event = None;
+                let mut data = None;
+                for line in block.lines() {
+                    if let Some(v) = line.strip_prefix("id:") {
+                        id = v.trim().parse().ok();
+                    } else if let Some(v) = line.strip_prefix("event:") {
+                        event = Some(v.trim().to_string());
+                    } else if let Some(v) = line.strip_prefix("data:") {
+                        data = serde_json::from_str(v.trim()).ok();
+                    }
+                }
+                Some((id?, event?, data?))
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_event_ids_keep_increasing_and_decode() {
+        use crate::api::handlers::EventCursor;
+
+        // One million other events used to overflow into the agent count.
+        let mut cursor = EventCursor {
+            agents: 1,
+            others: 999_998,
+        };
+        let mut last = cursor.id();
+        for step in 0..4 {
+            if step % 2 == 0 {
+                cursor.others += 1;
+            } else {
+                cursor.agents += 1;
+            }
+            assert!(cursor.id() > last);
+            assert_eq!(EventCursor::from_id(cursor.id()), Some(cursor));
+            last = cursor.id();
+        }
+        assert_ne!(
+            EventCursor {
+                agents: 1,
+                others: 1_000_000
+            }
+            .id(),
+            EventCursor {
+                agents: 2,
+                others: 0
+            }
+            .id()
+        );
+        assert_eq!(EventCursor::from_id(0), Some(EventCursor::default()));
+
+        // Ids from a client can be anything; large ones decode or are refused
+        // without overflowing.
+        let large = EventCursor::from_id(u64::MAX as u128).unwrap();
+        assert_eq!(large.id(), u64::MAX as u128);
+        assert_eq!(EventCursor::from_id(u128::MAX), None);
+    }
+
+    #[tokio::test]
+    async fn test_stream_events_resume_from_last_event_id() {
+        let (app, storage) = create_test_app();
+
+        let mut web = Web::new(
+            uuid::Uuid::new_v4(),
+            "Test task".to_string(),
+            WebConfig::default(),
+        );
+        web.state = crate::types::WebState::Converged;
+        storage.create_web(&web).await.unwrap();
+
+        let mut agents = Vec::new();
+        for i in 0..3 {
+            let mut agent = Agent::new(
+                web.id,
+                None,
+                format!("Agent {}", i),
+                vec![1.0; 1536],
+                CapabilityType::Search,
+                0.6,
+            );
+            agent.created_at += chrono::Duration::seconds(i);
+            storage.create_agent(&agent).await.unwrap();
+            agents.push(agent);
+        }
+
+        let spawned = |events: &[(u64, String, serde_json::Value)]| -> Vec<String> {
+            events
+                .iter()
+                .filter(|(_, e, _)| e == "agent_spawned")
+                .map(|(_, _, d)| d["agent_id"].as_str().unwrap().to_string())
+                .collect()
+        };
+
+        let response = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .uri(format!("/webs/{}/events", web.id))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        let first = parse_sse(std::str::from_utf8(&body).unwrap());
+        assert_eq!(spawned(&first).len(), 3);
+        assert!(first.windows(2).all(|w| w[0].0 < w[1].0));
+
+        // Reconnect as a client that only saw the first spawn.
+        let last_id = first
+            .iter()
+            .find(|(_, e, _)| e == "agent_spawned")
+            .unwrap()
+            .0;
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri(format!("/webs/{}/events", web.id))
+                    .header("Last-Event-Id", last_id.to_string())
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        let resumed = parse_sse(std::str::from_utf8(&body).unwrap());
+
+        let expected: Vec<String> = agents[1..].iter().map(|a| a.id.to_string()).collect();
+        assert_eq!(spawned(&resumed), expected);
+        assert!(resumed.iter().all(|(id, _, _)| *id > last_id));
+    }
+
+    #[tokio::test]
+    async fn test_stream_events_filtered_to_subtree() {
+        let (app, storage) = create_test_app();
+
+        let mut web = Web::new(
+            uuid::Uuid::new_v4(),
+            "Test task".to_string(),
+            WebConfig::default(),
+        );
+        web.state = crate::types::WebState::Converged;
+        storage.create_web(&web).await.unwrap();
+
+        let agent = |parent: Option<uuid::Uuid>, purpose: &str, offset: i64| {
+            let mut agent = Agent::new(
+                web.id,
+                parent,
+                purpose.to_string(),
+                vec![1.0; 1536],
+                CapabilityType::Search,
+                0.6,
+            );
+            agent.created_at += chrono::Duration::seconds(offset);
+            agent
+        };
+        let root = agent(None, "Root", 0);
+        let branch = agent(Some(root.id), "Branch", 1);
+        let leaf = agent(Some(branch.id), "Leaf", 2);
+        let sibling = agent(Some(root.id), "Sibling", 3);
+        for a in [&root, &branch, &leaf, &sibling] {
+            storage.create_agent(a).await.unwrap();
+        }
+
+        let response = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .uri(format!("/webs/{}/events?agent={}", web.id, branch.id))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        let events = parse_sse(std::str::from_utf8(&body).unwrap());
+        let spawned: Vec<&str> = events
+            .iter()
+            .filter(|(_, e, _)| e == "agent_spawned")
+            .map(|(_, _, d)| d["purpose"].as_str().unwrap())
+            .collect();
+        assert_eq!(spawned, vec!["Branch", "Leaf"]);
+        assert!(events.iter().any(|(_, e, _)| e == "web_state_changed"));
+
+        let other_web = Web::new(
+            uuid::Uuid::new_v4(),
+            "Other".to_string(),
+            WebConfig::default(),
+        );
+        storage.create_web(&other_web).await.unwrap();
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri(format!("/webs/{}/events?agent={}", other_web.id, branch.id))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_stream_events_agent_health_changed() {
+        use crate::providers::llm::MockLLMProvider;
+        use crate::validation::service::{ValidationConfig, ValidationJudgment, ValidationResult};
+        use crate::validation::ValidationService;
+
+        let storage = Arc::new(InMemoryStore::new());
+        let state = AppState::new(storage.clone() as Arc<dyn Storage>);
+        let events = state.events.clone();
+        let app = create_router(state);
+
+        let mut web = Web::new(
+            uuid::Uuid::new_v4(),
+            "Test task".to_string(),
+            WebConfig::default(),
+        );
+        storage.create_web(&web).await.unwrap();
+
+        let mut agent = Agent::new(
+            web.id,
+            None,
+            "Test agent".to_string(),
+            vec![1.0; 1536],
+            CapabilityType::Search,
+            0.6,
+        );
+        storage.create_agent(&agent).await.unwrap();
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri(format!("/webs/{}/events", web.id))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        let service = ValidationService::new(
+            Arc::new(MockLLMProvider::new()),
+            ValidationConfig::default(),
+        )
+        .with_observer(Arc::new(events));
+        let result = ValidationResult {
+            request_id: uuid::Uuid::new_v4(),
+            agent_id: agent.id,
+            judgment: ValidationJudgment::Challenge {
+                reason: "Unsupported claim".to_string(),
+                confidence: 1.0,
+            },
+            raw_response: String::new(),
+            validated_at: chrono::Utc::now(),
+        };
+        service
+            .apply_validation_result(&result, &mut agent)
+            .unwrap();
+
+        web.state = crate::types::WebState::Converged;
+        storage.update_web(&web).await.unwrap();
+
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        let events = parse_sse(std::str::from_utf8(&body).unwrap());
+        let (_, _, data) = events
+            .iter()
+            .find(|(_, e, _)| e == "agent_health_changed")
+            .expect("health change event");
+
+        assert_eq!(data["agent_id"], agent.id.to_string());
+        assert_eq!(data["old_health"], 1.0);
+        assert!(data["new_health"].as_f64().unwrap() < 1.0);
+        assert_eq!(data["reason"], "ValidationChallenge");
+    }
+
+    #[tokio::test]
+    async fn test_stream_events_validation_challenge() {
+        use crate::providers::llm::MockLLMProvider;
+        use crate::validation::service::{ValidationConfig, ValidationJudgment, ValidationResult};
+        use crate::validation::ValidationService;
+
+        let storage = Arc::new(InMemoryStore::new());
+        let state = AppState::new(storage.clone() as Arc<dyn Storage>);
+        let events = state.events.clone();
+        let app = create_router(state);
+
+        let mut web = Web::new(
+            uuid::Uuid::new_v4(),
+            "Test task".to_string(),
+            WebConfig::default(),
+        );
+        storage.create_web(&web).await.unwrap();
+
+        let mut agent = Agent::new(
+            web.id,
+            None,
+            "Test agent".to_string(),
+            vec![1.0; 1536],
+            CapabilityType::Search,
+            0.6,
+        );
+        storage.create_agent(&agent).await.unwrap();
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri(format!("/webs/{}/events", web.id))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        let service = ValidationService::new(
+            Arc::new(MockLLMProvider::new()),
+            ValidationConfig::default(),
+        )
+        .with_observer(Arc::new(events));
+        let result = ValidationResult {
+            request_id: uuid::Uuid::new_v4(),
+            agent_id: agent.id,
+            judgment: ValidationJudgment::Challenge {
+                reason: "Cites a source that does not exist".to_string(),
+                confidence: 0.7,
+            },
+            raw_response: String::new(),
+            validated_at: chrono::Utc::now(),
+        };
+        service
+            .apply_validation_result(&result, &mut agent)
+            .unwrap();
+
+        web.state = crate::types::WebState::Converged;
+        storage.update_web(&web).await.unwrap();
+
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        let events = parse_sse(std::str::from_utf8(&body).unwrap());
+        let names: Vec<&str> = events.iter().map(|(_, e, _)| e.as_str()).collect();
+        let validation = names.iter().position(|e| *e == "validation").unwrap();
+        let health = names
+            .iter()
+            .position(|e| *e == "agent_health_changed")
+            .unwrap();
+        assert!(validation < health);
+
+        let data = &events[validation].2;
+        assert_eq!(data["agent_id"], agent.id.to_string());
+        assert_eq!(data["judgment"], "challenge");
+        assert!((data["confidence"].as_f64().unwrap() - 0.7).abs() < 1e-6);
+        assert_eq!(data["reason"], "Cites a source that does not exist");
+    }
+
+    #[tokio::test]
+    async fn test_cors_allowlist() {
+        let storage = Arc::new(InMemoryStore::new());
+        let state = AppState::new(storage as Arc<dyn Storage>).with_cors(CorsConfig {
+            allowed_origins: vec!["https://dashboard.example".to_string()],
+            ..CorsConfig::default()
+        });
+        let app = create_router(state);
+
+        let preflight = |origin: &str| {
+            Request::builder()
+                .method("OPTIONS")
+                .uri("/webs")
+                .header("Origin", origin)
+                .header("Access-Control-Request-Method", "POST")
+                .body(Body::empty())
+                .unwrap()
+        };
+
+        let allowed = app
+            .clone()
+            .oneshot(preflight("https://dashboard.example"))
+            .await
+            .unwrap();
+        assert_eq!(
+            allowed
+                .headers()
+                .get("access-control-allow-origin")
+                .unwrap(),
+            "https://dashboard.example"
+        );
+
+        let rejected = app
+            .oneshot(preflight("https://evil.example"))
+            .await
+            .unwrap();
+        assert!(rejected
+            .headers()
+            .get("access-control-allow-origin")
+            .is_none());
+    }
+
+    #[tokio::test]
+    async fn test_cors_permissive_opt_in() {
+        let storage = Arc::new(InMemoryStore::new());
+        let state = AppState::new(storage as Arc<dyn Storage>).with_cors(CorsConfig {
+            permissive: true,
+            ..CorsConfig::default()
+        });
+        let app = create_router(state);
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri("/health")
+                    .header("Origin", "https://anywhere.example")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(
+            response
+                .headers()
+                .get("access-control-allow-origin")
+                .unwrap(),
+            "*"
+        );
+    }
+
+    #[derive(Clone, Default)]
+    struct CapturedLogs(Arc<std::sync::Mutex<Vec<u8>>>);
+
+    impl std::io::Write for CapturedLogs {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_request_id_header_and_log() {
+        let logs = CapturedLogs::default();
+        let writer = logs.clone();
+        let subscriber = tracing_subscriber::fmt()
+            .with_writer(move || writer.clone())
+            .with_ansi(false)
+            .finish();
+        let _guard = tracing::subscriber::set_default(subscriber);
+
+        let (app, _) = create_test_app();
+        let missing = uuid::Uuid::new_v4();
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri(format!("/webs/{}", missing))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        let request_id = response
+            .headers()
+            .get("x-request-id")
+            .expect("request id header")
+            .to_str()
+            .unwrap()
+            .to_string();
+
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(json["error"]["request_id"], request_id);
+
+        let output = String::from_utf8(logs.0.lock().unwrap().clone()).unwrap();
+        assert!(output.contains("request completed"));
+        assert!(output.contains(&request_id));
+        assert!(output.contains("status=404"));
+    }
+
+    #[tokio::test]
+    async fn test_request_id_is_preserved() {
+        let (app, _) = create_test_app();
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri("/health")
+                    .header("x-request-id", "client-supplied-id")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(
+            response.headers().get("x-request-id").unwrap(),
+            "client-supplied-id"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_error_body_not_found() {
+        let (app, _) = create_test_app();
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri(format!("/agents/{}", uuid::Uuid::new_v4()))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+
+        assert_eq!(json["error"]["code"], "not_found");
+        assert!(json["error"]["message"]
+            .as_str()
+            .unwrap()
+            .contains("not found"));
+    }
+
+    #[tokio::test]
+    async fn test_error_body_validation() {
+        let (app, _) = create_test_app();
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri("/webs")
+                    .header("content-type", "application/json")
+                    .body(Body::from(r#"{"task": "   "}"#))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+
+        assert_eq!(json["error"]["code"], "validation_failed");
+        assert_eq!(json["error"]["fields"][0]["field"], "task");
+        assert_eq!(json["error"]["fields"][0]["message"], "must not be empty");
+    }
+
+    #[tokio::test]
+    async fn test_error_body_malformed_json() {
+        let (app, _) = create_test_app();
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri("/webs")
+                    .header("content-type", "application/json")
+                    .body(Body::from("{not json"))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(json["error"]["code"], "bad_request");
+    }
+
+    #[tokio::test]
+    async fn test_get_web_tree() {
+        let (app, storage) = create_test_app();
+
+        let root = Agent::new(
+            uuid::Uuid::new_v4(),
+            None,
+            "Root".to_string(),
+            vec![1.0; 1536],
+            CapabilityType::Synthesizer,
+            0.6,
+        );
+        let web = Web::new(root.id, "Test task".to_string(), WebConfig::default());
+        let mut root = root;
+        root.web_id = web.id;
+        storage.create_web(&web).await.unwrap();
+        storage.create_agent(&root).await.unwrap();
+
+        let child = Agent::new(
+            web.id,
+            Some(root.id),
+            "Child".to_string(),
+            vec![1.0; 1536],
+            CapabilityType::Search,
+            0.6,
+        );
+        storage.create_agent(&child).await.unwrap();
+
+        let grandchild = Agent::new(
+            web.id,
+            Some(child.id),
+            "Grandchild".to_string(),
+            vec![1.0; 1536],
+            CapabilityType::Search,
+            0.6,
+        );
+        storage.create_agent(&grandchild).await.unwrap();
+
+        let fetch = |uri: String| {
+            let app = app.clone();
+            async move {
+                let response = app
+                    .oneshot(Request::builder().uri(uri).body(Body::empty()).unwrap())
+                    .await
+                    .unwrap();
+                assert_eq!(response.status(), StatusCode::OK);
+                let body = response.into_body().collect().await.unwrap().to_bytes();
+                serde_json::from_slice::<serde_json::Value>(&body).unwrap()
+            }
+        };
+
+        let tree = fetch(format!("/webs/{}/tree", web.id)).await;
+        assert_eq!(tree["id"], root.id.to_string());
+        assert_eq!(tree["children"][0]["purpose"], "Child");
+        assert_eq!(tree["children"][0]["children"][0]["purpose"], "Grandchild");
+        assert_eq!(tree["children"][0]["children"][0]["capability"], "Search");
+
+        let shallow = fetch(format!("/webs/{}/tree?depth=1", web.id)).await;
+        assert_eq!(shallow["children"][0]["purpose"], "Child");
+        assert!(shallow["children"][0]["children"]
+            .as_array()
+            .unwrap()
+            .is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_get_web_results_include_all() {
+        use crate::types::ContextItem;
+
+        let (app, storage) = create_test_app();
+        let knowledge = |source: uuid::Uuid, content: &str| ContextItem {
+            source_agent: source,
+            content: content.to_string(),
+            data: serde_json::Value::Null,
+            frequency: vec![],
+            amplitude: None,
+        };
+
+        let mut root = Agent::new(
+            uuid::Uuid::new_v4(),
+            None,
+            "Root".to_string(),
+            vec![1.0; 1536],
+            CapabilityType::Synthesizer,
+            0.6,
+        );
+        let mut web = Web::new(root.id, "Test task".to_string(), WebConfig::default());
+        web.report = Some(crate::types::FinalReport {
+            summary: "One finding".to_string(),
+            key_points: vec!["propagated finding".to_string()],
+            confidence: 0.7,
+        });
+        root.web_id = web.id;
+        let mut leaf = Agent::new(
+            web.id,
+            Some(root.id),
+            "Leaf".to_string(),
+            vec![1.0; 1536],
+            CapabilityType::Search,
+            0.6,
+        );
+        // The leaf's first finding reached the root; its second never did.
+        root.context
+            .accumulated_knowledge
+            .push(knowledge(leaf.id, "propagated finding"));
+        leaf.context.accumulated_knowledge = vec![
+            knowledge(leaf.id, "propagated finding"),
+            knowledge(leaf.id, "stranded finding"),
+        ];
+        storage.create_web(&web).await.unwrap();
+        storage.create_agent(&root).await.unwrap();
+        storage.create_agent(&leaf).await.unwrap();
+
+        let fetch = |uri: String| {
+            let app = app.clone();
+            async move {
+                let response = app
+                    .oneshot(Request::builder().uri(uri).body(Body::empty()).unwrap())
+                    .await
+                    .unwrap();
+                let status = response.status();
+                let body = response.into_body().collect().await.unwrap().to_bytes();
+                (
+                    status,
+                    serde_json::from_slice::<serde_json::Value>(&body).unwrap(),
+                )
+            }
+        };
+
+        let (status, root_only) = fetch(format!("/webs/{}/results", web.id)).await;
+        assert_eq!(status, StatusCode::OK);
+        let items = root_only["accumulated_knowledge"].as_array().unwrap();
+        assert_eq!(items.len(), 1);
+        assert!(items[0].get("agent_id").is_none());
+        assert_eq!(root_only["report"]["summary"], "One finding");
+        assert_eq!(root_only["report"]["key_points"][0], "propagated finding");
+
+        let (status, all) = fetch(format!("/webs/{}/results?include=all", web.id)).await;
+        assert_eq!(status, StatusCode::OK);
+        let items = all["accumulated_knowledge"].as_array().unwrap();
+        assert_eq!(items.len(), 2, "the propagated finding is listed once");
+        assert_eq!(items[0]["content"], "propagated finding");
+        assert_eq!(items[0]["agent_id"], root.id.to_string());
+        assert_eq!(items[1]["content"], "stranded finding");
+        assert_eq!(items[1]["agent_id"], leaf.id.to_string());
+        assert_eq!(items[1]["capability"], "Search");
+
+        let (status, _) = fetch(format!("/webs/{}/results?include=leaves", web.id)).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+    }
+
+    async fn patch_config(app: Router, web_id: uuid::Uuid, body: &str) -> axum::response::Response {
+        app.oneshot(
+            Request::builder()
+                .method("PATCH")
+                .uri(format!("/webs/{}/config", web_id))
+                .header("content-type", "application/json")
+                .body(Body::from(body.to_string()))
+                .unwrap(),
+        )
+        .await
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_get_web_config() {
+        let (app, storage) = create_test_app();
+
+        let web = Web::new(
+            uuid::Uuid::new_v4(),
+            "Test task".to_string(),
+            WebConfig::default(),
+        );
+        storage.create_web(&web).await.unwrap();
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri(format!("/webs/{}/config", web.id))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(json["max_agents"], 100);
+    }
+
+    #[tokio::test]
+    async fn test_get_metrics() {
+        let storage = Arc::new(InMemoryStore::new());
+        let metrics = MetricsRegistry::new();
+        let app = create_router(
+            AppState::new(storage.clone() as Arc<dyn Storage>).with_metrics(metrics.clone()),
+        );
+
+        let web = Web::new(
+            uuid::Uuid::new_v4(),
+            "Test task".to_string(),
+            WebConfig::default(),
+        );
+        storage.create_web(&web).await.unwrap();
+        let other = uuid::Uuid::new_v4();
+        for _ in 0..3 {
+            metrics.record_signal_processed(&web.id);
+        }
+        metrics.record_agent_spawned(&web.id);
+        metrics.record_signal_processed(&other);
+
+        let get = |uri: String| {
+            app.clone()
+                .oneshot(Request::builder().uri(uri).body(Body::empty()).unwrap())
+        };
+
+        let response = get(format!("/webs/{}/metrics", web.id)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(json["signals_processed"], 3);
+        assert_eq!(json["agents_spawned"], 1);
+
+        let response = get("/metrics".to_string()).await.unwrap();
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(json["signals_processed"], 4);
+        assert_eq!(json["agents_spawned"], 1);
+
+        let response = get(format!("/webs/{}/metrics", other)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_get_web_analytics() {
+        let (app, storage) = create_test_app();
+
+        let web = Web::new(
+            uuid::Uuid::new_v4(),
+            "Test task".to_string(),
+            WebConfig::default(),
+        );
+        storage.create_web(&web).await.unwrap();
+
+        let get = || {
+            app.clone().oneshot(
+                Request::builder()
+                    .uri(format!("/webs/{}/analytics", web.id))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+        };
+
+        // Still running.
+        assert_eq!(get().await.unwrap().status(), StatusCode::NOT_FOUND);
+
+        storage
+            .save_web_analytics(&WebAnalytics {
+                web_id: web.id,
+                state: WebState::Converged,
+                time_to_convergence_ms: Some(1500),
+                max_depth: 2,
+                peak_active_agents: 4,
+                iterations: 7,
+                completed_at: chrono::Utc::now(),
+            })
+            .await
+            .unwrap();
+
+        let response = get().await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(json["state"], "Converged");
+        assert_eq!(json["time_to_convergence_ms"], 1500);
+        assert_eq!(json["max_depth"], 2);
+        assert_eq!(json["peak_active_agents"], 4);
+        assert_eq!(json["iterations"], 7);
+    }
+
+    #[tokio::test]
+    async fn test_update_web_config() {
+        let (app, storage) = create_test_app();
+
+        let web = Web::new(
+            uuid::Uuid::new_v4(),
+            "Test task".to_string(),
+            WebConfig::default(),
+        );
+        storage.create_web(&web).await.unwrap();
+
+        let response = patch_config(app.clone(), web.id, r#"{"max_agents": 5}"#).await;
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let stored = storage.get_web(web.id).await.unwrap().unwrap();
+        assert_eq!(stored.config.max_agents, 5);
+        assert_eq!(stored.config.max_depth, 10);
+
+        let response = patch_config(app, web.id, r#"{"default_threshold": 1.5}"#).await;
+        assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+    }
+
+    #[tokio::test]
+    async fn test_update_web_config_rejected_when_converged() {
+        let (app, storage) = create_test_app();
+
+        let mut web = Web::new(
+            uuid::Uuid::new_v4(),
+            "Test task".to_string(),
+            WebConfig::default(),
+        );
+        web.state = crate::types::WebState::Converged;
+        storage.create_web(&web).await.unwrap();
+
+        let response = patch_config(app, web.id, r#"{"max_agents": 5}"#).await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+        let stored = storage.get_web(web.id).await.unwrap().unwrap();
+        assert_eq!(stored.config.max_agents, 100);
+    }
+
+    async fn get_json(app: Router, uri: &str) -> serde_json::Value {
+        let response = app
+            .oneshot(Request::builder().uri(uri).body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        serde_json::from_slice(&body).unwrap()
+    }
+
+    async fn reload(app: Router, token: Option<&str>) -> StatusCode {
+        let mut request = Request::builder()
+            .method("POST")
+            .uri("/admin/reload-config");
+        if let Some(token) = token {
+            request = request.header("authorization", format!("Bearer {}", token));
+        }
+        app.oneshot(request.body(Body::empty()).unwrap())
+            .await
+            .unwrap()
+            .status()
+    }
+
+    #[tokio::test]
+    async fn test_reload_config() {
+        let storage = Arc::new(InMemoryStore::new());
+        // Stands in for the config sources; the test edits it between reloads.
+        let source = Arc::new(RwLock::new(Config::default()));
+        let loader_source = source.clone();
+        // Stands in for rebuilding the engine's providers.
+        let applied = Arc::new(RwLock::new(Vec::new()));
+        let hook_applied = applied.clone();
+        let state = AppState::new(storage.clone() as Arc<dyn Storage>)
+            .with_config(Config::default(), move || {
+                Ok(loader_source.read().unwrap().clone())
+            })
+            .with_reload_hook(move |config| hook_applied.write().unwrap().push(config.max_agents))
+            .with_admin_token(Some("secret".to_string()));
+        let app = create_router(state);
+
+        let before = get_json(app.clone(), "/config").await;
+        assert_eq!(before["max_agents"], 100);
+
+        let created = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri("/webs")
+                    .header("content-type", "application/json")
+                    .body(Body::from(r#"{"task": "before reload"}"#))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        let body = created.into_body().collect().await.unwrap().to_bytes();
+        let web: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        let web_id: uuid::Uuid = web["id"].as_str().unwrap().parse().unwrap();
+
+        source.write().unwrap().max_agents = 7;
+
+        assert_eq!(reload(app.clone(), None).await, StatusCode::UNAUTHORIZED);
+        assert_eq!(
+            reload(app.clone(), Some("wrong")).await,
+            StatusCode::UNAUTHORIZED
+        );
+        assert_eq!(get_json(app.clone(), "/config").await["max_agents"], 100);
+        assert!(applied.read().unwrap().is_empty());
+
+        assert_eq!(reload(app.clone(), Some("secret")).await, StatusCode::OK);
+        assert_eq!(get_json(app.clone(), "/config").await["max_agents"], 7);
+        assert_eq!(*applied.read().unwrap(), vec![7]);
+
+        // Webs created before the reload keep the config they started with.
+        let stored = storage.get_web(web_id).await.unwrap().unwrap();
+        assert_eq!(stored.config.max_agents, 100);
+    }
+
+    #[tokio::test]
+    async fn test_reload_config_failure_is_internal() {
+        let storage = Arc::new(InMemoryStore::new());
+        let state = AppState::new(storage as Arc<dyn Storage>)
+            .with_config(Config::default(), || {
+                Err(anyhow::anyhow!("config file is not valid TOML"))
+            })
+            .with_admin_token(Some("secret".to_string()));
+        let app = create_router(state);
+
+        assert_eq!(
+            reload(app.clone(), Some("secret")).await,
+            StatusCode::INTERNAL_SERVER_ERROR
+        );
+        assert_eq!(get_json(app, "/config").await["max_agents"], 100);
+    }
+
+    #[tokio::test]
+    async fn test_reload_config_disabled_without_token() {
+        let (app, _) = create_test_app();
+        assert_eq!(reload(app, Some("")).await, StatusCode::UNAUTHORIZED);
+    }
 }
@@ -36,6 +36,7 @@ impl AnalystCapability {
         }
     }
 
+    #[allow(clippy::collapsible_match)]
     fn parse_analysis(&self, response: &str) -> Result<AnalysisResult> {
         let mut summary = String::new();
         let mut key_findings = Vec::new();
@@ -196,9 +197,10 @@ Structure your analysis as:
 
         for finding in &analysis.key_findings {
             signals.push(SignalDraft {
-                frequency: vec![0.7; 1536],
+                frequency: vec![0.7; context.embedding_dim],
                 content: format!("Analysis finding: {}", finding.title),
                 direction: SignalDirection::Upward,
+                amplitude: 1.0,
                 payload: Some(json!({
                     "type": "analysis_finding",
                     "finding": finding,
@@ -211,6 +213,8 @@ Structure your analysis as:
             output: json!(analysis),
             signals_to_emit: signals,
             needs: vec![],
+            context_updates: vec![],
+            capability_state: None,
         })
     }
 }
@@ -225,10 +229,7 @@ mod tests {
         let llm = Arc::new(MockLLMProvider::new());
         let capability = AnalystCapability::new(llm);
 
-        let context = AgentContext {
-            purpose: "Analyze data trends".to_string(),
-            accumulated_knowledge: vec![],
-        };
+        let context = AgentContext::new("Analyze data trends".to_string(), 1536);
 
         let providers = Providers {
             embedding: None,
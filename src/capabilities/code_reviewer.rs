@@ -118,7 +118,7 @@ impl Capability for CodeReviewerCapability {
 
     async fn execute(
         &self,
-        _context: &AgentContext,
+        context: &AgentContext,
         trigger: Option<&Signal>,
         _providers: &Providers,
     ) -> Result<ExecutionResult> {
@@ -155,9 +155,10 @@ End with: VERDICT: APPROVE, REQUEST_CHANGES, or NEEDS_DISCUSSION"#
             .filter(|f| matches!(f.severity, ReviewSeverity::Critical | ReviewSeverity::Major))
         {
             signals.push(SignalDraft {
-                frequency: vec![0.9; 1536],
+                frequency: vec![0.9; context.embedding_dim],
                 content: format!("Review finding: {}", finding.description),
                 direction: SignalDirection::Upward,
+                amplitude: 1.0,
                 payload: Some(json!({
                     "type": "review_finding",
                     "finding": finding,
@@ -174,6 +175,8 @@ End with: VERDICT: APPROVE, REQUEST_CHANGES, or NEEDS_DISCUSSION"#
             }),
             signals_to_emit: signals,
             needs: vec![],
+            context_updates: vec![],
+            capability_state: None,
         })
     }
 }
@@ -188,10 +191,7 @@ mod tests {
         let llm = Arc::new(MockLLMProvider::new());
         let capability = CodeReviewerCapability::new(llm);
 
-        let context = AgentContext {
-            purpose: "Review code".to_string(),
-            accumulated_knowledge: vec![],
-        };
+        let context = AgentContext::new("Review code".to_string(), 1536);
 
         let signal = Signal::new(
             uuid::Uuid::new_v4(),
@@ -51,9 +51,10 @@ impl Capability for CodeWriterCapability {
         let response = self.llm_provider.complete(messages).await?;
 
         let signals = vec![SignalDraft {
-            frequency: vec![0.8; 1536],
+            frequency: vec![0.8; context.embedding_dim],
             content: "Code written".to_string(),
             direction: SignalDirection::Upward,
+            amplitude: 1.0,
             payload: Some(json!({ "type": "code_artifact" })),
         }];
 
@@ -62,6 +63,8 @@ impl Capability for CodeWriterCapability {
             output: json!({ "code": response }),
             signals_to_emit: signals,
             needs: vec![],
+            context_updates: vec![],
+            capability_state: None,
         })
     }
 }
@@ -76,10 +79,8 @@ mod tests {
         let llm = Arc::new(MockLLMProvider::new());
         let capability = CodeWriterCapability::new(llm);
 
-        let context = AgentContext {
-            purpose: "Write a function".to_string(),
-            accumulated_knowledge: vec![],
-        };
+        // Sized by the web's embedding_dim, not the provider's.
+        let context = AgentContext::new("Write a function".to_string(), 64);
 
         let providers = Providers {
             embedding: None,
@@ -93,5 +94,6 @@ mod tests {
             .unwrap();
         assert_eq!(result.status, ExecutionStatus::Complete);
         assert!(!result.signals_to_emit.is_empty());
+        assert_eq!(result.signals_to_emit[0].frequency.len(), 64);
     }
 }
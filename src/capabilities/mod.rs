@@ -1,6 +1,7 @@
 pub mod analyst;
 pub mod code_reviewer;
 pub mod code_writer;
+pub mod planner;
 pub mod search;
 pub mod synthesizer;
 
@@ -8,7 +9,7 @@ use anyhow::Result;
 use async_trait::async_trait;
 
 use crate::engine::coordination::ExecutionResult;
-use crate::providers::embedding::EmbeddingProvider;
+use crate::providers::embedding::{EmbeddingProvider, DEFAULT_EMBEDDING_DIM};
 use crate::providers::llm::LLMProvider;
 use crate::providers::search::SearchProvider;
 use crate::types::{AgentContext, Signal};
@@ -19,6 +20,16 @@ pub struct Providers {
     pub search: Option<Box<dyn SearchProvider>>,
 }
 
+impl Providers {
+    /// Length of the embedding provider's vectors, or the default length
+    /// when there is none.
+    pub fn embedding_dim(&self) -> usize {
+        self.embedding
+            .as_ref()
+            .map_or(DEFAULT_EMBEDDING_DIM, |e| e.dimension())
+    }
+}
+
 #[async_trait]
 pub trait Capability: Send + Sync {
     fn name(&self) -> &str;
@@ -35,3 +46,4 @@ pub trait Capability: Send + Sync {
 pub use analyst::AnalystCapability;
 pub use code_reviewer::CodeReviewerCapability;
 pub use code_writer::CodeWriterCapability;
+pub use planner::PlannerCapability;
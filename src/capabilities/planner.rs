@@ -0,0 +1,369 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::sync::Arc;
+
+use crate::capabilities::{Capability, Providers};
+use crate::engine::coordination::ExecutionResult;
+use crate::providers::llm::{LLMProvider, Message};
+use crate::types::{AgentContext, ExecutionStatus, Signal, SignalDirection, SignalDraft};
+
+/// Amplitude given to steps with no dependencies.
+const BASE_AMPLITUDE: f32 = 1.0;
+/// Amplitude reduction per dependency level.
+const AMPLITUDE_STEP: f32 = 0.15;
+/// Floor so late steps still propagate at least one hop.
+const MIN_AMPLITUDE: f32 = 0.4;
+
+/// Breaks a task into ordered steps with dependencies.
+///
+/// Steps are dispatched as downward signals only once every step they depend on
+/// has reported back through an upward signal. The plan is kept in the agent's
+/// capability state, and a step completes when a result tagged with its id
+/// (`plan_step`) reaches the planner.
+pub struct PlannerCapability {
+    llm_provider: Arc<dyn LLMProvider>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StepStatus {
+    Pending,
+    Dispatched,
+    Done,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlanStep {
+    pub step: usize,
+    pub description: String,
+    pub depends_on: Vec<usize>,
+    /// Dependency depth; 0 runs first.
+    pub priority: u32,
+    pub amplitude: f32,
+    pub status: StepStatus,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Plan {
+    pub steps: Vec<PlanStep>,
+}
+
+impl Plan {
+    fn is_complete(&self) -> bool {
+        self.steps.iter().all(|s| s.status == StepStatus::Done)
+    }
+
+    fn is_done(&self, step: usize) -> bool {
+        self.steps
+            .iter()
+            .any(|s| s.step == step && s.status == StepStatus::Done)
+    }
+
+    /// Pending steps whose dependencies have all completed, in plan order.
+    fn ready_steps(&self) -> Vec<usize> {
+        self.steps
+            .iter()
+            .filter(|s| s.status == StepStatus::Pending)
+            .filter(|s| s.depends_on.iter().all(|d| self.is_done(*d)))
+            .map(|s| s.step)
+            .collect()
+    }
+
+    fn complete_step(&mut self, step: usize) {
+        if let Some(s) = self
+            .steps
+            .iter_mut()
+            .find(|s| s.step == step && s.status == StepStatus::Dispatched)
+        {
+            s.status = StepStatus::Done;
+        }
+    }
+}
+
+impl PlannerCapability {
+    pub fn new(llm_provider: Arc<dyn LLMProvider>) -> Self {
+        Self { llm_provider }
+    }
+
+    fn parse_plan(&self, response: &str) -> Plan {
+        let step_re = Regex::new(
+            r"(?i)^(\d+)[.)]\s+(.+?)(?:\s*[\(\[]\s*(?:depends on|after)\s*:?\s*([\d,\s]*)[\)\]])?$",
+        )
+        .unwrap();
+
+        let mut steps: Vec<PlanStep> = Vec::new();
+        for line in response.lines() {
+            let line = line.trim();
+            let Some(caps) = step_re.captures(line) else {
+                continue;
+            };
+
+            let step: usize = caps[1].parse().unwrap_or(steps.len() + 1);
+            let description = caps[2].trim().to_string();
+            let depends_on = caps
+                .get(3)
+                .map(|m| {
+                    m.as_str()
+                        .split(',')
+                        .filter_map(|d| d.trim().parse::<usize>().ok())
+                        .filter(|d| *d < step)
+                        .collect()
+                })
+                .unwrap_or_default();
+
+            steps.push(PlanStep {
+                step,
+                description,
+                depends_on,
+                priority: 0,
+                amplitude: BASE_AMPLITUDE,
+                status: StepStatus::Pending,
+            });
+        }
+
+        // Dependencies only point backwards, so one pass in order is enough.
+        for i in 0..steps.len() {
+            let level = steps[i]
+                .depends_on
+                .iter()
+                .filter_map(|d| steps[..i].iter().find(|s| s.step == *d))
+                .map(|s| s.priority + 1)
+                .max()
+                .unwrap_or(0);
+            steps[i].priority = level;
+            steps[i].amplitude =
+                (BASE_AMPLITUDE - AMPLITUDE_STEP * level as f32).max(MIN_AMPLITUDE);
+        }
+
+        Plan { steps }
+    }
+
+    /// Plan kept from an earlier execution, with the steps whose results
+    /// have arrived since marked done.
+    fn recorded_plan(&self, context: &AgentContext, trigger: Option<&Signal>) -> Option<Plan> {
+        let mut plan: Plan = serde_json::from_value(context.capability_state.clone()?).ok()?;
+        let results = context
+            .accumulated_knowledge
+            .iter()
+            .map(|item| &item.data)
+            .chain(trigger.and_then(|s| s.payload.as_ref()));
+        for data in results {
+            if let Some(step) = data["plan_step"].as_u64() {
+                plan.complete_step(step as usize);
+            }
+        }
+        Some(plan)
+    }
+
+    async fn create_plan(&self, context: &AgentContext) -> Result<Plan> {
+        let messages = vec![
+            Message::system(
+                r#"You are a planner. Break the task into a short sequence of concrete steps.
+List one step per line, numbered in execution order. If a step needs the result of
+earlier steps, add "(depends on: N, M)" at the end of the line.
+
+Example:
+1. Collect recent benchmark data
+2. Summarize vendor documentation
+3. Compare results against documentation (depends on: 1, 2)"#,
+            ),
+            Message::user(format!("Task: {}", context.purpose)),
+        ];
+
+        let response = self.llm_provider.complete(messages).await?;
+        Ok(self.parse_plan(&response))
+    }
+}
+
+#[async_trait]
+impl Capability for PlannerCapability {
+    fn name(&self) -> &str {
+        "planner"
+    }
+
+    fn description(&self) -> &str {
+        "Produces an ordered plan and dispatches steps as their dependencies complete"
+    }
+
+    async fn execute(
+        &self,
+        context: &AgentContext,
+        trigger: Option<&Signal>,
+        providers: &Providers,
+    ) -> Result<ExecutionResult> {
+        let mut plan = match self.recorded_plan(context, trigger) {
+            Some(plan) => plan,
+            None => self.create_plan(context).await?,
+        };
+
+        if plan.steps.is_empty() {
+            return Ok(ExecutionResult {
+                status: ExecutionStatus::Failed,
+                output: json!({"message": "Planner produced no steps"}),
+                signals_to_emit: vec![],
+                needs: vec![],
+                context_updates: vec![],
+                capability_state: None,
+            });
+        }
+
+        let total_steps = plan.steps.len();
+        let mut signals = vec![];
+        for step_number in plan.ready_steps() {
+            let step = plan
+                .steps
+                .iter_mut()
+                .find(|s| s.step == step_number)
+                .expect("ready step exists");
+            step.status = StepStatus::Dispatched;
+
+            let frequency = match &providers.embedding {
+                Some(provider) => provider.embed(&step.description).await?,
+                None => vec![0.8; context.embedding_dim],
+            };
+
+            signals.push(SignalDraft {
+                frequency,
+                content: step.description.clone(),
+                direction: SignalDirection::Downward,
+                amplitude: step.amplitude,
+                payload: Some(json!({
+                    "type": "plan_step",
+                    "step": step.step,
+                    "total_steps": total_steps,
+                    "depends_on": step.depends_on,
+                    "priority": step.priority,
+                    "amplitude": step.amplitude,
+                })),
+            });
+        }
+
+        let status = if plan.is_complete() {
+            ExecutionStatus::Complete
+        } else {
+            ExecutionStatus::NeedsMore
+        };
+
+        Ok(ExecutionResult {
+            status,
+            output: json!({ "plan": plan }),
+            signals_to_emit: signals,
+            needs: vec![],
+            context_updates: vec![],
+            capability_state: Some(json!(plan)),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::providers::llm::MockLLMProvider;
+    use crate::types::ContextItem;
+
+    const PLAN_RESPONSE: &str = "1. Gather sources\n\
+        2. Extract claims\n\
+        3. Cross-check claims (depends on: 1, 2)\n\
+        4. Write summary (depends on: 3)";
+
+    fn providers() -> Providers {
+        Providers {
+            embedding: None,
+            llm: None,
+            search: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_planner_emits_ordering_metadata() {
+        let llm = Arc::new(MockLLMProvider::with_response(PLAN_RESPONSE.to_string()));
+        let capability = PlannerCapability::new(llm);
+
+        let mut context = AgentContext::new("Fact-check an article".to_string(), 1536);
+
+        let result = capability
+            .execute(&context, None, &providers())
+            .await
+            .unwrap();
+        assert_eq!(result.status, ExecutionStatus::NeedsMore);
+
+        // Only the independent steps go out first, at full amplitude.
+        let payloads: Vec<_> = result
+            .signals_to_emit
+            .iter()
+            .map(|s| s.payload.clone().unwrap())
+            .collect();
+        assert_eq!(payloads.len(), 2);
+        assert_eq!(payloads[0]["step"], 1);
+        assert_eq!(payloads[1]["step"], 2);
+        for payload in &payloads {
+            assert_eq!(payload["type"], "plan_step");
+            assert_eq!(payload["priority"], 0);
+            assert_eq!(payload["total_steps"], 4);
+        }
+        let agent_id = uuid::Uuid::new_v4();
+        for draft in &result.signals_to_emit {
+            assert_eq!(draft.clone().into_signal(agent_id).amplitude, 1.0);
+        }
+
+        // An unrelated item and the result for step 1 leave step 3 waiting.
+        context.capability_state = result.capability_state.clone();
+        for (content, data) in [
+            ("Unrelated finding", json!({})),
+            ("Result for step 1", json!({ "plan_step": 1 })),
+        ] {
+            context.accumulated_knowledge.push(ContextItem {
+                source_agent: uuid::Uuid::new_v4(),
+                content: content.to_string(),
+                data,
+                frequency: Vec::new(),
+                amplitude: None,
+            });
+        }
+        let result = capability
+            .execute(&context, None, &providers())
+            .await
+            .unwrap();
+        assert!(result.signals_to_emit.is_empty());
+        assert_eq!(result.status, ExecutionStatus::NeedsMore);
+
+        // Evicting every item doesn't lose the plan; step 2's result arriving
+        // as the trigger releases step 3 without planning again.
+        context.capability_state = result.capability_state.clone();
+        context.accumulated_knowledge.clear();
+        let trigger = Signal::new(
+            uuid::Uuid::new_v4(),
+            vec![0.8; 1536],
+            "Result for step 2".to_string(),
+            SignalDirection::Upward,
+        )
+        .with_payload(json!({ "plan_step": 2 }));
+        let result = capability
+            .execute(&context, Some(&trigger), &providers())
+            .await
+            .unwrap();
+        assert_eq!(result.signals_to_emit.len(), 1);
+        let payload = result.signals_to_emit[0].payload.clone().unwrap();
+        assert_eq!(payload["step"], 3);
+        assert_eq!(payload["depends_on"], json!([1, 2]));
+        assert_eq!(payload["priority"], 1);
+
+        // The later step propagates more weakly.
+        let signal = result.signals_to_emit[0].clone().into_signal(agent_id);
+        assert_eq!(signal.amplitude, BASE_AMPLITUDE - AMPLITUDE_STEP);
+    }
+
+    #[test]
+    fn test_parse_plan_levels() {
+        let capability = PlannerCapability::new(Arc::new(MockLLMProvider::new()));
+        let plan = capability.parse_plan(PLAN_RESPONSE);
+
+        let priorities: Vec<u32> = plan.steps.iter().map(|s| s.priority).collect();
+        assert_eq!(priorities, vec![0, 0, 1, 2]);
+        assert!(plan.steps[3].amplitude < plan.steps[2].amplitude);
+        assert!(plan.steps[3].amplitude >= MIN_AMPLITUDE);
+    }
+}
@@ -53,6 +53,8 @@ impl Capability for SearchCapability {
                 }),
                 signals_to_emit: vec![],
                 needs: vec![],
+                context_updates: vec![],
+                capability_state: None,
             });
         }
 
@@ -65,13 +67,14 @@ impl Capability for SearchCapability {
             let frequency = if let Some(provider) = embedding_provider {
                 provider.embed(&concept).await?
             } else {
-                vec![1.0; 1536]
+                vec![1.0; context.embedding_dim]
             };
 
             signals.push(SignalDraft {
                 frequency,
                 content: concept.clone(),
                 direction: SignalDirection::Upward,
+                amplitude: 1.0,
                 payload: Some(serde_json::json!({
                     "title": result.title,
                     "url": result.url,
@@ -89,6 +92,8 @@ impl Capability for SearchCapability {
             }),
             signals_to_emit: signals,
             needs: vec![],
+            context_updates: vec![],
+            capability_state: None,
         })
     }
 }
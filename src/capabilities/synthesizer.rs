@@ -72,6 +72,8 @@ impl Capability for SynthesizerCapability {
                 }),
                 signals_to_emit: vec![],
                 needs,
+                context_updates: vec![],
+                capability_state: None,
             });
         }
 
@@ -98,7 +100,7 @@ impl Capability for SynthesizerCapability {
         let frequency = if let Some(provider) = embedding_provider {
             provider.embed(&synthesis).await?
         } else {
-            vec![1.0; 1536]
+            vec![1.0; context.embedding_dim]
         };
 
         Ok(ExecutionResult {
@@ -112,9 +114,12 @@ impl Capability for SynthesizerCapability {
                 frequency,
                 content: synthesis,
                 direction: SignalDirection::Upward,
+                amplitude: 1.0,
                 payload: Some(serde_json::json!({"type": "synthesis"})),
             }],
             needs: vec![],
+            context_updates: vec![],
+            capability_state: None,
         })
     }
 }
@@ -1,18 +1,753 @@
+use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use crate::providers::llm::{ANTHROPIC_BASE_URL, DEFAULT_LLM_MAX_RETRIES, OPENAI_BASE_URL};
+use crate::types::WebConfig;
+
+/// Config file read from the working directory when no path is given.
+pub const CONFIG_FILE: &str = "arachnid.toml";
+
+/// Names of the secrets the config resolves through a [`SecretProvider`].
+const SECRET_KEYS: [&str; 3] = ["OPENAI_API_KEY", "ANTHROPIC_API_KEY", "BRAVE_API_KEY"];
+
+/// Source of API keys and other secrets, looked up by variable name
+/// (e.g. `OPENAI_API_KEY`). Implement this to pull keys from a vault or
+/// cloud secret manager and pass it to [`Config::load_with_secrets`].
+pub trait SecretProvider: Send + Sync {
+    fn get(&self, name: &str) -> Result<Option<String>>;
+}
+
+type Lookup = Box<dyn Fn(&str) -> Option<String> + Send + Sync>;
+
+/// Reads `NAME` from the environment, falling back to the file named by
+/// `NAME_FILE` (as with Docker and Kubernetes secrets).
+pub struct EnvSecretProvider {
+    lookup: Lookup,
+}
+
+impl EnvSecretProvider {
+    pub fn new() -> Self {
+        Self::with_lookup(|name| std::env::var(name).ok())
+    }
+
+    fn with_lookup(lookup: impl Fn(&str) -> Option<String> + Send + Sync + 'static) -> Self {
+        Self {
+            lookup: Box::new(lookup),
+        }
+    }
+}
+
+impl Default for EnvSecretProvider {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SecretProvider for EnvSecretProvider {
+    fn get(&self, name: &str) -> Result<Option<String>> {
+        if let Some(value) = (self.lookup)(name) {
+            return Ok(Some(value));
+        }
+        match (self.lookup)(&format!("{}_FILE", name)) {
+            Some(path) => read_secret_file(Path::new(&path)).map(Some),
+            None => Ok(None),
+        }
+    }
+}
+
+/// Reads each secret from a file named after it in `dir`, e.g.
+/// `/run/secrets/OPENAI_API_KEY`. Missing files mean the secret is unset.
+pub struct FileSecretProvider {
+    dir: PathBuf,
+}
+
+impl FileSecretProvider {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+}
+
+impl SecretProvider for FileSecretProvider {
+    fn get(&self, name: &str) -> Result<Option<String>> {
+        let path = self.dir.join(name);
+        if !path.exists() {
+            return Ok(None);
+        }
+        read_secret_file(&path).map(Some)
+    }
+}
+
+fn read_secret_file(path: &Path) -> Result<String> {
+    let contents = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read secret file {}", path.display()))?;
+    Ok(contents.trim().to_string())
+}
+
+/// Where a resolved config value came from, lowest precedence first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ConfigSource {
+    Default,
+    File,
+    Env,
+    Cli,
+}
+
+impl ConfigSource {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ConfigSource::Default => "default",
+            ConfigSource::File => "file",
+            ConfigSource::Env => "env",
+            ConfigSource::Cli => "cli",
+        }
+    }
+}
+
+/// Which provider computes embeddings, from `ARACHNID_EMBEDDING`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EmbeddingBackend {
+    /// OpenAI's embedding API; needs `OPENAI_API_KEY`.
+    OpenAI,
+    /// Local n-gram hashing, for offline use and tests.
+    Hashing,
+}
+
+impl EmbeddingBackend {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            EmbeddingBackend::OpenAI => "openai",
+            EmbeddingBackend::Hashing => "hashing",
+        }
+    }
+}
+
+impl std::str::FromStr for EmbeddingBackend {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "openai" => Ok(EmbeddingBackend::OpenAI),
+            "hashing" => Ok(EmbeddingBackend::Hashing),
+            other => anyhow::bail!(
+                "Unknown embedding backend '{}'; expected openai or hashing",
+                other
+            ),
+        }
+    }
+}
+
+/// One layer of settings; unset fields fall through to the next layer down.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ConfigLayer {
+    pub openai_api_key: Option<String>,
+    pub anthropic_api_key: Option<String>,
+    pub brave_api_key: Option<String>,
+    pub openai_base_url: Option<String>,
+    pub anthropic_base_url: Option<String>,
+    pub ollama_host: Option<String>,
+    pub ollama_model: Option<String>,
+    pub embedding: Option<EmbeddingBackend>,
+    pub llm_max_retries: Option<u32>,
+    pub default_threshold: Option<f32>,
+    pub attenuation_factor: Option<f32>,
+    pub max_agents: Option<usize>,
+}
+
+fn parse_var<T: std::str::FromStr>(
+    var: &impl Fn(&str) -> Option<String>,
+    name: &str,
+) -> Result<Option<T>>
+where
+    T::Err: std::error::Error + Send + Sync + 'static,
+{
+    var(name)
+        .map(|value| value.trim().parse::<T>())
+        .transpose()
+        .with_context(|| format!("Invalid value for {}", name))
+}
+
+impl ConfigLayer {
+    /// Base URLs from the environment, API keys through `secrets`.
+    pub fn from_env(secrets: &dyn SecretProvider) -> Result<Self> {
+        Self::from_vars(|name| std::env::var(name).ok(), secrets)
+    }
+
+    fn from_vars(
+        var: impl Fn(&str) -> Option<String>,
+        secrets: &dyn SecretProvider,
+    ) -> Result<Self> {
+        let [openai_api_key, anthropic_api_key, brave_api_key] = SECRET_KEYS;
+        Ok(Self {
+            openai_api_key: secrets.get(openai_api_key)?,
+            anthropic_api_key: secrets.get(anthropic_api_key)?,
+            brave_api_key: secrets.get(brave_api_key)?,
+            openai_base_url: var("OPENAI_BASE_URL"),
+            anthropic_base_url: var("ANTHROPIC_BASE_URL"),
+            ollama_host: var("OLLAMA_HOST"),
+            ollama_model: var("OLLAMA_MODEL"),
+            embedding: var("ARACHNID_EMBEDDING")
+                .map(|value| value.trim().parse())
+                .transpose()
+                .context("Invalid value for ARACHNID_EMBEDDING")?,
+            llm_max_retries: parse_var(&var, "ARACHNID_LLM_MAX_RETRIES")?,
+            default_threshold: parse_var(&var, "ARACHNID_DEFAULT_THRESHOLD")?,
+            attenuation_factor: parse_var(&var, "ARACHNID_ATTENUATION_FACTOR")?,
+            max_agents: parse_var(&var, "ARACHNID_MAX_AGENTS")?,
+        })
+    }
+
+    pub fn from_file(path: &Path) -> Result<Self> {
+        let contents = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read config file {}", path.display()))?;
+        toml::from_str(&contents).with_context(|| format!("Invalid config file {}", path.display()))
+    }
+
+    /// Keys a config file may set, as accepted by [`ConfigLayer::set`].
+    pub const KEYS: [&'static str; 12] = [
+        "openai_api_key",
+        "anthropic_api_key",
+        "brave_api_key",
+        "openai_base_url",
+        "anthropic_base_url",
+        "ollama_host",
+        "ollama_model",
+        "embedding",
+        "llm_max_retries",
+        "default_threshold",
+        "attenuation_factor",
+        "max_agents",
+    ];
+
+    /// Whether `key` holds a secret that shouldn't be echoed back.
+    pub fn is_secret(key: &str) -> bool {
+        key.ends_with("_api_key")
+    }
+
+    /// Set `key` from its string form, checking the key and value.
+    pub fn set(&mut self, key: &str, value: &str) -> Result<()> {
+        let value = value.trim();
+        let unit = |value: &str| -> Result<f32> {
+            let v: f32 = value
+                .parse()
+                .with_context(|| format!("Invalid value for {}", key))?;
+            anyhow::ensure!(
+                (0.0..=1.0).contains(&v),
+                "{} must be between 0.0 and 1.0",
+                key
+            );
+            Ok(v)
+        };
+
+        match key {
+            "openai_api_key" => self.openai_api_key = Some(value.to_string()),
+            "anthropic_api_key" => self.anthropic_api_key = Some(value.to_string()),
+            "brave_api_key" => self.brave_api_key = Some(value.to_string()),
+            "openai_base_url" | "anthropic_base_url" | "ollama_host" => {
+                anyhow::ensure!(
+                    value.starts_with("http://") || value.starts_with("https://"),
+                    "{} must be an http(s) URL",
+                    key
+                );
+                let url = Some(value.to_string());
+                match key {
+                    "openai_base_url" => self.openai_base_url = url,
+                    "anthropic_base_url" => self.anthropic_base_url = url,
+                    _ => self.ollama_host = url,
+                }
+            }
+            "ollama_model" => self.ollama_model = Some(value.to_string()),
+            "embedding" => self.embedding = Some(value.parse()?),
+            "llm_max_retries" => {
+                self.llm_max_retries = Some(
+                    value
+                        .parse()
+                        .with_context(|| format!("Invalid value for {}", key))?,
+                )
+            }
+            "default_threshold" => self.default_threshold = Some(unit(value)?),
+            "attenuation_factor" => {
+                let v = unit(value)?;
+                anyhow::ensure!(v > 0.0, "attenuation_factor must be greater than 0.0");
+                self.attenuation_factor = Some(v);
+            }
+            "max_agents" => {
+                let v: usize = value
+                    .parse()
+                    .with_context(|| format!("Invalid value for {}", key))?;
+                anyhow::ensure!(v >= 1, "max_agents must be at least 1");
+                self.max_agents = Some(v);
+            }
+            _ => anyhow::bail!(
+                "Unknown config key '{}'; expected one of: {}",
+                key,
+                Self::KEYS.join(", ")
+            ),
+        }
+        Ok(())
+    }
+
+    pub fn unset(&mut self, key: &str) -> Result<()> {
+        match key {
+            "openai_api_key" => self.openai_api_key = None,
+            "anthropic_api_key" => self.anthropic_api_key = None,
+            "brave_api_key" => self.brave_api_key = None,
+            "openai_base_url" => self.openai_base_url = None,
+            "anthropic_base_url" => self.anthropic_base_url = None,
+            "ollama_host" => self.ollama_host = None,
+            "ollama_model" => self.ollama_model = None,
+            "embedding" => self.embedding = None,
+            "llm_max_retries" => self.llm_max_retries = None,
+            "default_threshold" => self.default_threshold = None,
+            "attenuation_factor" => self.attenuation_factor = None,
+            "max_agents" => self.max_agents = None,
+            _ => anyhow::bail!(
+                "Unknown config key '{}'; expected one of: {}",
+                key,
+                Self::KEYS.join(", ")
+            ),
+        }
+        Ok(())
+    }
+
+    /// Apply `edit` to the config file at `path`, creating it if absent.
+    pub fn update_file(path: &Path, edit: impl FnOnce(&mut Self) -> Result<()>) -> Result<()> {
+        let mut layer = if path.exists() {
+            Self::from_file(path)?
+        } else {
+            Self::default()
+        };
+        edit(&mut layer)?;
+
+        let contents = toml::to_string(&layer).context("Failed to serialize config")?;
+        std::fs::write(path, contents)
+            .with_context(|| format!("Failed to write config file {}", path.display()))
+    }
+
+    fn defaults() -> Self {
+        let web = WebConfig::default();
+        Self {
+            openai_base_url: Some(OPENAI_BASE_URL.to_string()),
+            anthropic_base_url: Some(ANTHROPIC_BASE_URL.to_string()),
+            llm_max_retries: Some(DEFAULT_LLM_MAX_RETRIES),
+            default_threshold: Some(web.default_threshold),
+            attenuation_factor: Some(web.attenuation_factor),
+            max_agents: Some(web.max_agents),
+            ..Self::default()
+        }
+    }
+}
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Config {
     pub openai_api_key: Option<String>,
     pub anthropic_api_key: Option<String>,
     pub brave_api_key: Option<String>,
+    pub openai_base_url: Option<String>,
+    pub anthropic_base_url: Option<String>,
+    /// Local Ollama server, used for completions when no cloud API key is set.
+    pub ollama_host: Option<String>,
+    pub ollama_model: Option<String>,
+    /// Embedding provider chosen explicitly; see [`Config::embedding_backend`].
+    pub embedding: Option<EmbeddingBackend>,
+    /// Retries for rate-limited (429) and failed (5xx) LLM requests.
+    pub llm_max_retries: u32,
+    /// Coordination defaults applied to newly created webs.
+    pub default_threshold: f32,
+    pub attenuation_factor: f32,
+    pub max_agents: usize,
+    /// Layer each set field was resolved from, keyed by field name.
+    #[serde(skip)]
+    pub sources: BTreeMap<&'static str, ConfigSource>,
 }
 
 impl Config {
+    /// Environment variables over built-in defaults, ignoring any config file.
+    /// Key files that can't be read are reported and treated as unset.
     pub fn from_env() -> Self {
+        let env = ConfigLayer::from_env(&EnvSecretProvider::new()).unwrap_or_else(|e| {
+            tracing::warn!("Ignoring environment config: {:#}", e);
+            ConfigLayer::default()
+        });
+        Self::merge(&ConfigLayer::default(), &env, None)
+    }
+
+    /// Resolve config with precedence CLI flags > env vars > config file >
+    /// built-in defaults, merged field by field. The file is `config_path` if
+    /// given (and must exist), otherwise `arachnid.toml` in the working
+    /// directory when present.
+    ///
+    /// API keys are read from `NAME` or the file at `NAME_FILE`; use
+    /// [`Config::load_with_secrets`] to resolve them elsewhere.
+    pub fn load(cli: &ConfigLayer, config_path: Option<&Path>) -> Result<Self> {
+        Self::load_with_secrets(cli, config_path, &EnvSecretProvider::new())
+    }
+
+    pub fn load_with_secrets(
+        cli: &ConfigLayer,
+        config_path: Option<&Path>,
+        secrets: &dyn SecretProvider,
+    ) -> Result<Self> {
+        let file = match config_path {
+            Some(path) => Some(ConfigLayer::from_file(path)?),
+            None => {
+                let default_path = PathBuf::from(CONFIG_FILE);
+                if default_path.exists() {
+                    Some(ConfigLayer::from_file(&default_path)?)
+                } else {
+                    None
+                }
+            }
+        };
+
+        Ok(Self::merge(
+            cli,
+            &ConfigLayer::from_env(secrets)?,
+            file.as_ref(),
+        ))
+    }
+
+    fn merge(cli: &ConfigLayer, env: &ConfigLayer, file: Option<&ConfigLayer>) -> Self {
+        let defaults = ConfigLayer::defaults();
+        let empty = ConfigLayer::default();
+        let file = file.unwrap_or(&empty);
+
+        let layers = [
+            (ConfigSource::Cli, cli),
+            (ConfigSource::Env, env),
+            (ConfigSource::File, file),
+            (ConfigSource::Default, &defaults),
+        ];
+
+        let mut sources = BTreeMap::new();
+        let mut pick = |name, field| resolve(&layers, &mut sources, name, field);
+
+        let openai_api_key = pick("openai_api_key", |l| &l.openai_api_key);
+        let anthropic_api_key = pick("anthropic_api_key", |l| &l.anthropic_api_key);
+        let brave_api_key = pick("brave_api_key", |l| &l.brave_api_key);
+        let openai_base_url = pick("openai_base_url", |l| &l.openai_base_url);
+        let anthropic_base_url = pick("anthropic_base_url", |l| &l.anthropic_base_url);
+        let ollama_host = pick("ollama_host", |l| &l.ollama_host);
+        let ollama_model = pick("ollama_model", |l| &l.ollama_model);
+        let embedding = resolve(&layers, &mut sources, "embedding", |l| &l.embedding);
+
+        // The defaults layer sets all of these, so the fallbacks never apply.
+        let web = WebConfig::default();
+        let default_threshold = resolve(&layers, &mut sources, "default_threshold", |l| {
+            &l.default_threshold
+        });
+        let attenuation_factor = resolve(&layers, &mut sources, "attenuation_factor", |l| {
+            &l.attenuation_factor
+        });
+        let max_agents = resolve(&layers, &mut sources, "max_agents", |l| &l.max_agents);
+        let llm_max_retries = resolve(&layers, &mut sources, "llm_max_retries", |l| {
+            &l.llm_max_retries
+        });
+
         Self {
-            openai_api_key: std::env::var("OPENAI_API_KEY").ok(),
-            anthropic_api_key: std::env::var("ANTHROPIC_API_KEY").ok(),
-            brave_api_key: std::env::var("BRAVE_API_KEY").ok(),
+            openai_api_key,
+            anthropic_api_key,
+            brave_api_key,
+            openai_base_url,
+            anthropic_base_url,
+            ollama_host,
+            ollama_model,
+            embedding,
+            llm_max_retries: llm_max_retries.unwrap_or(DEFAULT_LLM_MAX_RETRIES),
+            default_threshold: default_threshold.unwrap_or(web.default_threshold),
+            attenuation_factor: attenuation_factor.unwrap_or(web.attenuation_factor),
+            max_agents: max_agents.unwrap_or(web.max_agents),
+            sources,
         }
     }
+
+    /// Config for a new web, with the coordination defaults applied.
+    pub fn web_config(&self) -> WebConfig {
+        WebConfig {
+            default_threshold: self.default_threshold,
+            attenuation_factor: self.attenuation_factor,
+            max_agents: self.max_agents,
+            ..WebConfig::default()
+        }
+    }
+
+    /// Whether completions go to Ollama: it is configured and neither cloud
+    /// API key is set.
+    pub fn uses_ollama(&self) -> bool {
+        (self.ollama_host.is_some() || self.ollama_model.is_some())
+            && self.anthropic_api_key.is_none()
+            && self.openai_api_key.is_none()
+    }
+
+    /// The configured embedding backend, or OpenAI when its key is set and
+    /// hashing otherwise.
+    pub fn embedding_backend(&self) -> EmbeddingBackend {
+        self.embedding.unwrap_or(if self.openai_api_key.is_some() {
+            EmbeddingBackend::OpenAI
+        } else {
+            EmbeddingBackend::Hashing
+        })
+    }
+
+    /// Where `field` was resolved from, or `None` if it is unset.
+    pub fn source(&self, field: &str) -> Option<ConfigSource> {
+        self.sources.get(field).copied()
+    }
+}
+
+impl Default for Config {
+    /// Built-in defaults only.
+    fn default() -> Self {
+        Self::merge(&ConfigLayer::default(), &ConfigLayer::default(), None)
+    }
+}
+
+/// First layer that sets `field`, recording which one it was.
+fn resolve<T: Clone>(
+    layers: &[(ConfigSource, &ConfigLayer)],
+    sources: &mut BTreeMap<&'static str, ConfigSource>,
+    name: &'static str,
+    field: fn(&ConfigLayer) -> &Option<T>,
+) -> Option<T> {
+    layers.iter().find_map(|(source, layer)| {
+        field(layer).clone().inspect(|_| {
+            sources.insert(name, *source);
+        })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn layer(openai_base_url: Option<&str>, brave_api_key: Option<&str>) -> ConfigLayer {
+        ConfigLayer {
+            openai_base_url: openai_base_url.map(String::from),
+            brave_api_key: brave_api_key.map(String::from),
+            ..ConfigLayer::default()
+        }
+    }
+
+    #[test]
+    fn test_defaults_apply_when_nothing_is_set() {
+        let config = Config::merge(&ConfigLayer::default(), &ConfigLayer::default(), None);
+
+        assert_eq!(config.openai_base_url.as_deref(), Some(OPENAI_BASE_URL));
+        assert_eq!(
+            config.source("openai_base_url"),
+            Some(ConfigSource::Default)
+        );
+        assert_eq!(config.brave_api_key, None);
+        assert_eq!(config.source("brave_api_key"), None);
+    }
+
+    #[test]
+    fn test_file_overrides_defaults() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(CONFIG_FILE);
+        std::fs::write(
+            &path,
+            "openai_base_url = \"http://file\"\nbrave_api_key = \"file-key\"\n",
+        )
+        .unwrap();
+        let file = ConfigLayer::from_file(&path).unwrap();
+
+        let config = Config::merge(
+            &ConfigLayer::default(),
+            &ConfigLayer::default(),
+            Some(&file),
+        );
+
+        assert_eq!(config.openai_base_url.as_deref(), Some("http://file"));
+        assert_eq!(config.source("openai_base_url"), Some(ConfigSource::File));
+        assert_eq!(config.brave_api_key.as_deref(), Some("file-key"));
+        assert_eq!(
+            config.anthropic_base_url.as_deref(),
+            Some(ANTHROPIC_BASE_URL)
+        );
+    }
+
+    #[test]
+    fn test_env_overrides_file() {
+        let file = layer(Some("http://file"), Some("file-key"));
+        let env = ConfigLayer::from_vars(
+            |name| (name == "OPENAI_BASE_URL").then(|| "http://env".to_string()),
+            &EnvSecretProvider::with_lookup(|_| None),
+        )
+        .unwrap();
+
+        let config = Config::merge(&ConfigLayer::default(), &env, Some(&file));
+
+        assert_eq!(config.openai_base_url.as_deref(), Some("http://env"));
+        assert_eq!(config.source("openai_base_url"), Some(ConfigSource::Env));
+        // Fields the env leaves unset still come from the file.
+        assert_eq!(config.brave_api_key.as_deref(), Some("file-key"));
+        assert_eq!(config.source("brave_api_key"), Some(ConfigSource::File));
+    }
+
+    #[test]
+    fn test_cli_overrides_env() {
+        let file = layer(Some("http://file"), None);
+        let env = layer(Some("http://env"), Some("env-key"));
+        let cli = layer(Some("http://cli"), None);
+
+        let config = Config::merge(&cli, &env, Some(&file));
+
+        assert_eq!(config.openai_base_url.as_deref(), Some("http://cli"));
+        assert_eq!(config.source("openai_base_url"), Some(ConfigSource::Cli));
+        assert_eq!(config.brave_api_key.as_deref(), Some("env-key"));
+        assert_eq!(config.source("brave_api_key"), Some(ConfigSource::Env));
+    }
+
+    #[test]
+    fn test_unknown_file_keys_are_rejected() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(CONFIG_FILE);
+        std::fs::write(&path, "open_ai_key = \"typo\"\n").unwrap();
+
+        assert!(ConfigLayer::from_file(&path).is_err());
+    }
+
+    #[test]
+    fn test_set_then_load_reflects_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(CONFIG_FILE);
+        std::fs::write(&path, "brave_api_key = \"file-key\"\n").unwrap();
+
+        ConfigLayer::update_file(&path, |layer| layer.set("max_agents", "25")).unwrap();
+        ConfigLayer::update_file(&path, |layer| layer.unset("brave_api_key")).unwrap();
+
+        let file = ConfigLayer::from_file(&path).unwrap();
+        let config = Config::merge(
+            &ConfigLayer::default(),
+            &ConfigLayer::default(),
+            Some(&file),
+        );
+        assert_eq!(config.max_agents, 25);
+        assert_eq!(config.source("max_agents"), Some(ConfigSource::File));
+        assert_eq!(config.brave_api_key, None);
+
+        // Created when missing.
+        let new_path = dir.path().join("new.toml");
+        ConfigLayer::update_file(&new_path, |layer| {
+            layer.set("openai_base_url", "http://localhost:8000")
+        })
+        .unwrap();
+        assert_eq!(
+            ConfigLayer::from_file(&new_path)
+                .unwrap()
+                .openai_base_url
+                .as_deref(),
+            Some("http://localhost:8000")
+        );
+    }
+
+    #[test]
+    fn test_set_rejects_unknown_keys_and_bad_values() {
+        let mut layer = ConfigLayer::default();
+        assert!(layer.set("open_ai_key", "x").is_err());
+        assert!(layer.set("default_threshold", "1.5").is_err());
+        assert!(layer.set("attenuation_factor", "0").is_err());
+        assert!(layer.set("max_agents", "many").is_err());
+        assert!(layer.set("llm_max_retries", "-1").is_err());
+        assert!(layer.set("openai_base_url", "localhost").is_err());
+        assert!(layer.unset("open_ai_key").is_err());
+        assert_eq!(layer, ConfigLayer::default());
+    }
+
+    #[test]
+    fn test_api_key_from_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let key_path = dir.path().join("openai_key");
+        std::fs::write(&key_path, "  sk-from-file\n").unwrap();
+
+        let path = key_path.to_string_lossy().to_string();
+        let secrets = EnvSecretProvider::with_lookup(move |name| {
+            (name == "OPENAI_API_KEY_FILE").then(|| path.clone())
+        });
+        let env = ConfigLayer::from_vars(|_| None, &secrets).unwrap();
+        let config = Config::merge(&ConfigLayer::default(), &env, None);
+
+        assert_eq!(config.openai_api_key.as_deref(), Some("sk-from-file"));
+        assert_eq!(config.source("openai_api_key"), Some(ConfigSource::Env));
+        assert_eq!(config.anthropic_api_key, None);
+    }
+
+    #[test]
+    fn test_missing_key_file_is_an_error() {
+        let secrets = EnvSecretProvider::with_lookup(|name| {
+            (name == "BRAVE_API_KEY_FILE").then(|| "/nonexistent/brave_key".to_string())
+        });
+
+        assert!(ConfigLayer::from_vars(|_| None, &secrets).is_err());
+    }
+
+    #[test]
+    fn test_file_secret_provider() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("ANTHROPIC_API_KEY"), "sk-ant\n").unwrap();
+        let secrets = FileSecretProvider::new(dir.path());
+
+        assert_eq!(
+            secrets.get("ANTHROPIC_API_KEY").unwrap().as_deref(),
+            Some("sk-ant")
+        );
+        assert_eq!(secrets.get("OPENAI_API_KEY").unwrap(), None);
+    }
+
+    #[test]
+    fn test_ollama_only_without_cloud_keys() {
+        let env = ConfigLayer::from_vars(
+            |name| (name == "OLLAMA_HOST").then(|| "http://gpu-box:11434".to_string()),
+            &EnvSecretProvider::with_lookup(|_| None),
+        )
+        .unwrap();
+        let config = Config::merge(&ConfigLayer::default(), &env, None);
+        assert_eq!(config.ollama_host.as_deref(), Some("http://gpu-box:11434"));
+        assert_eq!(config.source("ollama_host"), Some(ConfigSource::Env));
+        assert!(config.uses_ollama());
+
+        let cloud = ConfigLayer {
+            anthropic_api_key: Some("sk-ant".to_string()),
+            ..ConfigLayer::default()
+        };
+        assert!(!Config::merge(&cloud, &env, None).uses_ollama());
+        assert!(!Config::default().uses_ollama());
+    }
+
+    #[test]
+    fn test_embedding_backend() {
+        assert_eq!(
+            Config::default().embedding_backend(),
+            EmbeddingBackend::Hashing
+        );
+        let with_key = ConfigLayer {
+            openai_api_key: Some("sk".to_string()),
+            ..ConfigLayer::default()
+        };
+        assert_eq!(
+            Config::merge(&with_key, &ConfigLayer::default(), None).embedding_backend(),
+            EmbeddingBackend::OpenAI
+        );
+
+        let env = ConfigLayer::from_vars(
+            |name| (name == "ARACHNID_EMBEDDING").then(|| "Hashing".to_string()),
+            &EnvSecretProvider::with_lookup(|_| None),
+        )
+        .unwrap();
+        let config = Config::merge(&with_key, &env, None);
+        assert_eq!(config.embedding_backend(), EmbeddingBackend::Hashing);
+        assert_eq!(config.source("embedding"), Some(ConfigSource::Env));
+
+        assert!(ConfigLayer::from_vars(
+            |name| (name == "ARACHNID_EMBEDDING").then(|| "word2vec".to_string()),
+            &EnvSecretProvider::with_lookup(|_| None),
+        )
+        .is_err());
+        assert!(ConfigLayer::default().set("embedding", "word2vec").is_err());
+    }
 }
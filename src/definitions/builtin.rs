@@ -33,6 +33,7 @@ The system will create appropriately specialized agents.
 Use emit_signal to communicate progress and findings."#
             .to_string(),
         temperature: 0.4,
+        max_tokens: None,
         tools: vec![
             ToolType::SpawnAgent,
             ToolType::EmitSignal,
@@ -44,6 +45,7 @@ Use emit_signal to communicate progress and findings."#
         use_count: 0,
         created_at: Utc::now(),
         version: Some("1.0.0".to_string()),
+        deleted_at: None,
     }
 }
 
@@ -1,22 +1,81 @@
 use anyhow::{anyhow, Result};
 use chrono::Utc;
+use std::collections::HashMap;
 use std::sync::Arc;
 use uuid::Uuid;
 
 use crate::providers::{EmbeddingProvider, LLMProvider, Message};
 
-use super::{AgentDefinition, DefinitionSource, ToolType};
+use super::{
+    clamp_temperature, normalize_keywords, AgentDefinition, DefinitionId, DefinitionSource,
+    ToolType, MAX_TEMPERATURE, MIN_TEMPERATURE,
+};
 
 const DEFINITION_SYSTEM_PROMPT: &str = r#"You are an expert at designing AI agent configurations.
 Create focused, single-purpose agents with clear instructions.
 Agents should use emit_signal to communicate results.
 Keep system prompts concise but complete.
-Only include tools the agent actually needs.
-Output valid YAML only, no markdown code fences or explanation."#;
+Only include tools the agent actually needs."#;
+
+const AVAILABLE_TOOLS: &str = r#"- web_search: Search the internet for information
+- fetch_url: Retrieve contents of a web page
+- http_request: Call an HTTP API with a method, headers and body
+- read_file: Read a file from the filesystem
+- write_file: Write content to a file
+- execute_code: Run code in a sandboxed environment
+- run_tests: Run a project's test suite and report failures
+- emit_signal: Emit a signal to other agents
+- spawn_agent: Create a child agent for a subtask
+- search_codebase: Search code with semantic or regex queries
+- query_database: Execute read-only SQL queries
+- list_directory: List files and directories in the sandbox
+- delete_file: Delete a file or directory in the sandbox"#;
+
+/// Largest team `generate_team` accepts.
+pub const MAX_TEAM_SIZE: usize = 6;
+
+/// Format the model is asked to write definitions in. Either format is
+/// accepted when parsing, whichever was requested.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DefinitionFormat {
+    #[default]
+    Yaml,
+    Json,
+}
+
+impl DefinitionFormat {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.to_lowercase().as_str() {
+            "yaml" | "yml" => Some(DefinitionFormat::Yaml),
+            "json" => Some(DefinitionFormat::Json),
+            _ => None,
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            DefinitionFormat::Yaml => "YAML",
+            DefinitionFormat::Json => "JSON",
+        }
+    }
+
+    fn instruction(&self) -> String {
+        format!(
+            "Output valid {} only, no markdown code fences or explanation.",
+            self.name()
+        )
+    }
+}
+
+/// Extra attempts after the first when the model's output can't be used.
+pub const DEFAULT_MAX_RETRIES: usize = 2;
 
 pub struct DefinitionGenerator {
     llm_provider: Arc<dyn LLMProvider>,
     embedding_provider: Arc<dyn EmbeddingProvider>,
+    max_retries: usize,
+    format: DefinitionFormat,
+    strict: bool,
 }
 
 impl DefinitionGenerator {
@@ -27,79 +86,228 @@ impl DefinitionGenerator {
         Self {
             llm_provider,
             embedding_provider,
+            max_retries: DEFAULT_MAX_RETRIES,
+            format: DefinitionFormat::default(),
+            strict: false,
         }
     }
 
+    pub fn with_format(mut self, format: DefinitionFormat) -> Self {
+        self.format = format;
+        self
+    }
+
+    /// Treat unknown tool names and out-of-range temperatures as an invalid
+    /// definition (so the model is asked to fix them) instead of dropping or
+    /// clamping them with a warning.
+    pub fn with_strict(mut self, strict: bool) -> Self {
+        self.strict = strict;
+        self
+    }
+
+    pub fn with_max_retries(mut self, max_retries: usize) -> Self {
+        self.max_retries = max_retries;
+        self
+    }
+
+    /// Ask the model for a definition, feeding parse or validation errors
+    /// back to it for up to `max_retries` corrections.
     pub async fn generate(&self, need: &str) -> Result<AgentDefinition> {
         let prompt = self.build_generation_prompt(need);
-
-        let response = self
-            .llm_provider
-            .complete(vec![
-                Message::system(DEFINITION_SYSTEM_PROMPT.to_string()),
-                Message::user(prompt),
-            ])
+        let mut definition = self
+            .complete_until_valid(prompt, need, |response| {
+                self.parse_generated_definition(response, need)
+            })
             .await?;
 
-        let mut definition = self.parse_generated_definition(&response, need)?;
         definition.tuning_embedding = self.compute_embedding(&definition).await?;
 
         Ok(definition)
     }
 
+    /// Ask the model for a coordinator plus specialists in one call. Members
+    /// come back ordered so every parent precedes its children, with the
+    /// coordinator first.
+    pub async fn generate_team(&self, need: &str) -> Result<Vec<TeamMember>> {
+        let prompt = self.build_team_prompt(need);
+        let mut team = self
+            .complete_until_valid(prompt, need, |response| {
+                self.parse_generated_team(response, need)
+            })
+            .await?;
+
+        for member in &mut team {
+            member.definition.tuning_embedding = self.compute_embedding(&member.definition).await?;
+        }
+
+        Ok(team)
+    }
+
+    async fn complete_until_valid<T>(
+        &self,
+        prompt: String,
+        need: &str,
+        parse: impl Fn(&str) -> Result<T>,
+    ) -> Result<T> {
+        let mut messages = vec![
+            Message::system(format!(
+                "{}\n{}",
+                DEFINITION_SYSTEM_PROMPT,
+                self.format.instruction()
+            )),
+            Message::user(prompt),
+        ];
+
+        let mut attempt = 0;
+        loop {
+            let response = self.llm_provider.complete(messages.clone()).await?;
+
+            match parse(&response) {
+                Ok(parsed) => return Ok(parsed),
+                Err(e) if attempt < self.max_retries => {
+                    attempt += 1;
+                    tracing::warn!(
+                        attempt,
+                        error = %e,
+                        "Generated definition was invalid, asking the model to fix it"
+                    );
+                    messages.push(Message::assistant(response));
+                    messages.push(Message::user(format!(
+                        "That output could not be used: {}\n\nFix the problem and output the complete agent definition again. {}",
+                        e,
+                        self.format.instruction()
+                    )));
+                }
+                Err(e) => {
+                    return Err(anyhow!(
+                        "Failed to generate a valid definition for '{}' after {} attempt(s): {}",
+                        need,
+                        attempt + 1,
+                        e
+                    ))
+                }
+            }
+        }
+    }
+
     fn build_generation_prompt(&self, need: &str) -> String {
+        let format = self.format.name();
+        let instruction = self.format.instruction();
         format!(
             r#"Generate an agent definition for the following need:
 
 Need: {need}
 
 Available tools the agent can use:
-- web_search: Search the internet for information
-- fetch_url: Retrieve contents of a web page
-- read_file: Read a file from the filesystem
-- write_file: Write content to a file
-- execute_code: Run code in a sandboxed environment
-- emit_signal: Emit a signal to other agents
-- spawn_agent: Create a child agent for a subtask
-- search_codebase: Search code with semantic or regex queries
-- query_database: Execute read-only SQL queries
+{AVAILABLE_TOOLS}
 
-Output a YAML agent definition with:
+Output a {format} agent definition with:
 - name: A short, descriptive name (lowercase, hyphens)
 - tuning_keywords: 5-10 keywords this agent should respond to
 - system_prompt: Instructions for the agent
 - temperature: 0.1-0.9 (lower = more focused)
+- max_tokens: Optional cap on each response (e.g. 512 for a terse router, 8192 for a synthesizer)
 - tools: List of tools this agent needs
 
-Only output valid YAML, no markdown code fences or explanation."#
+{instruction}"#
+        )
+    }
+
+    fn build_team_prompt(&self, need: &str) -> String {
+        let format = self.format.name();
+        let instruction = self.format.instruction();
+        format!(
+            r#"Design a small team of agents (2-{MAX_TEAM_SIZE}) for the following need:
+
+Need: {need}
+
+The team has one coordinator that breaks the work down, plus specialists
+that each handle one part of it. Specialists may have their own children.
+
+Available tools the agents can use:
+{AVAILABLE_TOOLS}
+
+Output a {format} document with a `team` list. Each entry has:
+- name: A short, unique, descriptive name (lowercase, hyphens)
+- parent: The name of the agent it reports to (omit for the coordinator)
+- purpose: One sentence describing its part of the work
+- tuning_keywords: 5-10 keywords this agent should respond to
+- system_prompt: Instructions for the agent
+- temperature: 0.1-0.9 (lower = more focused)
+- max_tokens: Optional cap on each response (e.g. 512 for a terse router, 8192 for a synthesizer)
+- tools: List of tools this agent needs
+
+{instruction}"#
         )
     }
 
     fn parse_generated_definition(&self, response: &str, need: &str) -> Result<AgentDefinition> {
-        let yaml_content = response
-            .trim()
-            .trim_start_matches("```yaml")
-            .trim_start_matches("```")
-            .trim_end_matches("```")
-            .trim();
+        self.definition_from_value(&parse_document(response)?, need)
+    }
 
-        let parsed: serde_yaml::Value = serde_yaml::from_str(yaml_content)
-            .map_err(|e| anyhow!("Failed to parse YAML: {}", e))?;
+    fn parse_generated_team(&self, response: &str, need: &str) -> Result<Vec<TeamMember>> {
+        let parsed = parse_document(response)?;
+        let entries = parsed["team"]
+            .as_array()
+            .or_else(|| parsed["agents"].as_array())
+            .or_else(|| parsed.as_array())
+            .ok_or_else(|| anyhow!("Expected a `team` list of agent definitions"))?;
+
+        if entries.len() < 2 || entries.len() > MAX_TEAM_SIZE {
+            return Err(anyhow!(
+                "A team needs between 2 and {} agents, got {}",
+                MAX_TEAM_SIZE,
+                entries.len()
+            ));
+        }
+
+        let mut members = Vec::with_capacity(entries.len());
+        for (i, entry) in entries.iter().enumerate() {
+            let name = entry["name"]
+                .as_str()
+                .ok_or_else(|| anyhow!("Team member {} has no name", i + 1))?;
+            let definition = self
+                .definition_from_value(entry, need)
+                .map_err(|e| anyhow!("Team member '{}': {}", name, e))?;
+            let purpose = entry["purpose"]
+                .as_str()
+                .map(str::to_string)
+                .unwrap_or_else(|| name.to_string());
+            members.push((definition, purpose, entry["parent"].as_str()));
+        }
+
+        order_team(members)
+    }
+
+    fn definition_from_value(
+        &self,
+        parsed: &serde_json::Value,
+        need: &str,
+    ) -> Result<AgentDefinition> {
+        if !parsed.is_object() {
+            return Err(anyhow!(
+                "Expected a mapping with name, tuning_keywords, system_prompt, temperature and tools"
+            ));
+        }
 
         let name = parsed["name"]
             .as_str()
             .map(|s| s.to_string())
             .unwrap_or_else(|| self.generate_name_from_need(need));
 
-        let tuning_keywords: Vec<String> = parsed["tuning_keywords"]
-            .as_sequence()
-            .or_else(|| parsed["tuning"]["keywords"].as_sequence())
+        // Normalized before the embedding is computed from them.
+        let tuning_keywords = parsed["tuning_keywords"]
+            .as_array()
+            .or_else(|| parsed["tuning"]["keywords"].as_array())
             .map(|seq| {
-                seq.iter()
+                let keywords: Vec<String> = seq
+                    .iter()
                     .filter_map(|v| v.as_str().map(|s| s.to_string()))
-                    .collect()
+                    .collect();
+                normalize_keywords(&keywords)
             })
-            .unwrap_or_else(|| self.extract_keywords_from_need(need));
+            .filter(|keywords| !keywords.is_empty())
+            .unwrap_or_else(|| normalize_keywords(&self.extract_keywords_from_need(need)));
 
         let system_prompt = parsed["system_prompt"]
             .as_str()
@@ -113,16 +321,66 @@ Only output valid YAML, no markdown code fences or explanation."#
             .map(|t| t as f32)
             .unwrap_or(0.4);
 
+        let clamped = clamp_temperature(temperature);
+        if clamped != temperature {
+            if self.strict {
+                return Err(anyhow!(
+                    "temperature {} is outside the valid range {}-{}",
+                    temperature,
+                    MIN_TEMPERATURE,
+                    MAX_TEMPERATURE
+                ));
+            }
+            tracing::warn!(need, temperature, clamped, "Clamping generated temperature");
+        }
+        let temperature = clamped;
+
+        let max_tokens = parsed["max_tokens"]
+            .as_u64()
+            .or_else(|| parsed["llm"]["max_tokens"].as_u64())
+            .filter(|&t| t > 0)
+            .map(|t| t.min(u32::MAX as u64) as u32);
+
+        let mut unknown_tools = Vec::new();
         let tools: Vec<ToolType> = parsed["tools"]
-            .as_sequence()
+            .as_array()
             .map(|seq| {
                 seq.iter()
                     .filter_map(|v| v.as_str())
-                    .filter_map(ToolType::parse)
+                    .filter_map(|name| {
+                        let tool = ToolType::parse(name);
+                        if tool.is_none() {
+                            unknown_tools.push(name.to_string());
+                        }
+                        tool
+                    })
                     .collect()
             })
             .unwrap_or_else(|| vec![ToolType::EmitSignal]);
 
+        if !unknown_tools.is_empty() {
+            let valid: Vec<&str> = ToolType::all().iter().map(|t| t.as_str()).collect();
+            let message = format!(
+                "Unknown tools: {} (valid tools are: {})",
+                unknown_tools.join(", "),
+                valid.join(", ")
+            );
+            if tools.is_empty() {
+                return Err(anyhow!(
+                    "Generated definition has no valid tools. {}",
+                    message
+                ));
+            }
+            if self.strict {
+                return Err(anyhow!("{}", message));
+            }
+            tracing::warn!(
+                need,
+                "Dropping tools from generated definition. {}",
+                message
+            );
+        }
+
         if tools.is_empty() {
             return Err(anyhow!("Generated definition has no valid tools"));
         }
@@ -134,12 +392,14 @@ Only output valid YAML, no markdown code fences or explanation."#
             tuning_embedding: vec![],
             system_prompt,
             temperature,
+            max_tokens,
             tools,
             source: DefinitionSource::Generated,
             health_score: 1.0,
             use_count: 0,
             created_at: Utc::now(),
             version: Some("1.0.0".to_string()),
+            deleted_at: None,
         })
     }
 
@@ -179,16 +439,121 @@ Only output valid YAML, no markdown code fences or explanation."#
     }
 }
 
+/// One generated agent and where it sits in the team.
+#[derive(Debug, Clone)]
+pub struct TeamMember {
+    pub definition: AgentDefinition,
+    /// This member's part of the overall need.
+    pub purpose: String,
+    /// Definition of the member this one reports to; `None` for the coordinator.
+    pub parent: Option<DefinitionId>,
+}
+
+/// Resolve parent names and order members breadth-first from the single
+/// coordinator, rejecting unknown parents, duplicate names and cycles.
+fn order_team(members: Vec<(AgentDefinition, String, Option<&str>)>) -> Result<Vec<TeamMember>> {
+    let mut ids: HashMap<&str, DefinitionId> = HashMap::new();
+    for (definition, _, _) in &members {
+        if ids.insert(&definition.name, definition.id).is_some() {
+            return Err(anyhow!("Duplicate team member name '{}'", definition.name));
+        }
+    }
+
+    let mut pending = Vec::with_capacity(members.len());
+    for (definition, purpose, parent) in &members {
+        let parent = match parent {
+            Some(parent) if *parent == definition.name => {
+                return Err(anyhow!("'{}' cannot be its own parent", parent))
+            }
+            Some(parent) => Some(*ids.get(parent).ok_or_else(|| {
+                anyhow!(
+                    "'{}' reports to '{}', which is not in the team",
+                    definition.name,
+                    parent
+                )
+            })?),
+            None => None,
+        };
+        pending.push(TeamMember {
+            definition: definition.clone(),
+            purpose: purpose.clone(),
+            parent,
+        });
+    }
+
+    let roots: Vec<&str> = pending
+        .iter()
+        .filter(|m| m.parent.is_none())
+        .map(|m| m.definition.name.as_str())
+        .collect();
+    if roots.len() != 1 {
+        return Err(anyhow!(
+            "A team needs exactly one coordinator without a parent, found {}: {}",
+            roots.len(),
+            roots.join(", ")
+        ));
+    }
+
+    let mut ordered: Vec<TeamMember> = Vec::with_capacity(pending.len());
+    let mut next = 0;
+    let (root, mut rest): (Vec<_>, Vec<_>) = pending.into_iter().partition(|m| m.parent.is_none());
+    ordered.extend(root);
+    while next < ordered.len() {
+        let parent_id = ordered[next].definition.id;
+        let (children, remaining): (Vec<_>, Vec<_>) =
+            rest.into_iter().partition(|m| m.parent == Some(parent_id));
+        ordered.extend(children);
+        rest = remaining;
+        next += 1;
+    }
+
+    if !rest.is_empty() {
+        let names: Vec<&str> = rest.iter().map(|m| m.definition.name.as_str()).collect();
+        return Err(anyhow!(
+            "Team members not reachable from the coordinator (cycle?): {}",
+            names.join(", ")
+        ));
+    }
+
+    Ok(ordered)
+}
+
+/// Parse a definition written as JSON or YAML, with or without code fences.
+/// Anything that looks like a JSON object is tried as JSON first; YAML is a
+/// superset of JSON, so the YAML parser is the fallback either way.
+fn parse_document(response: &str) -> Result<serde_json::Value> {
+    let content = response
+        .trim()
+        .trim_start_matches("```yaml")
+        .trim_start_matches("```yml")
+        .trim_start_matches("```json")
+        .trim_start_matches("```")
+        .trim_end_matches("```")
+        .trim();
+
+    if content.starts_with('{') {
+        if let Ok(value) = serde_json::from_str(content) {
+            return Ok(value);
+        }
+    }
+
+    serde_yaml::from_str(content).map_err(|e| {
+        if content.starts_with('{') {
+            anyhow!("Failed to parse JSON or YAML: {}", e)
+        } else {
+            anyhow!("Failed to parse YAML: {}", e)
+        }
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
     #[test]
     fn test_generate_name_from_need() {
-        let generator = DefinitionGenerator {
-            llm_provider: Arc::new(MockLLMProvider),
-            embedding_provider: Arc::new(MockEmbeddingProvider),
-        };
+        let generator =
+            DefinitionGenerator::new(Arc::new(MockLLMProvider), Arc::new(MockEmbeddingProvider));
 
         assert_eq!(
             generator.generate_name_from_need("analyze security vulnerabilities"),
@@ -202,10 +567,8 @@ mod tests {
 
     #[test]
     fn test_extract_keywords_from_need() {
-        let generator = DefinitionGenerator {
-            llm_provider: Arc::new(MockLLMProvider),
-            embedding_provider: Arc::new(MockEmbeddingProvider),
-        };
+        let generator =
+            DefinitionGenerator::new(Arc::new(MockLLMProvider), Arc::new(MockEmbeddingProvider));
 
         let keywords =
             generator.extract_keywords_from_need("analyze security vulnerabilities in code");
@@ -215,6 +578,271 @@ mod tests {
         assert!(keywords.contains(&"code".to_string()));
     }
 
+    #[tokio::test]
+    async fn test_generate_retries_invalid_yaml() {
+        let llm = Arc::new(ScriptedLLMProvider::new(vec![
+            "name: [unclosed\ntools: - emit_signal",
+            "name: fixed-agent\ntuning_keywords:\n  - fixed\ntools:\n  - emit_signal",
+        ]));
+        let generator = DefinitionGenerator::new(llm.clone(), Arc::new(MockEmbeddingProvider));
+
+        let definition = generator.generate("fix things").await.unwrap();
+        assert_eq!(definition.name, "fixed-agent");
+
+        let calls = llm.calls.lock().unwrap();
+        assert_eq!(calls.len(), 2);
+        let retry = &calls[1];
+        assert_eq!(retry.len(), 4);
+        assert_eq!(retry[2].role, "assistant");
+        assert!(retry[3].content.contains("Failed to parse YAML"));
+    }
+
+    #[tokio::test]
+    async fn test_generate_gives_up_after_max_retries() {
+        let llm = Arc::new(ScriptedLLMProvider::new(vec![
+            "tools:\n  - not_a_tool",
+            "tools:\n  - still_not_a_tool",
+        ]));
+        let generator = DefinitionGenerator::new(llm.clone(), Arc::new(MockEmbeddingProvider))
+            .with_max_retries(1);
+
+        let err = generator.generate("anything").await.unwrap_err();
+        assert!(err.to_string().contains("after 2 attempt(s)"));
+        assert!(err.to_string().contains("no valid tools"));
+        assert_eq!(llm.calls.lock().unwrap().len(), 2);
+    }
+
+    /// Replies with each scripted response in turn and records every request.
+    struct ScriptedLLMProvider {
+        responses: std::sync::Mutex<Vec<&'static str>>,
+        calls: std::sync::Mutex<Vec<Vec<Message>>>,
+    }
+
+    impl ScriptedLLMProvider {
+        fn new(mut responses: Vec<&'static str>) -> Self {
+            responses.reverse();
+            Self {
+                responses: std::sync::Mutex::new(responses),
+                calls: std::sync::Mutex::new(Vec::new()),
+            }
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl LLMProvider for ScriptedLLMProvider {
+        async fn complete(&self, messages: Vec<Message>) -> Result<String> {
+            self.calls.lock().unwrap().push(messages);
+            self.responses
+                .lock()
+                .unwrap()
+                .pop()
+                .map(str::to_string)
+                .ok_or_else(|| anyhow!("No scripted response left"))
+        }
+    }
+
+    #[test]
+    fn test_parse_yaml_and_json_equivalently() {
+        let generator =
+            DefinitionGenerator::new(Arc::new(MockLLMProvider), Arc::new(MockEmbeddingProvider));
+
+        let yaml = "name: review-agent\ntuning_keywords:\n  - review\n  - lint\nsystem_prompt: Review code.\ntemperature: 0.2\nmax_tokens: 512\ntools:\n  - read_file\n  - emit_signal\n";
+        let json = r#"```json
+{
+  "name": "review-agent",
+  "tuning_keywords": ["review", "lint"],
+  "system_prompt": "Review code.",
+  "temperature": 0.2,
+  "max_tokens": 512,
+  "tools": ["read_file", "emit_signal"]
+}
+```"#;
+
+        let from_yaml = generator
+            .parse_generated_definition(yaml, "review")
+            .unwrap();
+        let from_json = generator
+            .parse_generated_definition(json, "review")
+            .unwrap();
+
+        for def in [&from_yaml, &from_json] {
+            assert_eq!(def.name, "review-agent");
+            assert_eq!(def.tuning_keywords, vec!["review", "lint"]);
+            assert_eq!(def.system_prompt, "Review code.");
+            assert!((def.temperature - 0.2).abs() < 0.001);
+            assert_eq!(def.max_tokens, Some(512));
+            assert_eq!(def.tools, vec![ToolType::ReadFile, ToolType::EmitSignal]);
+        }
+    }
+
+    #[test]
+    fn test_json_format_prompt() {
+        let generator =
+            DefinitionGenerator::new(Arc::new(MockLLMProvider), Arc::new(MockEmbeddingProvider))
+                .with_format(DefinitionFormat::Json);
+
+        let prompt = generator.build_generation_prompt("anything");
+        assert!(prompt.contains("Output a JSON agent definition"));
+        assert!(prompt.contains("Output valid JSON only"));
+        assert_eq!(
+            DefinitionFormat::parse("json"),
+            Some(DefinitionFormat::Json)
+        );
+    }
+
+    #[test]
+    fn test_unknown_tool_names() {
+        let response =
+            "name: mailer\ntools:\n  - web_search\n  - send_email\n  - emit_signal\n  - teleport\n";
+
+        let lenient =
+            DefinitionGenerator::new(Arc::new(MockLLMProvider), Arc::new(MockEmbeddingProvider));
+        let def = lenient
+            .parse_generated_definition(response, "mail")
+            .unwrap();
+        assert_eq!(def.tools, vec![ToolType::WebSearch, ToolType::EmitSignal]);
+
+        let strict =
+            DefinitionGenerator::new(Arc::new(MockLLMProvider), Arc::new(MockEmbeddingProvider))
+                .with_strict(true);
+        let err = strict
+            .parse_generated_definition(response, "mail")
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("Unknown tools: send_email, teleport"));
+        assert!(err.contains("web_search"));
+    }
+
+    #[tokio::test]
+    async fn test_strict_tools_triggers_regeneration() {
+        let llm = Arc::new(ScriptedLLMProvider::new(vec![
+            "name: mailer\ntools:\n  - send_email\n  - emit_signal",
+            "name: mailer\ntools:\n  - http_request\n  - emit_signal",
+        ]));
+        let generator = DefinitionGenerator::new(llm.clone(), Arc::new(MockEmbeddingProvider))
+            .with_strict(true);
+
+        let def = generator.generate("send mail").await.unwrap();
+        assert_eq!(def.tools, vec![ToolType::HttpRequest, ToolType::EmitSignal]);
+        assert!(llm.calls.lock().unwrap()[1][3]
+            .content
+            .contains("Unknown tools: send_email"));
+    }
+
+    #[test]
+    fn test_out_of_range_temperature_is_clamped() {
+        let generator =
+            DefinitionGenerator::new(Arc::new(MockLLMProvider), Arc::new(MockEmbeddingProvider));
+
+        let hot = generator
+            .parse_generated_definition("name: hot\ntemperature: 2.0\ntools: [emit_signal]", "x")
+            .unwrap();
+        assert_eq!(hot.temperature, MAX_TEMPERATURE);
+
+        let cold = generator
+            .parse_generated_definition("name: cold\ntemperature: -1\ntools: [emit_signal]", "x")
+            .unwrap();
+        assert_eq!(cold.temperature, MIN_TEMPERATURE);
+
+        let strict =
+            DefinitionGenerator::new(Arc::new(MockLLMProvider), Arc::new(MockEmbeddingProvider))
+                .with_strict(true);
+        let err = strict
+            .parse_generated_definition("name: hot\ntemperature: 2.0\ntools: [emit_signal]", "x")
+            .unwrap_err();
+        assert!(err.to_string().contains("outside the valid range"));
+    }
+
+    #[test]
+    fn test_generated_keywords_are_normalized() {
+        let generator =
+            DefinitionGenerator::new(Arc::new(MockLLMProvider), Arc::new(MockEmbeddingProvider));
+
+        let def = generator
+            .parse_generated_definition(
+                "name: k\ntuning_keywords: [Security, ' security', SECURITY, '', Audit]\ntools: [emit_signal]",
+                "audit code",
+            )
+            .unwrap();
+        assert_eq!(def.tuning_keywords, vec!["security", "audit"]);
+
+        let def = generator
+            .parse_generated_definition(
+                "name: k\ntuning_keywords: ['', ' ']\ntools: [emit_signal]",
+                "audit code",
+            )
+            .unwrap();
+        assert_eq!(def.tuning_keywords, vec!["audit", "code"]);
+    }
+
+    #[tokio::test]
+    async fn test_generate_team() {
+        let llm = Arc::new(ScriptedLLMProvider::new(vec![
+            r#"team:
+  - name: test-writer
+    parent: audit-lead
+    purpose: Write regression tests for the findings
+    tuning_keywords: [tests, regression]
+    tools: [write_file, run_tests, emit_signal]
+  - name: audit-lead
+    purpose: Coordinate the security audit
+    tuning_keywords: [audit, coordinate]
+    tools: [spawn_agent, emit_signal]
+  - name: dependency-scanner
+    parent: audit-lead
+    tuning_keywords: [dependencies, cve]
+    tools: [read_file, web_search, emit_signal]
+"#,
+        ]));
+        let generator = DefinitionGenerator::new(llm, Arc::new(MockEmbeddingProvider));
+
+        let team = generator.generate_team("audit this service").await.unwrap();
+        assert_eq!(team.len(), 3);
+
+        let lead = &team[0];
+        assert_eq!(lead.definition.name, "audit-lead");
+        assert_eq!(lead.parent, None);
+        assert_eq!(lead.purpose, "Coordinate the security audit");
+
+        for member in &team[1..] {
+            assert_eq!(member.parent, Some(lead.definition.id));
+            assert_eq!(member.definition.tuning_embedding.len(), 1536);
+        }
+        let names: Vec<&str> = team.iter().map(|m| m.definition.name.as_str()).collect();
+        assert!(names.contains(&"test-writer"));
+        assert!(names.contains(&"dependency-scanner"));
+        assert_eq!(team[2].purpose, "dependency-scanner");
+    }
+
+    #[test]
+    fn test_team_validation() {
+        let generator =
+            DefinitionGenerator::new(Arc::new(MockLLMProvider), Arc::new(MockEmbeddingProvider));
+        let parse = |doc: &str| generator.parse_generated_team(doc, "need").map(|_| ());
+
+        let two_roots =
+            "team:\n  - {name: a, tools: [emit_signal]}\n  - {name: b, tools: [emit_signal]}";
+        assert!(parse(two_roots)
+            .unwrap_err()
+            .to_string()
+            .contains("exactly one coordinator"));
+
+        let unknown_parent = "team:\n  - {name: a, tools: [emit_signal]}\n  - {name: b, parent: z, tools: [emit_signal]}";
+        assert!(parse(unknown_parent)
+            .unwrap_err()
+            .to_string()
+            .contains("not in the team"));
+
+        let cycle = "team:\n  - {name: a, tools: [emit_signal]}\n  - {name: b, parent: c, tools: [emit_signal]}\n  - {name: c, parent: b, tools: [emit_signal]}";
+        assert!(parse(cycle)
+            .unwrap_err()
+            .to_string()
+            .contains("not reachable"));
+
+        let single = "team:\n  - {name: a, tools: [emit_signal]}";
+        assert!(parse(single).is_err());
+    }
+
     struct MockLLMProvider;
     struct MockEmbeddingProvider;
 
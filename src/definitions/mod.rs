@@ -3,5 +3,8 @@ pub mod generator;
 pub mod schema;
 
 pub use builtin::task_coordinator_definition;
-pub use generator::DefinitionGenerator;
-pub use schema::{AgentDefinition, DefinitionId, DefinitionSource, ToolType};
+pub use generator::{DefinitionFormat, DefinitionGenerator, TeamMember};
+pub use schema::{
+    clamp_temperature, definition_json_schema, normalize_keywords, AgentDefinition, DefinitionId,
+    DefinitionSource, ToolType, MAX_TEMPERATURE, MAX_TUNING_KEYWORDS, MIN_TEMPERATURE,
+};
@@ -1,10 +1,11 @@
 use chrono::{DateTime, Utc};
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
 pub type DefinitionId = Uuid;
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct AgentDefinition {
     pub id: DefinitionId,
     pub name: String,
@@ -17,7 +18,11 @@ pub struct AgentDefinition {
     // LLM configuration
     pub system_prompt: String,
     #[serde(default = "default_temperature")]
+    #[schemars(schema_with = "temperature_schema")]
     pub temperature: f32,
+    /// Cap on each response. Unset means `DEFAULT_MAX_TOKENS`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_tokens: Option<u32>,
 
     // Available tools
     pub tools: Vec<ToolType>,
@@ -33,9 +38,15 @@ pub struct AgentDefinition {
     // Optional version for user-defined
     #[serde(default)]
     pub version: Option<String>,
+
+    /// Set when soft-deleted. Deleted definitions still resolve by id for
+    /// the agents spawned from them, but are no longer offered for new ones.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(skip)]
+    pub deleted_at: Option<DateTime<Utc>>,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum DefinitionSource {
     BuiltIn,
@@ -43,7 +54,7 @@ pub enum DefinitionSource {
     Generated,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum ToolType {
     WebSearch,
@@ -55,6 +66,10 @@ pub enum ToolType {
     SpawnAgent,
     SearchCodebase,
     QueryDatabase,
+    ListDirectory,
+    DeleteFile,
+    HttpRequest,
+    RunTests,
 }
 
 impl ToolType {
@@ -73,6 +88,10 @@ impl ToolType {
             ToolType::SpawnAgent => "spawn_agent",
             ToolType::SearchCodebase => "search_codebase",
             ToolType::QueryDatabase => "query_database",
+            ToolType::ListDirectory => "list_directory",
+            ToolType::DeleteFile => "delete_file",
+            ToolType::HttpRequest => "http_request",
+            ToolType::RunTests => "run_tests",
         }
     }
 
@@ -87,6 +106,10 @@ impl ToolType {
             "spawn_agent" => Some(ToolType::SpawnAgent),
             "search_codebase" => Some(ToolType::SearchCodebase),
             "query_database" => Some(ToolType::QueryDatabase),
+            "list_directory" => Some(ToolType::ListDirectory),
+            "delete_file" => Some(ToolType::DeleteFile),
+            "http_request" => Some(ToolType::HttpRequest),
+            "run_tests" => Some(ToolType::RunTests),
             _ => None,
         }
     }
@@ -102,6 +125,10 @@ impl ToolType {
             ToolType::SpawnAgent,
             ToolType::SearchCodebase,
             ToolType::QueryDatabase,
+            ToolType::ListDirectory,
+            ToolType::DeleteFile,
+            ToolType::HttpRequest,
+            ToolType::RunTests,
         ]
     }
 }
@@ -110,6 +137,66 @@ fn default_temperature() -> f32 {
     0.4
 }
 
+/// Temperature range definitions are held to.
+pub const MIN_TEMPERATURE: f32 = 0.1;
+pub const MAX_TEMPERATURE: f32 = 0.9;
+
+fn temperature_schema(gen: &mut schemars::gen::SchemaGenerator) -> schemars::schema::Schema {
+    let mut schema = f32::json_schema(gen).into_object();
+    schema.number().minimum = Some(MIN_TEMPERATURE as f64);
+    schema.number().maximum = Some(MAX_TEMPERATURE as f64);
+    schema.into()
+}
+
+/// Clamp into `MIN_TEMPERATURE..=MAX_TEMPERATURE`; non-finite values fall
+/// back to the default.
+pub fn clamp_temperature(temperature: f32) -> f32 {
+    if temperature.is_finite() {
+        temperature.clamp(MIN_TEMPERATURE, MAX_TEMPERATURE)
+    } else {
+        default_temperature()
+    }
+}
+
+/// Most tuning keywords a definition keeps.
+pub const MAX_TUNING_KEYWORDS: usize = 10;
+
+/// Lowercase and trim keywords, dropping empties and duplicates (first
+/// occurrence wins), capped at `MAX_TUNING_KEYWORDS`.
+pub fn normalize_keywords(keywords: &[String]) -> Vec<String> {
+    let mut normalized: Vec<String> = Vec::new();
+    for keyword in keywords {
+        let keyword = keyword.trim().to_lowercase();
+        if !keyword.is_empty() && !normalized.contains(&keyword) {
+            normalized.push(keyword);
+        }
+        if normalized.len() == MAX_TUNING_KEYWORDS {
+            break;
+        }
+    }
+    normalized
+}
+
+impl AgentDefinition {
+    /// Bring fields from an untrusted source (a model, an import) into
+    /// their valid ranges.
+    pub fn normalize(&mut self) {
+        self.temperature = clamp_temperature(self.temperature);
+        self.max_tokens = self.max_tokens.filter(|&t| t > 0);
+        self.tuning_keywords = normalize_keywords(&self.tuning_keywords);
+    }
+
+    pub fn is_deleted(&self) -> bool {
+        self.deleted_at.is_some()
+    }
+}
+
+/// JSON Schema of an `AgentDefinition`, for validating definitions
+/// written by hand before they are imported.
+pub fn definition_json_schema() -> serde_json::Value {
+    serde_json::to_value(schemars::schema_for!(AgentDefinition)).expect("schema serializes to JSON")
+}
+
 impl Default for AgentDefinition {
     fn default() -> Self {
         Self {
@@ -119,12 +206,14 @@ impl Default for AgentDefinition {
             tuning_embedding: Vec::new(),
             system_prompt: String::new(),
             temperature: default_temperature(),
+            max_tokens: None,
             tools: Vec::new(),
             source: DefinitionSource::Generated,
             health_score: 1.0,
             use_count: 0,
             created_at: Utc::now(),
             version: None,
+            deleted_at: None,
         }
     }
 }
@@ -142,12 +231,14 @@ mod tests {
             tuning_embedding: vec![],
             system_prompt: "You are a test agent.".to_string(),
             temperature: 0.5,
+            max_tokens: None,
             tools: vec![ToolType::EmitSignal, ToolType::WebSearch],
             source: DefinitionSource::UserCustom,
             health_score: 1.0,
             use_count: 0,
             created_at: Utc::now(),
             version: Some("1.0.0".to_string()),
+            deleted_at: None,
         };
 
         let json = serde_json::to_string(&def).unwrap();
@@ -157,9 +248,61 @@ mod tests {
         assert_eq!(def.tools.len(), deserialized.tools.len());
     }
 
+    #[test]
+    fn test_normalize_clamps_temperature() {
+        let mut def = AgentDefinition {
+            temperature: 2.0,
+            ..AgentDefinition::default()
+        };
+        def.normalize();
+        assert_eq!(def.temperature, MAX_TEMPERATURE);
+
+        def.temperature = -1.0;
+        def.normalize();
+        assert_eq!(def.temperature, MIN_TEMPERATURE);
+
+        def.temperature = f32::NAN;
+        def.max_tokens = Some(0);
+        def.normalize();
+        assert_eq!(def.temperature, default_temperature());
+        assert_eq!(def.max_tokens, None);
+    }
+
+    #[test]
+    fn test_normalize_keywords() {
+        let keywords: Vec<String> = ["Rust", " rust ", "RUST", "", "  ", "Cargo", "rust"]
+            .iter()
+            .map(|k| k.to_string())
+            .collect();
+        assert_eq!(normalize_keywords(&keywords), vec!["rust", "cargo"]);
+
+        let many: Vec<String> = (0..20).map(|i| format!("k{}", i)).collect();
+        assert_eq!(normalize_keywords(&many).len(), MAX_TUNING_KEYWORDS);
+    }
+
     #[test]
     fn test_tool_type_as_str() {
         assert_eq!(ToolType::WebSearch.as_str(), "web_search");
         assert_eq!(ToolType::SpawnAgent.as_str(), "spawn_agent");
     }
+
+    #[test]
+    fn test_json_schema_covers_tools_and_temperature() {
+        let schema: serde_json::Value =
+            serde_json::from_str(&definition_json_schema().to_string()).unwrap();
+
+        let tools: Vec<&str> = schema["definitions"]["ToolType"]["enum"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|t| t.as_str().unwrap())
+            .collect();
+        let expected: Vec<&str> = ToolType::all().iter().map(|t| t.as_str()).collect();
+        assert_eq!(tools, expected);
+
+        let temperature = &schema["properties"]["temperature"];
+        assert!((temperature["minimum"].as_f64().unwrap() - MIN_TEMPERATURE as f64).abs() < 1e-6);
+        assert!((temperature["maximum"].as_f64().unwrap() - MAX_TEMPERATURE as f64).abs() < 1e-6);
+        assert!(schema["properties"].get("tuning_embedding").is_none());
+    }
 }
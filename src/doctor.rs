@@ -0,0 +1,235 @@
+use anyhow::Result;
+use serde::Serialize;
+use std::future::Future;
+use std::time::Duration;
+
+use crate::capabilities::Providers;
+use crate::storage::postgres::PostgresStorage;
+
+/// How long any single check may take before it counts as failed.
+pub const CHECK_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CheckStatus {
+    Pass,
+    Fail,
+    /// Not configured, so nothing to check.
+    Skip,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CheckResult {
+    pub name: String,
+    pub status: CheckStatus,
+    pub detail: String,
+}
+
+impl CheckResult {
+    fn pass(name: &str, detail: impl Into<String>) -> Self {
+        Self::new(name, CheckStatus::Pass, detail)
+    }
+
+    fn fail(name: &str, detail: impl Into<String>) -> Self {
+        Self::new(name, CheckStatus::Fail, detail)
+    }
+
+    fn skip(name: &str, detail: impl Into<String>) -> Self {
+        Self::new(name, CheckStatus::Skip, detail)
+    }
+
+    fn new(name: &str, status: CheckStatus, detail: impl Into<String>) -> Self {
+        Self {
+            name: name.to_string(),
+            status,
+            detail: detail.into(),
+        }
+    }
+}
+
+/// Results of `arachnid doctor`, one per dependency.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct DoctorReport {
+    pub checks: Vec<CheckResult>,
+}
+
+impl DoctorReport {
+    /// Check the providers, database, `rg` and Impresario host. Missing
+    /// settings are skipped rather than failed.
+    pub async fn run(
+        providers: &Providers,
+        database_url: Option<&str>,
+        impresario: Option<(&str, u16)>,
+    ) -> Self {
+        let mut checks = check_providers(providers).await;
+        checks.push(check_database(database_url).await);
+        checks.push(check_ripgrep().await);
+        checks.push(check_impresario(impresario).await);
+        Self { checks }
+    }
+
+    pub fn failures(&self) -> usize {
+        self.checks
+            .iter()
+            .filter(|c| c.status == CheckStatus::Fail)
+            .count()
+    }
+
+    pub fn passed(&self) -> bool {
+        self.failures() == 0
+    }
+}
+
+/// Run `check`, failing it if it errors or outlasts `CHECK_TIMEOUT`.
+async fn timed(name: &str, pass: &str, check: impl Future<Output = Result<()>>) -> CheckResult {
+    match tokio::time::timeout(CHECK_TIMEOUT, check).await {
+        Ok(Ok(())) => CheckResult::pass(name, pass),
+        Ok(Err(e)) => CheckResult::fail(name, format!("{:#}", e)),
+        Err(_) => CheckResult::fail(
+            name,
+            format!("no response within {}s", CHECK_TIMEOUT.as_secs()),
+        ),
+    }
+}
+
+pub async fn check_providers(providers: &Providers) -> Vec<CheckResult> {
+    let not_configured = |name| CheckResult::skip(name, "not configured");
+
+    let llm = match &providers.llm {
+        Some(llm) => timed("llm", "reachable", llm.health_check()).await,
+        None => not_configured("llm"),
+    };
+    let embedding = match &providers.embedding {
+        Some(embedding) => timed("embedding", "reachable", embedding.health_check()).await,
+        None => not_configured("embedding"),
+    };
+    let search = match &providers.search {
+        Some(search) => timed("search", "reachable", search.health_check()).await,
+        None => not_configured("search"),
+    };
+    vec![llm, embedding, search]
+}
+
+pub async fn check_database(database_url: Option<&str>) -> CheckResult {
+    let Some(url) = database_url else {
+        return CheckResult::skip("database", "DATABASE_URL not set; using in-memory storage");
+    };
+    timed("database", "connected", async {
+        PostgresStorage::new(url).await.map(|_| ())
+    })
+    .await
+}
+
+/// `search_codebase` shells out to ripgrep.
+pub async fn check_ripgrep() -> CheckResult {
+    let output = tokio::process::Command::new("rg")
+        .arg("--version")
+        .output()
+        .await;
+    match output {
+        Ok(output) if output.status.success() => {
+            let version = String::from_utf8_lossy(&output.stdout);
+            CheckResult::pass("ripgrep", version.lines().next().unwrap_or("found"))
+        }
+        Ok(output) => CheckResult::fail("ripgrep", format!("rg exited with {}", output.status)),
+        Err(e) => CheckResult::fail(
+            "ripgrep",
+            format!("rg not found ({}); search_codebase won't work", e),
+        ),
+    }
+}
+
+/// Whether the Impresario host accepts connections on its SSH port.
+pub async fn check_impresario(host: Option<(&str, u16)>) -> CheckResult {
+    let Some((host, port)) = host else {
+        return CheckResult::skip("impresario", "IMPRESARIO_HOST not set");
+    };
+    timed(
+        "impresario",
+        &format!("{}:{} reachable", host, port),
+        async {
+            tokio::net::TcpStream::connect((host, port)).await?;
+            Ok(())
+        },
+    )
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::providers::embedding::EmbeddingProvider;
+    use crate::providers::llm::{LLMProvider, Message};
+    use async_trait::async_trait;
+
+    struct HealthyLLM;
+
+    #[async_trait]
+    impl LLMProvider for HealthyLLM {
+        async fn complete(&self, _messages: Vec<Message>) -> Result<String> {
+            Ok("pong".to_string())
+        }
+    }
+
+    struct UnauthorizedEmbedding;
+
+    #[async_trait]
+    impl EmbeddingProvider for UnauthorizedEmbedding {
+        async fn embed(&self, _text: &str) -> Result<Vec<f32>> {
+            anyhow::bail!("401 Unauthorized")
+        }
+
+        async fn embed_batch(&self, _texts: &[String]) -> Result<Vec<Vec<f32>>> {
+            anyhow::bail!("401 Unauthorized")
+        }
+    }
+
+    #[tokio::test]
+    async fn test_report_mixes_pass_fail_and_skip() {
+        let providers = Providers {
+            llm: Some(Box::new(HealthyLLM)),
+            embedding: Some(Box::new(UnauthorizedEmbedding)),
+            search: None,
+        };
+
+        let mut report = DoctorReport {
+            checks: check_providers(&providers).await,
+        };
+        report.checks.push(check_database(None).await);
+        report.checks.push(check_impresario(None).await);
+
+        let status = |name: &str| {
+            report
+                .checks
+                .iter()
+                .find(|c| c.name == name)
+                .unwrap()
+                .status
+        };
+        assert_eq!(status("llm"), CheckStatus::Pass);
+        assert_eq!(status("embedding"), CheckStatus::Fail);
+        assert_eq!(status("search"), CheckStatus::Skip);
+        assert_eq!(status("database"), CheckStatus::Skip);
+        assert_eq!(status("impresario"), CheckStatus::Skip);
+        assert_eq!(report.failures(), 1);
+        assert!(!report.passed());
+
+        let json = serde_json::to_value(&report).unwrap();
+        assert_eq!(json["checks"][1]["status"], "fail");
+        assert!(json["checks"][1]["detail"]
+            .as_str()
+            .unwrap()
+            .contains("401"));
+    }
+
+    #[tokio::test]
+    async fn test_unreachable_impresario_fails() {
+        // Bind then drop a listener to get a port nothing is listening on.
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        drop(listener);
+
+        let result = check_impresario(Some(("127.0.0.1", port))).await;
+        assert_eq!(result.status, CheckStatus::Fail);
+    }
+}
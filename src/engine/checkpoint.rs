@@ -0,0 +1,54 @@
+use std::time::Duration;
+
+/// When the coordination loop snapshots a web to durable storage. A
+/// checkpoint is taken once either interval has passed since the last one.
+#[derive(Debug, Clone, Default)]
+pub struct CheckpointConfig {
+    pub every_iterations: Option<u64>,
+    pub every: Option<Duration>,
+}
+
+impl CheckpointConfig {
+    /// Reads `ARACHNID_CHECKPOINT_ITERATIONS` and `ARACHNID_CHECKPOINT_SECS`.
+    /// Returns `None` if neither is set.
+    pub fn from_env() -> Option<Self> {
+        let env = |var: &str| std::env::var(var).ok().and_then(|v| v.parse::<u64>().ok());
+
+        let config = Self {
+            every_iterations: env("ARACHNID_CHECKPOINT_ITERATIONS").filter(|n| *n > 0),
+            every: env("ARACHNID_CHECKPOINT_SECS")
+                .filter(|n| *n > 0)
+                .map(Duration::from_secs),
+        };
+        config.is_enabled().then_some(config)
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.every_iterations.is_some() || self.every.is_some()
+    }
+
+    /// Whether a checkpoint is due, given the iterations run and time
+    /// passed since the last one.
+    pub fn is_due(&self, iterations: u64, elapsed: Duration) -> bool {
+        self.every_iterations.is_some_and(|n| iterations >= n)
+            || self.every.is_some_and(|interval| elapsed >= interval)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_checkpoint_due_on_either_interval() {
+        let config = CheckpointConfig {
+            every_iterations: Some(5),
+            every: Some(Duration::from_secs(30)),
+        };
+        assert!(!config.is_due(4, Duration::from_secs(29)));
+        assert!(config.is_due(5, Duration::ZERO));
+        assert!(config.is_due(1, Duration::from_secs(30)));
+
+        assert!(!CheckpointConfig::default().is_due(100, Duration::from_secs(3600)));
+    }
+}
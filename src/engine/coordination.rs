@@ -1,79 +1,783 @@
 use anyhow::Result;
-use std::collections::HashMap;
-use std::sync::Arc;
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex, RwLock};
+use tokio_util::sync::CancellationToken;
 
 use crate::capabilities::{Capability, Providers};
+use crate::engine::checkpoint::CheckpointConfig;
+use crate::engine::events::{EngineEvent, EngineObserver};
+use crate::engine::lifecycle_management::LifecycleManager;
+use crate::engine::metrics::MetricsRegistry;
 use crate::engine::propagation::propagate_signal;
-use crate::engine::resonance::compute_resonance;
+use crate::engine::report::{concatenated_report, parse_report, report_prompt};
+use crate::engine::resonance::{compute_resonance, cosine_similarity, magnitude};
+use crate::lifecycle::state_machine::{AgentStateMachine, LifecycleEvent};
+use crate::lifecycle::tuning_drift::{cap_drift, TuningDriftTracker};
+use crate::providers::llm::LLMProvider;
+use crate::providers::usage::{MeteredLLMProvider, Usage, UsageMeter};
 use crate::storage::memory::WebStore;
+use crate::storage::traits::{
+    FailurePattern, FailurePatternType, Storage, WebAnalytics, WebCheckpoint,
+};
 use crate::types::{
-    Agent, AgentState, CapabilityType, ContextItem, ExecutionStatus, Signal, SignalDirection,
-    SignalDraft, WebState,
+    Agent, AgentState, CapabilityType, ContextItem, ExecutionStatus, FinalReport, Signal,
+    SignalDirection, SignalDraft, Web, WebConfig, WebState,
 };
 
 pub struct CoordinationEngine<S: WebStore> {
     store: Arc<S>,
     capabilities: HashMap<CapabilityType, Box<dyn Capability>>,
-    providers: Providers,
+    /// Providers for webs started from now on; see [`Self::set_providers`].
+    providers: RwLock<Arc<Providers>>,
+    /// Providers each running loop started with.
+    run_providers: Mutex<HashMap<uuid::Uuid, Arc<Providers>>>,
+    usage: UsageMeter,
+    /// Cancellation tokens of webs whose loop is running.
+    running: Mutex<HashMap<uuid::Uuid, CancellationToken>>,
+    checkpoints: Option<(Arc<dyn Storage>, CheckpointConfig)>,
+    /// Keep running webs' agents and signals in the checkpoint storage too.
+    write_through: bool,
+    /// Signals of each running web written through so far, and whether
+    /// they were written as processed.
+    written_signals: Mutex<HashMap<uuid::Uuid, HashMap<uuid::Uuid, bool>>>,
+    observer: Option<Arc<dyn EngineObserver>>,
+    metrics: MetricsRegistry,
+    /// Activity of each web's running loop.
+    run_stats: Mutex<HashMap<uuid::Uuid, RunStats>>,
+}
+
+#[derive(Default)]
+struct RunStats {
+    /// Spawns made and dropped in the current iteration.
+    spawned: usize,
+    dropped: usize,
+    /// Agents activated in the current iteration.
+    activated: HashSet<uuid::Uuid>,
+    peak_active: usize,
+    /// Iterations in a row, up to the current one, that dropped spawns.
+    throttled_iterations: u32,
 }
 
+/// Consecutive throttled iterations after which spawning counts as cyclic.
+const SPAWN_THROTTLE_ITERATIONS: u32 = 3;
+
 impl<S: WebStore> CoordinationEngine<S> {
     pub fn new(
         store: Arc<S>,
         capabilities: HashMap<CapabilityType, Box<dyn Capability>>,
         providers: Providers,
     ) -> Self {
+        let usage = UsageMeter::new();
+        let providers = metered(providers, &usage);
+
         Self {
             store,
             capabilities,
-            providers,
+            providers: RwLock::new(Arc::new(providers)),
+            run_providers: Mutex::new(HashMap::new()),
+            usage,
+            running: Mutex::new(HashMap::new()),
+            checkpoints: None,
+            write_through: false,
+            written_signals: Mutex::new(HashMap::new()),
+            observer: None,
+            metrics: MetricsRegistry::new(),
+            run_stats: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn with_observer(mut self, observer: Arc<dyn EngineObserver>) -> Self {
+        self.observer = Some(observer);
+        self
+    }
+
+    /// Snapshot running webs to `storage` on the given schedule, and once
+    /// more when the loop ends, so a crashed run can be picked back up.
+    pub fn with_checkpoints(mut self, storage: Arc<dyn Storage>, config: CheckpointConfig) -> Self {
+        self.checkpoints = Some((storage, config));
+        self
+    }
+
+    /// Also write running webs' agents and signals to the checkpoint storage
+    /// whenever they're checkpointed, and take config changes, terminations
+    /// and pauses made there each iteration. For webs that live in that
+    /// storage and only run here, as with `StorageRunner`.
+    pub fn with_write_through(mut self) -> Self {
+        self.write_through = true;
+        self
+    }
+
+    /// Count work into `metrics`, e.g. one shared with the API server.
+    pub fn with_metrics(mut self, metrics: MetricsRegistry) -> Self {
+        self.metrics = metrics;
+        self
+    }
+
+    pub fn metrics(&self) -> &MetricsRegistry {
+        &self.metrics
+    }
+
+    pub fn store(&self) -> &Arc<S> {
+        &self.store
+    }
+
+    /// Swap in `providers`, e.g. rebuilt after a config reload. Webs whose
+    /// loop is already running keep the providers they started with.
+    pub fn set_providers(&self, providers: Providers) {
+        *self.providers.write().unwrap() = Arc::new(metered(providers, &self.usage));
+    }
+
+    /// The providers `web_id`'s running loop started with, or the current
+    /// ones if it isn't running.
+    fn providers(&self, web_id: &uuid::Uuid) -> Arc<Providers> {
+        match self.run_providers.lock().unwrap().get(web_id) {
+            Some(providers) => providers.clone(),
+            None => self.providers.read().unwrap().clone(),
+        }
+    }
+
+    /// LLM usage recorded so far. Share it with a `ValidationService` so
+    /// validations count against the same web budgets.
+    pub fn usage_meter(&self) -> &UsageMeter {
+        &self.usage
+    }
+
+    /// Create the root agent and initial signal for a web that has none yet,
+    /// e.g. one created through the API. Returns `false` if it was already seeded.
+    pub async fn seed_web(&self, web_id: &uuid::Uuid) -> Result<bool> {
+        let web = self
+            .store
+            .get_web(web_id)?
+            .ok_or_else(|| anyhow::anyhow!("Web not found"))?;
+        if self.store.get_agent(&web.root_agent)?.is_some() {
+            return Ok(false);
+        }
+
+        let providers = self.providers(web_id);
+        let task_embedding = if let Some(provider) = &providers.embedding {
+            if provider.dimension() != web.config.embedding_dim {
+                tracing::warn!(
+                    web_id = %web.id,
+                    provider_dim = provider.dimension(),
+                    web_dim = web.config.embedding_dim,
+                    "Embedding provider dimension differs from the web's embedding_dim"
+                );
+            }
+            provider.embed(&web.task).await?
+        } else {
+            vec![1.0; web.config.embedding_dim]
+        };
+
+        let mut root_agent = Agent::new(
+            web.id,
+            None,
+            web.task.clone(),
+            task_embedding.clone(),
+            CapabilityType::Synthesizer,
+            web.config.default_threshold,
+        );
+        root_agent.id = web.root_agent;
+        self.store.add_agent(root_agent)?;
+
+        self.store.add_signal(Signal::new(
+            web.root_agent,
+            task_embedding,
+            web.task.clone(),
+            SignalDirection::Downward,
+        ))?;
+
+        Ok(true)
+    }
+
+    /// Stop a web whose loop is running: in-flight agent executions are
+    /// abandoned and the loop exits, leaving the web `Failed`. Returns `false`
+    /// if the web isn't running here.
+    pub fn cancel(&self, web_id: &uuid::Uuid) -> bool {
+        match self.running.lock().unwrap().get(web_id) {
+            Some(token) => {
+                token.cancel();
+                true
+            }
+            None => false,
         }
     }
 
+    fn cancellation_token(&self, web_id: &uuid::Uuid) -> Option<CancellationToken> {
+        self.running.lock().unwrap().get(web_id).cloned()
+    }
+
     pub async fn run_coordination_loop(&self, web_id: &uuid::Uuid) -> Result<()> {
+        let token = CancellationToken::new();
+        self.running.lock().unwrap().insert(*web_id, token.clone());
+        let providers = self.providers.read().unwrap().clone();
+        self.run_providers
+            .lock()
+            .unwrap()
+            .insert(*web_id, providers);
+
+        let started = std::time::Instant::now();
+        let result = self.drive_loop(web_id, &token).await;
+        self.running.lock().unwrap().remove(web_id);
+        self.run_providers.lock().unwrap().remove(web_id);
+        let stats = self.run_stats.lock().unwrap().remove(web_id);
+
+        let finished = async {
+            if token.is_cancelled() {
+                self.finish_cancelled(web_id)?;
+            }
+            let iteration = result?;
+            self.save_checkpoint(web_id, iteration).await;
+            self.save_analytics(
+                web_id,
+                iteration,
+                stats.map_or(0, |s| s.peak_active),
+                started.elapsed(),
+            )
+        }
+        .await;
+        // Even after a failed run, so storage shows how far it got.
+        let written = self.write_through(web_id).await;
+        self.written_signals.lock().unwrap().remove(web_id);
+        finished.and(written)
+    }
+
+    /// Record analytics for a web whose loop left it finished.
+    fn save_analytics(
+        &self,
+        web_id: &uuid::Uuid,
+        iterations: u64,
+        peak_active_agents: usize,
+        elapsed: std::time::Duration,
+    ) -> Result<()> {
+        let Some(web) = self.store.get_web(web_id)? else {
+            return Ok(());
+        };
+        if !web.state.is_terminal() {
+            return Ok(());
+        }
+
+        let agents = self.store.get_agents_by_web(web_id)?;
+        self.store.save_web_analytics(WebAnalytics {
+            web_id: *web_id,
+            state: web.state,
+            time_to_convergence_ms: web.is_converged().then_some(elapsed.as_millis() as u64),
+            max_depth: max_depth(&agents),
+            peak_active_agents,
+            iterations,
+            completed_at: chrono::Utc::now(),
+        })
+    }
+
+    /// Returns the number of iterations run.
+    async fn drive_loop(&self, web_id: &uuid::Uuid, token: &CancellationToken) -> Result<u64> {
         let mut iteration = 0;
-        const MAX_ITERATIONS: usize = 100;
+        const MAX_ITERATIONS: u64 = 100;
+        let mut last_checkpoint = (0, std::time::Instant::now());
+        let mut last_tick = (0, std::time::Instant::now());
 
         loop {
+            if token.is_cancelled() {
+                break;
+            }
+
             iteration += 1;
             if iteration > MAX_ITERATIONS {
                 self.mark_web_failed(web_id, "Max iterations reached")?;
                 break;
             }
 
+            if let Err(e) = self.pull_from_storage(web_id).await {
+                tracing::warn!(web_id = %web_id, error = %e, "Could not read web from storage");
+            }
+
             let should_continue = self.run_single_iteration(web_id).await?;
             if !should_continue {
                 break;
             }
 
-            tokio::time::sleep(tokio::time::Duration::from_millis(10)).await;
+            let tick_due = self.store.get_web(web_id)?.is_some_and(|web| {
+                web.config
+                    .lifecycle_tick_due(iteration - last_tick.0, last_tick.1.elapsed())
+            });
+            if tick_due {
+                let elapsed = chrono::Duration::from_std(last_tick.1.elapsed())?;
+                self.lifecycle_tick(web_id, elapsed)?;
+                last_tick = (iteration, std::time::Instant::now());
+            }
+
+            if let Some((_, config)) = &self.checkpoints {
+                if config.is_due(iteration - last_checkpoint.0, last_checkpoint.1.elapsed()) {
+                    self.save_checkpoint(web_id, iteration).await;
+                    if let Err(e) = self.write_through(web_id).await {
+                        tracing::warn!(web_id = %web_id, error = %e, "Could not write web to storage");
+                    }
+                    last_checkpoint = (iteration, std::time::Instant::now());
+                }
+            }
+
+            tokio::select! {
+                _ = token.cancelled() => break,
+                _ = tokio::time::sleep(tokio::time::Duration::from_millis(10)) => {}
+            }
+        }
+
+        Ok(iteration.min(MAX_ITERATIONS))
+    }
+
+    /// Continue a `Running` web left behind by an earlier run, e.g. one whose
+    /// process crashed.
+    pub async fn resume(&self, web_id: &uuid::Uuid) -> Result<()> {
+        self.restore_web(web_id).await?;
+        self.run_coordination_loop(web_id).await
+    }
+
+    /// Get a `Running` web ready to continue: it's loaded from its checkpoint
+    /// if it isn't in the store, and agents caught mid-execution go back to
+    /// listening. Unprocessed signals stay pending.
+    pub async fn restore_web(&self, web_id: &uuid::Uuid) -> Result<()> {
+        if self.store.get_web(web_id)?.is_none() {
+            let checkpoint = match &self.checkpoints {
+                Some((storage, _)) => storage.get_checkpoint(*web_id).await?,
+                None => None,
+            }
+            .ok_or_else(|| anyhow::anyhow!("Web {} has no checkpoint to resume from", web_id))?;
+            self.restore_checkpoint(&checkpoint)?;
+        }
+
+        let web = self
+            .store
+            .get_web(web_id)?
+            .ok_or_else(|| anyhow::anyhow!("Web not found"))?;
+        if web.state != WebState::Running {
+            anyhow::bail!("Web {} is {:?}, not running", web_id, web.state);
+        }
+
+        for mut agent in self.store.get_agents_by_web(web_id)? {
+            if agent.state == AgentState::Active {
+                agent.state = AgentState::Listening;
+                self.store.update_agent(agent)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Run the lifecycle checks over every agent in the web: idle timeout,
+    /// dormant TTL and idle health decay, with `elapsed` the time since the
+    /// last tick, then wind down any agent that ended up `WindingDown`.
+    /// Tuning drift isn't part of the tick; it happens as knowledge arrives.
+    pub fn lifecycle_tick(&self, web_id: &uuid::Uuid, elapsed: chrono::Duration) -> Result<()> {
+        let web = self
+            .store
+            .get_web(web_id)?
+            .ok_or_else(|| anyhow::anyhow!("Web not found"))?;
+        let now = chrono::Utc::now();
+
+        for mut agent in self.store.get_agents_by_web(web_id)? {
+            let (old_state, old_health) = (agent.state, agent.health);
+
+            let decay = LifecycleManager::apply_idle_decay(&mut agent, &web.config, now, elapsed)?;
+            LifecycleManager::check_idle_timeout(&mut agent, &web.config)?;
+            LifecycleManager::check_ttl_expiration(&mut agent, &web.config)?;
+
+            if let Some(event) = decay {
+                self.emit(EngineEvent::AgentHealthChanged {
+                    web_id: *web_id,
+                    agent_id: agent.id,
+                    old_health,
+                    new_health: agent.health,
+                    reason: event.reason,
+                });
+            }
+            if agent.state != old_state {
+                self.emit(EngineEvent::AgentStateChanged {
+                    web_id: *web_id,
+                    agent_id: agent.id,
+                    from: old_state,
+                    to: agent.state,
+                });
+            }
+            if agent.state != old_state || agent.health != old_health {
+                self.store.update_agent(agent)?;
+            }
+        }
+
+        self.process_wind_downs(web_id)
+    }
+
+    /// Wind down every agent in `WindingDown`: signal its summary upward,
+    /// hand healthy children to its parent, and terminate it. Unhealthy
+    /// children wind down with it, in the same pass.
+    fn process_wind_downs(&self, web_id: &uuid::Uuid) -> Result<()> {
+        let mut agents: HashMap<uuid::Uuid, Agent> = self
+            .store
+            .get_agents_by_web(web_id)?
+            .into_iter()
+            .map(|agent| (agent.id, agent))
+            .collect();
+        let mut winding_down: Vec<uuid::Uuid> = agents
+            .values()
+            .filter(|agent| agent.state == AgentState::WindingDown)
+            .map(|agent| agent.id)
+            .collect();
+
+        while let Some(agent_id) = winding_down.pop() {
+            let agent = agents[&agent_id].clone();
+            let children: Vec<(uuid::Uuid, AgentState)> = agents
+                .values()
+                .filter(|a| a.parent_id == Some(agent_id) && a.state != AgentState::Terminated)
+                .map(|a| (a.id, a.state))
+                .collect();
+
+            for signal in LifecycleManager::process_wind_down(&agent, &mut agents)? {
+                self.store.add_signal(signal)?;
+            }
+
+            for (child_id, old_state) in children {
+                let child = &agents[&child_id];
+                if child.state != old_state {
+                    self.emit(EngineEvent::AgentStateChanged {
+                        web_id: *web_id,
+                        agent_id: child_id,
+                        from: old_state,
+                        to: child.state,
+                    });
+                    winding_down.push(child_id);
+                }
+                self.store.update_agent(child.clone())?;
+            }
+
+            let agent = agents
+                .get_mut(&agent_id)
+                .ok_or_else(|| anyhow::anyhow!("Agent not found"))?;
+            AgentStateMachine::transition(agent, LifecycleEvent::HealthBelowTerminal)?;
+            self.emit(EngineEvent::AgentStateChanged {
+                web_id: *web_id,
+                agent_id,
+                from: AgentState::WindingDown,
+                to: agent.state,
+            });
+            self.store.update_agent(agent.clone())?;
+        }
+        Ok(())
+    }
+
+    fn emit(&self, event: EngineEvent) {
+        if let Some(observer) = &self.observer {
+            observer.on_event(&event);
+        }
+    }
+
+    /// The web's current agents and pending signals.
+    pub fn snapshot(&self, web_id: &uuid::Uuid, iteration: u64) -> Result<WebCheckpoint> {
+        let web = self
+            .store
+            .get_web(web_id)?
+            .ok_or_else(|| anyhow::anyhow!("Web not found"))?;
+
+        Ok(WebCheckpoint {
+            web,
+            agents: self.store.get_agents_by_web(web_id)?,
+            pending_signals: self.store.get_pending_signals(web_id)?,
+            iteration,
+            created_at: chrono::Utc::now(),
+        })
+    }
+
+    /// Load a checkpoint's web, agents and pending signals into the store.
+    pub fn restore_checkpoint(&self, checkpoint: &WebCheckpoint) -> Result<()> {
+        self.store.create_web(checkpoint.web.clone())?;
+        for agent in &checkpoint.agents {
+            self.store.add_agent(agent.clone())?;
+        }
+        for signal in &checkpoint.pending_signals {
+            self.store.add_signal(signal.clone())?;
+        }
+        Ok(())
+    }
+
+    /// A failed checkpoint is logged rather than failing the web; the
+    /// previous one still stands.
+    async fn save_checkpoint(&self, web_id: &uuid::Uuid, iteration: u64) {
+        let Some((storage, _)) = &self.checkpoints else {
+            return;
+        };
+        let saved = match self.snapshot(web_id, iteration) {
+            Ok(checkpoint) => storage.save_checkpoint(&checkpoint).await,
+            Err(e) => Err(e),
+        };
+        if let Err(e) = saved {
+            tracing::warn!(web_id = %web_id, error = %e, "Could not checkpoint web");
+        }
+    }
+
+    fn write_through_storage(&self) -> Option<&Arc<dyn Storage>> {
+        self.checkpoints
+            .as_ref()
+            .filter(|_| self.write_through)
+            .map(|(storage, _)| storage)
+    }
+
+    /// Take on what was changed in write-through storage since the last
+    /// iteration: the web's config, a termination, and agents paused or
+    /// resumed.
+    async fn pull_from_storage(&self, web_id: &uuid::Uuid) -> Result<()> {
+        let Some(storage) = self.write_through_storage() else {
+            return Ok(());
+        };
+        let Some(stored) = storage.get_web(*web_id).await? else {
+            return Ok(());
+        };
+        if let Some(mut web) = self.store.get_web(web_id)? {
+            web.config = stored.config;
+            if stored.state.is_terminal() && !web.state.is_terminal() {
+                web.state = stored.state;
+            }
+            self.store.update_web(web)?;
+        }
+
+        let paused: HashSet<uuid::Uuid> = storage
+            .get_agents_by_state(*web_id, AgentState::Paused)
+            .await?
+            .into_iter()
+            .map(|a| a.id)
+            .collect();
+        for mut agent in self.store.get_agents_by_web(web_id)? {
+            let stored_state = if paused.contains(&agent.id) {
+                AgentState::Paused
+            } else if agent.state == AgentState::Paused {
+                match storage.get_agent(agent.id).await? {
+                    Some(stored) => stored.state,
+                    None => continue,
+                }
+            } else {
+                continue;
+            };
+            if let Some(state) = operator_state(&agent, stored_state) {
+                agent.state = state;
+                self.store.update_agent(agent)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Write the web's agents, and signals not written yet, to write-through
+    /// storage. Agents paused or resumed there keep that state.
+    async fn write_through(&self, web_id: &uuid::Uuid) -> Result<()> {
+        let Some(storage) = self.write_through_storage() else {
+            return Ok(());
+        };
+        let stored: HashMap<uuid::Uuid, AgentState> = storage
+            .get_web_agents(*web_id)
+            .await?
+            .into_iter()
+            .map(|a| (a.id, a.state))
+            .collect();
+        let mut agents = self.store.get_agents_by_web(web_id)?;
+        // Parents before children.
+        agents.sort_by_key(|a| (a.created_at, a.id));
+        for mut agent in agents {
+            match stored.get(&agent.id) {
+                Some(&stored_state) => {
+                    if let Some(state) = operator_state(&agent, stored_state) {
+                        agent.state = state;
+                    }
+                    storage.update_agent(&agent).await?;
+                }
+                None => storage.create_agent(&agent).await?,
+            }
+        }
+
+        // Creating a signal storage already has is a no-op, so losing track
+        // after an error only means writing some again.
+        let mut written = self
+            .written_signals
+            .lock()
+            .unwrap()
+            .remove(web_id)
+            .unwrap_or_default();
+        for (signal, processed) in self.store.get_web_signals(web_id)? {
+            let was = written.get(&signal.id).copied();
+            if was.is_none() {
+                storage.create_signal(&signal).await?;
+            }
+            if processed && was != Some(true) {
+                storage.mark_signal_processed(signal.id).await?;
+            }
+            written.insert(signal.id, processed);
+        }
+        self.written_signals
+            .lock()
+            .unwrap()
+            .insert(*web_id, written);
+        Ok(())
+    }
+
+    /// Leave a cancelled web failed with no agent still marked active.
+    fn finish_cancelled(&self, web_id: &uuid::Uuid) -> Result<()> {
+        for mut agent in self.store.get_agents_by_web(web_id)? {
+            if agent.state == AgentState::Active {
+                agent.state = AgentState::Dormant;
+                self.store.update_agent(agent)?;
+            }
         }
 
+        let terminal = self
+            .store
+            .get_web(web_id)?
+            .is_some_and(|web| web.state.is_terminal());
+        if !terminal {
+            self.mark_web_failed(web_id, "Cancelled")?;
+        }
         Ok(())
     }
 
     /// Run a single iteration of the coordination loop.
     /// Returns `true` if the loop should continue, `false` if it should stop.
     pub async fn run_single_iteration(&self, web_id: &uuid::Uuid) -> Result<bool> {
-        if self.check_convergence(web_id).await? {
-            self.mark_web_converged(web_id)?;
+        // Terminated from elsewhere, e.g. through the API.
+        if let Some(web) = self.store.get_web(web_id)? {
+            if web.state.is_terminal() {
+                return Ok(false);
+            }
+        }
+
+        if self.enforce_budget(web_id)? {
             return Ok(false);
         }
 
-        let pending_signals = self.store.get_pending_signals(web_id)?;
-        if pending_signals.is_empty() {
-            let active_agents = self.get_active_agents(web_id)?;
-            if active_agents.is_empty() {
+        if self.check_convergence(web_id).await? {
+            // Quiet isn't enough: a root that gathered nothing has no answer.
+            if self.root_has_output(web_id)? {
                 self.mark_web_converged(web_id)?;
-                return Ok(false);
+                if let Err(e) = self.synthesize_final_report(web_id).await {
+                    tracing::warn!(web_id = %web_id, error = %e, "Could not write final report");
+                }
+            } else {
+                self.mark_web_failed(web_id, "no output produced")?;
             }
+            return Ok(false);
         }
 
+        let pending_signals = self.store.get_pending_signals(web_id)?;
+
+        {
+            let mut run_stats = self.run_stats.lock().unwrap();
+            let stats = run_stats.entry(*web_id).or_default();
+            stats.spawned = 0;
+            stats.dropped = 0;
+            stats.activated.clear();
+        }
+        let token = self.cancellation_token(web_id);
         for signal in pending_signals {
-            self.process_signal(&signal).await?;
+            if token.as_ref().is_some_and(|t| t.is_cancelled()) {
+                return Ok(false);
+            }
+            let before = self.usage.web_usage(web_id);
+            let processed = self
+                .usage
+                .scope(*web_id, self.process_signal(&signal))
+                .await;
+            self.record_token_usage(web_id, &before).await?;
+            processed?;
             self.store.mark_signal_processed(&signal.id)?;
+            self.metrics.record_signal_processed(web_id);
+
+            if self.enforce_budget(web_id)? {
+                return Ok(false);
+            }
+        }
+
+        self.record_spawn_throttling(web_id)?;
+        Ok(true)
+    }
+
+    /// Store what the web spent on LLM calls since `before`, a reading of
+    /// its meter. The durable copy, when checkpointing, only logs failures.
+    async fn record_token_usage(&self, web_id: &uuid::Uuid, before: &Usage) -> Result<()> {
+        let spent = self.usage.web_usage(web_id).since(before);
+        if spent == Usage::default() {
+            return Ok(());
+        }
+        self.store.record_token_usage(web_id, spent)?;
+        if let Some((storage, _)) = &self.checkpoints {
+            if let Err(e) = storage.record_token_usage(*web_id, &spent).await {
+                tracing::warn!(web_id = %web_id, error = %e, "Could not record token usage");
+            }
         }
+        Ok(())
+    }
+
+    /// Record a `CyclicSpawning` pattern once `SPAWN_THROTTLE_ITERATIONS`
+    /// iterations in a row have asked for more agents than
+    /// `max_spawns_per_iteration` allows. A single burst is only throttled.
+    fn record_spawn_throttling(&self, web_id: &uuid::Uuid) -> Result<()> {
+        let (spawned, dropped) = {
+            let mut run_stats = self.run_stats.lock().unwrap();
+            let Some(stats) = run_stats.get_mut(web_id) else {
+                return Ok(());
+            };
+            if stats.dropped == 0 {
+                stats.throttled_iterations = 0;
+                return Ok(());
+            }
+            stats.throttled_iterations += 1;
+            if stats.throttled_iterations != SPAWN_THROTTLE_ITERATIONS {
+                return Ok(());
+            }
+            (stats.spawned, stats.dropped)
+        };
+        let Some(web) = self.store.get_web(web_id)? else {
+            return Ok(());
+        };
+
+        tracing::warn!(
+            web_id = %web_id,
+            spawned,
+            dropped,
+            "Spawn limit reached"
+        );
+        self.store.add_failure_pattern(FailurePattern {
+            id: uuid::Uuid::new_v4(),
+            web_id: *web_id,
+            pattern_type: FailurePatternType::CyclicSpawning,
+            pattern_data: serde_json::json!({
+                "reason": "spawn_rate_limited",
+                "spawned": spawned,
+                "dropped": dropped,
+                "throttled_iterations": SPAWN_THROTTLE_ITERATIONS,
+                "max_spawns_per_iteration": web.config.max_spawns_per_iteration,
+            }),
+            created_at: chrono::Utc::now(),
+        })
+    }
+
+    /// Fail the web if its LLM usage has passed the configured token or cost
+    /// budget. Returns `true` if it did.
+    fn enforce_budget(&self, web_id: &uuid::Uuid) -> Result<bool> {
+        let Some(web) = self.store.get_web(web_id)? else {
+            return Ok(false);
+        };
+        let usage = self.usage.web_usage(web_id);
+        let Some(reason) = budget_exceeded(&web, &usage) else {
+            return Ok(false);
+        };
 
+        self.store.add_failure_pattern(FailurePattern {
+            id: uuid::Uuid::new_v4(),
+            web_id: *web_id,
+            pattern_type: FailurePatternType::ResourceExhaustion,
+            pattern_data: serde_json::json!({
+                "reason": "budget_exceeded",
+                "detail": reason,
+                "input_tokens": usage.input_tokens,
+                "output_tokens": usage.output_tokens,
+                "cost_usd": usage.cost_usd,
+                "token_budget": web.config.token_budget,
+                "cost_budget": web.config.cost_budget,
+            }),
+            created_at: chrono::Utc::now(),
+        })?;
+        self.mark_web_failed(web_id, &reason)?;
         Ok(true)
     }
 
@@ -88,7 +792,8 @@ impl<S: WebStore> CoordinationEngine<S> {
             .ok_or_else(|| anyhow::anyhow!("Web not found"))?;
 
         if signal.direction == SignalDirection::Upward {
-            self.accumulate_context_from_signal(signal).await?;
+            self.accumulate_context_from_signal(signal, &web.config)
+                .await?;
         }
 
         let propagation_results = propagate_signal(signal, &web.config, &*self.store).await?;
@@ -102,12 +807,25 @@ impl<S: WebStore> CoordinationEngine<S> {
         Ok(())
     }
 
-    async fn accumulate_context_from_signal(&self, signal: &Signal) -> Result<()> {
+    async fn accumulate_context_from_signal(
+        &self,
+        signal: &Signal,
+        config: &WebConfig,
+    ) -> Result<()> {
         let origin_agent = self.store.get_agent(&signal.origin)?;
         if origin_agent.is_none() {
             return Ok(());
         }
 
+        // Needs are handed over directly, not learned from.
+        if signal
+            .payload
+            .as_ref()
+            .is_some_and(|payload| payload["type"] == "need")
+        {
+            return Ok(());
+        }
+
         let origin = origin_agent.unwrap();
         if let Some(parent_id) = origin.parent_id {
             let mut parent = self
@@ -119,11 +837,23 @@ impl<S: WebStore> CoordinationEngine<S> {
                 source_agent: origin.id,
                 content: signal.content.clone(),
                 data: signal.payload.clone().unwrap_or(serde_json::json!({})),
+                frequency: signal.frequency.clone(),
+                amplitude: Some(signal.amplitude),
             });
+            parent.trim_knowledge(config.max_context_items, config.context_eviction);
 
-            const MAX_CONTEXT_ITEMS: usize = 10;
-            if parent.context.accumulated_knowledge.len() > MAX_CONTEXT_ITEMS {
-                parent.context.accumulated_knowledge.drain(0..1);
+            if config.tuning_drift {
+                let drifted = TuningDriftTracker::from_knowledge(
+                    &parent.context.accumulated_knowledge,
+                    config.tuning_drift_rate,
+                )
+                .compute_normalized_drift(&parent.tuning);
+                let purpose = parent
+                    .context
+                    .purpose_tuning
+                    .get_or_insert_with(|| parent.tuning.clone());
+                let capped = cap_drift(purpose, drifted, config.max_tuning_drift);
+                parent.set_tuning(capped);
             }
 
             self.store.update_agent(parent)?;
@@ -138,29 +868,104 @@ impl<S: WebStore> CoordinationEngine<S> {
             .get_agent(agent_id)?
             .ok_or_else(|| anyhow::anyhow!("Agent not found"))?;
 
-        if agent.state == AgentState::Active {
+        if matches!(
+            agent.state,
+            AgentState::Active
+                | AgentState::Paused
+                | AgentState::WindingDown
+                | AgentState::Terminated
+        ) {
+            return Ok(());
+        }
+        let prior_state = agent.state;
+
+        let token = self.cancellation_token(&agent.web_id).unwrap_or_default();
+        if token.is_cancelled() {
             return Ok(());
         }
 
-        agent.state = AgentState::Active;
+        match agent.state {
+            AgentState::Listening | AgentState::Dormant => {
+                AgentStateMachine::transition(&mut agent, LifecycleEvent::Activated)?;
+            }
+            // Quarantined and isolated agents still run, and go back to
+            // their state afterwards.
+            _ => agent.state = AgentState::Active,
+        }
         self.store.update_agent(agent.clone())?;
+        {
+            let mut run_stats = self.run_stats.lock().unwrap();
+            let stats = run_stats.entry(agent.web_id).or_default();
+            stats.activated.insert(agent.id);
+            stats.peak_active = stats.peak_active.max(stats.activated.len());
+        }
 
-        let result = self.execute_agent(&agent, Some(trigger_signal)).await?;
+        let result = tokio::select! {
+            result = self.execute_agent(&agent, Some(trigger_signal)) => result?,
+            _ = token.cancelled() => return Ok(()),
+        };
 
-        for signal_draft in result.signals_to_emit {
+        // Everything sent while working on a planner's step carries the step
+        // id, however deep below the planner the work happens, so the
+        // answer reaching the planner says which step it finishes.
+        let plan_step = plan_step(trigger_signal);
+        for mut signal_draft in result.signals_to_emit {
+            tag_plan_step(&mut signal_draft.payload, plan_step);
             let new_signal = signal_draft.into_signal(agent.id);
             self.store.add_signal(new_signal)?;
         }
 
         for need in result.needs {
-            self.handle_need(&agent, &need).await?;
+            self.handle_need(&agent, prior_state, &need, plan_step)
+                .await?;
+        }
+
+        for update in result.context_updates {
+            agent.context.accumulated_knowledge.push(ContextItem {
+                source_agent: agent.id,
+                content: update.content,
+                data: update.data,
+                frequency: Vec::new(),
+                amplitude: None,
+            });
+        }
+        if let Some(state) = result.capability_state {
+            agent.context.capability_state = Some(state);
         }
+        if let Some(web) = self.store.get_web(&agent.web_id)? {
+            agent.trim_knowledge(web.config.max_context_items, web.config.context_eviction);
+        }
+
+        agent.complete_execution();
+
+        // Paused by an operator while it ran.
+        let paused = self
+            .store
+            .get_agent(&agent.id)?
+            .is_some_and(|stored| stored.state == AgentState::Paused);
 
         agent.state = match result.status {
+            _ if paused => AgentState::Paused,
+            // Only health changes move an agent out of these.
+            _ if matches!(prior_state, AgentState::Quarantine | AgentState::Isolated) => {
+                prior_state
+            }
             ExecutionStatus::Complete => AgentState::Dormant,
             ExecutionStatus::NeedsMore => AgentState::Listening,
             ExecutionStatus::Failed => AgentState::Dormant,
         };
+        if matches!(result.status, ExecutionStatus::Complete) {
+            if let Some(event) = LifecycleManager::reward_execution(&mut agent, chrono::Utc::now())?
+            {
+                tracing::debug!(
+                    agent_id = %agent.id,
+                    delta = event.delta,
+                    health = agent.health,
+                    state = agent.state.as_str(),
+                    "Agent health rewarded"
+                );
+            }
+        }
         self.store.update_agent(agent)?;
 
         Ok(())
@@ -174,8 +979,12 @@ impl<S: WebStore> CoordinationEngine<S> {
         let capability = self.capabilities.get(&agent.capability);
 
         if let Some(cap) = capability {
+            let mut context = agent.context.clone();
+            if let Some(web) = self.store.get_web(&agent.web_id)? {
+                context.embedding_dim = web.config.embedding_dim;
+            }
             let result: ExecutionResult = cap
-                .execute(&agent.context, trigger, &self.providers)
+                .execute(&context, trigger, &self.providers(&agent.web_id))
                 .await?;
             Ok(result)
         } else {
@@ -184,15 +993,30 @@ impl<S: WebStore> CoordinationEngine<S> {
                 output: serde_json::json!({"message": format!("Agent {} executed (no capability)", agent.purpose)}),
                 signals_to_emit: vec![],
                 needs: vec![],
+                context_updates: vec![],
+                capability_state: None,
             })
         }
     }
 
-    async fn handle_need(&self, parent: &Agent, need: &Need) -> Result<()> {
-        let need_embedding = if let Some(provider) = &self.providers.embedding {
+    /// Route `need` to a resonating relative, or spawn a child for it.
+    /// `parent_state` is the parent's state before it was activated.
+    async fn handle_need(
+        &self,
+        parent: &Agent,
+        parent_state: AgentState,
+        need: &Need,
+        plan_step: Option<u64>,
+    ) -> Result<()> {
+        let web = self
+            .store
+            .get_web(&parent.web_id)?
+            .ok_or_else(|| anyhow::anyhow!("Web not found"))?;
+        let providers = self.providers(&parent.web_id);
+        let need_embedding = if let Some(provider) = &providers.embedding {
             provider.embed(&need.description).await?
         } else {
-            vec![1.0; 1536]
+            vec![1.0; web.config.embedding_dim]
         };
 
         let mut ancestors = self.store.get_ancestors(&parent.id)?;
@@ -211,26 +1035,106 @@ impl<S: WebStore> CoordinationEngine<S> {
             payload: None,
         };
 
+        let need_magnitude = magnitude(&need_embedding);
         for lineage_agent in &ancestors {
-            let resonance = compute_resonance(lineage_agent, &dummy_signal);
+            let resonance = compute_resonance(lineage_agent, &dummy_signal, need_magnitude);
             if resonance.activated {
-                let signal_to_agent = Signal::new(
+                let mut signal_to_agent = Signal::new(
                     parent.id,
                     need_embedding.clone(),
                     need.description.clone(),
                     SignalDirection::Downward,
                 );
+                tag_plan_step(&mut signal_to_agent.payload, plan_step);
                 self.store.add_signal(signal_to_agent)?;
                 return Ok(());
             }
         }
 
-        let web = self.store.get_web(&parent.web_id)?.unwrap();
+        // An unhealthy agent's children would inherit its bad judgement, and
+        // so would the dormant agents it wakes; the nearest healthy ancestor
+        // takes the need instead.
+        if !may_spawn(&web.config, parent, parent_state) {
+            let ancestor = self
+                .store
+                .get_ancestors(&parent.id)?
+                .into_iter()
+                .find(|a| may_spawn(&web.config, a, a.state));
+            // Kept as a record of the hand-over only; it isn't propagated, so
+            // it doesn't reach anyone's knowledge.
+            let mut record = Signal::new(
+                parent.id,
+                need_embedding,
+                need.description.clone(),
+                SignalDirection::Upward,
+            )
+            .with_payload(serde_json::json!({
+                "type": "need",
+                "reason": "parent_unhealthy",
+                "handed_to": ancestor.as_ref().map(|a| a.id),
+            }));
+            tag_plan_step(&mut record.payload, plan_step);
+            self.store.add_signal(record.clone())?;
+            self.store.mark_signal_processed(&record.id)?;
+
+            match ancestor {
+                Some(ancestor) => {
+                    tracing::debug!(
+                        agent_id = %parent.id,
+                        ancestor_id = %ancestor.id,
+                        health = parent.health,
+                        state = ?parent_state,
+                        "Handing need to a healthy ancestor instead of spawning"
+                    );
+                    // Boxed: the ancestor's need handling is this function.
+                    Box::pin(self.handle_need(&ancestor, ancestor.state, need, plan_step)).await?;
+                }
+                // The root, or an agent whose whole lineage is unhealthy, has
+                // no one to take the need.
+                None => tracing::warn!(
+                    agent_id = %parent.id,
+                    need = %need.description,
+                    "No healthy ancestor to take the need; dropping it"
+                ),
+            }
+            return Ok(());
+        }
+
+        if let Some(dormant) = self.dormant_match(&web, parent, &need_embedding)? {
+            tracing::debug!(
+                agent_id = %dormant.id,
+                need = %need.description,
+                "Reactivating dormant agent instead of spawning"
+            );
+            let mut need_signal = Signal::new(
+                parent.id,
+                need_embedding,
+                need.description.clone(),
+                SignalDirection::Downward,
+            );
+            tag_plan_step(&mut need_signal.payload, plan_step);
+            self.store.add_signal(need_signal.clone())?;
+            self.store.mark_signal_processed(&need_signal.id)?;
+            // Boxed: activating an agent can bring us back here.
+            Box::pin(self.activate_agent(&dormant.id, &need_signal)).await?;
+            return Ok(());
+        }
+
         let agents_count = self.store.get_agents_by_web(&parent.web_id)?.len();
         if agents_count >= web.config.max_agents {
             return Ok(());
         }
 
+        {
+            let mut run_stats = self.run_stats.lock().unwrap();
+            let stats = run_stats.entry(parent.web_id).or_default();
+            if stats.spawned >= web.config.max_spawns_per_iteration {
+                stats.dropped += 1;
+                return Ok(());
+            }
+            stats.spawned += 1;
+        }
+
         let child_capability = need
             .suggested_capability
             .clone()
@@ -246,28 +1150,105 @@ impl<S: WebStore> CoordinationEngine<S> {
         );
 
         self.store.add_agent(child_agent.clone())?;
+        self.metrics.record_agent_spawned(&parent.web_id);
 
-        let initial_signal = Signal::new(
+        let mut initial_signal = Signal::new(
             parent.id,
             need_embedding,
             need.description.clone(),
             SignalDirection::Downward,
         );
+        tag_plan_step(&mut initial_signal.payload, plan_step);
         self.store.add_signal(initial_signal)?;
 
         Ok(())
     }
 
+    /// The dormant agent in `web`, other than `parent`, whose tuning is most
+    /// similar to the need, if any clears `dormant_reactivation_threshold`.
+    fn dormant_match(
+        &self,
+        web: &Web,
+        parent: &Agent,
+        need_embedding: &[f32],
+    ) -> Result<Option<Agent>> {
+        Ok(self
+            .store
+            .get_agents_by_web(&web.id)?
+            .into_iter()
+            .filter(|agent| agent.state == AgentState::Dormant && agent.id != parent.id)
+            .map(|agent| (cosine_similarity(&agent.tuning, need_embedding), agent))
+            .filter(|(similarity, _)| *similarity > web.config.dormant_reactivation_threshold)
+            .max_by(|(a, _), (b, _)| a.total_cmp(b))
+            .map(|(_, agent)| agent))
+    }
+
     async fn check_convergence(&self, web_id: &uuid::Uuid) -> Result<bool> {
         let pending_signals = self.store.get_pending_signals(web_id)?;
         if !pending_signals.is_empty() {
             return Ok(false);
         }
 
-        let agents = self.store.get_agents_by_web(web_id)?;
-        let has_active = agents.iter().any(|a| a.state == AgentState::Active);
+        Ok(self.get_active_agents(web_id)?.is_empty())
+    }
 
-        Ok(!has_active)
+    /// Distill the root agent's knowledge into a `FinalReport` and store it
+    /// on the web. Without an LLM, or if its answer can't be used, the
+    /// report is the knowledge joined together.
+    pub async fn synthesize_final_report(&self, web_id: &uuid::Uuid) -> Result<FinalReport> {
+        let web = self
+            .store
+            .get_web(web_id)?
+            .ok_or_else(|| anyhow::anyhow!("Web not found"))?;
+        let knowledge: Vec<String> = self
+            .store
+            .get_agent(&web.root_agent)?
+            .map(|root| {
+                root.context
+                    .accumulated_knowledge
+                    .into_iter()
+                    .map(|item| item.content)
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        let providers = self.providers(web_id);
+        let report = match &providers.llm {
+            Some(llm) => {
+                let before = self.usage.web_usage(web_id);
+                let response = self
+                    .usage
+                    .scope(*web_id, llm.complete(report_prompt(&web.task, &knowledge)))
+                    .await;
+                self.record_token_usage(web_id, &before).await?;
+                match response.and_then(|text| parse_report(&text)) {
+                    Ok(report) => report,
+                    Err(e) => {
+                        tracing::warn!(web_id = %web_id, error = %e, "Falling back to a concatenated report");
+                        concatenated_report(&knowledge)
+                    }
+                }
+            }
+            None => concatenated_report(&knowledge),
+        };
+
+        // Re-read, in case the web changed while the LLM was writing.
+        if let Some(mut web) = self.store.get_web(web_id)? {
+            web.report = Some(report.clone());
+            self.store.update_web(web)?;
+        }
+        Ok(report)
+    }
+
+    /// Whether the web's root agent has accumulated any knowledge.
+    fn root_has_output(&self, web_id: &uuid::Uuid) -> Result<bool> {
+        let Some(web) = self.store.get_web(web_id)? else {
+            return Ok(false);
+        };
+        Ok(self
+            .store
+            .get_agent(&web.root_agent)?
+            .is_some_and(|root| !root.context.accumulated_knowledge.is_empty()))
     }
 
     fn get_active_agents(&self, web_id: &uuid::Uuid) -> Result<Vec<Agent>> {
@@ -286,7 +1267,8 @@ impl<S: WebStore> CoordinationEngine<S> {
         Ok(())
     }
 
-    fn mark_web_failed(&self, web_id: &uuid::Uuid, _reason: &str) -> Result<()> {
+    fn mark_web_failed(&self, web_id: &uuid::Uuid, reason: &str) -> Result<()> {
+        tracing::warn!(web_id = %web_id, reason, "Web failed");
         if let Some(mut web) = self.store.get_web(web_id)? {
             web.state = WebState::Failed;
             self.store.update_web(web)?;
@@ -295,11 +1277,116 @@ impl<S: WebStore> CoordinationEngine<S> {
     }
 }
 
+/// `providers` with LLM calls counted into `usage`.
+fn metered(mut providers: Providers, usage: &UsageMeter) -> Providers {
+    providers.llm = providers.llm.map(|llm| {
+        Box::new(MeteredLLMProvider::new(Arc::from(llm), usage.clone())) as Box<dyn LLMProvider>
+    });
+    providers
+}
+
+/// The state `agent` should take from its stored copy if that was paused
+/// or resumed through the API, which the loop never does itself.
+fn operator_state(agent: &Agent, stored: AgentState) -> Option<AgentState> {
+    let paused = agent.state == AgentState::Paused;
+    let changed = paused != (stored == AgentState::Paused);
+    (changed && agent.state != AgentState::Terminated).then_some(stored)
+}
+
+/// Levels below the root reached by `agents`, following parent links.
+fn max_depth(agents: &[Agent]) -> usize {
+    let parents: HashMap<uuid::Uuid, Option<uuid::Uuid>> =
+        agents.iter().map(|a| (a.id, a.parent_id)).collect();
+    agents
+        .iter()
+        .map(|agent| {
+            let mut depth = 0;
+            let mut parent = agent.parent_id;
+            while let Some(id) = parent {
+                depth += 1;
+                // Stop at agents outside the web, and on cycles.
+                parent = parents.get(&id).copied().flatten();
+                if depth > agents.len() {
+                    break;
+                }
+            }
+            depth
+        })
+        .max()
+        .unwrap_or(0)
+}
+
+/// Why `usage` puts `web` over budget, if it does.
+fn budget_exceeded(web: &Web, usage: &Usage) -> Option<String> {
+    if let Some(budget) = web.config.token_budget {
+        if usage.total_tokens() > budget {
+            return Some(format!(
+                "Token budget exceeded: {} of {} tokens used",
+                usage.total_tokens(),
+                budget
+            ));
+        }
+    }
+    if let Some(budget) = web.config.cost_budget {
+        if usage.cost_usd > budget {
+            return Some(format!(
+                "Cost budget exceeded: ${:.4} of ${:.4} spent",
+                usage.cost_usd, budget
+            ));
+        }
+    }
+    None
+}
+
+/// Whether `agent`, in `state`, is trusted to spawn or wake agents.
+fn may_spawn(config: &WebConfig, agent: &Agent, state: AgentState) -> bool {
+    agent.health >= config.min_spawn_health
+        && !matches!(state, AgentState::Quarantine | AgentState::Isolated)
+}
+
+/// The planner step `signal` belongs to: the one it dispatches, or the one
+/// it was sent while working on.
+fn plan_step(signal: &Signal) -> Option<u64> {
+    let payload = signal.payload.as_ref()?;
+    if payload["type"] == "plan_step" {
+        payload["step"].as_u64()
+    } else {
+        payload["plan_step"].as_u64()
+    }
+}
+
+/// Mark a signal's payload as sent while working on plan step `step`,
+/// unless it dispatches a step of its own.
+fn tag_plan_step(payload: &mut Option<serde_json::Value>, step: Option<u64>) {
+    let Some(step) = step else {
+        return;
+    };
+    match payload {
+        Some(serde_json::Value::Object(payload))
+            if payload.get("type").is_none_or(|t| t != "plan_step") =>
+        {
+            payload.insert("plan_step".to_string(), step.into());
+        }
+        None => *payload = Some(serde_json::json!({ "plan_step": step })),
+        Some(_) => {}
+    }
+}
+
 pub struct ExecutionResult {
     pub status: ExecutionStatus,
     pub output: serde_json::Value,
     pub signals_to_emit: Vec<SignalDraft>,
     pub needs: Vec<Need>,
+    pub context_updates: Vec<ContextUpdate>,
+    /// Replaces the agent's `capability_state` when set.
+    pub capability_state: Option<serde_json::Value>,
+}
+
+/// Knowledge a capability wants recorded in its own agent's context.
+#[derive(Debug, Clone)]
+pub struct ContextUpdate {
+    pub content: String,
+    pub data: serde_json::Value,
 }
 
 #[derive(Debug, Clone)]
@@ -311,7 +1398,115 @@ pub struct Need {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::engine::metrics::WebMetrics;
+    use crate::providers::embedding::EmbeddingProvider;
+    use crate::providers::hashing::HashingEmbeddingProvider;
+    use crate::providers::llm::Message;
     use crate::storage::memory::InMemoryStore;
+    use crate::storage::traits::Storage;
+    use crate::types::{AgentContext, ContextEvictionPolicy};
+    use async_trait::async_trait;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// Reports a fixed 600 tokens per call.
+    struct CostlyLLMProvider {
+        calls: Arc<AtomicUsize>,
+    }
+
+    #[async_trait]
+    impl LLMProvider for CostlyLLMProvider {
+        async fn complete(&self, messages: Vec<Message>) -> Result<String> {
+            Ok(self.complete_with_usage(messages).await?.0)
+        }
+
+        async fn complete_with_usage(&self, _messages: Vec<Message>) -> Result<(String, Usage)> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            Ok(("keep going".to_string(), Usage::new(500, 100)))
+        }
+    }
+
+    /// Asks the LLM, then re-broadcasts the trigger so the loop never settles.
+    struct LoopingCapability;
+
+    #[async_trait]
+    impl Capability for LoopingCapability {
+        fn name(&self) -> &str {
+            "looping"
+        }
+
+        fn description(&self) -> &str {
+            "Never finishes"
+        }
+
+        async fn execute(
+            &self,
+            _context: &AgentContext,
+            trigger: Option<&Signal>,
+            providers: &Providers,
+        ) -> Result<ExecutionResult> {
+            let llm = providers.llm.as_ref().unwrap();
+            let response = llm.complete(vec![Message::user("continue")]).await?;
+            let trigger = trigger.unwrap();
+
+            Ok(ExecutionResult {
+                status: ExecutionStatus::NeedsMore,
+                output: serde_json::json!({ "response": response }),
+                signals_to_emit: vec![SignalDraft {
+                    frequency: trigger.frequency.clone(),
+                    content: response,
+                    direction: SignalDirection::Downward,
+                    amplitude: 1.0,
+                    payload: None,
+                }],
+                needs: vec![],
+                context_updates: vec![],
+                capability_state: None,
+            })
+        }
+    }
+
+    /// Counts executions, each of which takes a while and re-broadcasts the
+    /// trigger.
+    struct SlowCapability {
+        executions: Arc<AtomicUsize>,
+    }
+
+    #[async_trait]
+    impl Capability for SlowCapability {
+        fn name(&self) -> &str {
+            "slow"
+        }
+
+        fn description(&self) -> &str {
+            "Takes its time"
+        }
+
+        async fn execute(
+            &self,
+            _context: &AgentContext,
+            trigger: Option<&Signal>,
+            _providers: &Providers,
+        ) -> Result<ExecutionResult> {
+            self.executions.fetch_add(1, Ordering::SeqCst);
+            tokio::time::sleep(tokio::time::Duration::from_millis(50)).await;
+            let trigger = trigger.unwrap();
+
+            Ok(ExecutionResult {
+                status: ExecutionStatus::NeedsMore,
+                output: serde_json::json!({}),
+                signals_to_emit: vec![SignalDraft {
+                    frequency: trigger.frequency.clone(),
+                    content: trigger.content.clone(),
+                    direction: SignalDirection::Downward,
+                    amplitude: 1.0,
+                    payload: None,
+                }],
+                needs: vec![],
+                context_updates: vec![],
+                capability_state: None,
+            })
+        }
+    }
 
     #[tokio::test]
     async fn test_coordination_engine_creation() {
@@ -324,4 +1519,1383 @@ mod tests {
         };
         let _engine = CoordinationEngine::new(store, capabilities, providers);
     }
+
+    #[tokio::test]
+    async fn test_set_providers_applies_to_webs_seeded_afterwards() {
+        let store = Arc::new(InMemoryStore::new());
+        let providers = Providers {
+            embedding: None,
+            llm: None,
+            search: None,
+        };
+        let engine = CoordinationEngine::new(store.clone(), HashMap::new(), providers);
+        let web = |task: &str| {
+            let web = Web::new(uuid::Uuid::new_v4(), task.to_string(), WebConfig::default());
+            WebStore::create_web(&*store, web.clone()).unwrap();
+            web
+        };
+        let root_tuning = |web: &Web| {
+            WebStore::get_agent(&*store, &web.root_agent)
+                .unwrap()
+                .unwrap()
+                .tuning
+        };
+
+        let before = web("seeded before the reload");
+        engine.seed_web(&before.id).await.unwrap();
+        engine.set_providers(Providers {
+            embedding: Some(Box::new(HashingEmbeddingProvider::new())),
+            llm: None,
+            search: None,
+        });
+        let after = web("seeded after the reload");
+        engine.seed_web(&after.id).await.unwrap();
+
+        assert!(root_tuning(&before).iter().all(|v| *v == 1.0));
+        assert!(root_tuning(&after).iter().any(|v| *v != 1.0));
+    }
+
+    /// Reports back up at a fixed frequency.
+    struct ReplyCapability {
+        frequency: Vec<f32>,
+    }
+
+    #[async_trait]
+    impl Capability for ReplyCapability {
+        fn name(&self) -> &str {
+            "reply"
+        }
+
+        fn description(&self) -> &str {
+            "Answers whatever reaches it"
+        }
+
+        async fn execute(
+            &self,
+            _context: &AgentContext,
+            _trigger: Option<&Signal>,
+            _providers: &Providers,
+        ) -> Result<ExecutionResult> {
+            Ok(ExecutionResult {
+                status: ExecutionStatus::Complete,
+                output: serde_json::json!({}),
+                signals_to_emit: vec![SignalDraft {
+                    frequency: self.frequency.clone(),
+                    content: "answer".to_string(),
+                    direction: SignalDirection::Upward,
+                    amplitude: 1.0,
+                    payload: Some(serde_json::json!({ "found": 1 })),
+                }],
+                needs: vec![],
+                context_updates: vec![],
+                capability_state: None,
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_plan_step_carried_up_from_grandchild() {
+        let store = Arc::new(InMemoryStore::new());
+        let agent = |parent: Option<&Agent>, tuning: Vec<f32>, capability| {
+            let web_id = parent.map_or_else(uuid::Uuid::new_v4, |p| p.web_id);
+            let mut agent = Agent::new(
+                web_id,
+                parent.map(|p| p.id),
+                "agent".to_string(),
+                tuning,
+                capability,
+                0.5,
+            );
+            agent.web_id = web_id;
+            agent
+        };
+        // The step reaches only the grandchild; each answer resonates with
+        // the agent one level up.
+        let planner = agent(None, vec![1.0, 0.0, 0.0], CapabilityType::Planner);
+        let middle = agent(Some(&planner), vec![0.0, 1.0, 0.0], CapabilityType::Analyst);
+        let worker = agent(Some(&middle), vec![0.0, 0.0, 1.0], CapabilityType::Search);
+        let mut web = Web::new(planner.id, "Plan".to_string(), WebConfig::default());
+        web.id = planner.web_id;
+        WebStore::create_web(&*store, web.clone()).unwrap();
+        for agent in [&planner, &middle, &worker] {
+            store.add_agent(agent.clone()).unwrap();
+        }
+        store
+            .add_signal(
+                Signal::new(
+                    planner.id,
+                    vec![0.0, 0.0, 1.0],
+                    "Gather sources".to_string(),
+                    SignalDirection::Downward,
+                )
+                .with_payload(serde_json::json!({ "type": "plan_step", "step": 1 })),
+            )
+            .unwrap();
+
+        let mut capabilities: HashMap<CapabilityType, Box<dyn Capability>> = HashMap::new();
+        capabilities.insert(CapabilityType::Planner, Box::new(OneShotCapability));
+        capabilities.insert(
+            CapabilityType::Analyst,
+            Box::new(ReplyCapability {
+                frequency: vec![1.0, 0.0, 0.0],
+            }),
+        );
+        capabilities.insert(
+            CapabilityType::Search,
+            Box::new(ReplyCapability {
+                frequency: vec![0.0, 1.0, 0.0],
+            }),
+        );
+        let providers = Providers {
+            embedding: None,
+            llm: None,
+            search: None,
+        };
+        let engine = CoordinationEngine::new(store.clone(), capabilities, providers);
+        engine.run_coordination_loop(&web.id).await.unwrap();
+
+        let step_of = |holder: &Agent, source: &Agent| {
+            let holder = WebStore::get_agent(&*store, &holder.id).unwrap().unwrap();
+            holder
+                .context
+                .accumulated_knowledge
+                .iter()
+                .find(|item| item.source_agent == source.id)
+                .map(|item| item.data.clone())
+                .unwrap()
+        };
+        assert_eq!(
+            step_of(&middle, &worker),
+            serde_json::json!({ "found": 1, "plan_step": 1 })
+        );
+        assert_eq!(
+            step_of(&planner, &middle),
+            serde_json::json!({ "found": 1, "plan_step": 1 })
+        );
+
+        // A signal dispatching a step of its own keeps its own step.
+        let mut dispatch = Some(serde_json::json!({ "type": "plan_step", "step": 2 }));
+        tag_plan_step(&mut dispatch, Some(1));
+        assert_eq!(
+            dispatch,
+            Some(serde_json::json!({ "type": "plan_step", "step": 2 }))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_token_budget_fails_web() {
+        let store = Arc::new(InMemoryStore::new());
+        let calls = Arc::new(AtomicUsize::new(0));
+
+        let tuning = vec![1.0, 0.0, 0.0];
+        let root = Agent::new(
+            uuid::Uuid::new_v4(),
+            None,
+            "root".to_string(),
+            tuning.clone(),
+            CapabilityType::Planner,
+            0.5,
+        );
+        let mut web = Web::new(
+            root.id,
+            "Loop forever".to_string(),
+            WebConfig {
+                token_budget: Some(1000),
+                ..WebConfig::default()
+            },
+        );
+        web.id = root.web_id;
+        WebStore::create_web(&*store, web.clone()).unwrap();
+        store.add_agent(root.clone()).unwrap();
+        store
+            .add_signal(Signal::new(
+                root.id,
+                tuning,
+                "start".to_string(),
+                SignalDirection::Downward,
+            ))
+            .unwrap();
+
+        let mut capabilities: HashMap<CapabilityType, Box<dyn Capability>> = HashMap::new();
+        capabilities.insert(CapabilityType::Planner, Box::new(LoopingCapability));
+        let providers = Providers {
+            embedding: None,
+            llm: Some(Box::new(CostlyLLMProvider {
+                calls: calls.clone(),
+            })),
+            search: None,
+        };
+        let engine = CoordinationEngine::new(store.clone(), capabilities, providers);
+
+        engine.run_coordination_loop(&web.id).await.unwrap();
+
+        let web = WebStore::get_web(&*store, &web.id).unwrap().unwrap();
+        assert_eq!(web.state, WebState::Failed);
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+        assert_eq!(engine.usage_meter().web_usage(&web.id).total_tokens(), 1200);
+        let stored = Storage::get_token_usage(&*store, web.id).await.unwrap();
+        assert_eq!(stored, engine.usage_meter().web_usage(&web.id));
+
+        let patterns = store.get_failure_patterns(web.id).await.unwrap();
+        assert_eq!(patterns.len(), 1);
+        assert!(matches!(
+            patterns[0].pattern_type,
+            FailurePatternType::ResourceExhaustion
+        ));
+        assert_eq!(patterns[0].pattern_data["reason"], "budget_exceeded");
+    }
+
+    /// Finishes on its first execution.
+    struct OneShotCapability;
+
+    #[async_trait]
+    impl Capability for OneShotCapability {
+        fn name(&self) -> &str {
+            "one_shot"
+        }
+
+        fn description(&self) -> &str {
+            "Done right away"
+        }
+
+        async fn execute(
+            &self,
+            _context: &AgentContext,
+            _trigger: Option<&Signal>,
+            _providers: &Providers,
+        ) -> Result<ExecutionResult> {
+            Ok(ExecutionResult {
+                status: ExecutionStatus::Complete,
+                output: serde_json::json!({}),
+                signals_to_emit: vec![],
+                needs: vec![],
+                context_updates: vec![ContextUpdate {
+                    content: "done".to_string(),
+                    data: serde_json::json!({}),
+                }],
+                capability_state: None,
+            })
+        }
+    }
+
+    /// Finishes without learning anything.
+    struct SilentCapability;
+
+    #[async_trait]
+    impl Capability for SilentCapability {
+        fn name(&self) -> &str {
+            "silent"
+        }
+
+        fn description(&self) -> &str {
+            "Finds nothing"
+        }
+
+        async fn execute(
+            &self,
+            _context: &AgentContext,
+            _trigger: Option<&Signal>,
+            _providers: &Providers,
+        ) -> Result<ExecutionResult> {
+            Ok(ExecutionResult {
+                status: ExecutionStatus::Complete,
+                output: serde_json::json!({}),
+                signals_to_emit: vec![],
+                needs: vec![],
+                context_updates: vec![],
+                capability_state: None,
+            })
+        }
+    }
+
+    /// A running web whose root planner has one pending signal.
+    fn seed_planner_web(store: &InMemoryStore, task: &str) -> Web {
+        let tuning = vec![1.0, 0.0, 0.0];
+        let root = Agent::new(
+            uuid::Uuid::new_v4(),
+            None,
+            "root".to_string(),
+            tuning.clone(),
+            CapabilityType::Planner,
+            0.5,
+        );
+        let mut web = Web::new(root.id, task.to_string(), WebConfig::default());
+        web.id = root.web_id;
+        WebStore::create_web(store, web.clone()).unwrap();
+        store.add_agent(root.clone()).unwrap();
+        store
+            .add_signal(Signal::new(
+                root.id,
+                tuning,
+                "start".to_string(),
+                SignalDirection::Downward,
+            ))
+            .unwrap();
+        web
+    }
+
+    fn slow_engine(
+        store: Arc<InMemoryStore>,
+        executions: Arc<AtomicUsize>,
+    ) -> CoordinationEngine<InMemoryStore> {
+        let mut capabilities: HashMap<CapabilityType, Box<dyn Capability>> = HashMap::new();
+        capabilities.insert(
+            CapabilityType::Planner,
+            Box::new(SlowCapability { executions }),
+        );
+        let providers = Providers {
+            embedding: None,
+            llm: None,
+            search: None,
+        };
+        CoordinationEngine::new(store, capabilities, providers)
+    }
+
+    #[tokio::test]
+    async fn test_cancel_stops_running_web() {
+        let store = Arc::new(InMemoryStore::new());
+        let executions = Arc::new(AtomicUsize::new(0));
+        let web = seed_planner_web(&store, "Run slowly");
+        let engine = Arc::new(slow_engine(store.clone(), executions.clone()));
+        assert!(!engine.cancel(&web.id));
+
+        let handle = tokio::spawn({
+            let engine = engine.clone();
+            async move { engine.run_coordination_loop(&web.id).await }
+        });
+        while executions.load(Ordering::SeqCst) < 2 {
+            tokio::time::sleep(tokio::time::Duration::from_millis(5)).await;
+        }
+
+        assert!(engine.cancel(&web.id));
+        let at_cancel = executions.load(Ordering::SeqCst);
+        tokio::time::timeout(tokio::time::Duration::from_millis(500), handle)
+            .await
+            .expect("loop did not stop")
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(executions.load(Ordering::SeqCst), at_cancel);
+        let web = WebStore::get_web(&*store, &web.id).unwrap().unwrap();
+        assert_eq!(web.state, WebState::Failed);
+        assert!(engine.get_active_agents(&web.id).unwrap().is_empty());
+        assert!(!engine.cancel(&web.id));
+    }
+
+    #[tokio::test]
+    async fn test_checkpoint_survives_crash() {
+        let store = Arc::new(InMemoryStore::new());
+        let durable = Arc::new(InMemoryStore::new());
+        let executions = Arc::new(AtomicUsize::new(0));
+        let web = seed_planner_web(&store, "Run slowly");
+        let engine = Arc::new(
+            slow_engine(store.clone(), executions.clone()).with_checkpoints(
+                durable.clone(),
+                CheckpointConfig {
+                    every_iterations: Some(1),
+                    every: None,
+                },
+            ),
+        );
+
+        let handle = tokio::spawn({
+            let engine = engine.clone();
+            async move { engine.run_coordination_loop(&web.id).await }
+        });
+        while executions.load(Ordering::SeqCst) < 3 {
+            tokio::time::sleep(tokio::time::Duration::from_millis(5)).await;
+        }
+        // Crash: the loop dies mid-run and the in-memory store is lost.
+        handle.abort();
+        let _ = handle.await;
+        drop(engine);
+        drop(store);
+
+        let checkpoint = durable.get_checkpoint(web.id).await.unwrap().unwrap();
+        assert!(checkpoint.iteration >= 2);
+        assert_eq!(checkpoint.web.state, WebState::Running);
+        assert_eq!(checkpoint.agents.len(), 1);
+        assert_eq!(checkpoint.pending_signals.len(), 1);
+
+        let store = Arc::new(InMemoryStore::new());
+        let engine = slow_engine(store.clone(), executions);
+        engine.restore_checkpoint(&checkpoint).unwrap();
+
+        let restored = engine.snapshot(&web.id, checkpoint.iteration).unwrap();
+        assert_eq!(restored.web.state, WebState::Running);
+        assert_eq!(restored.agents[0].id, checkpoint.agents[0].id);
+        assert_eq!(
+            restored.pending_signals[0].id,
+            checkpoint.pending_signals[0].id
+        );
+    }
+
+    #[tokio::test]
+    async fn test_resume_checkpointed_web_converges() {
+        let durable = Arc::new(InMemoryStore::new());
+
+        // The crashed run: the root agent was mid-execution on the signal.
+        let crashed = Arc::new(InMemoryStore::new());
+        let web = seed_planner_web(&crashed, "Finish after a crash");
+        let mut root = WebStore::get_agent(&*crashed, &web.root_agent)
+            .unwrap()
+            .unwrap();
+        root.state = AgentState::Active;
+        WebStore::update_agent(&*crashed, root).unwrap();
+        let checkpoint = slow_engine(crashed, Arc::new(AtomicUsize::new(0)))
+            .snapshot(&web.id, 3)
+            .unwrap();
+        durable.save_checkpoint(&checkpoint).await.unwrap();
+
+        let store = Arc::new(InMemoryStore::new());
+        let mut capabilities: HashMap<CapabilityType, Box<dyn Capability>> = HashMap::new();
+        capabilities.insert(CapabilityType::Planner, Box::new(OneShotCapability));
+        let providers = Providers {
+            embedding: None,
+            llm: None,
+            search: None,
+        };
+        let engine = CoordinationEngine::new(store.clone(), capabilities, providers)
+            .with_checkpoints(durable.clone(), CheckpointConfig::default());
+
+        engine.resume(&web.id).await.unwrap();
+
+        let resumed = WebStore::get_web(&*store, &web.id).unwrap().unwrap();
+        assert_eq!(resumed.state, WebState::Converged);
+        let root = WebStore::get_agent(&*store, &web.root_agent)
+            .unwrap()
+            .unwrap();
+        assert_eq!(root.state, AgentState::Dormant);
+        assert!(WebStore::get_pending_signals(&*store, &web.id)
+            .unwrap()
+            .is_empty());
+
+        // The final state is checkpointed, and a finished web can't resume.
+        let checkpoint = durable.get_checkpoint(web.id).await.unwrap().unwrap();
+        assert_eq!(checkpoint.web.state, WebState::Converged);
+        assert!(engine.resume(&web.id).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_write_through_keeps_storage_current() {
+        let store = Arc::new(InMemoryStore::new());
+        let durable = Arc::new(InMemoryStore::new());
+        let executions = Arc::new(AtomicUsize::new(0));
+        let web = seed_planner_web(&store, "Run slowly");
+        // Seeded into storage the way `StorageRunner` does.
+        Storage::create_web(&*durable, &web).await.unwrap();
+        let root = WebStore::get_agent(&*store, &web.root_agent)
+            .unwrap()
+            .unwrap();
+        Storage::create_agent(&*durable, &root).await.unwrap();
+        let engine = Arc::new(
+            slow_engine(store.clone(), executions.clone())
+                .with_checkpoints(
+                    durable.clone(),
+                    CheckpointConfig {
+                        every_iterations: Some(1),
+                        every: None,
+                    },
+                )
+                .with_write_through(),
+        );
+
+        let handle = tokio::spawn({
+            let engine = engine.clone();
+            async move { engine.run_coordination_loop(&web.id).await }
+        });
+        while executions.load(Ordering::SeqCst) < 3 {
+            tokio::time::sleep(tokio::time::Duration::from_millis(5)).await;
+        }
+
+        // Storage follows the run while it goes.
+        let signals = WebStore::get_web_signals(&*durable, &web.id).unwrap();
+        assert!(signals.len() >= 2);
+        assert!(signals.iter().any(|(_, processed)| *processed));
+        assert_eq!(
+            Storage::get_pending_signals(&*durable, web.id)
+                .await
+                .unwrap()
+                .len(),
+            1
+        );
+
+        // A config change and a pause made in storage reach the loop.
+        let mut config = web.config.clone();
+        config.max_depth = 7;
+        Storage::update_web_config(&*durable, web.id, &config)
+            .await
+            .unwrap();
+        let mut paused = Storage::get_agent(&*durable, web.root_agent)
+            .await
+            .unwrap()
+            .unwrap();
+        paused.state = AgentState::Paused;
+        Storage::update_agent(&*durable, &paused).await.unwrap();
+        loop {
+            let root = WebStore::get_agent(&*store, &web.root_agent)
+                .unwrap()
+                .unwrap();
+            if root.state == AgentState::Paused {
+                break;
+            }
+            tokio::time::sleep(tokio::time::Duration::from_millis(5)).await;
+        }
+        let running = WebStore::get_web(&*store, &web.id).unwrap().unwrap();
+        assert_eq!(running.config.max_depth, 7);
+
+        // So does a termination, and the pause outlives the run.
+        let mut stopped = Storage::get_web(&*durable, web.id).await.unwrap().unwrap();
+        stopped.state = WebState::Failed;
+        Storage::update_web(&*durable, &stopped).await.unwrap();
+        handle.await.unwrap().unwrap();
+        let root = Storage::get_agent(&*durable, web.root_agent)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(root.state, AgentState::Paused);
+    }
+
+    /// Finishes right away, handing one piece of work to a new search agent.
+    struct DelegatingCapability;
+
+    #[async_trait]
+    impl Capability for DelegatingCapability {
+        fn name(&self) -> &str {
+            "delegating"
+        }
+
+        fn description(&self) -> &str {
+            "Hands work off"
+        }
+
+        async fn execute(
+            &self,
+            _context: &AgentContext,
+            _trigger: Option<&Signal>,
+            _providers: &Providers,
+        ) -> Result<ExecutionResult> {
+            Ok(ExecutionResult {
+                status: ExecutionStatus::Complete,
+                output: serde_json::json!({}),
+                signals_to_emit: vec![],
+                needs: vec![Need {
+                    description: "Look it up".to_string(),
+                    suggested_capability: Some(CapabilityType::Search),
+                }],
+                context_updates: vec![ContextUpdate {
+                    content: "done".to_string(),
+                    data: serde_json::json!({}),
+                }],
+                capability_state: None,
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_metrics_count_signals_and_spawns() {
+        let store = Arc::new(InMemoryStore::new());
+        let first = seed_planner_web(&store, "Delegate once");
+        let second = seed_planner_web(&store, "Delegate again");
+
+        let mut capabilities: HashMap<CapabilityType, Box<dyn Capability>> = HashMap::new();
+        capabilities.insert(CapabilityType::Planner, Box::new(DelegatingCapability));
+        capabilities.insert(CapabilityType::Search, Box::new(OneShotCapability));
+        let providers = Providers {
+            embedding: None,
+            llm: None,
+            search: None,
+        };
+        let metrics = MetricsRegistry::new();
+        let engine = CoordinationEngine::new(store.clone(), capabilities, providers)
+            .with_metrics(metrics.clone());
+
+        for web in [&first, &second] {
+            engine.run_coordination_loop(&web.id).await.unwrap();
+            let web = WebStore::get_web(&*store, &web.id).unwrap().unwrap();
+            assert_eq!(web.state, WebState::Converged);
+
+            // The start signal, then the one handed to the spawned agent.
+            let agents = store.get_agents_by_web(&web.id).unwrap();
+            assert_eq!(agents.len(), 2);
+            assert_eq!(
+                metrics.web(&web.id),
+                WebMetrics {
+                    signals_processed: 2,
+                    agents_spawned: 1,
+                }
+            );
+        }
+
+        assert_eq!(
+            engine.metrics().total(),
+            WebMetrics {
+                signals_processed: 4,
+                agents_spawned: 2,
+            }
+        );
+        assert_eq!(metrics.web(&uuid::Uuid::new_v4()), WebMetrics::default());
+    }
+
+    /// Asks for `needs` pieces of work at once.
+    struct FloodingCapability {
+        needs: usize,
+    }
+
+    #[async_trait]
+    impl Capability for FloodingCapability {
+        fn name(&self) -> &str {
+            "flooding"
+        }
+
+        fn description(&self) -> &str {
+            "Decomposes too eagerly"
+        }
+
+        async fn execute(
+            &self,
+            _context: &AgentContext,
+            _trigger: Option<&Signal>,
+            _providers: &Providers,
+        ) -> Result<ExecutionResult> {
+            Ok(ExecutionResult {
+                status: ExecutionStatus::Complete,
+                output: serde_json::json!({}),
+                signals_to_emit: vec![],
+                needs: (0..self.needs)
+                    .map(|i| Need {
+                        description: format!("need-{}", i),
+                        suggested_capability: Some(CapabilityType::Search),
+                    })
+                    .collect(),
+                context_updates: vec![ContextUpdate {
+                    content: "done".to_string(),
+                    data: serde_json::json!({}),
+                }],
+                capability_state: None,
+            })
+        }
+    }
+
+    /// Gives each `need-<n>` its own axis, so no two needs resonate.
+    struct AxisEmbedding;
+
+    #[async_trait]
+    impl EmbeddingProvider for AxisEmbedding {
+        async fn embed(&self, text: &str) -> Result<Vec<f32>> {
+            let axis = text
+                .trim_start_matches("need-")
+                .parse::<usize>()
+                .unwrap_or(63);
+            let mut embedding = vec![0.0; 64];
+            embedding[axis] = 1.0;
+            Ok(embedding)
+        }
+
+        async fn embed_batch(&self, texts: &[String]) -> Result<Vec<Vec<f32>>> {
+            let mut embeddings = Vec::new();
+            for text in texts {
+                embeddings.push(self.embed(text).await?);
+            }
+            Ok(embeddings)
+        }
+    }
+
+    #[tokio::test]
+    async fn test_quiet_web_converges_only_with_root_output() {
+        let run = |capability: Box<dyn Capability>| async move {
+            let store = Arc::new(InMemoryStore::new());
+            let web = seed_planner_web(&store, "Answer something");
+            let mut capabilities: HashMap<CapabilityType, Box<dyn Capability>> = HashMap::new();
+            capabilities.insert(CapabilityType::Planner, capability);
+            let providers = Providers {
+                embedding: None,
+                llm: None,
+                search: None,
+            };
+            let engine = CoordinationEngine::new(store.clone(), capabilities, providers);
+
+            engine.run_coordination_loop(&web.id).await.unwrap();
+
+            let root = WebStore::get_agent(&*store, &web.root_agent)
+                .unwrap()
+                .unwrap();
+            let web = WebStore::get_web(&*store, &web.id).unwrap().unwrap();
+            (web.state, root.context.accumulated_knowledge.len())
+        };
+
+        // Nothing pending and nothing active, but the root learned nothing.
+        assert_eq!(run(Box::new(SilentCapability)).await, (WebState::Failed, 0));
+        assert_eq!(
+            run(Box::new(OneShotCapability)).await,
+            (WebState::Converged, 1)
+        );
+    }
+
+    /// Writes the same final report every time.
+    struct ReportingLLMProvider;
+
+    #[async_trait]
+    impl LLMProvider for ReportingLLMProvider {
+        async fn complete(&self, messages: Vec<Message>) -> Result<String> {
+            Ok(self.complete_with_usage(messages).await?.0)
+        }
+
+        async fn complete_with_usage(&self, messages: Vec<Message>) -> Result<(String, Usage)> {
+            assert!(messages[1].content.contains("- done"));
+            let report = r#"{"summary": "All done", "key_points": ["done"], "confidence": 0.8}"#;
+            Ok((report.to_string(), Usage::new(40, 20)))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_converged_web_gets_final_report() {
+        let run = |llm: Option<Box<dyn LLMProvider>>| async move {
+            let store = Arc::new(InMemoryStore::new());
+            let web = seed_planner_web(&store, "Report back");
+            let mut capabilities: HashMap<CapabilityType, Box<dyn Capability>> = HashMap::new();
+            capabilities.insert(CapabilityType::Planner, Box::new(OneShotCapability));
+            let providers = Providers {
+                embedding: None,
+                llm,
+                search: None,
+            };
+            let engine = CoordinationEngine::new(store.clone(), capabilities, providers);
+
+            engine.run_coordination_loop(&web.id).await.unwrap();
+
+            let usage = Storage::get_token_usage(&*store, web.id).await.unwrap();
+            let web = WebStore::get_web(&*store, &web.id).unwrap().unwrap();
+            assert_eq!(web.state, WebState::Converged);
+            (web.report.unwrap(), usage)
+        };
+
+        let (report, usage) = run(Some(Box::new(ReportingLLMProvider))).await;
+        assert_eq!(
+            report,
+            FinalReport {
+                summary: "All done".to_string(),
+                key_points: vec!["done".to_string()],
+                confidence: 0.8,
+            }
+        );
+        assert_eq!(usage.total_tokens(), 60);
+
+        // Without an LLM the findings are passed through as they are.
+        let (report, _) = run(None).await;
+        assert_eq!(report.summary, "done");
+        assert_eq!(report.key_points, vec!["done".to_string()]);
+        assert_eq!(report.confidence, 0.0);
+    }
+
+    #[tokio::test]
+    async fn test_unhealthy_root_drops_need() {
+        let store = Arc::new(InMemoryStore::new());
+        let web = seed_planner_web(&store, "Spawn carefully");
+        let engine = CoordinationEngine::new(
+            store.clone(),
+            HashMap::new(),
+            Providers {
+                embedding: None,
+                llm: None,
+                search: None,
+            },
+        );
+        let need = Need {
+            description: "Look it up".to_string(),
+            suggested_capability: Some(CapabilityType::Search),
+        };
+        let mut parent = WebStore::get_agent(&*store, &web.root_agent)
+            .unwrap()
+            .unwrap();
+        let pending = |direction: SignalDirection| {
+            WebStore::get_pending_signals(&*store, &web.id)
+                .unwrap()
+                .into_iter()
+                .filter(|s| s.direction == direction && s.content == "Look it up")
+                .count()
+        };
+        // Matches the need, but too picky to be routed to as lineage; only
+        // reactivation would reach it.
+        let mut dormant = Agent::new(
+            web.id,
+            Some(web.root_agent),
+            "sleeper".to_string(),
+            vec![1.0; web.config.embedding_dim],
+            CapabilityType::Search,
+            1.0,
+        );
+        dormant.web_id = web.id;
+        dormant.state = AgentState::Dormant;
+        store.add_agent(dormant.clone()).unwrap();
+
+        // The root has no ancestor to hand the need to, and mustn't wake
+        // anyone either.
+        parent.health = 0.5;
+        engine
+            .handle_need(&parent, AgentState::Listening, &need, None)
+            .await
+            .unwrap();
+        assert_eq!(store.get_agents_by_web(&web.id).unwrap().len(), 2);
+        assert_eq!(pending(SignalDirection::Upward), 0);
+        let sleeper = WebStore::get_agent(&*store, &dormant.id).unwrap().unwrap();
+        assert_eq!(sleeper.state, AgentState::Dormant);
+
+        // Healthy but quarantined is refused too.
+        parent.health = 0.9;
+        engine
+            .handle_need(&parent, AgentState::Quarantine, &need, None)
+            .await
+            .unwrap();
+        assert_eq!(store.get_agents_by_web(&web.id).unwrap().len(), 2);
+        let sleeper = WebStore::get_agent(&*store, &dormant.id).unwrap().unwrap();
+        assert_eq!(sleeper.state, AgentState::Dormant);
+
+        dormant.state = AgentState::Terminated;
+        WebStore::update_agent(&*store, dormant).unwrap();
+        engine
+            .handle_need(&parent, AgentState::Listening, &need, None)
+            .await
+            .unwrap();
+        assert_eq!(store.get_agents_by_web(&web.id).unwrap().len(), 3);
+        assert_eq!(pending(SignalDirection::Downward), 1);
+    }
+
+    #[tokio::test]
+    async fn test_unhealthy_parent_hands_need_to_ancestor() {
+        let store = Arc::new(InMemoryStore::new());
+        let web = seed_planner_web(&store, "Spawn carefully");
+        // The start signal would otherwise be the only thing pending.
+        for signal in WebStore::get_pending_signals(&*store, &web.id).unwrap() {
+            WebStore::mark_signal_processed(&*store, &signal.id).unwrap();
+        }
+        let engine = CoordinationEngine::new(
+            store.clone(),
+            HashMap::new(),
+            Providers {
+                embedding: Some(Box::new(AxisEmbedding)),
+                llm: None,
+                search: None,
+            },
+        );
+        let mut unhealthy = Agent::new(
+            web.id,
+            Some(web.root_agent),
+            "shaky".to_string(),
+            vec![0.0, 1.0, 0.0],
+            CapabilityType::Search,
+            0.5,
+        );
+        unhealthy.web_id = web.id;
+        unhealthy.health = 0.3;
+        store.add_agent(unhealthy.clone()).unwrap();
+        let need = Need {
+            description: "need-2".to_string(),
+            suggested_capability: Some(CapabilityType::Search),
+        };
+        engine
+            .handle_need(&unhealthy, AgentState::Listening, &need, None)
+            .await
+            .unwrap();
+
+        let agents = store.get_agents_by_web(&web.id).unwrap();
+        assert_eq!(agents.len(), 3);
+        let spawned = agents
+            .iter()
+            .find(|a| a.purpose == "need-2")
+            .expect("need spawned");
+        assert_eq!(spawned.parent_id, Some(web.root_agent));
+
+        // Only the spawned agent's start signal is pending; the hand-over
+        // never reaches the root's knowledge.
+        let pending = WebStore::get_pending_signals(&*store, &web.id).unwrap();
+        assert_eq!(pending.len(), 1);
+        assert_eq!(pending[0].direction, SignalDirection::Downward);
+        let need_signal = Signal::new(
+            unhealthy.id,
+            vec![0.0, 0.0, 1.0],
+            "need-2".to_string(),
+            SignalDirection::Upward,
+        )
+        .with_payload(serde_json::json!({ "type": "need" }));
+        engine.process_signal(&need_signal).await.unwrap();
+        assert!(!engine.root_has_output(&web.id).unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_spawns_throttled_per_iteration() {
+        let store = Arc::new(InMemoryStore::new());
+        let mut web = seed_planner_web(&store, "Spawn everything");
+        web.config.max_spawns_per_iteration = 5;
+        WebStore::update_web(&*store, web.clone()).unwrap();
+
+        let mut capabilities: HashMap<CapabilityType, Box<dyn Capability>> = HashMap::new();
+        capabilities.insert(
+            CapabilityType::Planner,
+            Box::new(FloodingCapability { needs: 50 }),
+        );
+        capabilities.insert(CapabilityType::Search, Box::new(OneShotCapability));
+        let providers = Providers {
+            embedding: Some(Box::new(AxisEmbedding)),
+            llm: None,
+            search: None,
+        };
+        let engine = CoordinationEngine::new(store.clone(), capabilities, providers);
+
+        engine.run_coordination_loop(&web.id).await.unwrap();
+
+        let web = WebStore::get_web(&*store, &web.id).unwrap().unwrap();
+        assert_eq!(web.state, WebState::Converged);
+        assert_eq!(store.get_agents_by_web(&web.id).unwrap().len(), 6);
+        assert_eq!(engine.metrics().web(&web.id).agents_spawned, 5);
+
+        // One throttled iteration isn't a pattern yet.
+        let patterns = store.get_failure_patterns(web.id).await.unwrap();
+        assert!(patterns.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_repeated_spawn_throttling_records_pattern() {
+        let store = Arc::new(InMemoryStore::new());
+        let web = seed_planner_web(&store, "Spawn everything, every time");
+        let engine = CoordinationEngine::new(
+            store.clone(),
+            HashMap::new(),
+            Providers {
+                embedding: None,
+                llm: None,
+                search: None,
+            },
+        );
+        let iteration = |dropped: usize| {
+            let mut run_stats = engine.run_stats.lock().unwrap();
+            let stats = run_stats.entry(web.id).or_default();
+            stats.spawned = 5;
+            stats.dropped = dropped;
+            drop(run_stats);
+            engine.record_spawn_throttling(&web.id).unwrap();
+        };
+
+        // A quiet iteration breaks the streak.
+        for dropped in [3, 3, 0, 3, 3] {
+            iteration(dropped);
+        }
+        assert!(store.get_failure_patterns(web.id).await.unwrap().is_empty());
+
+        iteration(3);
+        let patterns = store.get_failure_patterns(web.id).await.unwrap();
+        assert_eq!(patterns.len(), 1);
+        assert!(matches!(
+            patterns[0].pattern_type,
+            FailurePatternType::CyclicSpawning
+        ));
+        assert_eq!(
+            patterns[0].pattern_data["throttled_iterations"],
+            SPAWN_THROTTLE_ITERATIONS
+        );
+
+        // Staying throttled doesn't record it again.
+        iteration(3);
+        assert_eq!(store.get_failure_patterns(web.id).await.unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_analytics_for_two_level_web() {
+        let store = Arc::new(InMemoryStore::new());
+        let web = seed_planner_web(&store, "Split in two");
+
+        let mut capabilities: HashMap<CapabilityType, Box<dyn Capability>> = HashMap::new();
+        capabilities.insert(
+            CapabilityType::Planner,
+            Box::new(FloodingCapability { needs: 2 }),
+        );
+        capabilities.insert(CapabilityType::Search, Box::new(OneShotCapability));
+        let providers = Providers {
+            embedding: Some(Box::new(AxisEmbedding)),
+            llm: None,
+            search: None,
+        };
+        let engine = CoordinationEngine::new(store.clone(), capabilities, providers);
+
+        engine.run_coordination_loop(&web.id).await.unwrap();
+
+        // The root runs, both its children run in the next iteration, and
+        // the third finds nothing left to do.
+        let analytics = store.get_web_analytics(web.id).await.unwrap().unwrap();
+        assert_eq!(analytics.state, WebState::Converged);
+        assert!(analytics.time_to_convergence_ms.is_some());
+        assert_eq!(analytics.max_depth, 1);
+        assert_eq!(analytics.peak_active_agents, 2);
+        assert_eq!(analytics.iterations, 3);
+    }
+
+    #[tokio::test]
+    async fn test_parent_tuning_drifts_toward_child_findings() {
+        let store = Arc::new(InMemoryStore::new());
+        let root = Agent::new(
+            uuid::Uuid::new_v4(),
+            None,
+            "root".to_string(),
+            vec![1.0, 0.0, 0.0],
+            CapabilityType::Synthesizer,
+            0.99,
+        );
+        let child = Agent::new(
+            root.web_id,
+            Some(root.id),
+            "child".to_string(),
+            vec![0.0, 1.0, 0.0],
+            CapabilityType::Synthesizer,
+            0.99,
+        );
+        let mut web = Web::new(
+            root.id,
+            "Drift".to_string(),
+            WebConfig {
+                tuning_drift: true,
+                ..WebConfig::default()
+            },
+        );
+        web.id = root.web_id;
+        WebStore::create_web(&*store, web.clone()).unwrap();
+        store.add_agent(root.clone()).unwrap();
+        store.add_agent(child.clone()).unwrap();
+
+        let engine = CoordinationEngine::new(
+            store.clone(),
+            HashMap::new(),
+            Providers {
+                embedding: None,
+                llm: None,
+                search: None,
+            },
+        );
+
+        let mut similarity = Vec::new();
+        for finding in ["first", "second", "third"] {
+            let signal = Signal::new(
+                child.id,
+                vec![0.0, 1.0, 0.0],
+                finding.to_string(),
+                SignalDirection::Upward,
+            );
+            engine
+                .accumulate_context_from_signal(&signal, &web.config)
+                .await
+                .unwrap();
+            let parent = WebStore::get_agent(&*store, &root.id).unwrap().unwrap();
+            let norm = parent.tuning.iter().map(|v| v * v).sum::<f32>().sqrt();
+            assert!((norm - 1.0).abs() < 1e-5);
+            similarity.push(parent.tuning[1]);
+        }
+
+        // Each repeated finding pulls the parent further toward it, a step at a time.
+        assert!(similarity[0] > 0.0 && similarity[0] < 0.5);
+        assert!(similarity.windows(2).all(|w| w[1] > w[0]));
+
+        // However many findings arrive, it stays within reach of its purpose.
+        let parent = WebStore::get_agent(&*store, &root.id).unwrap().unwrap();
+        assert_eq!(parent.context.purpose_tuning, Some(vec![1.0, 0.0, 0.0]));
+        let capped = WebConfig {
+            tuning_drift: true,
+            tuning_drift_rate: 0.5,
+            max_tuning_drift: 0.2,
+            ..WebConfig::default()
+        };
+        for _ in 0..20 {
+            let signal = Signal::new(
+                child.id,
+                vec![0.0, 1.0, 0.0],
+                "again".to_string(),
+                SignalDirection::Upward,
+            );
+            engine
+                .accumulate_context_from_signal(&signal, &capped)
+                .await
+                .unwrap();
+        }
+        let parent = WebStore::get_agent(&*store, &root.id).unwrap().unwrap();
+        let distance = (parent.tuning[0] - 1.0).hypot(parent.tuning[1]);
+        assert!((distance - 0.2).abs() < 1e-4);
+        assert!(parent.tuning[1] > 0.0);
+
+        // Without the flag the tuning stays put.
+        let mut parent = WebStore::get_agent(&*store, &root.id).unwrap().unwrap();
+        parent.set_tuning(vec![1.0, 0.0, 0.0]);
+        WebStore::update_agent(&*store, parent).unwrap();
+        let signal = Signal::new(
+            child.id,
+            vec![0.0, 1.0, 0.0],
+            "ignored".to_string(),
+            SignalDirection::Upward,
+        );
+        engine
+            .accumulate_context_from_signal(&signal, &WebConfig::default())
+            .await
+            .unwrap();
+        let parent = WebStore::get_agent(&*store, &root.id).unwrap().unwrap();
+        assert_eq!(parent.tuning, vec![1.0, 0.0, 0.0]);
+    }
+
+    #[tokio::test]
+    async fn test_context_eviction_policies() {
+        let root = Agent::new(
+            uuid::Uuid::new_v4(),
+            None,
+            "root".to_string(),
+            vec![1.0, 0.0],
+            CapabilityType::Synthesizer,
+            0.99,
+        );
+        let child = Agent::new(
+            root.web_id,
+            Some(root.id),
+            "child".to_string(),
+            vec![0.0, 1.0],
+            CapabilityType::Synthesizer,
+            0.99,
+        );
+        // (content, frequency, attenuations before arriving)
+        let findings = [
+            ("on-purpose", vec![1.0, 0.0], 3),
+            ("off-purpose", vec![0.0, 1.0], 0),
+            ("weak", vec![0.9, 0.1], 6),
+            ("strong", vec![0.8, 0.2], 0),
+        ];
+
+        let kept = |policy: ContextEvictionPolicy, limit: usize| {
+            let (root, child) = (root.clone(), child.clone());
+            let findings = findings.clone();
+            async move {
+                let store = Arc::new(InMemoryStore::new());
+                let config = WebConfig {
+                    max_context_items: limit,
+                    context_eviction: policy,
+                    ..WebConfig::default()
+                };
+                let mut web = Web::new(root.id, "Evict".to_string(), config.clone());
+                web.id = root.web_id;
+                WebStore::create_web(&*store, web).unwrap();
+                store.add_agent(root.clone()).unwrap();
+                store.add_agent(child.clone()).unwrap();
+                let engine = CoordinationEngine::new(
+                    store.clone(),
+                    HashMap::new(),
+                    Providers {
+                        embedding: None,
+                        llm: None,
+                        search: None,
+                    },
+                );
+
+                for (content, frequency, hops) in findings {
+                    let mut signal = Signal::new(
+                        child.id,
+                        frequency,
+                        content.to_string(),
+                        SignalDirection::Upward,
+                    );
+                    for _ in 0..hops {
+                        signal.attenuate(0.8);
+                    }
+                    engine
+                        .accumulate_context_from_signal(&signal, &config)
+                        .await
+                        .unwrap();
+                }
+                WebStore::get_agent(&*store, &root.id)
+                    .unwrap()
+                    .unwrap()
+                    .context
+                    .accumulated_knowledge
+                    .into_iter()
+                    .map(|item| item.content)
+                    .collect::<Vec<_>>()
+            }
+        };
+
+        assert_eq!(
+            kept(ContextEvictionPolicy::Fifo, 2).await,
+            vec!["weak", "strong"]
+        );
+        assert_eq!(
+            kept(ContextEvictionPolicy::LowestAmplitude, 2).await,
+            vec!["off-purpose", "strong"]
+        );
+        assert_eq!(
+            kept(ContextEvictionPolicy::LeastSimilarToPurpose, 3).await,
+            vec!["on-purpose", "weak", "strong"]
+        );
+        assert_eq!(kept(ContextEvictionPolicy::Fifo, 10).await.len(), 4);
+    }
+
+    #[tokio::test]
+    async fn test_successful_executions_recover_quarantined_agent() {
+        let store = Arc::new(InMemoryStore::new());
+        let web = seed_planner_web(&store, "Recover");
+        let mut agent = WebStore::get_agent(&*store, &web.root_agent)
+            .unwrap()
+            .unwrap();
+        agent.health = 0.5;
+        agent.state = AgentState::Quarantine;
+        WebStore::update_agent(&*store, agent.clone()).unwrap();
+
+        let mut capabilities: HashMap<CapabilityType, Box<dyn Capability>> = HashMap::new();
+        capabilities.insert(CapabilityType::Planner, Box::new(OneShotCapability));
+        let providers = Providers {
+            embedding: None,
+            llm: None,
+            search: None,
+        };
+        let engine = CoordinationEngine::new(store.clone(), capabilities, providers);
+        let trigger = Signal::new(
+            agent.id,
+            agent.tuning.clone(),
+            "work".to_string(),
+            SignalDirection::Downward,
+        );
+
+        let mut health = agent.health;
+        let mut runs = 0;
+        loop {
+            engine.activate_agent(&agent.id, &trigger).await.unwrap();
+            runs += 1;
+            let agent = WebStore::get_agent(&*store, &agent.id).unwrap().unwrap();
+            assert!(agent.health > health);
+            health = agent.health;
+
+            if agent.state != AgentState::Quarantine {
+                assert_eq!(agent.state, AgentState::Listening);
+                assert!(agent.health >= 0.6);
+                break;
+            }
+            assert!(runs < 10, "agent never left quarantine");
+        }
+        assert!(runs > 1);
+    }
+
+    #[derive(Default)]
+    struct RecordingObserver {
+        events: Mutex<Vec<EngineEvent>>,
+    }
+
+    impl EngineObserver for RecordingObserver {
+        fn on_event(&self, event: &EngineEvent) {
+            self.events.lock().unwrap().push(event.clone());
+        }
+    }
+
+    #[tokio::test]
+    async fn test_lifecycle_tick_puts_idle_agent_to_sleep() {
+        let store = Arc::new(InMemoryStore::new());
+        let mut web = seed_planner_web(&store, "Tick");
+        web.config.lifecycle_tick_iterations = 1;
+        WebStore::update_web(&*store, web.clone()).unwrap();
+
+        // Tuned away from the task, so nothing wakes it.
+        let mut idle = Agent::new(
+            web.id,
+            Some(web.root_agent),
+            "idle".to_string(),
+            vec![0.0, 1.0, 0.0],
+            CapabilityType::Planner,
+            0.5,
+        );
+        idle.last_active_at = chrono::Utc::now() - chrono::Duration::seconds(60);
+        store.add_agent(idle.clone()).unwrap();
+
+        let mut capabilities: HashMap<CapabilityType, Box<dyn Capability>> = HashMap::new();
+        capabilities.insert(CapabilityType::Planner, Box::new(OneShotCapability));
+        let providers = Providers {
+            embedding: None,
+            llm: None,
+            search: None,
+        };
+        let observer = Arc::new(RecordingObserver::default());
+        let engine = CoordinationEngine::new(store.clone(), capabilities, providers)
+            .with_observer(observer.clone());
+
+        engine.run_coordination_loop(&web.id).await.unwrap();
+
+        let idle = WebStore::get_agent(&*store, &idle.id).unwrap().unwrap();
+        assert_eq!(idle.state, AgentState::Dormant);
+        assert!(idle.dormant_since.is_some());
+        let root = WebStore::get_agent(&*store, &web.root_agent)
+            .unwrap()
+            .unwrap();
+        assert_eq!(root.state, AgentState::Dormant);
+
+        let events = observer.events.lock().unwrap();
+        assert!(events.iter().any(|event| matches!(
+            event,
+            EngineEvent::AgentStateChanged {
+                agent_id,
+                from: AgentState::Listening,
+                to: AgentState::Dormant,
+                ..
+            } if *agent_id == idle.id
+        )));
+    }
+
+    #[tokio::test]
+    async fn test_lifecycle_tick_winds_down_failing_agent() {
+        let store = Arc::new(InMemoryStore::new());
+        let web = seed_planner_web(&store, "Wind down");
+
+        let mut failing = Agent::new(
+            web.id,
+            Some(web.root_agent),
+            "failing".to_string(),
+            vec![0.0, 1.0, 0.0],
+            CapabilityType::Planner,
+            0.5,
+        );
+        failing.health = 0.1;
+        failing.state = AgentState::WindingDown;
+        let healthy_child = Agent::new(
+            web.id,
+            Some(failing.id),
+            "healthy".to_string(),
+            vec![0.0, 1.0, 0.0],
+            CapabilityType::Planner,
+            0.5,
+        );
+        let mut sick_child = healthy_child.clone();
+        sick_child.id = uuid::Uuid::new_v4();
+        sick_child.health = 0.3;
+        for agent in [&failing, &healthy_child, &sick_child] {
+            store.add_agent(agent.clone()).unwrap();
+        }
+
+        let providers = Providers {
+            embedding: None,
+            llm: None,
+            search: None,
+        };
+        let engine = CoordinationEngine::new(store.clone(), HashMap::new(), providers);
+        engine
+            .lifecycle_tick(&web.id, chrono::Duration::seconds(1))
+            .unwrap();
+
+        let failing = WebStore::get_agent(&*store, &failing.id).unwrap().unwrap();
+        assert_eq!(failing.state, AgentState::Terminated);
+        let healthy_child = WebStore::get_agent(&*store, &healthy_child.id)
+            .unwrap()
+            .unwrap();
+        assert_eq!(healthy_child.parent_id, Some(web.root_agent));
+        assert_eq!(healthy_child.state, AgentState::Listening);
+        let sick_child = WebStore::get_agent(&*store, &sick_child.id)
+            .unwrap()
+            .unwrap();
+        assert_eq!(sick_child.state, AgentState::Terminated);
+
+        // Both wound-down agents report upward.
+        let pending = WebStore::get_pending_signals(&*store, &web.id).unwrap();
+        let origins: HashSet<uuid::Uuid> = pending
+            .iter()
+            .filter(|signal| signal.direction == SignalDirection::Upward)
+            .map(|signal| signal.origin)
+            .collect();
+        assert_eq!(origins, HashSet::from([failing.id, sick_child.id]));
+    }
 }
@@ -0,0 +1,118 @@
+use serde::Serialize;
+use tokio::sync::broadcast;
+
+use crate::lifecycle::HealthChangeReason;
+use crate::types::{AgentId, AgentState, WebId};
+
+/// Notable changes raised while a web runs.
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum EngineEvent {
+    AgentHealthChanged {
+        web_id: WebId,
+        agent_id: AgentId,
+        old_health: f32,
+        new_health: f32,
+        reason: HealthChangeReason,
+    },
+    AgentStateChanged {
+        web_id: WebId,
+        agent_id: AgentId,
+        from: AgentState,
+        to: AgentState,
+    },
+    Validation {
+        web_id: WebId,
+        agent_id: AgentId,
+        /// `confirm`, `challenge` or `uncertain`.
+        judgment: String,
+        confidence: Option<f32>,
+        reason: String,
+    },
+}
+
+impl EngineEvent {
+    pub fn web_id(&self) -> WebId {
+        match self {
+            EngineEvent::AgentHealthChanged { web_id, .. }
+            | EngineEvent::AgentStateChanged { web_id, .. }
+            | EngineEvent::Validation { web_id, .. } => *web_id,
+        }
+    }
+
+    pub fn agent_id(&self) -> AgentId {
+        match self {
+            EngineEvent::AgentHealthChanged { agent_id, .. }
+            | EngineEvent::AgentStateChanged { agent_id, .. }
+            | EngineEvent::Validation { agent_id, .. } => *agent_id,
+        }
+    }
+
+    /// SSE event name for this event.
+    pub fn name(&self) -> &'static str {
+        match self {
+            EngineEvent::AgentHealthChanged { .. } => "agent_health_changed",
+            EngineEvent::AgentStateChanged { .. } => "agent_state_changed",
+            EngineEvent::Validation { .. } => "validation",
+        }
+    }
+}
+
+/// Hook for components that want to react to engine events.
+pub trait EngineObserver: Send + Sync {
+    fn on_event(&self, event: &EngineEvent);
+}
+
+/// Fan-out of engine events to any number of subscribers (e.g. SSE streams).
+#[derive(Clone)]
+pub struct EventBus {
+    sender: broadcast::Sender<EngineEvent>,
+}
+
+impl EventBus {
+    pub fn new(capacity: usize) -> Self {
+        let (sender, _) = broadcast::channel(capacity);
+        Self { sender }
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<EngineEvent> {
+        self.sender.subscribe()
+    }
+}
+
+impl Default for EventBus {
+    fn default() -> Self {
+        Self::new(256)
+    }
+}
+
+impl EngineObserver for EventBus {
+    fn on_event(&self, event: &EngineEvent) {
+        // No subscribers is fine; events are only interesting to live streams.
+        let _ = self.sender.send(event.clone());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_event_bus_delivers_to_subscribers() {
+        let bus = EventBus::default();
+        let mut rx = bus.subscribe();
+
+        let web_id = WebId::new_v4();
+        bus.on_event(&EngineEvent::AgentHealthChanged {
+            web_id,
+            agent_id: AgentId::new_v4(),
+            old_health: 1.0,
+            new_health: 0.9,
+            reason: HealthChangeReason::ValidationChallenge,
+        });
+
+        let event = rx.try_recv().unwrap();
+        assert_eq!(event.web_id(), web_id);
+        assert_eq!(event.name(), "agent_health_changed");
+    }
+}
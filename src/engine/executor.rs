@@ -4,10 +4,11 @@ use std::path::PathBuf;
 use std::sync::Arc;
 
 use crate::definitions::{AgentDefinition, ToolType};
-use crate::providers::{LLMProvider, Message};
-use crate::storage::traits::Storage;
+use crate::providers::llm::DEFAULT_MAX_TOKENS;
+use crate::providers::{LLMProvider, Message, NativeToolCall};
+use crate::storage::traits::{Storage, ToolExecution};
 use crate::tools::runtime::{ToolConfig, ToolRuntime};
-use crate::tools::{ToolCall, ToolContext, ToolResult};
+use crate::tools::{Artifact, SideEffect, ToolCall, ToolContext, ToolResult};
 use crate::types::{Agent, ExecutionStatus, Signal, SignalDirection};
 
 #[derive(Debug, Clone)]
@@ -66,9 +67,16 @@ impl AgentExecutor {
         let context = self.build_context(agent, &definition, trigger_content);
         let messages = self.build_messages(&definition, &context);
         let tool_schemas = self.tool_runtime.get_schemas(&definition.tools);
+        let max_tokens = definition.max_tokens.unwrap_or(DEFAULT_MAX_TOKENS);
 
         let (output, tool_results) = self
-            .run_conversation(messages, &definition.tools, &tool_schemas, agent)
+            .run_conversation(
+                messages,
+                &definition.tools,
+                &tool_schemas,
+                max_tokens,
+                agent,
+            )
             .await?;
 
         let signals = self.extract_signals(&output, agent);
@@ -99,12 +107,14 @@ impl AgentExecutor {
                 agent.purpose
             ),
             temperature: 0.4,
+            max_tokens: None,
             tools: vec![ToolType::EmitSignal],
             source: crate::definitions::DefinitionSource::BuiltIn,
             health_score: 1.0,
             use_count: 0,
             created_at: chrono::Utc::now(),
             version: None,
+            deleted_at: None,
         })
     }
 
@@ -141,7 +151,8 @@ impl AgentExecutor {
         &self,
         mut messages: Vec<Message>,
         allowed_tools: &[ToolType],
-        _tool_schemas: &[Value],
+        tool_schemas: &[Value],
+        max_tokens: u32,
         agent: &Agent,
     ) -> Result<(Value, Vec<ToolResult>)> {
         let mut all_tool_results = Vec::new();
@@ -153,8 +164,24 @@ impl AgentExecutor {
                 return Err(anyhow!("Exceeded maximum tool call iterations"));
             }
 
-            let response = self.llm_provider.complete(messages.clone()).await?;
-            let tool_calls = self.parse_tool_calls(&response, allowed_tools);
+            let (response, tool_calls) = match self
+                .llm_provider
+                .complete_with_tools(messages.clone(), tool_schemas, max_tokens)
+                .await?
+            {
+                Some(completion) => {
+                    let calls = self.native_tool_calls(&completion.tool_calls, allowed_tools);
+                    (with_tool_call_lines(completion.text, &calls), calls)
+                }
+                None => {
+                    let (response, _) = self
+                        .llm_provider
+                        .complete_with_max_tokens(messages.clone(), max_tokens)
+                        .await?;
+                    let calls = self.parse_tool_calls(&response, allowed_tools);
+                    (response, calls)
+                }
+            };
 
             if tool_calls.is_empty() {
                 return Ok((json!({ "response": response }), all_tool_results));
@@ -168,7 +195,9 @@ impl AgentExecutor {
 
             let mut tool_outputs = Vec::new();
             for tool_call in tool_calls {
-                let result = self.tool_runtime.execute(&tool_call, &tool_context).await?;
+                let result = self.tool_runtime.execute(&tool_call, &tool_context).await;
+                self.record_tool_execution(agent, &tool_call, &result).await;
+                let result = result?;
                 tool_outputs.push(format!(
                     "Tool {} result: {}",
                     tool_call.tool_type.as_str(),
@@ -185,6 +214,66 @@ impl AgentExecutor {
         }
     }
 
+    /// Audit a tool call. Storage problems are logged rather than failing the agent.
+    async fn record_tool_execution(
+        &self,
+        agent: &Agent,
+        tool_call: &ToolCall,
+        result: &Result<ToolResult>,
+    ) {
+        let (output, success, artifacts, side_effects) = match result {
+            Ok(r) => (
+                r.output.clone(),
+                r.success,
+                r.artifacts.iter().map(Artifact::summary).collect(),
+                r.side_effects.iter().map(SideEffect::summary).collect(),
+            ),
+            Err(e) => (
+                json!({ "error": e.to_string() }),
+                false,
+                Vec::new(),
+                Vec::new(),
+            ),
+        };
+        let execution = ToolExecution::new(
+            agent.id,
+            agent.web_id,
+            tool_call.tool_type,
+            tool_call.params.clone(),
+            output,
+            success,
+        )
+        .with_effects(artifacts, side_effects);
+
+        if let Err(e) = self.storage.record_tool_execution(&execution).await {
+            log::warn!(
+                "Failed to record {} execution for agent {}: {}",
+                tool_call.tool_type.as_str(),
+                agent.id,
+                e
+            );
+        }
+    }
+
+    fn native_tool_calls(
+        &self,
+        calls: &[NativeToolCall],
+        allowed_tools: &[ToolType],
+    ) -> Vec<ToolCall> {
+        calls
+            .iter()
+            .filter_map(|call| {
+                let tool_type = ToolType::parse(&call.name)?;
+                allowed_tools.contains(&tool_type).then(|| ToolCall {
+                    tool_type,
+                    params: call.arguments.clone(),
+                })
+            })
+            .collect()
+    }
+
+    /// Fallback for providers without native tool calling: one
+    /// `{"tool": ..., "params": ...}` object per line.
     fn parse_tool_calls(&self, response: &str, allowed_tools: &[ToolType]) -> Vec<ToolCall> {
         let mut calls = Vec::new();
 
@@ -271,9 +360,411 @@ impl AgentExecutor {
     }
 }
 
+/// The assistant turn kept in the conversation for a native completion.
+/// Messages only carry text, so the calls are written out the way the
+/// line-scanning fallback reads them.
+fn with_tool_call_lines(text: String, calls: &[ToolCall]) -> String {
+    let lines = calls
+        .iter()
+        .map(|call| json!({ "tool": call.tool_type.as_str(), "params": call.params }).to_string());
+    std::iter::once(text)
+        .filter(|t| !t.is_empty())
+        .chain(lines)
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::storage::memory::InMemoryStore;
+    use crate::tools::runtime::DEFAULT_TOOL_TIMEOUT_SECS;
+    use crate::types::CapabilityType;
+    use async_trait::async_trait;
+    use std::collections::{HashMap, VecDeque};
+    use std::sync::Mutex;
+
+    /// Replies with each scripted response in turn.
+    struct ScriptedLLM {
+        responses: Mutex<VecDeque<String>>,
+    }
+
+    #[async_trait]
+    impl LLMProvider for ScriptedLLM {
+        async fn complete(&self, _messages: Vec<Message>) -> Result<String> {
+            Ok(self
+                .responses
+                .lock()
+                .unwrap()
+                .pop_front()
+                .unwrap_or_else(|| "Done.".to_string()))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_tool_executions_are_recorded() {
+        let sandbox = tempfile::tempdir().unwrap();
+        let storage: Arc<dyn Storage> = Arc::new(InMemoryStore::new());
+
+        let definition = AgentDefinition {
+            name: "auditor".to_string(),
+            system_prompt: "Audit the sandbox.".to_string(),
+            tools: vec![ToolType::EmitSignal, ToolType::ListDirectory],
+            ..AgentDefinition::default()
+        };
+        storage.create_definition(&definition).await.unwrap();
+
+        let mut agent = Agent::new(
+            uuid::Uuid::new_v4(),
+            None,
+            "Audit the sandbox".to_string(),
+            vec![1.0, 0.0],
+            CapabilityType::Search,
+            0.5,
+        );
+        agent.definition_id = Some(definition.id);
+
+        let llm = Arc::new(ScriptedLLM {
+            responses: Mutex::new(VecDeque::from(vec![
+                concat!(
+                    r#"{"tool": "list_directory", "params": {}}"#,
+                    "\n",
+                    r#"{"tool": "emit_signal", "params": {"content": "sandbox is empty"}}"#
+                )
+                .to_string(),
+                "All done.".to_string(),
+            ])),
+        });
+        let executor = AgentExecutor::new(
+            storage.clone(),
+            llm,
+            ToolConfig {
+                sandbox_root: sandbox.path().to_path_buf(),
+                search_provider: None,
+                impresario_client: None,
+                enable_remote_execution: false,
+                agent_factory: None,
+                embedding_provider: None,
+                tool_timeout_secs: DEFAULT_TOOL_TIMEOUT_SECS,
+                tool_timeouts: HashMap::new(),
+            },
+            ExecutorConfig {
+                sandbox_root: sandbox.path().to_path_buf(),
+                ..ExecutorConfig::default()
+            },
+        )
+        .unwrap();
+
+        let result = executor.execute(&agent, None).await.unwrap();
+        assert_eq!(result.tool_results.len(), 2);
+
+        let executions = storage.get_tool_executions(agent.id).await.unwrap();
+        let tools: Vec<ToolType> = executions.iter().map(|e| e.tool_type).collect();
+        assert_eq!(tools, vec![ToolType::ListDirectory, ToolType::EmitSignal]);
+        assert!(executions
+            .iter()
+            .all(|e| e.success && e.web_id == agent.web_id));
+        assert_eq!(executions[1].params["content"], "sandbox is empty");
+        assert_eq!(executions[1].output["content"], "sandbox is empty");
+        assert!(executions[0].side_effects.is_empty());
+        assert_eq!(executions[1].side_effects.len(), 1);
+        assert_eq!(executions[1].side_effects[0]["type"], "signal_emitted");
+        let SideEffect::SignalEmitted(signal) = &result.tool_results[1].side_effects[0] else {
+            panic!("emit_signal should report the signal");
+        };
+        assert_eq!(
+            executions[1].side_effects[0]["signal_id"],
+            signal.id.to_string()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_agent_resolves_soft_deleted_definition() {
+        let sandbox = tempfile::tempdir().unwrap();
+        let storage: Arc<dyn Storage> = Arc::new(InMemoryStore::new());
+        let definition = AgentDefinition {
+            name: "retired".to_string(),
+            tuning_embedding: vec![1.0, 0.0],
+            source: crate::definitions::DefinitionSource::Generated,
+            ..AgentDefinition::default()
+        };
+        storage.create_definition(&definition).await.unwrap();
+
+        let mut agent = Agent::new(
+            uuid::Uuid::new_v4(),
+            None,
+            "Spawned before the delete".to_string(),
+            vec![1.0, 0.0],
+            CapabilityType::Search,
+            0.5,
+        );
+        agent.definition_id = Some(definition.id);
+
+        assert!(storage.soft_delete_definition(definition.id).await.unwrap());
+        assert!(!storage.soft_delete_definition(definition.id).await.unwrap());
+
+        let executor = AgentExecutor::new(
+            storage.clone(),
+            Arc::new(ScriptedLLM {
+                responses: Mutex::new(VecDeque::new()),
+            }),
+            ToolConfig {
+                sandbox_root: sandbox.path().to_path_buf(),
+                search_provider: None,
+                impresario_client: None,
+                enable_remote_execution: false,
+                agent_factory: None,
+                embedding_provider: None,
+                tool_timeout_secs: DEFAULT_TOOL_TIMEOUT_SECS,
+                tool_timeouts: HashMap::new(),
+            },
+            ExecutorConfig::default(),
+        )
+        .unwrap();
+        let resolved = executor.get_agent_definition(&agent).await.unwrap();
+        assert_eq!(resolved.id, definition.id);
+        assert!(resolved.is_deleted());
+
+        assert!(storage.list_definitions(None).await.unwrap().is_empty());
+        assert!(storage
+            .get_definition_by_name("retired")
+            .await
+            .unwrap()
+            .is_none());
+        assert!(storage
+            .find_definitions_by_similarity(&[1.0, 0.0], 0.5, &[definition.source], 10)
+            .await
+            .unwrap()
+            .is_empty());
+        assert!(storage
+            .get_definitions_by_health(1.0, None)
+            .await
+            .unwrap()
+            .is_empty());
+
+        assert!(storage.restore_definition(definition.id).await.unwrap());
+        assert_eq!(storage.list_definitions(None).await.unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_agent_falls_back_after_definition_delete() {
+        let sandbox = tempfile::tempdir().unwrap();
+        let storage: Arc<dyn Storage> = Arc::new(InMemoryStore::new());
+        let definition = AgentDefinition {
+            name: "discarded".to_string(),
+            source: crate::definitions::DefinitionSource::UserCustom,
+            ..AgentDefinition::default()
+        };
+        storage.create_definition(&definition).await.unwrap();
+        let builtin = crate::definitions::builtin::task_coordinator_definition();
+        storage.create_definition(&builtin).await.unwrap();
+
+        let mut agent = Agent::new(
+            uuid::Uuid::new_v4(),
+            None,
+            "Spawned before the delete".to_string(),
+            vec![1.0, 0.0],
+            CapabilityType::Search,
+            0.5,
+        );
+        agent.definition_id = Some(definition.id);
+
+        assert!(storage.delete_definition(definition.id).await.unwrap());
+        assert!(!storage.delete_definition(definition.id).await.unwrap());
+        assert!(storage.delete_definition(builtin.id).await.is_err());
+
+        let executor = AgentExecutor::new(
+            storage.clone(),
+            Arc::new(ScriptedLLM {
+                responses: Mutex::new(VecDeque::new()),
+            }),
+            ToolConfig {
+                sandbox_root: sandbox.path().to_path_buf(),
+                search_provider: None,
+                impresario_client: None,
+                enable_remote_execution: false,
+                agent_factory: None,
+                embedding_provider: None,
+                tool_timeout_secs: DEFAULT_TOOL_TIMEOUT_SECS,
+                tool_timeouts: HashMap::new(),
+            },
+            ExecutorConfig::default(),
+        )
+        .unwrap();
+        let resolved = executor.get_agent_definition(&agent).await.unwrap();
+        assert_eq!(resolved.name, "legacy-agent");
+        assert!(resolved.system_prompt.contains("Spawned before the delete"));
+    }
+
+    /// Records the response cap of every call.
+    struct CappedLLM {
+        caps: Mutex<Vec<u32>>,
+    }
+
+    #[async_trait]
+    impl LLMProvider for CappedLLM {
+        async fn complete(&self, _messages: Vec<Message>) -> Result<String> {
+            Ok("Done.".to_string())
+        }
+
+        async fn complete_with_max_tokens(
+            &self,
+            messages: Vec<Message>,
+            max_tokens: u32,
+        ) -> Result<(String, crate::providers::Usage)> {
+            self.caps.lock().unwrap().push(max_tokens);
+            self.complete_with_usage(messages).await
+        }
+    }
+
+    #[tokio::test]
+    async fn test_definition_max_tokens_reaches_provider() {
+        let sandbox = tempfile::tempdir().unwrap();
+        let storage: Arc<dyn Storage> = Arc::new(InMemoryStore::new());
+        let router = AgentDefinition {
+            name: "router".to_string(),
+            max_tokens: Some(256),
+            ..AgentDefinition::default()
+        };
+        storage.create_definition(&router).await.unwrap();
+
+        let llm = Arc::new(CappedLLM {
+            caps: Mutex::new(Vec::new()),
+        });
+        let executor = AgentExecutor::new(
+            storage.clone(),
+            llm.clone(),
+            ToolConfig {
+                sandbox_root: sandbox.path().to_path_buf(),
+                search_provider: None,
+                impresario_client: None,
+                enable_remote_execution: false,
+                agent_factory: None,
+                embedding_provider: None,
+                tool_timeout_secs: DEFAULT_TOOL_TIMEOUT_SECS,
+                tool_timeouts: HashMap::new(),
+            },
+            ExecutorConfig::default(),
+        )
+        .unwrap();
+
+        let mut agent = Agent::new(
+            uuid::Uuid::new_v4(),
+            None,
+            "Route requests".to_string(),
+            vec![1.0, 0.0],
+            CapabilityType::Search,
+            0.5,
+        );
+        executor.execute(&agent, None).await.unwrap();
+        agent.definition_id = Some(router.id);
+        executor.execute(&agent, None).await.unwrap();
+
+        assert_eq!(*llm.caps.lock().unwrap(), vec![DEFAULT_MAX_TOKENS, 256]);
+    }
+
+    /// Calls `emit_signal` natively, wrapped in prose, then finishes.
+    struct NativeToolLLM {
+        calls: Mutex<Vec<Vec<Message>>>,
+    }
+
+    #[async_trait]
+    impl LLMProvider for NativeToolLLM {
+        async fn complete(&self, _messages: Vec<Message>) -> Result<String> {
+            Err(anyhow!("native tool calling should be used"))
+        }
+
+        async fn complete_with_tools(
+            &self,
+            messages: Vec<Message>,
+            tools: &[Value],
+            _max_tokens: u32,
+        ) -> Result<Option<crate::providers::ToolCompletion>> {
+            assert!(tools.iter().any(|t| t["name"] == "emit_signal"));
+            let mut calls = self.calls.lock().unwrap();
+            calls.push(messages);
+            let completion = if calls.len() == 1 {
+                crate::providers::ToolCompletion {
+                    text: "Here is what I found:\n\n{\n  not a tool line".to_string(),
+                    tool_calls: vec![
+                        NativeToolCall {
+                            name: "emit_signal".to_string(),
+                            arguments: json!({ "content": "multi\nline finding" }),
+                        },
+                        NativeToolCall {
+                            name: "delete_file".to_string(),
+                            arguments: json!({ "path": "x" }),
+                        },
+                    ],
+                    ..Default::default()
+                }
+            } else {
+                crate::providers::ToolCompletion {
+                    text: "All done.".to_string(),
+                    ..Default::default()
+                }
+            };
+            Ok(Some(completion))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_native_tool_calls_are_executed() {
+        let sandbox = tempfile::tempdir().unwrap();
+        let storage: Arc<dyn Storage> = Arc::new(InMemoryStore::new());
+        let definition = AgentDefinition {
+            name: "reporter".to_string(),
+            tools: vec![ToolType::EmitSignal],
+            ..AgentDefinition::default()
+        };
+        storage.create_definition(&definition).await.unwrap();
+
+        let mut agent = Agent::new(
+            uuid::Uuid::new_v4(),
+            None,
+            "Report findings".to_string(),
+            vec![1.0, 0.0],
+            CapabilityType::Search,
+            0.5,
+        );
+        agent.definition_id = Some(definition.id);
+
+        let llm = Arc::new(NativeToolLLM {
+            calls: Mutex::new(Vec::new()),
+        });
+        let executor = AgentExecutor::new(
+            storage.clone(),
+            llm.clone(),
+            ToolConfig {
+                sandbox_root: sandbox.path().to_path_buf(),
+                search_provider: None,
+                impresario_client: None,
+                enable_remote_execution: false,
+                agent_factory: None,
+                embedding_provider: None,
+                tool_timeout_secs: DEFAULT_TOOL_TIMEOUT_SECS,
+                tool_timeouts: HashMap::new(),
+            },
+            ExecutorConfig::default(),
+        )
+        .unwrap();
+
+        let result = executor.execute(&agent, None).await.unwrap();
+        assert_eq!(result.output["response"], "All done.");
+        // delete_file is not among the definition's tools.
+        assert_eq!(result.tool_results.len(), 1);
+        assert_eq!(
+            result.tool_results[0].output["content"],
+            "multi\nline finding"
+        );
+
+        let calls = llm.calls.lock().unwrap();
+        assert_eq!(calls.len(), 2);
+        let assistant_turn = &calls[1][calls[1].len() - 2];
+        assert_eq!(assistant_turn.role, "assistant");
+        assert!(assistant_turn.content.starts_with("Here is what I found:"));
+        assert!(assistant_turn.content.contains(r#""tool":"emit_signal""#));
+    }
 
     #[test]
     fn test_executor_config_default() {
@@ -1,10 +1,21 @@
 use anyhow::Result;
-use chrono::Utc;
+use chrono::{DateTime, Duration, Utc};
 use std::collections::HashMap;
 
-use crate::lifecycle::{AgentStateMachine, LifecycleEvent, WindDownProcess};
+use crate::lifecycle::{
+    AgentStateMachine, HealthChangeReason, HealthEvent, LifecycleEvent, WindDownProcess,
+};
 use crate::types::{Agent, AgentId, AgentState, Signal, WebConfig, WebId};
 
+/// Idle decay alone never takes health below this: enough to quarantine a
+/// stale agent, not to isolate or wind it down.
+pub const IDLE_DECAY_FLOOR: f32 = 0.5;
+
+/// Health gained for an execution that completes with no failed tool. Well
+/// below the validation penalties, so one bad result outweighs several good
+/// ones.
+pub const EXECUTION_REWARD: f32 = 0.03;
+
 pub struct LifecycleManager;
 
 impl LifecycleManager {
@@ -45,6 +56,61 @@ impl LifecycleManager {
         Ok(false)
     }
 
+    /// Take health from a listening or dormant agent that has been idle past
+    /// `idle_decay_after_secs`, for the idle part of the `elapsed` time since
+    /// the last check, then apply any state change the new health calls for.
+    pub fn apply_idle_decay(
+        agent: &mut Agent,
+        config: &WebConfig,
+        now: DateTime<Utc>,
+        elapsed: Duration,
+    ) -> Result<Option<HealthEvent>> {
+        if !matches!(agent.state, AgentState::Listening | AgentState::Dormant)
+            || agent.health <= IDLE_DECAY_FLOOR
+        {
+            return Ok(None);
+        }
+
+        let idle_secs = now
+            .signed_duration_since(agent.last_active_at)
+            .num_seconds();
+        let decaying_secs = (idle_secs - config.idle_decay_after_secs as i64)
+            .min(elapsed.num_seconds())
+            .max(0);
+        if decaying_secs == 0 {
+            return Ok(None);
+        }
+
+        let old_health = agent.health;
+        let decay = config.idle_decay_per_min * decaying_secs as f32 / 60.0;
+        agent.health = (agent.health - decay).max(IDLE_DECAY_FLOOR);
+        AgentStateMachine::check_health_thresholds(agent)?;
+
+        Ok(Some(HealthEvent {
+            timestamp: now,
+            delta: agent.health - old_health,
+            reason: HealthChangeReason::Idle,
+        }))
+    }
+
+    /// Reward a successful execution, letting a degraded agent work its way
+    /// back out of quarantine.
+    pub fn reward_execution(agent: &mut Agent, now: DateTime<Utc>) -> Result<Option<HealthEvent>> {
+        if agent.health >= 1.0 {
+            return Ok(None);
+        }
+
+        let old_health = agent.health;
+        agent.health = (agent.health + EXECUTION_REWARD).min(1.0);
+        AgentStateMachine::check_health_thresholds(agent)?;
+
+        Ok(Some(HealthEvent {
+            timestamp: now,
+            delta: agent.health - old_health,
+            reason: HealthChangeReason::SuccessfulExecution,
+        }))
+    }
+
     pub fn process_wind_down(
         agent: &Agent,
         all_agents: &mut HashMap<AgentId, Agent>,
@@ -57,7 +123,7 @@ impl LifecycleManager {
 
         let children: Vec<AgentId> = all_agents
             .values()
-            .filter(|a| a.parent_id == Some(agent.id))
+            .filter(|a| a.parent_id == Some(agent.id) && a.state != AgentState::Terminated)
             .map(|a| a.id)
             .collect();
 
@@ -138,7 +204,6 @@ impl ConvergenceDetector {
 mod tests {
     use super::*;
     use crate::types::{CapabilityType, WebConfig};
-    use chrono::Duration;
 
     fn create_test_agent() -> Agent {
         Agent::new(
@@ -197,6 +262,48 @@ mod tests {
         assert_eq!(agent.state, AgentState::Terminated);
     }
 
+    #[test]
+    fn test_idle_decay_over_time() {
+        let mut agent = create_test_agent();
+        let config = WebConfig {
+            idle_decay_after_secs: 60,
+            idle_decay_per_min: 0.05,
+            ..WebConfig::default()
+        };
+        let start = agent.last_active_at;
+        let tick = Duration::minutes(1);
+
+        // Still inside the grace period.
+        let event =
+            LifecycleManager::apply_idle_decay(&mut agent, &config, start + tick, tick).unwrap();
+        assert!(event.is_none());
+        assert_eq!(agent.health, 1.0);
+
+        // Only the 30s past the grace period count on the first decaying tick.
+        let event = LifecycleManager::apply_idle_decay(
+            &mut agent,
+            &config,
+            start + tick + Duration::seconds(30),
+            Duration::seconds(30),
+        )
+        .unwrap()
+        .unwrap();
+        assert!(matches!(event.reason, HealthChangeReason::Idle));
+        assert!((event.delta + 0.025).abs() < 1e-6);
+
+        let mut now = start + tick + Duration::seconds(30);
+        for _ in 0..20 {
+            now += tick;
+            LifecycleManager::apply_idle_decay(&mut agent, &config, now, tick).unwrap();
+        }
+        // Decay quarantines the agent, then leaves it there.
+        assert_eq!(agent.state, AgentState::Quarantine);
+        assert!(agent.health >= IDLE_DECAY_FLOOR && agent.health < 0.6);
+        let health = agent.health;
+        LifecycleManager::apply_idle_decay(&mut agent, &config, now + tick, tick).unwrap();
+        assert_eq!(agent.health, health);
+    }
+
     #[test]
     fn test_convergence_detection() {
         let web_id = WebId::new_v4();
@@ -213,6 +320,8 @@ mod tests {
                 source_agent: root_id,
                 content: "result".to_string(),
                 data: serde_json::json!({}),
+                frequency: Vec::new(),
+                amplitude: None,
             });
         agents.insert(root_id, root);
 
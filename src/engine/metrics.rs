@@ -0,0 +1,62 @@
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use crate::types::WebId;
+
+/// Work counters for a web, or summed over all webs.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct WebMetrics {
+    pub signals_processed: u64,
+    pub agents_spawned: u64,
+}
+
+/// Counters kept by the coordination engine, per web and overall.
+#[derive(Clone, Default)]
+pub struct MetricsRegistry {
+    per_web: Arc<Mutex<HashMap<WebId, WebMetrics>>>,
+}
+
+impl MetricsRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn record_signal_processed(&self, web_id: &WebId) {
+        self.per_web
+            .lock()
+            .unwrap()
+            .entry(*web_id)
+            .or_default()
+            .signals_processed += 1;
+    }
+
+    pub fn record_agent_spawned(&self, web_id: &WebId) {
+        self.per_web
+            .lock()
+            .unwrap()
+            .entry(*web_id)
+            .or_default()
+            .agents_spawned += 1;
+    }
+
+    pub fn web(&self, web_id: &WebId) -> WebMetrics {
+        self.per_web
+            .lock()
+            .unwrap()
+            .get(web_id)
+            .copied()
+            .unwrap_or_default()
+    }
+
+    pub fn total(&self) -> WebMetrics {
+        self.per_web
+            .lock()
+            .unwrap()
+            .values()
+            .fold(WebMetrics::default(), |total, web| WebMetrics {
+                signals_processed: total.signals_processed + web.signals_processed,
+                agents_spawned: total.agents_spawned + web.agents_spawned,
+            })
+    }
+}
@@ -1,9 +1,20 @@
+pub mod checkpoint;
 pub mod coordination;
+pub mod events;
 pub mod executor;
 pub mod lifecycle_management;
+pub mod metrics;
+#[cfg(feature = "otel")]
+pub mod otel;
 pub mod propagation;
+pub mod report;
 pub mod resonance;
+pub mod scheduler;
 pub mod spawning;
 
+pub use checkpoint::CheckpointConfig;
+pub use events::{EngineEvent, EngineObserver, EventBus};
 pub use executor::{AgentExecutionResult, AgentExecutor, ExecutorConfig};
 pub use lifecycle_management::{ConvergenceDetector, LifecycleManager};
+pub use metrics::{MetricsRegistry, WebMetrics};
+pub use scheduler::{SchedulerConfig, StorageRunner, WebRunner, WebScheduler};
@@ -0,0 +1,63 @@
+use anyhow::Result;
+use opentelemetry::metrics::{MeterProvider, ObservableCounter};
+use opentelemetry_otlp::MetricExporter;
+use opentelemetry_sdk::metrics::{PeriodicReader, SdkMeterProvider};
+
+use crate::engine::metrics::MetricsRegistry;
+
+/// Pushes the counters of a `MetricsRegistry` to an OTLP collector. The
+/// instruments read the registry when collected, so OTLP and `/metrics`
+/// always report the same values.
+pub struct OtlpMetricsExporter {
+    provider: SdkMeterProvider,
+    _instruments: [ObservableCounter<u64>; 2],
+}
+
+impl OtlpMetricsExporter {
+    /// Starts exporting when `OTEL_EXPORTER_OTLP_ENDPOINT` is set. The other
+    /// standard `OTEL_*` variables configure the exporter as usual.
+    pub fn from_env(metrics: &MetricsRegistry) -> Result<Option<Self>> {
+        let endpoint = std::env::var("OTEL_EXPORTER_OTLP_ENDPOINT").unwrap_or_default();
+        if endpoint.is_empty() {
+            return Ok(None);
+        }
+
+        let exporter = MetricExporter::builder().with_http().build()?;
+        Ok(Some(Self::new(metrics, exporter)))
+    }
+
+    fn new(metrics: &MetricsRegistry, exporter: MetricExporter) -> Self {
+        let provider = SdkMeterProvider::builder()
+            .with_reader(PeriodicReader::builder(exporter).build())
+            .build();
+        let meter = provider.meter("arachnid");
+
+        let signals = metrics.clone();
+        let spawns = metrics.clone();
+        let instruments = [
+            meter
+                .u64_observable_counter("arachnid.signals_processed")
+                .with_description("Signals processed by the coordination engine")
+                .with_callback(move |observer| {
+                    observer.observe(signals.total().signals_processed, &[])
+                })
+                .build(),
+            meter
+                .u64_observable_counter("arachnid.agents_spawned")
+                .with_description("Agents spawned to handle needs")
+                .with_callback(move |observer| observer.observe(spawns.total().agents_spawned, &[]))
+                .build(),
+        ];
+
+        Self {
+            provider,
+            _instruments: instruments,
+        }
+    }
+
+    /// Flush the last readings and stop exporting.
+    pub fn shutdown(self) -> Result<()> {
+        self.provider.shutdown()?;
+        Ok(())
+    }
+}
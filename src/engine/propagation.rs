@@ -1,9 +1,24 @@
 use anyhow::Result;
 use std::collections::HashSet;
 
-use crate::engine::resonance::{compute_resonance, ResonanceResult};
+use crate::engine::resonance::{compute_resonance, magnitude, ResonanceResult};
 use crate::storage::memory::WebStore;
-use crate::types::{Agent, AgentId, Signal, SignalDirection, WebConfig};
+use crate::types::{Agent, AgentId, AgentState, Signal, SignalDirection, WebConfig};
+
+/// Starting amplitude multiplier for signals from an isolated agent.
+pub const ISOLATED_DAMPENING: f32 = 0.5;
+/// Starting amplitude multiplier for signals from a quarantined agent.
+pub const QUARANTINE_DAMPENING: f32 = 0.8;
+
+/// How much of its amplitude a signal keeps given the state of the agent that
+/// sent it, so untrusted agents have less reach.
+pub fn origin_dampening(state: AgentState) -> f32 {
+    match state {
+        AgentState::Isolated => ISOLATED_DAMPENING,
+        AgentState::Quarantine => QUARANTINE_DAMPENING,
+        _ => 1.0,
+    }
+}
 
 #[derive(Debug, Clone)]
 pub struct PropagationResult {
@@ -19,16 +34,29 @@ pub async fn propagate_signal<S: WebStore>(
     let mut results = Vec::new();
     let mut visited = HashSet::new();
 
+    if signal.frequency.len() != config.embedding_dim {
+        tracing::warn!(
+            signal_id = %signal.id,
+            frequency_dim = signal.frequency.len(),
+            web_dim = config.embedding_dim,
+            "Signal frequency dimension differs from the web's embedding_dim; agents tuned at the web's dimension won't resonate"
+        );
+    }
+
     let origin_agent = store
         .get_agent(&signal.origin)?
         .ok_or_else(|| anyhow::anyhow!("Origin agent not found"))?;
 
     let mut current_signal = signal.clone();
+    current_signal.amplitude *= origin_dampening(origin_agent.state);
+    // Attenuation only changes the amplitude, so this holds for every hop.
+    let frequency_magnitude = magnitude(&signal.frequency);
 
     match signal.direction {
         SignalDirection::Upward => {
             propagate_upward(
                 &mut current_signal,
+                frequency_magnitude,
                 &origin_agent,
                 config,
                 store,
@@ -40,6 +68,7 @@ pub async fn propagate_signal<S: WebStore>(
         SignalDirection::Downward => {
             propagate_downward(
                 &mut current_signal,
+                frequency_magnitude,
                 &origin_agent,
                 config,
                 store,
@@ -55,6 +84,7 @@ pub async fn propagate_signal<S: WebStore>(
 
 async fn propagate_upward<S: WebStore>(
     signal: &mut Signal,
+    frequency_magnitude: f32,
     origin: &Agent,
     config: &WebConfig,
     store: &S,
@@ -68,7 +98,7 @@ async fn propagate_upward<S: WebStore>(
             if !visited.contains(&agent.id) {
                 visited.insert(agent.id);
 
-                let resonance = compute_resonance(&agent, signal);
+                let resonance = compute_resonance(&agent, signal, frequency_magnitude);
                 results.push(PropagationResult {
                     agent_id: agent.id,
                     resonance,
@@ -94,6 +124,7 @@ async fn propagate_upward<S: WebStore>(
 
 async fn propagate_downward<S: WebStore>(
     signal: &mut Signal,
+    frequency_magnitude: f32,
     origin: &Agent,
     config: &WebConfig,
     store: &S,
@@ -123,7 +154,7 @@ async fn propagate_downward<S: WebStore>(
             if !visited.contains(&agent.id) {
                 visited.insert(agent.id);
 
-                let resonance = compute_resonance(&agent, signal);
+                let resonance = compute_resonance(&agent, signal, frequency_magnitude);
                 results.push(PropagationResult {
                     agent_id: agent.id,
                     resonance,
@@ -340,4 +371,51 @@ mod tests {
 
         assert!(results.iter().any(|r| r.agent_id == child.id));
     }
+
+    #[tokio::test]
+    async fn test_untrusted_origin_has_less_reach() {
+        let config = WebConfig {
+            attenuation_factor: 0.8,
+            min_amplitude: 0.3,
+            ..Default::default()
+        };
+
+        let mut reach = Vec::new();
+        for state in [
+            AgentState::Listening,
+            AgentState::Quarantine,
+            AgentState::Isolated,
+        ] {
+            let store = InMemoryStore::new();
+            let mut parent: Option<Agent> = None;
+            for depth in 0..8 {
+                let agent = Agent::new(
+                    parent
+                        .as_ref()
+                        .map_or_else(uuid::Uuid::new_v4, |p| p.web_id),
+                    parent.as_ref().map(|p| p.id),
+                    format!("depth {}", depth),
+                    vec![1.0, 0.0, 0.0],
+                    CapabilityType::Synthesizer,
+                    0.5,
+                );
+                store.add_agent(agent.clone()).unwrap();
+                parent = Some(agent);
+            }
+            let mut leaf = parent.unwrap();
+            leaf.state = state;
+            store.update_agent(leaf.clone()).unwrap();
+
+            let signal = Signal::new(
+                leaf.id,
+                vec![1.0, 0.0, 0.0],
+                "same finding".to_string(),
+                SignalDirection::Upward,
+            );
+            let results = propagate_signal(&signal, &config, &store).await.unwrap();
+            reach.push(results.len());
+        }
+
+        assert_eq!(reach, vec![6, 5, 3]);
+    }
 }
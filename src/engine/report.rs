@@ -0,0 +1,80 @@
+use anyhow::{anyhow, Result};
+use serde::Deserialize;
+
+use crate::providers::llm::Message;
+use crate::types::FinalReport;
+
+/// Ask for a report on `task` from what the root agent gathered.
+pub(crate) fn report_prompt(task: &str, knowledge: &[String]) -> Vec<Message> {
+    let gathered = knowledge
+        .iter()
+        .map(|item| format!("- {}", item))
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    vec![
+        Message::system(
+            "You write the final report of a research task. Respond with only a JSON object: \
+             {\"summary\": string, \"key_points\": [string], \"confidence\": number between 0 and 1}",
+        ),
+        Message::user(format!(
+            "Task: {}\n\nFindings:\n{}\n\nSummarize what the findings say about the task, list the key points, and rate how well they answer it.",
+            task, gathered
+        )),
+    ]
+}
+
+#[derive(Deserialize)]
+struct ReportResponse {
+    summary: String,
+    #[serde(default)]
+    key_points: Vec<String>,
+    confidence: f32,
+}
+
+/// Parse the LLM's report, with or without code fences or surrounding prose.
+pub(crate) fn parse_report(response: &str) -> Result<FinalReport> {
+    let start = response
+        .find('{')
+        .ok_or_else(|| anyhow!("No JSON object in report response"))?;
+    let end = response
+        .rfind('}')
+        .filter(|end| *end > start)
+        .ok_or_else(|| anyhow!("No JSON object in report response"))?;
+    let parsed: ReportResponse = serde_json::from_str(&response[start..=end])
+        .map_err(|e| anyhow!("Failed to parse report: {}", e))?;
+
+    Ok(FinalReport {
+        summary: parsed.summary,
+        key_points: parsed.key_points,
+        confidence: parsed.confidence.clamp(0.0, 1.0),
+    })
+}
+
+/// The report when no LLM wrote one: every finding, unjudged.
+pub(crate) fn concatenated_report(knowledge: &[String]) -> FinalReport {
+    FinalReport {
+        summary: knowledge.join("\n\n"),
+        key_points: knowledge.to_vec(),
+        confidence: 0.0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_report() {
+        let report = parse_report(
+            "Here it is:\n```json\n{\"summary\": \"Rust wins\", \"key_points\": [\"fast\"], \"confidence\": 1.4}\n```",
+        )
+        .unwrap();
+        assert_eq!(report.summary, "Rust wins");
+        assert_eq!(report.key_points, vec!["fast"]);
+        assert_eq!(report.confidence, 1.0);
+
+        assert!(parse_report("no report today").is_err());
+        assert!(parse_report("{\"key_points\": []}").is_err());
+    }
+}
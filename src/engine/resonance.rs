@@ -8,28 +8,125 @@ pub struct ResonanceResult {
 }
 
 pub fn cosine_similarity(a: &[f32], b: &[f32]) -> f32 {
-    if a.len() != b.len() {
+    if a.len() != b.len() || a.is_empty() {
         return 0.0;
     }
 
-    if a.is_empty() {
+    let (dot_product, norm_b_squared) = dot_and_norm_squared(a, b);
+    normalize(dot_product, magnitude(a), norm_b_squared.sqrt())
+}
+
+/// `cosine_similarity` for vectors whose magnitudes are already known, so
+/// only the dot product is computed.
+pub fn cosine_similarity_with_norms(
+    a: &[f32],
+    magnitude_a: f32,
+    b: &[f32],
+    magnitude_b: f32,
+) -> f32 {
+    if a.len() != b.len() || a.is_empty() {
         return 0.0;
     }
+    normalize(dot(a, b), magnitude_a, magnitude_b)
+}
+
+/// `cosine_similarity` of `query` with each candidate, computing the query's
+/// magnitude once instead of once per candidate.
+pub fn cosine_similarity_batch(query: &[f32], candidates: &[&[f32]]) -> Vec<f32> {
+    let magnitude_query = magnitude(query);
+
+    candidates
+        .iter()
+        .map(|candidate| {
+            if candidate.len() != query.len() || query.is_empty() {
+                return 0.0;
+            }
+            let (dot_product, norm_squared) = dot_and_norm_squared(query, candidate);
+            normalize(dot_product, magnitude_query, norm_squared.sqrt())
+        })
+        .collect()
+}
+
+/// `cosine_similarity_batch` for candidates whose magnitudes are already
+/// known, given alongside each one, so only dot products are computed.
+pub fn cosine_similarity_batch_with_norms(query: &[f32], candidates: &[(&[f32], f32)]) -> Vec<f32> {
+    let magnitude_query = magnitude(query);
 
-    let dot_product: f32 = a.iter().zip(b.iter()).map(|(x, y)| x * y).sum();
+    candidates
+        .iter()
+        .map(|(candidate, magnitude_candidate)| {
+            cosine_similarity_with_norms(query, magnitude_query, candidate, *magnitude_candidate)
+        })
+        .collect()
+}
 
-    let magnitude_a: f32 = a.iter().map(|x| x * x).sum::<f32>().sqrt();
-    let magnitude_b: f32 = b.iter().map(|x| x * x).sum::<f32>().sqrt();
+pub fn magnitude(v: &[f32]) -> f32 {
+    v.iter().map(|x| x * x).sum::<f32>().sqrt()
+}
 
+fn normalize(dot_product: f32, magnitude_a: f32, magnitude_b: f32) -> f32 {
     if magnitude_a == 0.0 || magnitude_b == 0.0 {
         return 0.0;
     }
-
     dot_product / (magnitude_a * magnitude_b)
 }
 
-pub fn compute_resonance(agent: &Agent, signal: &Signal) -> ResonanceResult {
-    let similarity = cosine_similarity(&agent.tuning, &signal.frequency);
+/// Four independent accumulators let the compiler vectorize the loop,
+/// which a single running sum prevents.
+fn dot(a: &[f32], b: &[f32]) -> f32 {
+    const LANES: usize = 4;
+    let mut sums = [0.0f32; LANES];
+
+    let a_chunks = a.chunks_exact(LANES);
+    let b_chunks = b.chunks_exact(LANES);
+    let (a_rest, b_rest) = (a_chunks.remainder(), b_chunks.remainder());
+    for (x, y) in a_chunks.zip(b_chunks) {
+        for lane in 0..LANES {
+            sums[lane] += x[lane] * y[lane];
+        }
+    }
+
+    sums.iter().sum::<f32>() + a_rest.iter().zip(b_rest).map(|(x, y)| x * y).sum::<f32>()
+}
+
+/// `a·b` and `b·b` in one pass, unrolled like `dot`.
+fn dot_and_norm_squared(a: &[f32], b: &[f32]) -> (f32, f32) {
+    const LANES: usize = 4;
+    let mut dot = [0.0f32; LANES];
+    let mut norm = [0.0f32; LANES];
+
+    let a_chunks = a.chunks_exact(LANES);
+    let b_chunks = b.chunks_exact(LANES);
+    let (a_rest, b_rest) = (a_chunks.remainder(), b_chunks.remainder());
+    for (x, y) in a_chunks.zip(b_chunks) {
+        for lane in 0..LANES {
+            dot[lane] += x[lane] * y[lane];
+            norm[lane] += y[lane] * y[lane];
+        }
+    }
+
+    let mut dot_sum = dot.iter().sum::<f32>();
+    let mut norm_sum = norm.iter().sum::<f32>();
+    for (x, y) in a_rest.iter().zip(b_rest) {
+        dot_sum += x * y;
+        norm_sum += y * y;
+    }
+    (dot_sum, norm_sum)
+}
+
+/// `frequency_magnitude` is `magnitude(&signal.frequency)`, which callers
+/// scoring one signal against many agents compute once.
+pub fn compute_resonance(
+    agent: &Agent,
+    signal: &Signal,
+    frequency_magnitude: f32,
+) -> ResonanceResult {
+    let similarity = cosine_similarity_with_norms(
+        &agent.tuning,
+        agent.tuning_norm(),
+        &signal.frequency,
+        frequency_magnitude,
+    );
     let effective_strength = similarity * signal.amplitude;
     let activated = effective_strength > agent.activation_threshold;
 
@@ -93,6 +190,116 @@ mod tests {
         assert_eq!(result, 0.0);
     }
 
+    /// The plain loop `cosine_similarity` used to be, as a reference.
+    fn scalar_cosine_similarity(a: &[f32], b: &[f32]) -> f32 {
+        let dot: f32 = a.iter().zip(b).map(|(x, y)| x * y).sum();
+        let magnitude_a = a.iter().map(|x| x * x).sum::<f32>().sqrt();
+        let magnitude_b = b.iter().map(|x| x * x).sum::<f32>().sqrt();
+        dot / (magnitude_a * magnitude_b)
+    }
+
+    #[test]
+    fn test_cosine_similarity_batch_matches_scalar() {
+        // Deterministic pseudo-random vectors; odd length exercises the remainder.
+        let mut seed = 42u32;
+        let mut next = || {
+            seed = seed.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+            (seed >> 8) as f32 / (1 << 24) as f32 - 0.5
+        };
+        let query: Vec<f32> = (0..1537).map(|_| next()).collect();
+        let vectors: Vec<Vec<f32>> = (0..1000)
+            .map(|_| (0..1537).map(|_| next()).collect())
+            .collect();
+        let candidates: Vec<&[f32]> = vectors.iter().map(Vec::as_slice).collect();
+
+        let scalar: Vec<f32> = candidates
+            .iter()
+            .map(|c| scalar_cosine_similarity(&query, c))
+            .collect();
+        let batched = cosine_similarity_batch(&query, &candidates);
+
+        assert_eq!(batched.len(), scalar.len());
+        for (b, s) in batched.iter().zip(&scalar) {
+            assert!((b - s).abs() < 1e-5, "batched {} vs scalar {}", b, s);
+        }
+        for (c, b) in candidates.iter().zip(&batched) {
+            assert!((cosine_similarity(&query, c) - b).abs() < 1e-5);
+        }
+    }
+
+    #[test]
+    fn test_cosine_similarity_batch_edge_cases() {
+        let query = [1.0, 0.0, 0.0];
+        let candidates: [&[f32]; 4] = [
+            &[2.0, 0.0, 0.0],
+            &[1.0, 2.0],
+            &[0.0, 0.0, 0.0],
+            &[0.0, 3.0, 0.0],
+        ];
+        assert_eq!(
+            cosine_similarity_batch(&query, &candidates),
+            vec![1.0, 0.0, 0.0, 0.0]
+        );
+        assert!(cosine_similarity_batch(&[], &[&[]])
+            .iter()
+            .all(|s| *s == 0.0));
+        assert!(cosine_similarity_batch(&query, &[]).is_empty());
+    }
+
+    #[test]
+    fn test_cosine_similarity_batch_with_norms() {
+        let query = [1.0, 2.0, 0.5];
+        let vectors: [&[f32]; 4] = [
+            &[2.0, 0.0, 1.0],
+            &[1.0, 2.0],
+            &[0.0, 0.0, 0.0],
+            &[-1.0, 3.0, 2.0],
+        ];
+        let candidates: Vec<(&[f32], f32)> = vectors.iter().map(|v| (*v, magnitude(v))).collect();
+
+        assert_eq!(
+            cosine_similarity_batch_with_norms(&query, &candidates),
+            cosine_similarity_batch(&query, &vectors)
+        );
+    }
+
+    #[test]
+    fn test_cosine_similarity_with_norms() {
+        let a = [1.0, 2.0, 3.0, 4.0, 5.0];
+        let b = [5.0, 4.0, 3.0, 2.0, 1.0];
+        let expected = cosine_similarity(&a, &b);
+        let result = cosine_similarity_with_norms(&a, magnitude(&a), &b, magnitude(&b));
+        assert!((result - expected).abs() < 1e-6);
+        assert_eq!(
+            cosine_similarity_with_norms(&a, 0.0, &b, magnitude(&b)),
+            0.0
+        );
+        assert_eq!(cosine_similarity_with_norms(&a, 1.0, &b[..4], 1.0), 0.0);
+    }
+
+    #[test]
+    fn test_agent_tuning_norm_is_cached() {
+        let mut agent = crate::types::Agent::new(
+            WebId::new_v4(),
+            None,
+            "test".to_string(),
+            vec![3.0, 4.0],
+            CapabilityType::Search,
+            0.5,
+        );
+        assert_eq!(agent.tuning_norm, 5.0);
+
+        agent.set_tuning(vec![0.0, 2.0]);
+        assert_eq!(agent.tuning_norm(), 2.0);
+
+        // Agents serialized before the field existed get it computed on use.
+        let mut json = serde_json::to_value(&agent).unwrap();
+        json.as_object_mut().unwrap().remove("tuning_norm");
+        let restored: crate::types::Agent = serde_json::from_value(json).unwrap();
+        assert_eq!(restored.tuning_norm, 0.0);
+        assert_eq!(restored.tuning_norm(), 2.0);
+    }
+
     #[test]
     fn test_compute_resonance_activates() {
         let agent = crate::types::Agent::new(
@@ -115,7 +322,7 @@ mod tests {
             payload: None,
         };
 
-        let result = compute_resonance(&agent, &signal);
+        let result = compute_resonance(&agent, &signal, magnitude(&signal.frequency));
         assert!((result.similarity - 1.0).abs() < 1e-6);
         assert!((result.effective_strength - 1.0).abs() < 1e-6);
         assert!(result.activated);
@@ -143,7 +350,7 @@ mod tests {
             payload: None,
         };
 
-        let result = compute_resonance(&agent, &signal);
+        let result = compute_resonance(&agent, &signal, magnitude(&signal.frequency));
         assert!((result.similarity - 0.0).abs() < 1e-6);
         assert!((result.effective_strength - 0.0).abs() < 1e-6);
         assert!(!result.activated);
@@ -171,7 +378,7 @@ mod tests {
             payload: None,
         };
 
-        let result = compute_resonance(&agent, &signal);
+        let result = compute_resonance(&agent, &signal, magnitude(&signal.frequency));
         assert!((result.similarity - 1.0).abs() < 1e-6);
         assert!((result.effective_strength - 0.3).abs() < 1e-6);
         assert!(!result.activated);
@@ -0,0 +1,665 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use std::collections::HashSet;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::Notify;
+use tokio::task::JoinHandle;
+
+use crate::engine::coordination::CoordinationEngine;
+use crate::storage::memory::{InMemoryStore, WebStore};
+use crate::storage::{JobStatus, Storage, WebRunJob, WebRunQueue};
+use crate::types::{WebId, WebState};
+
+/// Something that can drive a web to a terminal state.
+#[async_trait]
+pub trait WebRunner: Send + Sync {
+    /// Prepare a web to run; a no-op if it has already started.
+    async fn seed(&self, web_id: &WebId) -> Result<()>;
+
+    /// Run the web until it converges or fails, picking up where an earlier
+    /// run left off if there was one.
+    async fn run(&self, web_id: &WebId) -> Result<()>;
+
+    /// Stop a web that is running; returns `false` if it isn't.
+    fn cancel(&self, web_id: &WebId) -> bool;
+}
+
+#[async_trait]
+impl<S: WebStore + 'static> WebRunner for CoordinationEngine<S> {
+    async fn seed(&self, web_id: &WebId) -> Result<()> {
+        self.seed_web(web_id).await?;
+        Ok(())
+    }
+
+    async fn run(&self, web_id: &WebId) -> Result<()> {
+        self.resume(web_id).await
+    }
+
+    fn cancel(&self, web_id: &WebId) -> bool {
+        CoordinationEngine::cancel(self, web_id)
+    }
+}
+
+/// Runs webs kept in shared `Storage`, e.g. Postgres, on an engine over its
+/// own in-memory store.
+///
+/// Seeding writes the root agent and a first checkpoint to storage, so any
+/// worker sharing it can run the web. A run loads the latest checkpoint
+/// into the engine, which writes checkpoints, agents and signals back to
+/// storage as it goes and picks up config changes and pauses made there.
+/// Once the loop ends, the web, its failure patterns and analytics are
+/// written back too. The in-memory copy only lives as long as the run.
+pub struct StorageRunner {
+    engine: Arc<CoordinationEngine<InMemoryStore>>,
+    storage: Arc<dyn Storage>,
+}
+
+impl StorageRunner {
+    /// `engine` should checkpoint to `storage` with write-through (see
+    /// [`CoordinationEngine::with_checkpoints`] and
+    /// [`CoordinationEngine::with_write_through`]), or runs never reach
+    /// storage beyond the web itself.
+    pub fn new(engine: Arc<CoordinationEngine<InMemoryStore>>, storage: Arc<dyn Storage>) -> Self {
+        Self { engine, storage }
+    }
+
+    /// Copy the run's outcome from the engine's store to storage; the
+    /// engine has written its agents and signals through already.
+    async fn write_back(&self, web_id: &WebId) -> Result<()> {
+        let store = &**self.engine.store();
+        let Some(mut web) = WebStore::get_web(store, web_id)? else {
+            return Ok(());
+        };
+
+        for pattern in Storage::get_failure_patterns(store, *web_id).await? {
+            self.storage
+                .record_failure_pattern(*web_id, &pattern)
+                .await?;
+        }
+        if let Some(analytics) = Storage::get_web_analytics(store, *web_id).await? {
+            self.storage.save_web_analytics(&analytics).await?;
+        }
+        // Keep config changes made while it ran, and a termination through
+        // the API on another worker.
+        if let Some(stored) = self.storage.get_web(*web_id).await? {
+            web.config = stored.config;
+            if stored.state.is_terminal() {
+                web.state = stored.state;
+            }
+        }
+        // Last, so a finished web has everything else in place.
+        self.storage.update_web(&web).await?;
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl WebRunner for StorageRunner {
+    async fn seed(&self, web_id: &WebId) -> Result<()> {
+        let store = self.engine.store();
+        // Running here, or seeded already by some worker.
+        if WebStore::get_web(&**store, web_id)?.is_some()
+            || self.storage.get_checkpoint(*web_id).await?.is_some()
+        {
+            return Ok(());
+        }
+
+        let web = self
+            .storage
+            .get_web(*web_id)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("Web not found"))?;
+        WebStore::create_web(&**store, web)?;
+        let seeded = async {
+            self.engine.seed_web(web_id).await?;
+            let checkpoint = self.engine.snapshot(web_id, 0)?;
+            for agent in &checkpoint.agents {
+                self.storage.create_agent(agent).await?;
+            }
+            self.storage.save_checkpoint(&checkpoint).await
+        }
+        .await;
+        store.evict_web(web_id);
+        seeded
+    }
+
+    async fn run(&self, web_id: &WebId) -> Result<()> {
+        let web = self
+            .storage
+            .get_web(*web_id)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("Web not found"))?;
+        if web.state.is_terminal() {
+            return Ok(());
+        }
+        let mut checkpoint = self
+            .storage
+            .get_checkpoint(*web_id)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("Web {} has not been seeded", web_id))?;
+        checkpoint.web.config = web.config;
+
+        let store = self.engine.store();
+        // Another worker may have run it since this one last did.
+        store.evict_web(web_id);
+        self.engine.restore_checkpoint(&checkpoint)?;
+        let result = self.engine.resume(web_id).await;
+        let saved = self.write_back(web_id).await;
+        store.evict_web(web_id);
+        result.and(saved)
+    }
+
+    fn cancel(&self, web_id: &WebId) -> bool {
+        self.engine.cancel(web_id)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct SchedulerConfig {
+    /// Webs advanced at the same time; further webs wait for a free slot.
+    pub max_concurrent_webs: usize,
+    /// No new web is started while running webs hold this many agents.
+    pub max_total_agents: usize,
+    /// How often to look for runnable webs when nothing wakes the scheduler.
+    pub poll_interval: Duration,
+}
+
+impl Default for SchedulerConfig {
+    fn default() -> Self {
+        Self {
+            max_concurrent_webs: 4,
+            max_total_agents: 1000,
+            poll_interval: Duration::from_secs(1),
+        }
+    }
+}
+
+impl SchedulerConfig {
+    /// Reads `ARACHNID_MAX_CONCURRENT_WEBS` and `ARACHNID_MAX_TOTAL_AGENTS`.
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+        let env = |var: &str| std::env::var(var).ok().and_then(|v| v.parse().ok());
+
+        Self {
+            max_concurrent_webs: env("ARACHNID_MAX_CONCURRENT_WEBS")
+                .unwrap_or(defaults.max_concurrent_webs),
+            max_total_agents: env("ARACHNID_MAX_TOTAL_AGENTS").unwrap_or(defaults.max_total_agents),
+            ..defaults
+        }
+    }
+}
+
+/// Runs queued webs in the background with bounded concurrency.
+///
+/// Webs are queued with [`WebScheduler::start_web`] and claimed from a
+/// [`WebRunQueue`], so several schedulers sharing storage split the work.
+/// Each web runs in its own task; an error or panic fails that web and
+/// leaves the others running. If a worker dies mid-run, the web's job is
+/// claimed again once its lease expires and the web resumes where it was
+/// left.
+#[derive(Clone)]
+pub struct WebScheduler {
+    storage: Arc<dyn Storage>,
+    runner: Arc<dyn WebRunner>,
+    queue: WebRunQueue,
+    config: SchedulerConfig,
+    active: Arc<Mutex<HashSet<WebId>>>,
+    wake: Arc<Notify>,
+}
+
+/// Frees a web's slot however its task ends.
+struct ActiveSlot {
+    web_id: WebId,
+    active: Arc<Mutex<HashSet<WebId>>>,
+    wake: Arc<Notify>,
+}
+
+impl Drop for ActiveSlot {
+    fn drop(&mut self) {
+        self.active.lock().unwrap().remove(&self.web_id);
+        self.wake.notify_one();
+    }
+}
+
+impl WebScheduler {
+    pub fn new(storage: Arc<dyn Storage>, runner: Arc<dyn WebRunner>) -> Self {
+        let queue = WebRunQueue::new(storage.clone(), format!("worker-{}", uuid::Uuid::new_v4()));
+        Self {
+            storage,
+            runner,
+            queue,
+            config: SchedulerConfig::default(),
+            active: Arc::new(Mutex::new(HashSet::new())),
+            wake: Arc::new(Notify::new()),
+        }
+    }
+
+    pub fn with_config(mut self, config: SchedulerConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    pub fn with_queue(mut self, queue: WebRunQueue) -> Self {
+        self.queue = queue;
+        self
+    }
+
+    /// Seed a web and queue it to run.
+    pub async fn start_web(&self, web_id: &WebId) -> Result<WebRunJob> {
+        let web = self
+            .storage
+            .get_web(*web_id)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("Web not found"))?;
+        self.runner.seed(web_id).await?;
+        let job = self.queue.enqueue_web_run(&web).await?;
+        self.wake.notify_one();
+        Ok(job)
+    }
+
+    /// Queue the `Running` webs a previous process left behind: ones that
+    /// were started, so have a checkpoint, but have no job left to pick
+    /// them up. A job whose worker died is claimed again by the queue
+    /// itself once its lease runs out. Returns how many were queued.
+    pub async fn resume_running(&self) -> Result<usize> {
+        let mut queued = 0;
+        for web in self.storage.list_webs(Some(WebState::Running)).await? {
+            let open = self
+                .storage
+                .get_web_jobs(web.id)
+                .await?
+                .iter()
+                .any(|job| matches!(job.status, JobStatus::Queued | JobStatus::Claimed));
+            if open || self.storage.get_checkpoint(web.id).await?.is_none() {
+                continue;
+            }
+            self.queue.enqueue_web_run(&web).await?;
+            queued += 1;
+        }
+        if queued > 0 {
+            self.wake.notify_one();
+        }
+        Ok(queued)
+    }
+
+    /// Stop a web running on this scheduler. Returns `false` if it isn't
+    /// running here.
+    pub fn cancel_web(&self, web_id: &WebId) -> bool {
+        self.runner.cancel(web_id)
+    }
+
+    pub fn active_webs(&self) -> Vec<WebId> {
+        self.active.lock().unwrap().iter().copied().collect()
+    }
+
+    /// Run the scheduling loop in the background.
+    pub fn spawn(&self) -> JoinHandle<()> {
+        let scheduler = self.clone();
+        tokio::spawn(async move {
+            loop {
+                if let Err(e) = scheduler.schedule().await {
+                    tracing::warn!(error = %e, "Web scheduling pass failed");
+                }
+                tokio::select! {
+                    _ = scheduler.wake.notified() => {}
+                    _ = tokio::time::sleep(scheduler.config.poll_interval) => {}
+                }
+            }
+        })
+    }
+
+    /// Claim and start queued webs while slots and agent capacity remain.
+    /// Returns how many were started.
+    pub async fn schedule(&self) -> Result<usize> {
+        let mut total_agents = 0;
+        for web in self.storage.list_webs(Some(WebState::Running)).await? {
+            total_agents += self.storage.get_web_agents(web.id).await?.len();
+        }
+
+        let mut started = 0;
+        loop {
+            if total_agents >= self.config.max_total_agents {
+                tracing::debug!(total_agents, "Agent limit reached; deferring webs");
+                break;
+            }
+            if self.active.lock().unwrap().len() >= self.config.max_concurrent_webs {
+                break;
+            }
+            let Some(job) = self.queue.claim_next().await? else {
+                break;
+            };
+
+            if !self.active.lock().unwrap().insert(job.web_id) {
+                self.queue.fail(&job, "Web is already running").await?;
+                continue;
+            }
+            self.run_web(job);
+            started += 1;
+        }
+
+        Ok(started)
+    }
+
+    fn run_web(&self, job: WebRunJob) {
+        let web_id = job.web_id;
+        let slot = ActiveSlot {
+            web_id,
+            active: self.active.clone(),
+            wake: self.wake.clone(),
+        };
+        let runner = self.runner.clone();
+        let storage = self.storage.clone();
+        let queue = self.queue.clone();
+
+        tokio::spawn(async move {
+            let _slot = slot;
+            // Run in a nested task so a panic surfaces here as a JoinError.
+            let mut run = tokio::spawn(async move { runner.run(&web_id).await });
+
+            // Keep the claim alive for as long as the web runs.
+            let mut heartbeat = tokio::time::interval(queue.visibility_timeout() / 2);
+            heartbeat.tick().await;
+            let outcome = loop {
+                tokio::select! {
+                    outcome = &mut run => break outcome,
+                    _ = heartbeat.tick() => {
+                        if let Err(e) = queue.renew(&job).await {
+                            tracing::warn!(web_id = %web_id, error = %e, "Could not renew web run");
+                        }
+                    }
+                }
+            };
+
+            let error = match outcome {
+                Ok(Ok(())) => None,
+                Ok(Err(e)) => Some(e.to_string()),
+                Err(e) => Some(format!("Web task panicked: {}", e)),
+            };
+
+            let finished = match &error {
+                None => queue.complete(&job).await,
+                Some(error) => {
+                    tracing::warn!(web_id = %web_id, error = %error, "Web run failed");
+                    if let Err(e) = fail_web(&*storage, web_id).await {
+                        tracing::warn!(web_id = %web_id, error = %e, "Could not mark web failed");
+                    }
+                    queue.fail(&job, error).await
+                }
+            };
+            if let Err(e) = finished {
+                tracing::warn!(web_id = %web_id, error = %e, "Could not finish web run");
+            }
+        });
+    }
+}
+
+async fn fail_web(storage: &dyn Storage, web_id: WebId) -> Result<()> {
+    if let Some(mut web) = storage.get_web(web_id).await? {
+        if web.state == WebState::Running {
+            web.state = WebState::Failed;
+            storage.update_web(&web).await?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::capabilities::{Capability, Providers};
+    use crate::engine::checkpoint::CheckpointConfig;
+    use crate::engine::coordination::{ContextUpdate, ExecutionResult};
+    use crate::storage::memory::InMemoryStore;
+    use crate::types::{AgentContext, CapabilityType, ExecutionStatus, Signal, Web, WebConfig};
+    use std::collections::HashMap;
+    use tokio::sync::Barrier;
+
+    /// Completes once every other web has reached the barrier too, so webs
+    /// only finish if they run concurrently. Fails outright for "explode".
+    struct RendezvousCapability {
+        barrier: Arc<Barrier>,
+    }
+
+    #[async_trait]
+    impl Capability for RendezvousCapability {
+        fn name(&self) -> &str {
+            "rendezvous"
+        }
+
+        fn description(&self) -> &str {
+            "Waits for the other webs"
+        }
+
+        async fn execute(
+            &self,
+            context: &AgentContext,
+            _trigger: Option<&Signal>,
+            _providers: &Providers,
+        ) -> Result<ExecutionResult> {
+            if context.purpose == "explode" {
+                anyhow::bail!("capability blew up");
+            }
+            self.barrier.wait().await;
+
+            Ok(ExecutionResult {
+                status: ExecutionStatus::Complete,
+                output: serde_json::json!({}),
+                signals_to_emit: vec![],
+                needs: vec![],
+                context_updates: vec![ContextUpdate {
+                    content: "done".to_string(),
+                    data: serde_json::json!({}),
+                }],
+                capability_state: None,
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_scheduler_runs_webs_concurrently() {
+        let store = Arc::new(InMemoryStore::new());
+        let mut capabilities: HashMap<CapabilityType, Box<dyn Capability>> = HashMap::new();
+        capabilities.insert(
+            CapabilityType::Synthesizer,
+            Box::new(RendezvousCapability {
+                barrier: Arc::new(Barrier::new(2)),
+            }),
+        );
+        let providers = Providers {
+            embedding: None,
+            llm: None,
+            search: None,
+        };
+        let engine = CoordinationEngine::new(store.clone(), capabilities, providers);
+        let scheduler =
+            WebScheduler::new(store.clone(), Arc::new(engine)).with_config(SchedulerConfig {
+                max_concurrent_webs: 3,
+                poll_interval: Duration::from_millis(10),
+                ..SchedulerConfig::default()
+            });
+
+        let mut web_ids = Vec::new();
+        for task in ["first", "second", "explode"] {
+            let web = Web::new(uuid::Uuid::new_v4(), task.to_string(), WebConfig::default());
+            Storage::create_web(&*store, &web).await.unwrap();
+            web_ids.push(web.id);
+        }
+        // Never queued, so it stays put.
+        let idle = Web::new(
+            uuid::Uuid::new_v4(),
+            "idle".to_string(),
+            WebConfig::default(),
+        );
+        Storage::create_web(&*store, &idle).await.unwrap();
+
+        for web_id in &web_ids {
+            scheduler.start_web(web_id).await.unwrap();
+        }
+        let handle = scheduler.spawn();
+
+        let states = tokio::time::timeout(Duration::from_secs(5), async {
+            loop {
+                let mut states = Vec::new();
+                for web_id in &web_ids {
+                    let web = Storage::get_web(&*store, *web_id).await.unwrap().unwrap();
+                    states.push(web.state);
+                }
+                if states.iter().all(|s| *s != WebState::Running) {
+                    return states;
+                }
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .expect("webs did not finish");
+        handle.abort();
+
+        assert_eq!(
+            states,
+            vec![WebState::Converged, WebState::Converged, WebState::Failed]
+        );
+        let idle = Storage::get_web(&*store, idle.id).await.unwrap().unwrap();
+        assert_eq!(idle.state, WebState::Running);
+    }
+
+    /// A scheduler over `storage` whose engine runs on its own store, as
+    /// `serve` does with Postgres.
+    fn storage_scheduler(storage: Arc<InMemoryStore>) -> (WebScheduler, Arc<InMemoryStore>) {
+        let mut capabilities: HashMap<CapabilityType, Box<dyn Capability>> = HashMap::new();
+        capabilities.insert(
+            CapabilityType::Synthesizer,
+            Box::new(RendezvousCapability {
+                barrier: Arc::new(Barrier::new(1)),
+            }),
+        );
+        let providers = Providers {
+            embedding: None,
+            llm: None,
+            search: None,
+        };
+        let engine_store = Arc::new(InMemoryStore::new());
+        let engine = CoordinationEngine::new(engine_store.clone(), capabilities, providers)
+            .with_checkpoints(storage.clone(), CheckpointConfig::default())
+            .with_write_through();
+        let runner = StorageRunner::new(Arc::new(engine), storage.clone());
+        let scheduler = WebScheduler::new(storage, Arc::new(runner)).with_config(SchedulerConfig {
+            poll_interval: Duration::from_millis(10),
+            ..SchedulerConfig::default()
+        });
+        (scheduler, engine_store)
+    }
+
+    async fn wait_until_finished(storage: &InMemoryStore, web_id: WebId) -> WebState {
+        tokio::time::timeout(Duration::from_secs(5), async {
+            loop {
+                let web = Storage::get_web(storage, web_id).await.unwrap().unwrap();
+                if web.state != WebState::Running {
+                    return web.state;
+                }
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .expect("web did not finish")
+    }
+
+    #[tokio::test]
+    async fn test_storage_runner_runs_webs_kept_in_storage() {
+        // Stands in for Postgres: the engine never sees it directly.
+        let storage = Arc::new(InMemoryStore::new());
+        let (scheduler, engine_store) = storage_scheduler(storage.clone());
+
+        let web = Web::new(
+            uuid::Uuid::new_v4(),
+            "task".to_string(),
+            WebConfig::default(),
+        );
+        Storage::create_web(&*storage, &web).await.unwrap();
+        scheduler.start_web(&web.id).await.unwrap();
+        // Seeding leaves the root agent and a checkpoint in storage.
+        assert_eq!(
+            Storage::get_web_agents(&*storage, web.id)
+                .await
+                .unwrap()
+                .len(),
+            1
+        );
+        assert!(Storage::get_checkpoint(&*storage, web.id)
+            .await
+            .unwrap()
+            .is_some());
+        assert!(WebStore::get_web(&*engine_store, &web.id)
+            .unwrap()
+            .is_none());
+
+        let handle = scheduler.spawn();
+        let state = wait_until_finished(&storage, web.id).await;
+        handle.abort();
+
+        assert_eq!(state, WebState::Converged);
+        let signals = WebStore::get_web_signals(&*storage, &web.id).unwrap();
+        assert!(!signals.is_empty());
+        assert!(signals.iter().all(|(_, processed)| *processed));
+        assert!(Storage::get_web_analytics(&*storage, web.id)
+            .await
+            .unwrap()
+            .is_some());
+        assert!(WebStore::get_web(&*engine_store, &web.id)
+            .unwrap()
+            .is_none());
+    }
+
+    #[tokio::test]
+    async fn test_resume_running_picks_up_orphaned_webs() {
+        let storage = Arc::new(InMemoryStore::new());
+        let (previous, _) = storage_scheduler(storage.clone());
+
+        // Started by a process that then died, taking its in-memory queue
+        // with it: a checkpoint but no job.
+        let orphaned = Web::new(
+            uuid::Uuid::new_v4(),
+            "orphaned".to_string(),
+            WebConfig::default(),
+        );
+        Storage::create_web(&*storage, &orphaned).await.unwrap();
+        previous.runner.seed(&orphaned.id).await.unwrap();
+        // Still waiting in the queue.
+        let queued = Web::new(
+            uuid::Uuid::new_v4(),
+            "queued".to_string(),
+            WebConfig::default(),
+        );
+        Storage::create_web(&*storage, &queued).await.unwrap();
+        previous.start_web(&queued.id).await.unwrap();
+        // Created but never run.
+        let idle = Web::new(
+            uuid::Uuid::new_v4(),
+            "idle".to_string(),
+            WebConfig::default(),
+        );
+        Storage::create_web(&*storage, &idle).await.unwrap();
+
+        let (scheduler, _) = storage_scheduler(storage.clone());
+        assert_eq!(scheduler.resume_running().await.unwrap(), 1);
+        assert_eq!(scheduler.resume_running().await.unwrap(), 0);
+
+        let handle = scheduler.spawn();
+        assert_eq!(
+            wait_until_finished(&storage, orphaned.id).await,
+            WebState::Converged
+        );
+        assert_eq!(
+            wait_until_finished(&storage, queued.id).await,
+            WebState::Converged
+        );
+        handle.abort();
+
+        let idle = Storage::get_web(&*storage, idle.id).await.unwrap().unwrap();
+        assert_eq!(idle.state, WebState::Running);
+        assert!(Storage::get_web_jobs(&*storage, idle.id)
+            .await
+            .unwrap()
+            .is_empty());
+    }
+}
@@ -1,9 +1,11 @@
 use anyhow::Result;
+use std::collections::HashMap;
 use std::sync::Arc;
 
+use crate::definitions::generator::DEFAULT_MAX_RETRIES;
 use crate::definitions::{
-    task_coordinator_definition, AgentDefinition, DefinitionGenerator, DefinitionId,
-    DefinitionSource,
+    task_coordinator_definition, AgentDefinition, DefinitionFormat, DefinitionGenerator,
+    DefinitionId, DefinitionSource,
 };
 use crate::engine::resonance::cosine_similarity;
 use crate::providers::{EmbeddingProvider, LLMProvider};
@@ -15,6 +17,13 @@ pub struct FactoryConfig {
     pub definition_match_threshold: f32,
     pub dormant_reactivation_threshold: f32,
     pub cache_generated_definitions: bool,
+    /// Times the generator may ask the model to fix an unusable definition.
+    pub generation_retries: usize,
+    /// Format generated definitions are requested in.
+    pub definition_format: DefinitionFormat,
+    /// Reject generated definitions with unknown tools or out-of-range
+    /// values instead of repairing them.
+    pub strict_generation: bool,
 }
 
 impl Default for FactoryConfig {
@@ -23,6 +32,9 @@ impl Default for FactoryConfig {
             definition_match_threshold: 0.75,
             dormant_reactivation_threshold: 0.80,
             cache_generated_definitions: true,
+            generation_retries: DEFAULT_MAX_RETRIES,
+            definition_format: DefinitionFormat::default(),
+            strict_generation: false,
         }
     }
 }
@@ -41,7 +53,10 @@ impl AgentFactory {
         embedding_provider: Arc<dyn EmbeddingProvider>,
         config: FactoryConfig,
     ) -> Self {
-        let generator = DefinitionGenerator::new(llm_provider, embedding_provider.clone());
+        let generator = DefinitionGenerator::new(llm_provider, embedding_provider.clone())
+            .with_max_retries(config.generation_retries)
+            .with_format(config.definition_format)
+            .with_strict(config.strict_generation);
 
         Self {
             storage,
@@ -51,6 +66,10 @@ impl AgentFactory {
         }
     }
 
+    pub fn storage(&self) -> &Arc<dyn Storage> {
+        &self.storage
+    }
+
     pub fn get_builtin_task_coordinator(&self) -> AgentDefinition {
         task_coordinator_definition()
     }
@@ -110,6 +129,56 @@ impl AgentFactory {
         Ok(agent)
     }
 
+    /// Generate a coordinator plus specialists for `need` and instantiate
+    /// them together. The coordinator (first in the result) is a child of
+    /// `parent_id`; every other agent is a child of its team parent. Agents
+    /// are returned parents-first and are not persisted.
+    pub async fn spawn_team(
+        &self,
+        need: &str,
+        parent_id: Option<AgentId>,
+        web_id: WebId,
+        web_config: &WebConfig,
+    ) -> Result<Vec<Agent>> {
+        let team = self.generator.generate_team(need).await?;
+
+        let mut agent_ids: HashMap<DefinitionId, AgentId> = HashMap::new();
+        let mut agents = Vec::with_capacity(team.len());
+        for member in team {
+            let definition = member.definition;
+            if self.config.cache_generated_definitions {
+                self.storage.create_definition(&definition).await?;
+                self.storage
+                    .increment_definition_use_count(definition.id)
+                    .await?;
+            }
+
+            let purpose = if member.parent.is_none() {
+                need.to_string()
+            } else {
+                member.purpose
+            };
+            let tuning = self.compute_instance_tuning(&definition, &purpose).await?;
+            let parent = match member.parent {
+                Some(definition_id) => agent_ids.get(&definition_id).copied(),
+                None => parent_id,
+            };
+
+            let agent = Agent::from_definition(
+                definition.id,
+                web_id,
+                parent,
+                purpose,
+                tuning,
+                web_config.default_threshold,
+            );
+            agent_ids.insert(definition.id, agent.id);
+            agents.push(agent);
+        }
+
+        Ok(agents)
+    }
+
     pub async fn find_or_generate_definition(&self, need: &str) -> Result<AgentDefinition> {
         let need_embedding = self.embedding_provider.embed(need).await?;
 
@@ -213,6 +282,37 @@ impl AgentFactory {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::providers::Message;
+    use crate::storage::memory::InMemoryStore;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// Counts calls and fails them, so any generation attempt is visible.
+    #[derive(Default)]
+    struct UnreachableLLM {
+        calls: AtomicUsize,
+    }
+
+    #[async_trait::async_trait]
+    impl LLMProvider for UnreachableLLM {
+        async fn complete(&self, _messages: Vec<Message>) -> Result<String> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            anyhow::bail!("generator should not be called")
+        }
+    }
+
+    /// Embeds every text as the same vector.
+    struct FixedEmbedding(Vec<f32>);
+
+    #[async_trait::async_trait]
+    impl EmbeddingProvider for FixedEmbedding {
+        async fn embed(&self, _text: &str) -> Result<Vec<f32>> {
+            Ok(self.0.clone())
+        }
+
+        async fn embed_batch(&self, texts: &[String]) -> Result<Vec<Vec<f32>>> {
+            Ok(texts.iter().map(|_| self.0.clone()).collect())
+        }
+    }
 
     #[test]
     fn test_factory_config_default() {
@@ -221,4 +321,42 @@ mod tests {
         assert_eq!(config.dormant_reactivation_threshold, 0.80);
         assert!(config.cache_generated_definitions);
     }
+
+    #[tokio::test]
+    async fn test_matching_definition_found_in_memory_without_generating() {
+        let storage = Arc::new(InMemoryStore::new());
+        let mut reviewer = task_coordinator_definition();
+        reviewer.id = uuid::Uuid::new_v4();
+        reviewer.name = "security-reviewer".to_string();
+        reviewer.source = DefinitionSource::UserCustom;
+        reviewer.tuning_embedding = vec![1.0, 0.0, 0.0];
+        storage.create_definition(&reviewer).await.unwrap();
+
+        let llm = Arc::new(UnreachableLLM::default());
+        let factory = AgentFactory::new(
+            storage.clone(),
+            llm.clone(),
+            Arc::new(FixedEmbedding(vec![0.9, 0.1, 0.0])),
+            FactoryConfig::default(),
+        );
+        let found = factory
+            .find_or_generate_definition("review this code for injection bugs")
+            .await
+            .unwrap();
+        assert_eq!(found.id, reviewer.id);
+        assert_eq!(llm.calls.load(Ordering::SeqCst), 0);
+
+        // Below the match threshold the factory falls through to the generator.
+        let factory = AgentFactory::new(
+            storage,
+            llm.clone(),
+            Arc::new(FixedEmbedding(vec![0.0, 1.0, 0.0])),
+            FactoryConfig::default(),
+        );
+        assert!(factory
+            .find_or_generate_definition("plan a product launch")
+            .await
+            .is_err());
+        assert!(llm.calls.load(Ordering::SeqCst) > 0);
+    }
 }
@@ -2,6 +2,7 @@ pub mod api;
 pub mod capabilities;
 pub mod config;
 pub mod definitions;
+pub mod doctor;
 pub mod engine;
 pub mod factory;
 pub mod lifecycle;
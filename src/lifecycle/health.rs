@@ -22,6 +22,8 @@ pub enum HealthChangeReason {
     ValidationChallenge,
     InconsistentOutput,
     Recovered,
+    Idle,
+    SuccessfulExecution,
 }
 
 impl HealthTracker {
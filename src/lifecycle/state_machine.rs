@@ -13,6 +13,8 @@ pub enum LifecycleEvent {
     HealthBelowIsolated,
     HealthBelowTerminal,
     HealthRecovered,
+    Paused,
+    Resumed,
     ManualTermination,
 }
 
@@ -27,6 +29,11 @@ impl AgentStateMachine {
             (AgentState::Dormant, LifecycleEvent::Activated) => AgentState::Active,
             (AgentState::Dormant, LifecycleEvent::TTLExpired) => AgentState::Terminated,
 
+            (AgentState::Active | AgentState::Listening, LifecycleEvent::Paused) => {
+                AgentState::Paused
+            }
+            (AgentState::Paused, LifecycleEvent::Resumed) => AgentState::Listening,
+
             (
                 AgentState::Active | AgentState::Listening | AgentState::Dormant,
                 LifecycleEvent::HealthBelowQuarantine,
@@ -189,6 +196,36 @@ mod tests {
         assert_eq!(agent.state, AgentState::Listening);
     }
 
+    #[test]
+    fn test_pause_and_resume() {
+        for from in [AgentState::Active, AgentState::Listening] {
+            let mut agent = create_test_agent();
+            agent.state = from;
+
+            AgentStateMachine::transition(&mut agent, LifecycleEvent::Paused).unwrap();
+            assert_eq!(agent.state, AgentState::Paused);
+            AgentStateMachine::transition(&mut agent, LifecycleEvent::Resumed).unwrap();
+            assert_eq!(agent.state, AgentState::Listening);
+        }
+    }
+
+    #[test]
+    fn test_paused_agent_ignores_other_events() {
+        let mut agent = create_test_agent();
+        agent.state = AgentState::Paused;
+        agent.health = 0.1;
+
+        assert!(AgentStateMachine::transition(&mut agent, LifecycleEvent::Activated).is_err());
+        assert!(AgentStateMachine::transition(&mut agent, LifecycleEvent::IdleTimeout).is_err());
+        AgentStateMachine::check_health_thresholds(&mut agent).unwrap();
+        assert_eq!(agent.state, AgentState::Paused);
+
+        let mut agent = create_test_agent();
+        agent.state = AgentState::Dormant;
+        assert!(AgentStateMachine::transition(&mut agent, LifecycleEvent::Paused).is_err());
+        assert!(AgentStateMachine::transition(&mut agent, LifecycleEvent::Resumed).is_err());
+    }
+
     #[test]
     fn test_invalid_transition() {
         let mut agent = create_test_agent();
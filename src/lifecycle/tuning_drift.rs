@@ -1,6 +1,56 @@
 use serde::{Deserialize, Serialize};
 use std::collections::VecDeque;
 
+use crate::types::ContextItem;
+
+/// Fraction of the way toward its knowledge a parent's tuning moves each
+/// time a child reports back.
+pub const KNOWLEDGE_DRIFT_STEP: f32 = 0.1;
+/// Upper bound on any single drift step.
+pub const MAX_DRIFT_STEP: f32 = 0.5;
+/// How far, as the distance between unit vectors, a tuning may drift from
+/// its purpose in total. 0.5 keeps a cosine similarity of 0.875.
+pub const MAX_TOTAL_DRIFT: f32 = 0.5;
+
+/// Pull `drifted` back toward `purpose` so the two unit vectors are at most
+/// `max_distance` apart, keeping the direction it drifted in. Tunings of
+/// another dimension are returned unchanged.
+pub fn cap_drift(purpose: &[f32], drifted: Vec<f32>, max_distance: f32) -> Vec<f32> {
+    let unit = |v: &[f32]| {
+        let norm = v.iter().map(|x| x * x).sum::<f32>().sqrt();
+        (norm > 0.0).then(|| v.iter().map(|x| x / norm).collect::<Vec<f32>>())
+    };
+    if purpose.len() != drifted.len() {
+        return drifted;
+    }
+    let (Some(a), Some(b)) = (unit(purpose), unit(&drifted)) else {
+        return drifted;
+    };
+
+    let distance = a
+        .iter()
+        .zip(&b)
+        .map(|(x, y)| (x - y).powi(2))
+        .sum::<f32>()
+        .sqrt();
+    if distance <= max_distance {
+        return drifted;
+    }
+
+    // Rotate from the purpose toward the drift, stopping at the largest
+    // allowed angle: the chord `max_distance` spans 2 * asin(max / 2).
+    let cos_ab = a.iter().zip(&b).map(|(x, y)| x * y).sum::<f32>();
+    let orthogonal: Vec<f32> = b.iter().zip(&a).map(|(y, x)| y - cos_ab * x).collect();
+    let Some(direction) = unit(&orthogonal) else {
+        return a;
+    };
+    let angle = 2.0 * (max_distance.clamp(0.0, 2.0) / 2.0).asin();
+    a.iter()
+        .zip(&direction)
+        .map(|(x, d)| angle.cos() * x + angle.sin() * d)
+        .collect()
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TuningDriftTracker {
     recent_signals: VecDeque<Vec<f32>>,
@@ -17,6 +67,17 @@ impl TuningDriftTracker {
         }
     }
 
+    /// A tracker over the frequencies of accumulated knowledge, moving
+    /// `step` of the way toward their mean (capped at [`MAX_DRIFT_STEP`]).
+    /// Items that didn't arrive as signals are ignored.
+    pub fn from_knowledge(knowledge: &[ContextItem], step: f32) -> Self {
+        let mut tracker = Self::new(1.0 - step.clamp(0.0, MAX_DRIFT_STEP), knowledge.len());
+        for item in knowledge.iter().filter(|item| !item.frequency.is_empty()) {
+            tracker.record_successful_response(item.frequency.clone());
+        }
+        tracker
+    }
+
     pub fn record_successful_response(&mut self, signal_frequency: Vec<f32>) {
         self.recent_signals.push_back(signal_frequency);
         if self.recent_signals.len() > self.window_size {
@@ -37,6 +98,25 @@ impl TuningDriftTracker {
             .collect()
     }
 
+    /// Like [`Self::compute_drifted_tuning`], rescaled to unit length. The
+    /// tuning is left alone if any recorded frequency has another dimension.
+    pub fn compute_normalized_drift(&self, current_tuning: &[f32]) -> Vec<f32> {
+        if self
+            .recent_signals
+            .iter()
+            .any(|signal| signal.len() != current_tuning.len())
+        {
+            return current_tuning.to_vec();
+        }
+
+        let drifted = self.compute_drifted_tuning(current_tuning);
+        let norm = drifted.iter().map(|v| v * v).sum::<f32>().sqrt();
+        if norm == 0.0 {
+            return current_tuning.to_vec();
+        }
+        drifted.iter().map(|v| v / norm).collect()
+    }
+
     fn average_signals(&self) -> Vec<f32> {
         if self.recent_signals.is_empty() {
             return Vec::new();
@@ -119,6 +199,44 @@ mod tests {
         assert!((drifted[1] - expected_1).abs() < 1e-6);
     }
 
+    #[test]
+    fn test_normalized_drift_toward_knowledge() {
+        let item = |frequency: Vec<f32>| ContextItem {
+            source_agent: uuid::Uuid::new_v4(),
+            content: "finding".to_string(),
+            data: serde_json::json!({}),
+            frequency,
+            amplitude: None,
+        };
+        let knowledge = vec![item(vec![0.0, 1.0]), item(vec![0.0, 1.0]), item(vec![])];
+
+        // The step is capped, so even a huge one only goes halfway.
+        let tracker = TuningDriftTracker::from_knowledge(&knowledge, 5.0);
+        let drifted = tracker.compute_normalized_drift(&[1.0, 0.0]);
+        let expected = 0.5 / 0.5f32.hypot(0.5);
+        assert!((drifted[0] - expected).abs() < 1e-6);
+        assert!((drifted[1] - expected).abs() < 1e-6);
+
+        let unchanged = tracker.compute_normalized_drift(&[1.0, 0.0, 0.0]);
+        assert_eq!(unchanged, vec![1.0, 0.0, 0.0]);
+    }
+
+    #[test]
+    fn test_cap_drift() {
+        let purpose = vec![1.0, 0.0];
+
+        let near = vec![0.9, 0.1];
+        assert_eq!(cap_drift(&purpose, near.clone(), 0.5), near);
+
+        let capped = cap_drift(&purpose, vec![0.0, 1.0], 0.5);
+        let distance = (capped[0] - 1.0).hypot(capped[1]);
+        assert!((distance - 0.5).abs() < 1e-5);
+        assert!(capped[1] > 0.0, "keeps the direction of the drift");
+        assert!((capped[0].hypot(capped[1]) - 1.0).abs() < 1e-5);
+
+        assert_eq!(cap_drift(&purpose, vec![0.0, 1.0, 0.0], 0.5).len(), 3);
+    }
+
     #[test]
     fn test_average_signals() {
         let mut tracker = TuningDriftTracker::new(0.8, 3);
@@ -1,21 +1,32 @@
 use anyhow::{Context, Result};
-use clap::{Parser, Subcommand, ValueEnum};
+use clap::{CommandFactory, Parser, Subcommand, ValueEnum};
+use clap_complete::Shell;
 use std::collections::HashMap;
 use std::sync::Arc;
 use std::time::Duration;
 use uuid::Uuid;
 
-use arachnid::api::{serve, AppState};
+use arachnid::api::{serve, AppState, CorsConfig};
 use arachnid::capabilities::{
     search::SearchCapability, synthesizer::SynthesizerCapability, Capability, Providers,
 };
+use arachnid::config::{ConfigLayer, EmbeddingBackend, CONFIG_FILE};
+use arachnid::definitions::{definition_json_schema, DefinitionSource};
+use arachnid::doctor::{CheckStatus, DoctorReport};
+use arachnid::engine::checkpoint::CheckpointConfig;
 use arachnid::engine::coordination::CoordinationEngine;
+use arachnid::engine::events::EventBus;
+use arachnid::engine::metrics::MetricsRegistry;
+use arachnid::engine::scheduler::{SchedulerConfig, StorageRunner, WebRunner, WebScheduler};
+use arachnid::providers::dry_run::DryRunLog;
 use arachnid::providers::embedding::{EmbeddingProvider, OpenAIEmbeddingProvider};
-use arachnid::providers::llm::{AnthropicProvider, LLMProvider, OpenAIProvider};
+use arachnid::providers::hashing::HashingEmbeddingProvider;
+use arachnid::providers::llm::{AnthropicProvider, LLMProvider, OpenAIProvider, RetryPolicy};
+use arachnid::providers::ollama::{OllamaProvider, DEFAULT_OLLAMA_HOST, DEFAULT_OLLAMA_MODEL};
 use arachnid::providers::search::{BraveSearchProvider, SearchProvider};
 use arachnid::storage::memory::{InMemoryStore, WebStore};
-use arachnid::storage::postgres::PostgresStorage;
-use arachnid::storage::Storage;
+use arachnid::storage::postgres::{PostgresStorage, POSTGRES_EMBEDDING_DIM};
+use arachnid::storage::{Storage, StorageStats, WebRunQueue};
 use arachnid::types::{Agent, CapabilityType, Signal, SignalDirection, Web, WebConfig, WebState};
 use arachnid::Config;
 
@@ -28,6 +39,18 @@ struct Cli {
     #[arg(short, long, global = true)]
     verbose: bool,
 
+    /// Config file to read instead of ./arachnid.toml
+    #[arg(long, global = true)]
+    config: Option<std::path::PathBuf>,
+
+    /// OpenAI-compatible API base URL
+    #[arg(long, global = true)]
+    openai_base_url: Option<String>,
+
+    /// Anthropic API base URL
+    #[arg(long, global = true)]
+    anthropic_base_url: Option<String>,
+
     #[command(subcommand)]
     command: Commands,
 }
@@ -37,7 +60,13 @@ enum Commands {
     /// Run a task and return results
     Run {
         /// The task to execute
-        task: String,
+        #[arg(required_unless_present = "resume")]
+        task: Option<String>,
+
+        /// Continue a web left running by an earlier, interrupted run from its
+        /// last checkpoint (needs DATABASE_URL)
+        #[arg(long, value_name = "WEB_ID", conflicts_with_all = ["task", "max_tokens"])]
+        resume: Option<Uuid>,
 
         /// Watch mode: show live progress
         #[arg(long)]
@@ -50,6 +79,15 @@ enum Commands {
         /// Timeout in seconds
         #[arg(long, default_value = "300")]
         timeout: u64,
+
+        /// Fail the web once its LLM calls have used this many tokens
+        #[arg(long)]
+        max_tokens: Option<u64>,
+
+        /// Use canned provider responses instead of calling any API, then
+        /// list the calls that would have been made
+        #[arg(long, conflicts_with = "resume")]
+        dry_run: bool,
     },
 
     /// Start the HTTP API server
@@ -78,6 +116,13 @@ enum Commands {
         limit: usize,
     },
 
+    /// Show counts of webs, agents and definitions across storage
+    Stats {
+        /// Output format
+        #[arg(long, default_value = "text", value_enum)]
+        output: OutputFormat,
+    },
+
     /// Inspect a web
     Web {
         /// Web ID
@@ -101,6 +146,12 @@ enum Commands {
         signals: bool,
     },
 
+    /// Agent definition tooling
+    Definitions {
+        #[command(subcommand)]
+        action: DefinitionsAction,
+    },
+
     /// Configuration management
     Config {
         #[command(subcommand)]
@@ -113,14 +164,27 @@ enum Commands {
         #[arg(long)]
         status: bool,
 
-        /// Rollback last migration
-        #[arg(long)]
+        /// Roll back the latest applied migration with its .down.sql script
+        #[arg(long, conflicts_with = "status")]
         rollback: bool,
     },
 
     /// Validate configuration
     ValidateConfig,
 
+    /// Check that providers, the database and tool dependencies are reachable
+    Doctor {
+        /// Output format
+        #[arg(long, default_value = "text", value_enum)]
+        output: OutputFormat,
+    },
+
+    /// Print a shell completion script
+    Completions {
+        #[arg(value_enum)]
+        shell: Shell,
+    },
+
     /// Show version information
     Version {
         /// Show detailed version info
@@ -141,12 +205,56 @@ enum WebAction {
     Terminate,
 }
 
+#[derive(Subcommand)]
+enum DefinitionsAction {
+    /// Print the JSON Schema of an agent definition
+    Schema,
+    /// List stored definitions
+    List {
+        /// Only show definitions at or below this health, least healthy first
+        #[arg(long, value_name = "HEALTH")]
+        below_health: Option<f32>,
+
+        /// Filter by source (built_in, user_custom, generated)
+        #[arg(long)]
+        source: Option<String>,
+
+        /// Maximum number of definitions to show, most used first
+        #[arg(long, default_value_t = 50, conflicts_with = "below_health")]
+        limit: usize,
+
+        /// Number of definitions to skip, for the next page
+        #[arg(long, default_value_t = 0, conflicts_with = "below_health")]
+        offset: usize,
+
+        /// Output format
+        #[arg(long, default_value = "text", value_enum)]
+        output: OutputFormat,
+    },
+    /// Permanently delete a generated or custom definition
+    Delete {
+        /// Definition ID
+        id: Uuid,
+    },
+}
+
 #[derive(Subcommand)]
 enum ConfigAction {
     /// Show current configuration
     Show,
     /// Show configuration file path
     Path,
+    /// Write a key to the config file, creating it if needed
+    Set {
+        /// Config key, e.g. max_agents
+        key: String,
+        value: String,
+    },
+    /// Remove a key from the config file
+    Unset {
+        /// Config key, e.g. max_agents
+        key: String,
+    },
 }
 
 #[derive(Clone, Copy, ValueEnum)]
@@ -160,56 +268,106 @@ enum OutputFormat {
 async fn main() -> Result<()> {
     let cli = Cli::parse();
 
+    // Flags form the top layer of the config; see `Config::load`.
+    let overrides = ConfigLayer {
+        openai_base_url: cli.openai_base_url,
+        anthropic_base_url: cli.anthropic_base_url,
+        ..ConfigLayer::default()
+    };
+    let load_config = || Config::load(&overrides, cli.config.as_deref());
+
     match cli.command {
         Commands::Run {
             task,
+            resume,
             watch,
             output,
             timeout,
-        } => run_task(&task, watch, output, timeout, cli.verbose).await?,
-        Commands::Serve { port, host } => run_serve(port, &host).await?,
+            max_tokens,
+            dry_run,
+        } => match (resume, task) {
+            (Some(web_id), _) => {
+                resume_task(&load_config()?, web_id, watch, output, timeout, cli.verbose).await?
+            }
+            (None, Some(task)) => {
+                run_task(
+                    &load_config()?,
+                    &task,
+                    watch,
+                    output,
+                    timeout,
+                    max_tokens,
+                    dry_run.then(DryRunLog::new),
+                    cli.verbose,
+                )
+                .await?
+            }
+            (None, None) => unreachable!("clap requires a task or --resume"),
+        },
+        Commands::Serve { port, host } => {
+            run_serve(port, &host, overrides.clone(), cli.config.clone()).await?
+        }
         Commands::Status {
             detailed,
             state,
             limit,
         } => run_status(detailed, state, limit).await?,
+        Commands::Stats { output } => run_stats(output).await?,
         Commands::Web { id, action } => run_web(id, action).await?,
         Commands::Agent {
             id,
             context,
             signals,
         } => run_agent(id, context, signals).await?,
-        Commands::Config { action } => run_config(action)?,
+        Commands::Definitions { action } => run_definitions(action).await?,
+        Commands::Config { action } => run_config(load_config, cli.config.as_deref(), action)?,
         Commands::Migrate { status, rollback } => run_migrate(status, rollback).await?,
-        Commands::ValidateConfig => run_validate_config()?,
+        Commands::ValidateConfig => run_validate_config(&load_config()?)?,
+        Commands::Doctor { output } => run_doctor(&load_config()?, output).await?,
+        Commands::Completions { shell } => print_completions(shell, &mut std::io::stdout()),
         Commands::Version { detailed } => run_version(detailed)?,
     }
 
     Ok(())
 }
 
-async fn run_task(
-    task: &str,
-    watch: bool,
-    output: OutputFormat,
-    timeout_secs: u64,
-    verbose: bool,
-) -> Result<()> {
-    let config = Config::from_env();
-    let store = Arc::new(InMemoryStore::new());
-
-    let embedding_provider: Option<Box<dyn EmbeddingProvider>> =
-        if let Some(api_key) = config.openai_api_key.clone() {
-            Some(Box::new(OpenAIEmbeddingProvider::new(api_key)))
-        } else {
-            None
+/// LLM, embedding and search providers for the API keys in `config`.
+/// Ollama only handles completions; its chat models don't produce
+/// embeddings of the width the rest of the system expects, so without an
+/// OpenAI key embeddings come from local hashing.
+fn build_providers(config: &Config) -> Providers {
+    let embedding_provider: Box<dyn EmbeddingProvider> =
+        match (config.embedding_backend(), config.openai_api_key.clone()) {
+            (EmbeddingBackend::OpenAI, Some(api_key)) => {
+                let mut provider = OpenAIEmbeddingProvider::new(api_key);
+                if let Some(base_url) = config.openai_base_url.clone() {
+                    provider = provider.with_base_url(base_url);
+                }
+                Box::new(provider)
+            }
+            _ => Box::new(HashingEmbeddingProvider::new()),
         };
 
     let llm_provider: Option<Box<dyn LLMProvider>> =
         if let Some(api_key) = config.anthropic_api_key.clone() {
-            Some(Box::new(AnthropicProvider::new(api_key)))
+            let mut provider = AnthropicProvider::new(api_key)
+                .with_retry(config.llm_max_retries, RetryPolicy::default().base_delay);
+            if let Some(base_url) = config.anthropic_base_url.clone() {
+                provider = provider.with_base_url(base_url);
+            }
+            Some(Box::new(provider))
         } else if let Some(api_key) = config.openai_api_key.clone() {
-            Some(Box::new(OpenAIProvider::new(api_key)))
+            let mut provider = OpenAIProvider::new(api_key)
+                .with_retry(config.llm_max_retries, RetryPolicy::default().base_delay);
+            if let Some(base_url) = config.openai_base_url.clone() {
+                provider = provider.with_base_url(base_url);
+            }
+            Some(Box::new(provider))
+        } else if config.uses_ollama() {
+            Some(Box::new(OllamaProvider::new(
+                config.ollama_host.clone(),
+                config.ollama_model.clone(),
+            )))
         } else {
             None
         };
@@ -221,23 +379,46 @@ async fn run_task(
             None
         };
 
-    let providers = Providers {
-        embedding: embedding_provider,
+    Providers {
+        embedding: Some(embedding_provider),
         llm: llm_provider,
         search: search_provider,
-    };
+    }
+}
 
+fn default_capabilities() -> HashMap<CapabilityType, Box<dyn Capability>> {
     let mut capabilities: HashMap<CapabilityType, Box<dyn Capability>> = HashMap::new();
     capabilities.insert(CapabilityType::Search, Box::new(SearchCapability::new()));
     capabilities.insert(
         CapabilityType::Synthesizer,
         Box::new(SynthesizerCapability::new()),
     );
+    capabilities
+}
+
+#[allow(clippy::too_many_arguments)]
+async fn run_task(
+    config: &Config,
+    task: &str,
+    watch: bool,
+    output: OutputFormat,
+    timeout_secs: u64,
+    max_tokens: Option<u64>,
+    dry_run: Option<DryRunLog>,
+    verbose: bool,
+) -> Result<()> {
+    let store = Arc::new(InMemoryStore::new());
+
+    let providers = match &dry_run {
+        Some(log) => log.providers(),
+        None => build_providers(config),
+    };
+    let capabilities = default_capabilities();
 
     let task_embedding = if let Some(provider) = &providers.embedding {
         provider.embed(task).await?
     } else {
-        vec![1.0; 1536]
+        HashingEmbeddingProvider::new().embed_text(task)
     };
 
     let web_id = uuid::Uuid::new_v4();
@@ -255,7 +436,14 @@ async fn run_task(
         root_agent: root_agent.id,
         task: task.to_string(),
         state: arachnid::types::WebState::Running,
-        config: WebConfig::default(),
+        config: WebConfig {
+            token_budget: max_tokens,
+            embedding_dim: task_embedding.len(),
+            ..config.web_config()
+        },
+        priority: 0,
+        metadata: HashMap::new(),
+        report: None,
     };
 
     WebStore::create_web(&*store, web.clone())?;
@@ -273,6 +461,9 @@ async fn run_task(
         OutputFormat::Text => {
             println!("Starting web {} for task: {}", web.id, task);
             println!("Root agent: {}", root_agent.id);
+            if dry_run.is_some() {
+                println!("Dry run: providers return canned responses");
+            }
         }
         OutputFormat::Json => {
             println!(
@@ -291,23 +482,128 @@ async fn run_task(
     if providers.llm.is_none() {
         print_warning(
             &output,
-            "No LLM provider configured. Set ANTHROPIC_API_KEY or OPENAI_API_KEY",
+            "No LLM provider configured. Set ANTHROPIC_API_KEY, OPENAI_API_KEY or OLLAMA_HOST",
         );
     }
     if providers.search.is_none() {
         print_warning(&output, "No search provider configured. Set BRAVE_API_KEY");
     }
 
-    let engine = CoordinationEngine::new(store.clone(), capabilities, providers);
+    let mut engine = CoordinationEngine::new(store.clone(), capabilities, providers);
+    if let Some(checkpoints) = CheckpointConfig::from_env().filter(|_| dry_run.is_none()) {
+        match std::env::var("DATABASE_URL") {
+            Ok(url) => {
+                engine = engine.with_checkpoints(checkpoint_storage(&url).await?, checkpoints)
+            }
+            Err(_) => print_warning(&output, "Checkpointing needs DATABASE_URL; skipping it"),
+        }
+    }
+
+    let result = drive_web(
+        &engine,
+        &store,
+        &web.id,
+        watch,
+        &output,
+        timeout_secs,
+        verbose,
+    )
+    .await;
+    if let Some(log) = dry_run {
+        print_dry_run_calls(&log, &output);
+    }
+    result
+}
+
+fn print_dry_run_calls(log: &DryRunLog, output: &OutputFormat) {
+    let calls = log.calls();
+    match output {
+        OutputFormat::Text => {
+            println!("\nDry run: {} calls would have been made", calls.len());
+            for call in &calls {
+                println!("  {:<9} {}", call.kind, call.detail);
+            }
+        }
+        OutputFormat::Json => {
+            println!(
+                "{}",
+                serde_json::json!({ "event": "dry_run", "calls": calls })
+            );
+        }
+        OutputFormat::Quiet => {}
+    }
+}
+
+async fn resume_task(
+    config: &Config,
+    web_id: Uuid,
+    watch: bool,
+    output: OutputFormat,
+    timeout_secs: u64,
+    verbose: bool,
+) -> Result<()> {
+    let database_url =
+        std::env::var("DATABASE_URL").context("DATABASE_URL is required to resume a web")?;
+    let store = Arc::new(InMemoryStore::new());
+    let engine = CoordinationEngine::new(
+        store.clone(),
+        default_capabilities(),
+        build_providers(config),
+    )
+    .with_checkpoints(
+        checkpoint_storage(&database_url).await?,
+        CheckpointConfig::from_env().unwrap_or_default(),
+    );
+
+    engine.restore_web(&web_id).await?;
+    match output {
+        OutputFormat::Text => println!("Resuming web {}", web_id),
+        OutputFormat::Json => {
+            println!(
+                "{}",
+                serde_json::json!({ "event": "resumed", "web_id": web_id })
+            );
+        }
+        OutputFormat::Quiet => {}
+    }
+
+    drive_web(
+        &engine,
+        &store,
+        &web_id,
+        watch,
+        &output,
+        timeout_secs,
+        verbose,
+    )
+    .await
+}
+
+async fn checkpoint_storage(database_url: &str) -> Result<Arc<dyn Storage>> {
+    let pg = PostgresStorage::new(database_url)
+        .await
+        .context("Failed to connect to PostgreSQL for checkpoints")?;
+    Ok(Arc::new(pg))
+}
 
+/// Run a web on the in-memory store to the end and report how it went.
+async fn drive_web(
+    engine: &CoordinationEngine<InMemoryStore>,
+    store: &Arc<InMemoryStore>,
+    web_id: &Uuid,
+    watch: bool,
+    output: &OutputFormat,
+    timeout_secs: u64,
+    verbose: bool,
+) -> Result<()> {
     let timeout = Duration::from_secs(timeout_secs);
     let start = std::time::Instant::now();
 
     let coordination_result = tokio::time::timeout(timeout, async {
         if watch {
-            run_with_watch(&engine, &web.id, &store, &output, verbose).await
+            run_with_watch(engine, web_id, store, output, verbose).await
         } else {
-            engine.run_coordination_loop(&web.id).await
+            engine.run_coordination_loop(web_id).await
         }
     })
     .await;
@@ -316,8 +612,8 @@ async fn run_task(
 
     match coordination_result {
         Ok(Ok(())) => {
-            let final_web = WebStore::get_web(&*store, &web.id)?.expect("Web not found");
-            let agents = WebStore::get_agents_by_web(&*store, &web.id)?;
+            let final_web = WebStore::get_web(&**store, web_id)?.expect("Web not found");
+            let agents = WebStore::get_agents_by_web(&**store, web_id)?;
 
             match output {
                 OutputFormat::Text => {
@@ -342,6 +638,16 @@ async fn run_task(
                             }
                         }
                     }
+
+                    if let Some(report) = &final_web.report {
+                        println!(
+                            "\nFinal report (confidence {:.2}):\n{}",
+                            report.confidence, report.summary
+                        );
+                        for point in &report.key_points {
+                            println!("  - {}", point);
+                        }
+                    }
                 }
                 OutputFormat::Json => {
                     let root_knowledge: Vec<String> = agents
@@ -360,11 +666,12 @@ async fn run_task(
                         "{}",
                         serde_json::json!({
                             "event": "completed",
-                            "web_id": web.id,
+                            "web_id": web_id,
                             "state": format!("{:?}", final_web.state),
                             "duration_secs": elapsed.as_secs_f32(),
                             "agent_count": agents.len(),
-                            "output": root_knowledge
+                            "output": root_knowledge,
+                            "report": final_web.report
                         })
                     );
                 }
@@ -390,7 +697,7 @@ async fn run_task(
                         "{}",
                         serde_json::json!({
                             "event": "timeout",
-                            "web_id": web.id,
+                            "web_id": web_id,
                             "timeout_secs": timeout_secs
                         })
                     );
@@ -493,24 +800,157 @@ async fn run_with_watch<S: WebStore>(
     Ok(())
 }
 
-async fn run_serve(port: u16, host: &str) -> Result<()> {
+/// Checkpoint interval for webs served from PostgreSQL when
+/// `ARACHNID_CHECKPOINT_*` isn't set.
+const SERVE_CHECKPOINT_ITERATIONS: u64 = 5;
+
+async fn run_serve(
+    port: u16,
+    host: &str,
+    overrides: ConfigLayer,
+    config_path: Option<std::path::PathBuf>,
+) -> Result<()> {
+    tracing_subscriber::fmt()
+        .with_env_filter(
+            tracing_subscriber::EnvFilter::try_from_default_env()
+                .unwrap_or_else(|_| tracing_subscriber::EnvFilter::new("info")),
+        )
+        .init();
+
     let database_url = std::env::var("DATABASE_URL").ok();
+    let config = Config::load(&overrides, config_path.as_deref())?;
+    let events = EventBus::default();
+    let metrics = MetricsRegistry::new();
+    #[cfg(feature = "otel")]
+    let otlp = arachnid::engine::otel::OtlpMetricsExporter::from_env(&metrics)
+        .context("Failed to set up OTLP metrics export")?;
+    #[cfg(not(feature = "otel"))]
+    if std::env::var_os("OTEL_EXPORTER_OTLP_ENDPOINT").is_some() {
+        println!("OTEL_EXPORTER_OTLP_ENDPOINT is ignored: built without the `otel` feature");
+    }
 
-    let storage: Arc<dyn Storage> = if let Some(url) = database_url {
-        println!("Connecting to PostgreSQL...");
-        let pg = PostgresStorage::new(&url)
-            .await
-            .context("Failed to connect to PostgreSQL")?;
-        Arc::new(pg)
-    } else {
-        println!("No DATABASE_URL set, using in-memory storage");
-        Arc::new(InMemoryStore::new())
+    let providers = build_providers(&config);
+    let embedding_dim = providers.embedding_dim();
+    let mut engine = CoordinationEngine::new(
+        Arc::new(InMemoryStore::new()),
+        default_capabilities(),
+        providers,
+    )
+    .with_observer(Arc::new(events.clone()))
+    .with_metrics(metrics.clone());
+    let pg: Option<Arc<dyn Storage>> = match database_url {
+        Some(url) => {
+            if embedding_dim != POSTGRES_EMBEDDING_DIM {
+                anyhow::bail!(
+                    "The embedding provider returns {}-dimensional vectors; PostgreSQL storage needs {}",
+                    embedding_dim,
+                    POSTGRES_EMBEDDING_DIM
+                );
+            }
+            println!("Connecting to PostgreSQL...");
+            let pg: Arc<dyn Storage> = Arc::new(
+                PostgresStorage::new(&url)
+                    .await
+                    .context("Failed to connect to PostgreSQL")?,
+            );
+            // Checkpoint and write through as it goes, so the API sees
+            // running webs and a crashed server loses little.
+            let checkpoints = CheckpointConfig::from_env().unwrap_or(CheckpointConfig {
+                every_iterations: Some(SERVE_CHECKPOINT_ITERATIONS),
+                every: None,
+            });
+            engine = engine
+                .with_checkpoints(pg.clone(), checkpoints)
+                .with_write_through();
+            Some(pg)
+        }
+        None => {
+            println!("No DATABASE_URL set, using in-memory storage");
+            None
+        }
+    };
+    let engine = Arc::new(engine);
+    // With Postgres, webs live there and each run works on an in-memory copy.
+    let (storage, runner): (Arc<dyn Storage>, Arc<dyn WebRunner>) = match pg {
+        Some(pg) => (pg.clone(), Arc::new(StorageRunner::new(engine.clone(), pg))),
+        None => (engine.store().clone(), engine.clone()),
     };
 
-    let state = AppState { storage };
+    let queue = WebRunQueue::from_env(storage.clone(), format!("serve-{}", Uuid::new_v4()));
+    let scheduler = WebScheduler::new(storage.clone(), runner)
+        .with_config(SchedulerConfig::from_env())
+        .with_queue(queue);
+    let resumed = scheduler
+        .resume_running()
+        .await
+        .context("Failed to queue webs left running")?;
+    if resumed > 0 {
+        println!(
+            "Resuming {} web(s) left running by a previous server",
+            resumed
+        );
+    }
+    scheduler.spawn();
+
+    let state = AppState::new(storage)
+        .with_events(events)
+        .with_metrics(metrics)
+        .with_cors(CorsConfig::from_env())
+        .with_config(config, move || {
+            Config::load(&overrides, config_path.as_deref())
+        })
+        // New API keys take effect for webs started after the reload.
+        .with_reload_hook(move |config| engine.set_providers(build_providers(config)))
+        .with_admin_token(std::env::var("ARACHNID_ADMIN_TOKEN").ok())
+        .with_scheduler(scheduler);
 
     println!("Starting Arachnid API server on {}:{}", host, port);
-    serve(state, port).await
+    let result = serve(state, port).await;
+    #[cfg(feature = "otel")]
+    if let Some(otlp) = otlp {
+        otlp.shutdown()?;
+    }
+    result
+}
+
+async fn run_stats(output: OutputFormat) -> Result<()> {
+    let storage: Arc<dyn Storage> = match std::env::var("DATABASE_URL").ok() {
+        Some(url) => Arc::new(
+            PostgresStorage::new(&url)
+                .await
+                .context("Failed to connect to PostgreSQL")?,
+        ),
+        None => {
+            // On stderr, so `--output json` stays parseable.
+            eprintln!("Note: No DATABASE_URL set. Showing empty stats (no persistent storage).");
+            Arc::new(InMemoryStore::new())
+        }
+    };
+
+    let stats = StorageStats::collect(&*storage).await?;
+    match output {
+        OutputFormat::Text => {
+            println!("Webs:");
+            println!("  Running: {}", stats.webs.running);
+            println!("  Converged: {}", stats.webs.converged);
+            println!("  Failed: {}", stats.webs.failed);
+            println!(
+                "Agents: {} total, {} in running webs",
+                stats.agents.total, stats.agents.active
+            );
+            println!("Definitions:");
+            println!("  Built-in: {}", stats.definitions.built_in);
+            println!("  User: {}", stats.definitions.user_custom);
+            println!("  Generated: {}", stats.definitions.generated);
+            match stats.avg_convergence_ms {
+                Some(ms) => println!("Average convergence time: {:.1}s", ms / 1000.0),
+                None => println!("Average convergence time: n/a"),
+            }
+        }
+        OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&stats)?),
+        OutputFormat::Quiet => {}
+    }
+    Ok(())
 }
 
 async fn run_status(detailed: bool, state_filter: Option<String>, limit: usize) -> Result<()> {
@@ -748,52 +1188,215 @@ async fn run_agent(id: Uuid, show_context: bool, show_signals: bool) -> Result<(
     Ok(())
 }
 
-fn run_config(action: ConfigAction) -> Result<()> {
-    let config = Config::from_env();
+fn print_completions(shell: Shell, out: &mut dyn std::io::Write) {
+    clap_complete::generate(shell, &mut Cli::command(), "arachnid", out);
+}
+
+async fn run_definitions(action: DefinitionsAction) -> Result<()> {
+    match action {
+        DefinitionsAction::Schema => {
+            println!(
+                "{}",
+                serde_json::to_string_pretty(&definition_json_schema())?
+            );
+        }
+        DefinitionsAction::List {
+            below_health,
+            source,
+            limit,
+            offset,
+            output,
+        } => {
+            let source = source
+                .map(|s| match s.to_lowercase().as_str() {
+                    "built_in" => Ok(DefinitionSource::BuiltIn),
+                    "user_custom" => Ok(DefinitionSource::UserCustom),
+                    "generated" => Ok(DefinitionSource::Generated),
+                    other => Err(anyhow::anyhow!("Unknown definition source: {}", other)),
+                })
+                .transpose()?;
+
+            let storage: Arc<dyn Storage> = match std::env::var("DATABASE_URL").ok() {
+                Some(url) => Arc::new(
+                    PostgresStorage::new(&url)
+                        .await
+                        .context("Failed to connect to PostgreSQL")?,
+                ),
+                None => {
+                    eprintln!("Note: No DATABASE_URL set. No stored definitions to list.");
+                    Arc::new(InMemoryStore::new())
+                }
+            };
+            let definitions = match below_health {
+                Some(max_health) => {
+                    storage
+                        .get_definitions_by_health(max_health, source)
+                        .await?
+                }
+                None => {
+                    storage
+                        .list_definitions_paged(source, limit, offset)
+                        .await?
+                }
+            };
+
+            match output {
+                OutputFormat::Text => {
+                    if definitions.is_empty() {
+                        println!("No definitions found.");
+                    }
+                    for definition in &definitions {
+                        println!(
+                            "{:<32} {:<12} health {:.2}  used {}",
+                            definition.name,
+                            format!("{:?}", definition.source),
+                            definition.health_score,
+                            definition.use_count
+                        );
+                    }
+                    if below_health.is_none() && definitions.len() == limit {
+                        println!("More may follow: --offset {}", offset + limit);
+                    }
+                }
+                OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&definitions)?),
+                OutputFormat::Quiet => {
+                    for definition in &definitions {
+                        println!("{}", definition.name);
+                    }
+                }
+            }
+        }
+        DefinitionsAction::Delete { id } => {
+            let database_url = std::env::var("DATABASE_URL")
+                .context("DATABASE_URL required to delete definitions")?;
+            let storage = PostgresStorage::new(&database_url)
+                .await
+                .context("Failed to connect to PostgreSQL")?;
+
+            if !storage.delete_definition(id).await? {
+                anyhow::bail!("Definition {} not found", id);
+            }
+            println!("Deleted definition {}", id);
+        }
+    }
+    Ok(())
+}
 
+fn run_config(
+    load_config: impl Fn() -> Result<Config>,
+    config_path: Option<&std::path::Path>,
+    action: ConfigAction,
+) -> Result<()> {
+    let file = config_path.unwrap_or(std::path::Path::new(CONFIG_FILE));
     match action {
         ConfigAction::Show => {
+            let config = &load_config()?;
+            let secret = |value: &Option<String>| value.as_ref().map(|_| "[set]".to_string());
+            let rows = [
+                (
+                    "Anthropic API Key",
+                    "anthropic_api_key",
+                    secret(&config.anthropic_api_key),
+                ),
+                (
+                    "OpenAI API Key",
+                    "openai_api_key",
+                    secret(&config.openai_api_key),
+                ),
+                (
+                    "Brave API Key",
+                    "brave_api_key",
+                    secret(&config.brave_api_key),
+                ),
+                (
+                    "Anthropic Base URL",
+                    "anthropic_base_url",
+                    config.anthropic_base_url.clone(),
+                ),
+                (
+                    "OpenAI Base URL",
+                    "openai_base_url",
+                    config.openai_base_url.clone(),
+                ),
+                ("Ollama Host", "ollama_host", config.ollama_host.clone()),
+                ("Ollama Model", "ollama_model", config.ollama_model.clone()),
+                (
+                    "Embedding",
+                    "embedding",
+                    config.embedding.map(|e| e.as_str().to_string()),
+                ),
+                (
+                    "LLM Max Retries",
+                    "llm_max_retries",
+                    Some(config.llm_max_retries.to_string()),
+                ),
+                (
+                    "Default Threshold",
+                    "default_threshold",
+                    Some(config.default_threshold.to_string()),
+                ),
+                (
+                    "Attenuation Factor",
+                    "attenuation_factor",
+                    Some(config.attenuation_factor.to_string()),
+                ),
+                (
+                    "Max Agents",
+                    "max_agents",
+                    Some(config.max_agents.to_string()),
+                ),
+            ];
+
             println!("Current Configuration:");
             println!("{:-<40}", "");
-            println!(
-                "Anthropic API Key: {}",
-                if config.anthropic_api_key.is_some() {
-                    "[set]"
-                } else {
-                    "[not set]"
+            for (label, field, value) in rows {
+                match (value, config.source(field)) {
+                    (Some(value), Some(source)) => {
+                        println!("{}: {} ({})", label, value, source.as_str())
+                    }
+                    _ => println!("{}: [not set]", label),
                 }
-            );
+            }
             println!(
-                "OpenAI API Key: {}",
-                if config.openai_api_key.is_some() {
-                    "[set]"
+                "Database URL: {}",
+                if std::env::var("DATABASE_URL").is_ok() {
+                    "[set] (env)"
                 } else {
                     "[not set]"
                 }
             );
+        }
+        ConfigAction::Path => {
+            println!("Configuration is resolved per field, highest precedence first:");
+            println!("  1. CLI flags (--openai-base-url, --anthropic-base-url)");
+            println!("  2. Environment variables:");
+            println!("       ANTHROPIC_API_KEY, OPENAI_API_KEY, BRAVE_API_KEY,");
+            println!("       ANTHROPIC_BASE_URL, OPENAI_BASE_URL, OLLAMA_HOST, OLLAMA_MODEL,");
             println!(
-                "Brave API Key: {}",
-                if config.brave_api_key.is_some() {
-                    "[set]"
-                } else {
-                    "[not set]"
-                }
+                "       ARACHNID_EMBEDDING, ARACHNID_LLM_MAX_RETRIES, ARACHNID_DEFAULT_THRESHOLD,"
             );
+            println!("       ARACHNID_ATTENUATION_FACTOR, ARACHNID_MAX_AGENTS");
+            println!("     API keys may instead be read from a file named by <KEY>_FILE");
+            println!("  3. Config file: {} (or --config <path>)", CONFIG_FILE);
             println!(
-                "Database URL: {}",
-                if std::env::var("DATABASE_URL").is_ok() {
-                    "[set]"
-                } else {
-                    "[not set]"
-                }
+                "     Edit it with `arachnid config set <key> <value>` and `config unset <key>`"
             );
+            println!("  4. Built-in defaults");
+            println!();
+            println!("DATABASE_URL and ARACHNID_ADMIN_TOKEN are read from the environment only.");
         }
-        ConfigAction::Path => {
-            println!("Configuration is loaded from environment variables:");
-            println!("  ANTHROPIC_API_KEY");
-            println!("  OPENAI_API_KEY");
-            println!("  BRAVE_API_KEY");
-            println!("  DATABASE_URL");
+        ConfigAction::Set { key, value } => {
+            ConfigLayer::update_file(file, |layer| layer.set(&key, &value))?;
+            let shown = if ConfigLayer::is_secret(&key) {
+                "[set]"
+            } else {
+                value.as_str()
+            };
+            println!("Set {} = {} in {}", key, shown, file.display());
+        }
+        ConfigAction::Unset { key } => {
+            ConfigLayer::update_file(file, |layer| layer.unset(&key))?;
+            println!("Removed {} from {}", key, file.display());
         }
     }
 
@@ -804,53 +1407,131 @@ async fn run_migrate(status_only: bool, rollback: bool) -> Result<()> {
     let database_url =
         std::env::var("DATABASE_URL").context("DATABASE_URL required for migrations")?;
 
-    if rollback {
-        println!("Rollback is not yet implemented.");
-        println!("To rollback, manually run SQL against your database.");
-        return Ok(());
-    }
-
     println!("Connecting to database...");
     let storage = PostgresStorage::new(&database_url)
         .await
         .context("Failed to connect to PostgreSQL")?;
 
+    if rollback {
+        match storage.rollback_last_migration().await? {
+            Some(migration) => println!("Rolled back {}.", migration.name),
+            None => println!("No applied migrations to roll back."),
+        }
+        return Ok(());
+    }
+
     if status_only {
-        println!("Migration status check...");
-        println!("Available migrations:");
-        println!("  - V001__initial_schema.sql");
-        println!("  - V002__add_validations.sql");
-        println!("  - V010__agent_definitions.sql");
+        let statuses = storage.migration_status().await?;
+        for status in &statuses {
+            let state = match status.applied_at {
+                Some(_) if status.checksum_changed => "CHANGED".to_string(),
+                Some(at) => format!("applied {}", at.format("%Y-%m-%d %H:%M")),
+                None => "pending".to_string(),
+            };
+            println!("  {:<24} {}", state, status.migration.name);
+        }
+        let pending = statuses.iter().filter(|s| s.applied_at.is_none()).count();
         println!();
-        println!("Note: Run without --status to apply migrations.");
+        if pending > 0 {
+            println!("{} pending. Run without --status to apply them.", pending);
+        } else {
+            println!("Database is up to date.");
+        }
         return Ok(());
     }
 
-    println!("Running migrations...");
-    storage.run_migrations().await?;
-    println!("Migrations completed successfully.");
+    let applied = storage.run_migrations().await?;
+    if applied.is_empty() {
+        println!("Database is up to date.");
+    } else {
+        for migration in &applied {
+            println!("Applied {}", migration.name);
+        }
+        println!("Migrations completed successfully.");
+    }
+
+    Ok(())
+}
+
+async fn run_doctor(config: &Config, output: OutputFormat) -> Result<()> {
+    let database_url = std::env::var("DATABASE_URL").ok();
+    let impresario = std::env::var("IMPRESARIO_HOST").ok().map(|host| {
+        let port = std::env::var("IMPRESARIO_PORT")
+            .ok()
+            .and_then(|p| p.parse().ok())
+            .unwrap_or(22);
+        (host, port)
+    });
+
+    let report = DoctorReport::run(
+        &build_providers(config),
+        database_url.as_deref(),
+        impresario
+            .as_ref()
+            .map(|(host, port)| (host.as_str(), *port)),
+    )
+    .await;
+
+    match output {
+        OutputFormat::Text => {
+            for check in &report.checks {
+                let mark = match check.status {
+                    CheckStatus::Pass => "ok",
+                    CheckStatus::Fail => "FAIL",
+                    CheckStatus::Skip => "skip",
+                };
+                println!("[{:>4}] {}: {}", mark, check.name, check.detail);
+            }
+        }
+        OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&report)?),
+        OutputFormat::Quiet => {}
+    }
 
+    if !report.passed() {
+        return Err(anyhow::anyhow!(
+            "{} of {} checks failed",
+            report.failures(),
+            report.checks.len()
+        ));
+    }
     Ok(())
 }
 
-fn run_validate_config() -> Result<()> {
-    let config = Config::from_env();
+fn run_validate_config(config: &Config) -> Result<()> {
     let mut errors: Vec<String> = vec![];
     let mut warnings: Vec<String> = vec![];
 
-    if config.anthropic_api_key.is_none() && config.openai_api_key.is_none() {
+    if config.anthropic_api_key.is_none()
+        && config.openai_api_key.is_none()
+        && !config.uses_ollama()
+    {
         errors.push(
-            "No LLM provider configured. Set ANTHROPIC_API_KEY or OPENAI_API_KEY.".to_string(),
+            "No LLM provider configured. Set ANTHROPIC_API_KEY, OPENAI_API_KEY or OLLAMA_HOST."
+                .to_string(),
         );
     }
 
-    if config.openai_api_key.is_none() {
-        warnings.push(
-            "No embedding provider configured (OPENAI_API_KEY). Will use dummy embeddings."
-                .to_string(),
+    if config.uses_ollama() {
+        println!(
+            "Using Ollama at {} with model {}.",
+            config.ollama_host.as_deref().unwrap_or(DEFAULT_OLLAMA_HOST),
+            config
+                .ollama_model
+                .as_deref()
+                .unwrap_or(DEFAULT_OLLAMA_MODEL)
         );
     }
 
+    match config.embedding_backend() {
+        EmbeddingBackend::OpenAI if config.openai_api_key.is_none() => {
+            errors.push("ARACHNID_EMBEDDING=openai needs OPENAI_API_KEY.".to_string())
+        }
+        EmbeddingBackend::Hashing => warnings.push(
+            "Using local hashing embeddings; set OPENAI_API_KEY for semantic matching.".to_string(),
+        ),
+        EmbeddingBackend::OpenAI => {}
+    }
+
     if config.brave_api_key.is_none() {
         warnings.push(
             "No search provider configured (BRAVE_API_KEY). Search capability will not work."
@@ -936,3 +1617,33 @@ fn truncate(s: &str, max_len: usize) -> String {
 fn rustc_version() -> &'static str {
     "stable"
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_completions_for_every_shell() {
+        for shell in Shell::value_variants() {
+            let mut script = Vec::new();
+            print_completions(*shell, &mut script);
+            let script = String::from_utf8(script).unwrap();
+            assert!(script.contains("completions"), "{} script", shell);
+            assert!(script.contains("definitions"), "{} script", shell);
+        }
+    }
+
+    #[test]
+    fn test_ollama_only_keeps_embedding_fallback() {
+        let config = Config {
+            ollama_model: Some("qwen2.5".to_string()),
+            ..Config::default()
+        };
+        let providers = build_providers(&config);
+        assert!(providers.llm.is_some());
+        assert!(providers.embedding.is_some());
+
+        let providers = build_providers(&Config::default());
+        assert!(providers.llm.is_none());
+    }
+}
@@ -0,0 +1,259 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use serde::Serialize;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::sync::{Arc, Mutex};
+
+use super::embedding::EmbeddingProvider;
+use super::llm::{LLMProvider, Message};
+use super::search::{SearchProvider, SearchResult};
+use crate::capabilities::Providers;
+
+/// Dimensions of the stub embeddings. Small, since only their similarity matters.
+pub const DRY_RUN_EMBEDDING_DIM: usize = 64;
+
+/// A call a dry run answered with a canned response instead of making it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct DryRunCall {
+    /// `llm`, `embedding`, `search` or `tool`.
+    pub kind: String,
+    pub detail: String,
+}
+
+/// Shared record of the calls a dry run would have made.
+#[derive(Debug, Clone, Default)]
+pub struct DryRunLog {
+    calls: Arc<Mutex<Vec<DryRunCall>>>,
+}
+
+impl DryRunLog {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn record(&self, kind: &str, detail: impl Into<String>) {
+        self.calls.lock().unwrap().push(DryRunCall {
+            kind: kind.to_string(),
+            detail: detail.into(),
+        });
+    }
+
+    pub fn calls(&self) -> Vec<DryRunCall> {
+        self.calls.lock().unwrap().clone()
+    }
+
+    /// Stub LLM, embedding and search providers that record into this log.
+    pub fn providers(&self) -> Providers {
+        Providers {
+            llm: Some(Box::new(DryRunLLMProvider::new(self.clone()))),
+            embedding: Some(Box::new(DryRunEmbeddingProvider::new(self.clone()))),
+            search: Some(Box::new(DryRunSearchProvider::new(self.clone()))),
+        }
+    }
+}
+
+fn preview(text: &str) -> String {
+    let line = text.lines().find(|l| !l.trim().is_empty()).unwrap_or("");
+    if line.chars().count() > 80 {
+        format!("{}...", line.chars().take(77).collect::<String>())
+    } else {
+        line.to_string()
+    }
+}
+
+/// Answers every completion with the same two unrelated lines, so a
+/// synthesizer asking for subtopics spawns two children.
+pub struct DryRunLLMProvider {
+    log: DryRunLog,
+}
+
+impl DryRunLLMProvider {
+    pub fn new(log: DryRunLog) -> Self {
+        Self { log }
+    }
+}
+
+#[async_trait]
+impl LLMProvider for DryRunLLMProvider {
+    async fn complete(&self, messages: Vec<Message>) -> Result<String> {
+        let prompt = messages.last().map(|m| m.content.as_str()).unwrap_or("");
+        self.log.record(
+            "llm",
+            format!(
+                "complete ({} messages): {}",
+                messages.len(),
+                preview(prompt)
+            ),
+        );
+        Ok("Background and history\nCurrent alternatives compared".to_string())
+    }
+}
+
+/// Hashes words into a small normalized vector. Texts that share words are
+/// similar, which is enough for resonance to behave plausibly.
+pub struct DryRunEmbeddingProvider {
+    log: DryRunLog,
+}
+
+impl DryRunEmbeddingProvider {
+    pub fn new(log: DryRunLog) -> Self {
+        Self { log }
+    }
+
+    fn embed_text(text: &str) -> Vec<f32> {
+        let mut vector = vec![0.0; DRY_RUN_EMBEDDING_DIM];
+        for word in text
+            .split(|c: char| !c.is_alphanumeric())
+            .filter(|w| !w.is_empty())
+        {
+            let mut hasher = DefaultHasher::new();
+            word.to_lowercase().hash(&mut hasher);
+            vector[(hasher.finish() % DRY_RUN_EMBEDDING_DIM as u64) as usize] += 1.0;
+        }
+
+        let norm = vector.iter().map(|x| x * x).sum::<f32>().sqrt();
+        if norm == 0.0 {
+            vector[0] = 1.0;
+        } else {
+            vector.iter_mut().for_each(|x| *x /= norm);
+        }
+        vector
+    }
+}
+
+#[async_trait]
+impl EmbeddingProvider for DryRunEmbeddingProvider {
+    async fn embed(&self, text: &str) -> Result<Vec<f32>> {
+        self.log.record("embedding", preview(text));
+        Ok(Self::embed_text(text))
+    }
+
+    async fn embed_batch(&self, texts: &[String]) -> Result<Vec<Vec<f32>>> {
+        self.log
+            .record("embedding", format!("batch of {} texts", texts.len()));
+        Ok(texts.iter().map(|t| Self::embed_text(t)).collect())
+    }
+
+    fn dimension(&self) -> usize {
+        DRY_RUN_EMBEDDING_DIM
+    }
+}
+
+/// Returns one canned result per query. It shares no words with the query, so
+/// a searching agent doesn't resonate with its own findings.
+pub struct DryRunSearchProvider {
+    log: DryRunLog,
+}
+
+impl DryRunSearchProvider {
+    pub fn new(log: DryRunLog) -> Self {
+        Self { log }
+    }
+}
+
+#[async_trait]
+impl SearchProvider for DryRunSearchProvider {
+    async fn search(&self, query: &str, count: usize) -> Result<Vec<SearchResult>> {
+        self.log
+            .record("search", format!("{} (count {})", preview(query), count));
+        Ok(vec![SearchResult {
+            title: "Dry-run result".to_string(),
+            url: "https://example.invalid/dry-run".to_string(),
+            snippet: "Canned placeholder; no request was sent.".to_string(),
+        }])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::capabilities::search::SearchCapability;
+    use crate::capabilities::synthesizer::SynthesizerCapability;
+    use crate::capabilities::Capability;
+    use crate::engine::coordination::CoordinationEngine;
+    use crate::storage::memory::InMemoryStore;
+    use crate::storage::memory::WebStore;
+    use crate::types::{Agent, CapabilityType, Signal, SignalDirection, Web, WebConfig, WebState};
+    use std::collections::HashMap;
+
+    #[tokio::test]
+    async fn test_dry_run_completes_without_external_calls() {
+        let log = DryRunLog::new();
+        let providers = log.providers();
+        let task = "Compare rust async runtimes";
+        assert_eq!(providers.embedding_dim(), DRY_RUN_EMBEDDING_DIM);
+        let frequency = providers
+            .embedding
+            .as_ref()
+            .unwrap()
+            .embed(task)
+            .await
+            .unwrap();
+
+        let store = Arc::new(InMemoryStore::new());
+        let web_id = uuid::Uuid::new_v4();
+        let root = Agent::new(
+            web_id,
+            None,
+            task.to_string(),
+            frequency.clone(),
+            CapabilityType::Synthesizer,
+            0.6,
+        );
+        let mut web = Web::new(root.id, task.to_string(), WebConfig::default());
+        web.id = web_id;
+        WebStore::create_web(&*store, web).unwrap();
+        WebStore::add_agent(&*store, root.clone()).unwrap();
+        WebStore::add_signal(
+            &*store,
+            Signal::new(
+                root.id,
+                frequency,
+                task.to_string(),
+                SignalDirection::Downward,
+            ),
+        )
+        .unwrap();
+
+        let mut capabilities: HashMap<CapabilityType, Box<dyn Capability>> = HashMap::new();
+        capabilities.insert(CapabilityType::Search, Box::new(SearchCapability::new()));
+        capabilities.insert(
+            CapabilityType::Synthesizer,
+            Box::new(SynthesizerCapability::new()),
+        );
+        let engine = CoordinationEngine::new(store.clone(), capabilities, providers);
+        engine.run_coordination_loop(&web_id).await.unwrap();
+
+        let web = WebStore::get_web(&*store, &web_id).unwrap().unwrap();
+        assert_eq!(web.state, WebState::Converged);
+        let agents = WebStore::get_agents_by_web(&*store, &web_id).unwrap();
+        assert_eq!(agents.len(), 3, "root plus one child per canned subtopic");
+        let root = agents.iter().find(|a| a.is_root()).unwrap();
+        assert_eq!(root.context.accumulated_knowledge.len(), 2);
+
+        let calls = log.calls();
+        let count = |kind: &str| calls.iter().filter(|c| c.kind == kind).count();
+        // Subtopics, then the final report, which falls back to the
+        // knowledge itself since the canned answer isn't a report.
+        assert_eq!(count("llm"), 2);
+        assert_eq!(web.report.unwrap().key_points.len(), 2);
+        assert_eq!(count("search"), 2);
+        assert!(count("embedding") > 0);
+    }
+
+    #[test]
+    fn test_stub_embeddings_are_deterministic_and_normalized() {
+        let a = DryRunEmbeddingProvider::embed_text("rust async runtimes");
+        assert_eq!(
+            a,
+            DryRunEmbeddingProvider::embed_text("Rust async runtimes")
+        );
+        let norm = a.iter().map(|x| x * x).sum::<f32>().sqrt();
+        assert!((norm - 1.0).abs() < 1e-5);
+        assert_ne!(
+            a,
+            DryRunEmbeddingProvider::embed_text("python web frameworks")
+        );
+    }
+}
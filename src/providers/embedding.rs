@@ -2,16 +2,31 @@ use anyhow::Result;
 use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
 
+/// Width of `text-embedding-3-small`, and of the pgvector columns.
+pub const DEFAULT_EMBEDDING_DIM: usize = 1536;
+
 #[async_trait]
 pub trait EmbeddingProvider: Send + Sync {
     async fn embed(&self, text: &str) -> Result<Vec<f32>>;
     async fn embed_batch(&self, texts: &[String]) -> Result<Vec<Vec<f32>>>;
+
+    /// Length of the vectors this provider returns.
+    fn dimension(&self) -> usize {
+        DEFAULT_EMBEDDING_DIM
+    }
+
+    /// Check the provider is reachable and accepts our credentials. Defaults
+    /// to embedding a single word.
+    async fn health_check(&self) -> Result<()> {
+        self.embed("ping").await.map(|_| ())
+    }
 }
 
 #[derive(Debug, Clone)]
 pub struct OpenAIEmbeddingProvider {
     api_key: String,
     model: String,
+    base_url: String,
     client: reqwest::Client,
 }
 
@@ -36,6 +51,7 @@ impl OpenAIEmbeddingProvider {
         Self {
             api_key,
             model: "text-embedding-3-small".to_string(),
+            base_url: super::llm::OPENAI_BASE_URL.to_string(),
             client: reqwest::Client::new(),
         }
     }
@@ -44,10 +60,24 @@ impl OpenAIEmbeddingProvider {
         self.model = model;
         self
     }
+
+    /// Send requests to a compatible gateway instead of the official API.
+    /// The URL may end in `/v1` or not.
+    pub fn with_base_url(mut self, base_url: String) -> Self {
+        self.base_url = super::llm::api_root(&base_url);
+        self
+    }
 }
 
 #[async_trait]
 impl EmbeddingProvider for OpenAIEmbeddingProvider {
+    fn dimension(&self) -> usize {
+        match self.model.as_str() {
+            "text-embedding-3-large" => 3072,
+            _ => DEFAULT_EMBEDDING_DIM,
+        }
+    }
+
     async fn embed(&self, text: &str) -> Result<Vec<f32>> {
         let mut embeddings = self.embed_batch(&[text.to_string()]).await?;
         embeddings
@@ -63,7 +93,7 @@ impl EmbeddingProvider for OpenAIEmbeddingProvider {
 
         let response = self
             .client
-            .post("https://api.openai.com/v1/embeddings")
+            .post(format!("{}/v1/embeddings", self.base_url))
             .header("Authorization", format!("Bearer {}", self.api_key))
             .header("Content-Type", "application/json")
             .json(&request)
@@ -97,4 +127,17 @@ mod tests {
             .with_model("text-embedding-3-large".to_string());
         assert_eq!(provider.model, "text-embedding-3-large");
     }
+
+    #[test]
+    fn test_base_url_with_version() {
+        for base_url in [
+            "http://localhost:8000/v1",
+            "http://localhost:8000/v1/",
+            "http://localhost:8000",
+        ] {
+            let provider = OpenAIEmbeddingProvider::new("test-key".to_string())
+                .with_base_url(base_url.to_string());
+            assert_eq!(provider.base_url, "http://localhost:8000");
+        }
+    }
 }
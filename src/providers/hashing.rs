@@ -0,0 +1,130 @@
+use anyhow::Result;
+use async_trait::async_trait;
+
+use super::embedding::{EmbeddingProvider, DEFAULT_EMBEDDING_DIM};
+
+pub const HASHING_EMBEDDING_DIM: usize = DEFAULT_EMBEDDING_DIM;
+
+/// Embeds text offline by hashing its words and adjacent word pairs into a
+/// fixed number of buckets, then normalizing. Texts sharing vocabulary are
+/// similar and unrelated texts are near orthogonal; there is no notion of
+/// synonyms, so it is meant for offline use and tests rather than real runs.
+#[derive(Debug, Clone)]
+pub struct HashingEmbeddingProvider {
+    dimensions: usize,
+}
+
+impl HashingEmbeddingProvider {
+    pub fn new() -> Self {
+        Self {
+            dimensions: HASHING_EMBEDDING_DIM,
+        }
+    }
+
+    pub fn with_dimensions(mut self, dimensions: usize) -> Self {
+        self.dimensions = dimensions.max(1);
+        self
+    }
+
+    pub fn embed_text(&self, text: &str) -> Vec<f32> {
+        let words: Vec<String> = text
+            .split(|c: char| !c.is_alphanumeric())
+            .filter(|w| !w.is_empty())
+            .map(str::to_lowercase)
+            .collect();
+
+        let mut vector = vec![0.0f32; self.dimensions];
+        let unigrams = words.iter().map(|w| fnv1a(&[w]));
+        let bigrams = words.windows(2).map(|pair| fnv1a(&[&pair[0], &pair[1]]));
+        for (hash, weight) in unigrams.map(|h| (h, 1.0)).chain(bigrams.map(|h| (h, 0.5))) {
+            // The top bit picks a sign so colliding n-grams tend to cancel
+            // rather than pile up in one bucket.
+            let sign = if hash >> 63 == 0 { 1.0 } else { -1.0 };
+            vector[(hash % self.dimensions as u64) as usize] += sign * weight;
+        }
+
+        let norm = vector.iter().map(|x| x * x).sum::<f32>().sqrt();
+        if norm == 0.0 {
+            vector[0] = 1.0;
+        } else {
+            vector.iter_mut().for_each(|x| *x /= norm);
+        }
+        vector
+    }
+}
+
+impl Default for HashingEmbeddingProvider {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// FNV-1a over the words joined by a space. Unlike `DefaultHasher` it is
+/// stable across Rust releases, so stored embeddings stay comparable.
+fn fnv1a(words: &[&String]) -> u64 {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for (i, word) in words.iter().enumerate() {
+        let separator: &[u8] = if i == 0 { b"" } else { b" " };
+        for byte in separator.iter().chain(word.as_bytes()) {
+            hash ^= u64::from(*byte);
+            hash = hash.wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+    hash
+}
+
+#[async_trait]
+impl EmbeddingProvider for HashingEmbeddingProvider {
+    async fn embed(&self, text: &str) -> Result<Vec<f32>> {
+        Ok(self.embed_text(text))
+    }
+
+    async fn embed_batch(&self, texts: &[String]) -> Result<Vec<Vec<f32>>> {
+        Ok(texts.iter().map(|t| self.embed_text(t)).collect())
+    }
+
+    fn dimension(&self) -> usize {
+        self.dimensions
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::resonance::cosine_similarity;
+
+    #[tokio::test]
+    async fn test_hashing_embeddings_are_deterministic_and_normalized() {
+        let provider = HashingEmbeddingProvider::new();
+        let a = provider
+            .embed("Quantum error correction codes")
+            .await
+            .unwrap();
+        let b = provider
+            .embed("quantum ERROR correction, codes")
+            .await
+            .unwrap();
+
+        assert_eq!(a.len(), HASHING_EMBEDDING_DIM);
+        assert_eq!(a, b);
+        let norm = a.iter().map(|x| x * x).sum::<f32>().sqrt();
+        assert!((norm - 1.0).abs() < 1e-5);
+
+        let small = HashingEmbeddingProvider::new().with_dimensions(8);
+        assert_eq!(small.embed_text("").len(), 8);
+        assert_eq!(small.embed_text("")[0], 1.0);
+    }
+
+    #[test]
+    fn test_shared_words_are_more_similar() {
+        let provider = HashingEmbeddingProvider::new();
+        let task = provider.embed_text("survey quantum error correction");
+        let related = provider.embed_text("surface codes for quantum error correction");
+        let unrelated = provider.embed_text("sourdough bread baking schedule");
+
+        let related_similarity = cosine_similarity(&task, &related);
+        let unrelated_similarity = cosine_similarity(&task, &unrelated);
+        assert!(related_similarity > 0.5, "{}", related_similarity);
+        assert!(unrelated_similarity.abs() < 0.2, "{}", unrelated_similarity);
+    }
+}
@@ -1,6 +1,11 @@
-use anyhow::Result;
+use anyhow::{Context, Result};
 use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::hash::{BuildHasher, Hasher};
+use std::time::Duration;
+
+use super::usage::Usage;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Message {
@@ -31,16 +36,198 @@ impl Message {
     }
 }
 
+/// A call the model made through the provider's native tool API.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NativeToolCall {
+    pub name: String,
+    pub arguments: Value,
+}
+
+/// A completion that may have asked for tools.
+#[derive(Debug, Clone, Default)]
+pub struct ToolCompletion {
+    pub text: String,
+    pub tool_calls: Vec<NativeToolCall>,
+    pub usage: Usage,
+}
+
+pub const ANTHROPIC_BASE_URL: &str = "https://api.anthropic.com";
+pub const OPENAI_BASE_URL: &str = "https://api.openai.com";
+
+/// A base URL without a trailing slash or `/v1`, which requests add
+/// themselves. Gateways are usually configured with the version included
+/// (`OPENAI_BASE_URL=http://localhost:8000/v1`), as the official SDKs expect.
+pub(crate) fn api_root(base_url: &str) -> String {
+    let base_url = base_url.trim_end_matches('/');
+    base_url
+        .strip_suffix("/v1")
+        .unwrap_or(base_url)
+        .trim_end_matches('/')
+        .to_string()
+}
+
 #[async_trait]
 pub trait LLMProvider: Send + Sync {
     async fn complete(&self, messages: Vec<Message>) -> Result<String>;
+
+    /// Complete and report the tokens spent. Providers that don't return
+    /// usage get an estimate from the message lengths.
+    async fn complete_with_usage(&self, messages: Vec<Message>) -> Result<(String, Usage)> {
+        let response = self.complete(messages.clone()).await?;
+        let usage = Usage::estimate(&messages, &response);
+        Ok((response, usage))
+    }
+
+    /// `complete_with_usage` with each response capped at `max_tokens`.
+    /// Providers without such a cap ignore it.
+    async fn complete_with_max_tokens(
+        &self,
+        messages: Vec<Message>,
+        _max_tokens: u32,
+    ) -> Result<(String, Usage)> {
+        self.complete_with_usage(messages).await
+    }
+
+    /// Offer `tools`, schemas as built by `ToolRuntime::get_schemas`, through
+    /// the provider's native function calling. `None` when the provider has
+    /// none, leaving the caller to find calls in the text.
+    async fn complete_with_tools(
+        &self,
+        _messages: Vec<Message>,
+        _tools: &[Value],
+        _max_tokens: u32,
+    ) -> Result<Option<ToolCompletion>> {
+        Ok(None)
+    }
+
+    /// Check the provider is reachable and accepts our credentials. Defaults
+    /// to a one-word completion.
+    async fn health_check(&self) -> Result<()> {
+        self.complete(vec![Message::user("ping")]).await.map(|_| ())
+    }
+}
+
+/// How a provider retries rate limits (429) and server errors (5xx). Other
+/// errors fail immediately.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryPolicy {
+    pub max_retries: u32,
+    /// First backoff; doubles per attempt, with jitter, up to `MAX_RETRY_DELAY`.
+    pub base_delay: Duration,
+}
+
+pub const DEFAULT_LLM_MAX_RETRIES: u32 = 3;
+/// Response cap for definitions that don't set their own.
+pub const DEFAULT_MAX_TOKENS: u32 = 4096;
+const MAX_RETRY_DELAY: Duration = Duration::from_secs(30);
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_retries: DEFAULT_LLM_MAX_RETRIES,
+            base_delay: Duration::from_millis(500),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Backoff before retry `attempt` (0-based): somewhere between half and
+    /// all of `base_delay * 2^attempt`, so concurrent agents spread out.
+    fn backoff(&self, attempt: u32) -> Duration {
+        let full = self
+            .base_delay
+            .saturating_mul(2u32.saturating_pow(attempt))
+            .min(MAX_RETRY_DELAY);
+        let random = std::collections::hash_map::RandomState::new()
+            .build_hasher()
+            .finish();
+        full / 2 + full.mul_f64((random % 1000) as f64 / 2000.0)
+    }
+
+    /// Send the request built by `request`, retrying per this policy. Returns
+    /// the first successful response, or the last error.
+    async fn send(
+        &self,
+        provider: &str,
+        request: impl Fn() -> reqwest::RequestBuilder,
+    ) -> Result<reqwest::Response> {
+        let mut attempt = 0;
+        loop {
+            let response = request().send().await?;
+            let status = response.status();
+            if status.is_success() {
+                return Ok(response);
+            }
+
+            let retryable =
+                status == reqwest::StatusCode::TOO_MANY_REQUESTS || status.is_server_error();
+            if !retryable || attempt >= self.max_retries {
+                let body = response.text().await?;
+                anyhow::bail!("{} API error {}: {}", provider, status, body);
+            }
+
+            let delay = retry_after(&response)
+                .map(|d| d.min(MAX_RETRY_DELAY))
+                .unwrap_or_else(|| self.backoff(attempt));
+            tracing::warn!(
+                provider,
+                %status,
+                attempt = attempt + 1,
+                delay_ms = delay.as_millis() as u64,
+                "Retrying LLM request"
+            );
+            tokio::time::sleep(delay).await;
+            attempt += 1;
+        }
+    }
+}
+
+/// The `Retry-After` header of a 429, in seconds. HTTP dates are ignored in
+/// favour of our own backoff.
+fn retry_after(response: &reqwest::Response) -> Option<Duration> {
+    if response.status() != reqwest::StatusCode::TOO_MANY_REQUESTS {
+        return None;
+    }
+    response
+        .headers()
+        .get(reqwest::header::RETRY_AFTER)?
+        .to_str()
+        .ok()?
+        .trim()
+        .parse::<u64>()
+        .ok()
+        .map(Duration::from_secs)
+}
+
+/// List price in dollars per million input/output tokens, for models we know.
+fn model_price(model: &str) -> Option<(f64, f64)> {
+    let prices = [
+        ("claude-3-5-haiku", (0.8, 4.0)),
+        ("claude-3-5-sonnet", (3.0, 15.0)),
+        ("claude-3-opus", (15.0, 75.0)),
+        ("gpt-4o-mini", (0.15, 0.6)),
+        ("gpt-4o", (2.5, 10.0)),
+    ];
+    prices
+        .iter()
+        .find(|(prefix, _)| model.starts_with(prefix))
+        .map(|(_, price)| *price)
+}
+
+fn priced(model: &str, usage: Usage) -> Usage {
+    match model_price(model) {
+        Some((input, output)) => usage.priced(input, output),
+        None => usage,
+    }
 }
 
 #[derive(Debug, Clone)]
 pub struct AnthropicProvider {
     api_key: String,
     model: String,
+    base_url: String,
     client: reqwest::Client,
+    retry: RetryPolicy,
 }
 
 #[derive(Debug, Serialize)]
@@ -49,6 +236,8 @@ struct AnthropicRequest {
     messages: Vec<AnthropicMessage>,
     max_tokens: u32,
     system: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    tools: Vec<Value>,
 }
 
 #[derive(Debug, Serialize)]
@@ -60,11 +249,27 @@ struct AnthropicMessage {
 #[derive(Debug, Deserialize)]
 struct AnthropicResponse {
     content: Vec<AnthropicContent>,
+    #[serde(default)]
+    usage: Option<AnthropicUsage>,
 }
 
+#[derive(Debug, Deserialize)]
+struct AnthropicUsage {
+    input_tokens: u64,
+    output_tokens: u64,
+}
+
+/// A text block, or with `type` "tool_use", a tool call.
 #[derive(Debug, Deserialize)]
 struct AnthropicContent {
+    #[serde(rename = "type", default)]
+    kind: String,
+    #[serde(default)]
     text: String,
+    #[serde(default)]
+    name: String,
+    #[serde(default)]
+    input: Value,
 }
 
 impl AnthropicProvider {
@@ -72,7 +277,9 @@ impl AnthropicProvider {
         Self {
             api_key,
             model: "claude-3-5-sonnet-20240620".to_string(),
+            base_url: ANTHROPIC_BASE_URL.to_string(),
             client: reqwest::Client::new(),
+            retry: RetryPolicy::default(),
         }
     }
 
@@ -80,11 +287,71 @@ impl AnthropicProvider {
         self.model = model;
         self
     }
+
+    /// Send requests to a compatible gateway instead of the official API.
+    /// The URL may end in `/v1` or not.
+    pub fn with_base_url(mut self, base_url: String) -> Self {
+        self.base_url = api_root(&base_url);
+        self
+    }
+
+    pub fn with_retry(mut self, max_retries: u32, base_delay: Duration) -> Self {
+        self.retry = RetryPolicy {
+            max_retries,
+            base_delay,
+        };
+        self
+    }
 }
 
 #[async_trait]
 impl LLMProvider for AnthropicProvider {
     async fn complete(&self, messages: Vec<Message>) -> Result<String> {
+        Ok(self.complete_with_usage(messages).await?.0)
+    }
+
+    async fn complete_with_usage(&self, messages: Vec<Message>) -> Result<(String, Usage)> {
+        self.complete_with_max_tokens(messages, DEFAULT_MAX_TOKENS)
+            .await
+    }
+
+    async fn complete_with_max_tokens(
+        &self,
+        messages: Vec<Message>,
+        max_tokens: u32,
+    ) -> Result<(String, Usage)> {
+        let completion = self.send_messages(messages, max_tokens, Vec::new()).await?;
+        Ok((completion.text, completion.usage))
+    }
+
+    async fn complete_with_tools(
+        &self,
+        messages: Vec<Message>,
+        tools: &[Value],
+        max_tokens: u32,
+    ) -> Result<Option<ToolCompletion>> {
+        let tools = tools
+            .iter()
+            .map(|t| {
+                json!({
+                    "name": t["name"],
+                    "description": t["description"],
+                    "input_schema": t["parameters"],
+                })
+            })
+            .collect();
+        Ok(Some(self.send_messages(messages, max_tokens, tools).await?))
+    }
+}
+
+impl AnthropicProvider {
+    async fn send_messages(
+        &self,
+        messages: Vec<Message>,
+        max_tokens: u32,
+        tools: Vec<Value>,
+    ) -> Result<ToolCompletion> {
+        let estimate_input = messages.clone();
         let system_msg = messages
             .iter()
             .find(|m| m.role == "system")
@@ -102,32 +369,52 @@ impl LLMProvider for AnthropicProvider {
         let request = AnthropicRequest {
             model: self.model.clone(),
             messages: api_messages,
-            max_tokens: 4096,
+            max_tokens,
             system: system_msg,
+            tools,
         };
 
         let response = self
-            .client
-            .post("https://api.anthropic.com/v1/messages")
-            .header("x-api-key", &self.api_key)
-            .header("anthropic-version", "2023-06-01")
-            .header("content-type", "application/json")
-            .json(&request)
-            .send()
+            .retry
+            .send("Anthropic", || {
+                self.client
+                    .post(format!("{}/v1/messages", self.base_url))
+                    .header("x-api-key", &self.api_key)
+                    .header("anthropic-version", "2023-06-01")
+                    .header("content-type", "application/json")
+                    .json(&request)
+            })
             .await?;
 
-        if !response.status().is_success() {
-            let status = response.status();
-            let body = response.text().await?;
-            anyhow::bail!("Anthropic API error {}: {}", status, body);
-        }
-
         let result: AnthropicResponse = response.json().await?;
-        result
+        if result.content.is_empty() {
+            return Err(anyhow::anyhow!("No content in response"));
+        }
+        let (calls, blocks): (Vec<_>, Vec<_>) = result
             .content
-            .first()
-            .map(|c| c.text.clone())
-            .ok_or_else(|| anyhow::anyhow!("No content in response"))
+            .into_iter()
+            .partition(|c| c.kind == "tool_use");
+        let text = blocks
+            .into_iter()
+            .map(|c| c.text)
+            .collect::<Vec<_>>()
+            .join("\n");
+        let tool_calls = calls
+            .into_iter()
+            .map(|c| NativeToolCall {
+                name: c.name,
+                arguments: c.input,
+            })
+            .collect();
+        let usage = match result.usage {
+            Some(u) => Usage::new(u.input_tokens, u.output_tokens),
+            None => Usage::estimate(&estimate_input, &text),
+        };
+        Ok(ToolCompletion {
+            text,
+            tool_calls,
+            usage: priced(&self.model, usage),
+        })
     }
 }
 
@@ -135,7 +422,9 @@ impl LLMProvider for AnthropicProvider {
 pub struct OpenAIProvider {
     api_key: String,
     model: String,
+    base_url: String,
     client: reqwest::Client,
+    retry: RetryPolicy,
 }
 
 #[derive(Debug, Serialize)]
@@ -143,6 +432,8 @@ struct OpenAIRequest {
     model: String,
     messages: Vec<OpenAIMessage>,
     max_tokens: Option<u32>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    tools: Vec<Value>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -154,11 +445,39 @@ struct OpenAIMessage {
 #[derive(Debug, Deserialize)]
 struct OpenAIResponse {
     choices: Vec<OpenAIChoice>,
+    #[serde(default)]
+    usage: Option<OpenAIUsage>,
+}
+
+#[derive(Debug, Deserialize)]
+struct OpenAIUsage {
+    prompt_tokens: u64,
+    completion_tokens: u64,
 }
 
 #[derive(Debug, Deserialize)]
 struct OpenAIChoice {
-    message: OpenAIMessage,
+    message: OpenAIResponseMessage,
+}
+
+/// `content` is null when the model only calls tools.
+#[derive(Debug, Deserialize)]
+struct OpenAIResponseMessage {
+    content: Option<String>,
+    #[serde(default)]
+    tool_calls: Vec<OpenAIToolCall>,
+}
+
+#[derive(Debug, Deserialize)]
+struct OpenAIToolCall {
+    function: OpenAIFunctionCall,
+}
+
+#[derive(Debug, Deserialize)]
+struct OpenAIFunctionCall {
+    name: String,
+    /// JSON, encoded as a string.
+    arguments: String,
 }
 
 impl OpenAIProvider {
@@ -166,7 +485,9 @@ impl OpenAIProvider {
         Self {
             api_key,
             model: "gpt-4o".to_string(),
+            base_url: OPENAI_BASE_URL.to_string(),
             client: reqwest::Client::new(),
+            retry: RetryPolicy::default(),
         }
     }
 
@@ -174,11 +495,65 @@ impl OpenAIProvider {
         self.model = model;
         self
     }
+
+    /// Send requests to a compatible gateway instead of the official API.
+    /// The URL may end in `/v1` or not.
+    pub fn with_base_url(mut self, base_url: String) -> Self {
+        self.base_url = api_root(&base_url);
+        self
+    }
+
+    pub fn with_retry(mut self, max_retries: u32, base_delay: Duration) -> Self {
+        self.retry = RetryPolicy {
+            max_retries,
+            base_delay,
+        };
+        self
+    }
 }
 
 #[async_trait]
 impl LLMProvider for OpenAIProvider {
     async fn complete(&self, messages: Vec<Message>) -> Result<String> {
+        Ok(self.complete_with_usage(messages).await?.0)
+    }
+
+    async fn complete_with_usage(&self, messages: Vec<Message>) -> Result<(String, Usage)> {
+        self.complete_with_max_tokens(messages, DEFAULT_MAX_TOKENS)
+            .await
+    }
+
+    async fn complete_with_max_tokens(
+        &self,
+        messages: Vec<Message>,
+        max_tokens: u32,
+    ) -> Result<(String, Usage)> {
+        let completion = self.send_messages(messages, max_tokens, Vec::new()).await?;
+        Ok((completion.text, completion.usage))
+    }
+
+    async fn complete_with_tools(
+        &self,
+        messages: Vec<Message>,
+        tools: &[Value],
+        max_tokens: u32,
+    ) -> Result<Option<ToolCompletion>> {
+        let tools = tools
+            .iter()
+            .map(|t| json!({ "type": "function", "function": t }))
+            .collect();
+        Ok(Some(self.send_messages(messages, max_tokens, tools).await?))
+    }
+}
+
+impl OpenAIProvider {
+    async fn send_messages(
+        &self,
+        messages: Vec<Message>,
+        max_tokens: u32,
+        tools: Vec<Value>,
+    ) -> Result<ToolCompletion> {
+        let estimate_input = messages.clone();
         let api_messages: Vec<OpenAIMessage> = messages
             .into_iter()
             .map(|m| OpenAIMessage {
@@ -190,30 +565,50 @@ impl LLMProvider for OpenAIProvider {
         let request = OpenAIRequest {
             model: self.model.clone(),
             messages: api_messages,
-            max_tokens: Some(4096),
+            max_tokens: Some(max_tokens),
+            tools,
         };
 
         let response = self
-            .client
-            .post("https://api.openai.com/v1/chat/completions")
-            .header("Authorization", format!("Bearer {}", self.api_key))
-            .header("Content-Type", "application/json")
-            .json(&request)
-            .send()
+            .retry
+            .send("OpenAI", || {
+                self.client
+                    .post(format!("{}/v1/chat/completions", self.base_url))
+                    .header("Authorization", format!("Bearer {}", self.api_key))
+                    .header("Content-Type", "application/json")
+                    .json(&request)
+            })
             .await?;
 
-        if !response.status().is_success() {
-            let status = response.status();
-            let body = response.text().await?;
-            anyhow::bail!("OpenAI API error {}: {}", status, body);
-        }
-
         let result: OpenAIResponse = response.json().await?;
-        result
+        let message = result
             .choices
-            .first()
-            .map(|c| c.message.content.clone())
-            .ok_or_else(|| anyhow::anyhow!("No choices in response"))
+            .into_iter()
+            .next()
+            .map(|c| c.message)
+            .ok_or_else(|| anyhow::anyhow!("No choices in response"))?;
+        let text = message.content.unwrap_or_default();
+        let tool_calls = message
+            .tool_calls
+            .into_iter()
+            .map(|call| {
+                let arguments = serde_json::from_str(&call.function.arguments)
+                    .with_context(|| format!("Invalid arguments for {}", call.function.name))?;
+                Ok(NativeToolCall {
+                    name: call.function.name,
+                    arguments,
+                })
+            })
+            .collect::<Result<_>>()?;
+        let usage = match result.usage {
+            Some(u) => Usage::new(u.prompt_tokens, u.completion_tokens),
+            None => Usage::estimate(&estimate_input, &text),
+        };
+        Ok(ToolCompletion {
+            text,
+            tool_calls,
+            usage: priced(&self.model, usage),
+        })
     }
 }
 
@@ -250,6 +645,17 @@ impl LLMProvider for MockLLMProvider {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use axum::{routing::post, Json, Router};
+    use serde_json::{json, Value};
+
+    async fn mock_server(router: Router) -> String {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            axum::serve(listener, router).await.unwrap();
+        });
+        format!("http://{}/", addr)
+    }
 
     #[test]
     fn test_message_constructors() {
@@ -278,6 +684,160 @@ mod tests {
         assert_eq!(provider.model, "gpt-4o");
     }
 
+    #[tokio::test]
+    async fn test_base_url_override() {
+        let router = Router::new()
+            .route(
+                "/v1/messages",
+                post(|Json(body): Json<Value>| async move {
+                    Json(json!({
+                        "content": [{ "text": format!("anthropic:{}", body["model"]) }],
+                        "usage": { "input_tokens": 1000, "output_tokens": 200 }
+                    }))
+                }),
+            )
+            .route(
+                "/v1/chat/completions",
+                post(|Json(body): Json<Value>| async move {
+                    Json(json!({
+                        "choices": [{ "message": { "role": "assistant", "content": format!("openai:{}", body["model"]) } }]
+                    }))
+                }),
+            );
+        let base_url = mock_server(router).await;
+
+        let anthropic =
+            AnthropicProvider::new("test-key".to_string()).with_base_url(base_url.clone());
+        let (result, usage) = anthropic
+            .complete_with_usage(vec![Message::user("hi")])
+            .await
+            .unwrap();
+        assert_eq!(result, "anthropic:\"claude-3-5-sonnet-20240620\"");
+        assert_eq!(usage.total_tokens(), 1200);
+        assert!((usage.cost_usd - 0.006).abs() < 1e-9);
+
+        let openai = OpenAIProvider::new("test-key".to_string()).with_base_url(base_url.clone());
+        let result = openai.complete(vec![Message::user("hi")]).await.unwrap();
+        assert_eq!(result, "openai:\"gpt-4o\"");
+
+        // The form OPENAI_BASE_URL usually takes, version included.
+        let openai =
+            OpenAIProvider::new("test-key".to_string()).with_base_url(format!("{}v1", base_url));
+        let result = openai.complete(vec![Message::user("hi")]).await.unwrap();
+        assert_eq!(result, "openai:\"gpt-4o\"");
+    }
+
+    #[tokio::test]
+    async fn test_max_tokens_is_sent() {
+        let router = Router::new()
+            .route(
+                "/v1/messages",
+                post(|Json(body): Json<Value>| async move {
+                    Json(json!({ "content": [{ "text": body["max_tokens"].to_string() }] }))
+                }),
+            )
+            .route(
+                "/v1/chat/completions",
+                post(|Json(body): Json<Value>| async move {
+                    Json(json!({
+                        "choices": [{ "message": { "role": "assistant", "content": body["max_tokens"].to_string() } }]
+                    }))
+                }),
+            );
+        let base_url = mock_server(router).await;
+        let messages = vec![Message::user("hi")];
+
+        let anthropic =
+            AnthropicProvider::new("test-key".to_string()).with_base_url(base_url.clone());
+        let result = anthropic.complete(messages.clone()).await.unwrap();
+        assert_eq!(result, DEFAULT_MAX_TOKENS.to_string());
+        let (result, _) = anthropic
+            .complete_with_max_tokens(messages.clone(), 256)
+            .await
+            .unwrap();
+        assert_eq!(result, "256");
+
+        let openai = OpenAIProvider::new("test-key".to_string()).with_base_url(base_url);
+        let (result, _) = openai
+            .complete_with_max_tokens(messages, 8192)
+            .await
+            .unwrap();
+        assert_eq!(result, "8192");
+    }
+
+    #[tokio::test]
+    async fn test_native_tool_calls() {
+        let router = Router::new()
+            .route(
+                "/v1/messages",
+                post(|Json(body): Json<Value>| async move {
+                    assert_eq!(body["tools"][0]["name"], "emit_signal");
+                    assert_eq!(body["tools"][0]["input_schema"]["type"], "object");
+                    Json(json!({
+                        "content": [
+                            { "type": "text", "text": "Reporting back." },
+                            { "type": "tool_use", "id": "toolu_1", "name": "emit_signal",
+                              "input": { "content": "found it" } }
+                        ]
+                    }))
+                }),
+            )
+            .route(
+                "/v1/chat/completions",
+                post(|Json(body): Json<Value>| async move {
+                    assert_eq!(body["tools"][0]["type"], "function");
+                    assert_eq!(body["tools"][0]["function"]["name"], "emit_signal");
+                    Json(json!({
+                        "choices": [{ "message": {
+                            "role": "assistant",
+                            "content": null,
+                            "tool_calls": [{ "id": "call_1", "type": "function", "function": {
+                                "name": "emit_signal",
+                                "arguments": "{\"content\": \"found it\"}"
+                            } }]
+                        } }]
+                    }))
+                }),
+            );
+        let base_url = mock_server(router).await;
+        let tools = vec![json!({
+            "name": "emit_signal",
+            "description": "Send a signal",
+            "parameters": { "type": "object", "properties": { "content": { "type": "string" } } }
+        })];
+        let messages = vec![Message::user("report")];
+        let expected = vec![NativeToolCall {
+            name: "emit_signal".to_string(),
+            arguments: json!({ "content": "found it" }),
+        }];
+
+        let anthropic =
+            AnthropicProvider::new("test-key".to_string()).with_base_url(base_url.clone());
+        let completion = anthropic
+            .complete_with_tools(messages.clone(), &tools, DEFAULT_MAX_TOKENS)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(completion.text, "Reporting back.");
+        assert_eq!(completion.tool_calls, expected);
+
+        let openai = OpenAIProvider::new("test-key".to_string()).with_base_url(base_url);
+        let completion = openai
+            .complete_with_tools(messages.clone(), &tools, DEFAULT_MAX_TOKENS)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(completion.text, "");
+        assert_eq!(completion.tool_calls, expected);
+
+        let mock = MockLLMProvider::new();
+        assert!(mock
+            .complete_with_tools(messages, &tools, DEFAULT_MAX_TOKENS)
+            .await
+            .unwrap()
+            .is_none());
+    }
+
     #[tokio::test]
     async fn test_mock_provider() {
         let provider = MockLLMProvider::new();
@@ -287,4 +847,101 @@ mod tests {
             .unwrap();
         assert!(result.contains("CONFIRM"));
     }
+
+    #[tokio::test]
+    async fn test_retries_rate_limit_then_succeeds() {
+        use axum::http::StatusCode;
+        use axum::response::IntoResponse;
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use std::sync::Arc;
+
+        // 429 with Retry-After, then a 503, then success.
+        let calls = Arc::new(AtomicUsize::new(0));
+        let counter = calls.clone();
+        let router = Router::new().route(
+            "/v1/chat/completions",
+            post(move || {
+                let counter = counter.clone();
+                async move {
+                    match counter.fetch_add(1, Ordering::SeqCst) {
+                        0 => (
+                            StatusCode::TOO_MANY_REQUESTS,
+                            [("retry-after", "0")],
+                            "slow down",
+                        )
+                            .into_response(),
+                        1 => (StatusCode::SERVICE_UNAVAILABLE, "overloaded").into_response(),
+                        _ => Json(json!({
+                            "choices": [{ "message": { "role": "assistant", "content": "ok" } }]
+                        }))
+                        .into_response(),
+                    }
+                }
+            }),
+        );
+        let base_url = mock_server(router).await;
+
+        let provider = OpenAIProvider::new("test-key".to_string())
+            .with_base_url(base_url.clone())
+            .with_retry(3, Duration::from_millis(1));
+        let result = provider.complete(vec![Message::user("hi")]).await.unwrap();
+        assert_eq!(result, "ok");
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+
+        // Out of retries: the last error is returned.
+        calls.store(0, Ordering::SeqCst);
+        let provider = OpenAIProvider::new("test-key".to_string())
+            .with_base_url(base_url)
+            .with_retry(1, Duration::from_millis(1));
+        let err = provider
+            .complete(vec![Message::user("hi")])
+            .await
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("503"), "{}", err);
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_client_errors_are_not_retried() {
+        use axum::http::StatusCode;
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use std::sync::Arc;
+
+        let calls = Arc::new(AtomicUsize::new(0));
+        let counter = calls.clone();
+        let router = Router::new().route(
+            "/v1/messages",
+            post(move || {
+                counter.fetch_add(1, Ordering::SeqCst);
+                async { (StatusCode::UNAUTHORIZED, "bad key") }
+            }),
+        );
+        let base_url = mock_server(router).await;
+
+        let provider = AnthropicProvider::new("test-key".to_string())
+            .with_base_url(base_url)
+            .with_retry(3, Duration::from_millis(1));
+        let err = provider
+            .complete(vec![Message::user("hi")])
+            .await
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("Anthropic API error 401"), "{}", err);
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn test_backoff_grows_with_jitter() {
+        let policy = RetryPolicy {
+            max_retries: 5,
+            base_delay: Duration::from_millis(100),
+        };
+        for attempt in 0..4 {
+            let full = Duration::from_millis(100 * 2u64.pow(attempt));
+            let delay = policy.backoff(attempt);
+            assert!(delay >= full / 2 && delay <= full, "{:?}", delay);
+        }
+        assert!(policy.backoff(20) <= MAX_RETRY_DELAY);
+    }
 }
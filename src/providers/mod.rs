@@ -1,8 +1,14 @@
+pub mod dry_run;
 pub mod embedding;
+pub mod hashing;
 pub mod llm;
 pub mod ollama;
 pub mod search;
+pub mod usage;
 
+pub use dry_run::DryRunLog;
 pub use embedding::EmbeddingProvider;
-pub use llm::{LLMProvider, Message};
+pub use hashing::HashingEmbeddingProvider;
+pub use llm::{LLMProvider, Message, NativeToolCall, ToolCompletion};
 pub use ollama::OllamaProvider;
+pub use usage::{MeteredLLMProvider, Usage, UsageMeter};
@@ -5,6 +5,9 @@ use serde_json::json;
 use crate::providers::embedding::EmbeddingProvider;
 use crate::providers::llm::{LLMProvider, Message};
 
+pub const DEFAULT_OLLAMA_HOST: &str = "http://localhost:11434";
+pub const DEFAULT_OLLAMA_MODEL: &str = "llama3.1";
+
 pub struct OllamaProvider {
     base_url: String,
     model: String,
@@ -14,8 +17,8 @@ pub struct OllamaProvider {
 impl OllamaProvider {
     pub fn new(base_url: Option<String>, model: Option<String>) -> Self {
         Self {
-            base_url: base_url.unwrap_or_else(|| "http://localhost:11434".to_string()),
-            model: model.unwrap_or_else(|| "llama3.1".to_string()),
+            base_url: base_url.unwrap_or_else(|| DEFAULT_OLLAMA_HOST.to_string()),
+            model: model.unwrap_or_else(|| DEFAULT_OLLAMA_MODEL.to_string()),
             client: reqwest::Client::new(),
         }
     }
@@ -12,6 +12,12 @@ pub struct SearchResult {
 #[async_trait]
 pub trait SearchProvider: Send + Sync {
     async fn search(&self, query: &str, count: usize) -> Result<Vec<SearchResult>>;
+
+    /// Check the provider is reachable and accepts our credentials. Defaults
+    /// to a one-result search.
+    async fn health_check(&self) -> Result<()> {
+        self.search("ping", 1).await.map(|_| ())
+    }
 }
 
 #[derive(Debug, Clone)]
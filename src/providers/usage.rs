@@ -0,0 +1,198 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::future::Future;
+use std::ops::AddAssign;
+use std::sync::{Arc, Mutex};
+
+use super::llm::{LLMProvider, Message, ToolCompletion};
+use crate::types::WebId;
+
+/// Tokens (and, when the model's price is known, dollars) spent on LLM calls.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct Usage {
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    pub cost_usd: f64,
+}
+
+impl Usage {
+    pub fn new(input_tokens: u64, output_tokens: u64) -> Self {
+        Self {
+            input_tokens,
+            output_tokens,
+            cost_usd: 0.0,
+        }
+    }
+
+    /// Price the usage at the given dollars per million input/output tokens.
+    pub fn priced(mut self, input_per_million: f64, output_per_million: f64) -> Self {
+        self.cost_usd = (self.input_tokens as f64 * input_per_million
+            + self.output_tokens as f64 * output_per_million)
+            / 1_000_000.0;
+        self
+    }
+
+    /// Rough count (about four characters per token) for providers that
+    /// don't report usage.
+    pub fn estimate(messages: &[Message], response: &str) -> Self {
+        let input_chars: usize = messages.iter().map(|m| m.content.len()).sum();
+        Self::new(
+            input_chars.div_ceil(4) as u64,
+            response.len().div_ceil(4) as u64,
+        )
+    }
+
+    pub fn total_tokens(&self) -> u64 {
+        self.input_tokens + self.output_tokens
+    }
+
+    /// What was spent after `earlier`, a reading of the same running total.
+    pub fn since(&self, earlier: &Usage) -> Self {
+        Self {
+            input_tokens: self.input_tokens.saturating_sub(earlier.input_tokens),
+            output_tokens: self.output_tokens.saturating_sub(earlier.output_tokens),
+            cost_usd: (self.cost_usd - earlier.cost_usd).max(0.0),
+        }
+    }
+}
+
+impl AddAssign for Usage {
+    fn add_assign(&mut self, other: Self) {
+        self.input_tokens += other.input_tokens;
+        self.output_tokens += other.output_tokens;
+        self.cost_usd += other.cost_usd;
+    }
+}
+
+tokio::task_local! {
+    static CURRENT_WEB: WebId;
+}
+
+/// Running usage totals, overall and per web. Calls made inside
+/// [`UsageMeter::scope`] are charged to that web.
+#[derive(Clone, Default)]
+pub struct UsageMeter {
+    total: Arc<Mutex<Usage>>,
+    per_web: Arc<Mutex<HashMap<WebId, Usage>>>,
+}
+
+impl UsageMeter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn record(&self, usage: Usage) {
+        *self.total.lock().unwrap() += usage;
+        if let Ok(web_id) = CURRENT_WEB.try_with(|id| *id) {
+            *self.per_web.lock().unwrap().entry(web_id).or_default() += usage;
+        }
+    }
+
+    /// Run `fut` with every metered call inside it charged to `web_id`.
+    pub async fn scope<F: Future>(&self, web_id: WebId, fut: F) -> F::Output {
+        CURRENT_WEB.scope(web_id, fut).await
+    }
+
+    pub fn total(&self) -> Usage {
+        *self.total.lock().unwrap()
+    }
+
+    pub fn web_usage(&self, web_id: &WebId) -> Usage {
+        self.per_web
+            .lock()
+            .unwrap()
+            .get(web_id)
+            .copied()
+            .unwrap_or_default()
+    }
+}
+
+/// Wraps a provider and records the usage of every call in a [`UsageMeter`].
+pub struct MeteredLLMProvider {
+    inner: Arc<dyn LLMProvider>,
+    meter: UsageMeter,
+}
+
+impl MeteredLLMProvider {
+    pub fn new(inner: Arc<dyn LLMProvider>, meter: UsageMeter) -> Self {
+        Self { inner, meter }
+    }
+}
+
+#[async_trait]
+impl LLMProvider for MeteredLLMProvider {
+    async fn complete(&self, messages: Vec<Message>) -> Result<String> {
+        Ok(self.complete_with_usage(messages).await?.0)
+    }
+
+    async fn complete_with_usage(&self, messages: Vec<Message>) -> Result<(String, Usage)> {
+        let (response, usage) = self.inner.complete_with_usage(messages).await?;
+        self.meter.record(usage);
+        Ok((response, usage))
+    }
+
+    async fn complete_with_max_tokens(
+        &self,
+        messages: Vec<Message>,
+        max_tokens: u32,
+    ) -> Result<(String, Usage)> {
+        let (response, usage) = self
+            .inner
+            .complete_with_max_tokens(messages, max_tokens)
+            .await?;
+        self.meter.record(usage);
+        Ok((response, usage))
+    }
+
+    async fn complete_with_tools(
+        &self,
+        messages: Vec<Message>,
+        tools: &[Value],
+        max_tokens: u32,
+    ) -> Result<Option<ToolCompletion>> {
+        let completion = self
+            .inner
+            .complete_with_tools(messages, tools, max_tokens)
+            .await?;
+        if let Some(completion) = &completion {
+            self.meter.record(completion.usage);
+        }
+        Ok(completion)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_usage_pricing() {
+        let usage = Usage::new(1_000_000, 500_000).priced(3.0, 15.0);
+        assert_eq!(usage.total_tokens(), 1_500_000);
+        assert!((usage.cost_usd - 10.5).abs() < 1e-9);
+
+        let later = Usage::new(1_200_000, 600_000).priced(3.0, 15.0);
+        let delta = later.since(&usage);
+        assert_eq!(delta.input_tokens, 200_000);
+        assert_eq!(delta.output_tokens, 100_000);
+        assert!((delta.cost_usd - 2.1).abs() < 1e-9);
+    }
+
+    #[tokio::test]
+    async fn test_meter_attributes_usage_to_scoped_web() {
+        let meter = UsageMeter::new();
+        let web_id = uuid::Uuid::new_v4();
+
+        meter.record(Usage::new(10, 5));
+        meter
+            .scope(web_id, async { meter.record(Usage::new(100, 50)) })
+            .await;
+
+        assert_eq!(meter.total().total_tokens(), 165);
+        assert_eq!(meter.web_usage(&web_id).total_tokens(), 150);
+        assert_eq!(meter.web_usage(&uuid::Uuid::new_v4()), Usage::default());
+    }
+}
@@ -1,12 +1,20 @@
-use anyhow::Result;
+use anyhow::{Context, Result};
 use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
-use std::sync::{Arc, RwLock};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{mpsc, Arc, RwLock};
+use std::time::Duration;
 
 use crate::definitions::{AgentDefinition, DefinitionId, DefinitionSource};
-use crate::engine::resonance::cosine_similarity;
-use crate::storage::traits::{FailurePattern, Storage};
-use crate::types::{Agent, AgentId, AgentState, Signal, SignalId, Web, WebId, WebState};
+use crate::engine::resonance::{cosine_similarity, cosine_similarity_batch_with_norms};
+use crate::providers::usage::Usage;
+use crate::storage::traits::{
+    truncate_output, ClaimPolicy, FailurePattern, JobId, JobStatus, Storage, ToolExecution,
+    WebAnalytics, WebCheckpoint, WebRunJob,
+};
+use crate::types::{Agent, AgentId, AgentState, Signal, SignalId, Web, WebConfig, WebId, WebState};
 
 // Deprecated WebStore trait - kept for backward compatibility
 // New code should use Storage trait
@@ -26,17 +34,61 @@ pub trait WebStore: Send + Sync {
     fn add_signal(&self, signal: Signal) -> Result<()>;
     fn get_signal(&self, signal_id: &SignalId) -> Result<Option<Signal>>;
     fn get_pending_signals(&self, web_id: &WebId) -> Result<Vec<Signal>>;
+    /// Every signal in the web in the order added, with whether it has
+    /// been processed.
+    fn get_web_signals(&self, web_id: &WebId) -> Result<Vec<(Signal, bool)>>;
     fn mark_signal_processed(&self, signal_id: &SignalId) -> Result<()>;
+
+    fn add_failure_pattern(&self, pattern: FailurePattern) -> Result<()>;
+
+    fn save_web_analytics(&self, analytics: WebAnalytics) -> Result<()>;
+
+    fn record_token_usage(&self, web_id: &WebId, usage: Usage) -> Result<()>;
+}
+
+/// A signal with the order it was added in, which stands in for the
+/// `created_at` Postgres sorts pending signals by.
+#[derive(Clone)]
+struct StoredSignal {
+    seq: u64,
+    signal: Signal,
 }
 
 #[derive(Clone)]
 pub struct InMemoryStore {
     webs: Arc<RwLock<HashMap<WebId, Web>>>,
     agents: Arc<RwLock<HashMap<AgentId, Agent>>>,
-    signals: Arc<RwLock<HashMap<SignalId, Signal>>>,
+    signals: Arc<RwLock<HashMap<SignalId, StoredSignal>>>,
+    next_signal_seq: Arc<AtomicU64>,
     processed_signals: Arc<RwLock<HashMap<SignalId, bool>>>,
     failure_patterns: Arc<RwLock<HashMap<uuid::Uuid, FailurePattern>>>,
     definitions: Arc<RwLock<HashMap<DefinitionId, AgentDefinition>>>,
+    tool_executions: Arc<RwLock<Vec<ToolExecution>>>,
+    jobs: Arc<RwLock<Vec<WebRunJob>>>,
+    checkpoints: Arc<RwLock<HashMap<WebId, WebCheckpoint>>>,
+    analytics: Arc<RwLock<HashMap<WebId, WebAnalytics>>>,
+    token_usage: Arc<RwLock<HashMap<WebId, Usage>>>,
+    /// Wakes the auto-persist thread, if there is one.
+    persist_tx: Option<mpsc::Sender<()>>,
+}
+
+/// What `save_to` writes: the webs, agents, signals and definitions.
+#[derive(Serialize, Deserialize)]
+struct Snapshot {
+    webs: Vec<Web>,
+    agents: Vec<Agent>,
+    /// Oldest first, so loading keeps the pending queue in order.
+    signals: Vec<Signal>,
+    processed_signals: Vec<SignalId>,
+    definitions: Vec<StoredDefinition>,
+}
+
+/// Definitions don't serialize their embedding, so the snapshot carries it.
+#[derive(Serialize, Deserialize)]
+struct StoredDefinition {
+    #[serde(flatten)]
+    definition: AgentDefinition,
+    tuning_embedding: Vec<f32>,
 }
 
 impl InMemoryStore {
@@ -45,10 +97,203 @@ impl InMemoryStore {
             webs: Arc::new(RwLock::new(HashMap::new())),
             agents: Arc::new(RwLock::new(HashMap::new())),
             signals: Arc::new(RwLock::new(HashMap::new())),
+            next_signal_seq: Arc::new(AtomicU64::new(0)),
             processed_signals: Arc::new(RwLock::new(HashMap::new())),
             failure_patterns: Arc::new(RwLock::new(HashMap::new())),
             definitions: Arc::new(RwLock::new(HashMap::new())),
+            tool_executions: Arc::new(RwLock::new(Vec::new())),
+            jobs: Arc::new(RwLock::new(Vec::new())),
+            checkpoints: Arc::new(RwLock::new(HashMap::new())),
+            analytics: Arc::new(RwLock::new(HashMap::new())),
+            token_usage: Arc::new(RwLock::new(HashMap::new())),
+            persist_tx: None,
+        }
+    }
+
+    /// Write the webs, agents, signals and definitions to `path` as JSON.
+    /// Failure patterns, jobs, tool executions, checkpoints, analytics and
+    /// token usage are not saved.
+    pub fn save_to(&self, path: impl AsRef<Path>) -> Result<()> {
+        let path = path.as_ref();
+        let snapshot = {
+            let mut signals: Vec<StoredSignal> =
+                self.signals.read().unwrap().values().cloned().collect();
+            signals.sort_by_key(|s| s.seq);
+            Snapshot {
+                webs: self.webs.read().unwrap().values().cloned().collect(),
+                agents: self.agents.read().unwrap().values().cloned().collect(),
+                signals: signals.into_iter().map(|s| s.signal).collect(),
+                processed_signals: self
+                    .processed_signals
+                    .read()
+                    .unwrap()
+                    .keys()
+                    .copied()
+                    .collect(),
+                definitions: self
+                    .definitions
+                    .read()
+                    .unwrap()
+                    .values()
+                    .map(|d| StoredDefinition {
+                        tuning_embedding: d.tuning_embedding.clone(),
+                        definition: d.clone(),
+                    })
+                    .collect(),
+            }
+        };
+
+        // Write then rename, so a crash mid-write leaves the old file intact.
+        let tmp = path.with_extension("tmp");
+        std::fs::write(&tmp, serde_json::to_vec(&snapshot)?)
+            .with_context(|| format!("Failed to write {}", tmp.display()))?;
+        std::fs::rename(&tmp, path)
+            .with_context(|| format!("Failed to replace {}", path.display()))?;
+        Ok(())
+    }
+
+    /// A store holding what `save_to` wrote to `path`.
+    pub fn load_from(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let bytes =
+            std::fs::read(path).with_context(|| format!("Failed to read {}", path.display()))?;
+        let snapshot: Snapshot = serde_json::from_slice(&bytes)
+            .with_context(|| format!("Failed to parse {}", path.display()))?;
+
+        let store = Self::new();
+        {
+            let mut webs = store.webs.write().unwrap();
+            webs.extend(snapshot.webs.into_iter().map(|w| (w.id, w)));
+            let mut agents = store.agents.write().unwrap();
+            agents.extend(snapshot.agents.into_iter().map(|a| (a.id, a)));
+            let mut processed = store.processed_signals.write().unwrap();
+            processed.extend(snapshot.processed_signals.into_iter().map(|id| (id, true)));
+            let mut definitions = store.definitions.write().unwrap();
+            definitions.extend(snapshot.definitions.into_iter().map(|stored| {
+                let mut definition = stored.definition;
+                definition.tuning_embedding = stored.tuning_embedding;
+                (definition.id, definition)
+            }));
+        }
+        for signal in snapshot.signals {
+            store.insert_signal(signal);
+        }
+        Ok(store)
+    }
+
+    /// Save to `path` after every change to the persisted entities, at most
+    /// once per `debounce`. Saving happens on a background thread, so call
+    /// `save_to` before exiting to be sure the last changes are written.
+    pub fn with_auto_persist(mut self, path: impl Into<PathBuf>, debounce: Duration) -> Self {
+        let path = path.into();
+        let (tx, rx) = mpsc::channel::<()>();
+        let store = Self {
+            persist_tx: None,
+            ..self.clone()
+        };
+
+        std::thread::spawn(move || {
+            // Ends once every store holding the sender is dropped.
+            while rx.recv().is_ok() {
+                std::thread::sleep(debounce);
+                while rx.try_recv().is_ok() {}
+                if let Err(e) = store.save_to(&path) {
+                    tracing::warn!(path = %path.display(), error = %e, "Failed to persist store");
+                }
+            }
+        });
+
+        self.persist_tx = Some(tx);
+        self
+    }
+
+    fn mark_dirty(&self) {
+        if let Some(tx) = &self.persist_tx {
+            let _ = tx.send(());
+        }
+    }
+}
+
+impl InMemoryStore {
+    /// Re-adding a signal replaces it but keeps its place in the queue.
+    fn insert_signal(&self, signal: Signal) {
+        let mut signals = self.signals.write().unwrap();
+        let seq = match signals.get(&signal.id) {
+            Some(stored) => stored.seq,
+            None => self.next_signal_seq.fetch_add(1, Ordering::Relaxed),
+        };
+        signals.insert(signal.id, StoredSignal { seq, signal });
+        self.mark_dirty();
+    }
+
+    /// Drop a web and everything recorded for it, e.g. once a copy kept
+    /// here only to run it has been written back to durable storage.
+    pub fn evict_web(&self, web_id: &WebId) {
+        self.webs.write().unwrap().remove(web_id);
+        let removed: Vec<AgentId> = {
+            let mut agents = self.agents.write().unwrap();
+            let ids: Vec<AgentId> = agents
+                .values()
+                .filter(|a| &a.web_id == web_id)
+                .map(|a| a.id)
+                .collect();
+            for id in &ids {
+                agents.remove(id);
+            }
+            ids
+        };
+
+        {
+            let mut signals = self.signals.write().unwrap();
+            let mut processed = self.processed_signals.write().unwrap();
+            signals.retain(|id, s| {
+                let keep = !removed.contains(&s.signal.origin);
+                if !keep {
+                    processed.remove(id);
+                }
+                keep
+            });
         }
+
+        self.failure_patterns
+            .write()
+            .unwrap()
+            .retain(|_, p| &p.web_id != web_id);
+        self.checkpoints.write().unwrap().remove(web_id);
+        self.analytics.write().unwrap().remove(web_id);
+        self.token_usage.write().unwrap().remove(web_id);
+        self.mark_dirty();
+    }
+
+    /// Unprocessed signals of `web_id`, oldest first like Postgres.
+    fn pending_signals(&self, web_id: &WebId) -> Vec<Signal> {
+        self.web_signals(web_id)
+            .into_iter()
+            .filter(|(_, processed)| !processed)
+            .map(|(signal, _)| signal)
+            .collect()
+    }
+
+    /// Signals of `web_id` in the order added, each with whether it was
+    /// processed.
+    fn web_signals(&self, web_id: &WebId) -> Vec<(Signal, bool)> {
+        let signals = self.signals.read().unwrap();
+        let agents = self.agents.read().unwrap();
+        let processed = self.processed_signals.read().unwrap();
+
+        let mut in_web: Vec<&StoredSignal> = signals
+            .values()
+            .filter(|s| {
+                agents
+                    .get(&s.signal.origin)
+                    .is_some_and(|a| &a.web_id == web_id)
+            })
+            .collect();
+        in_web.sort_by_key(|s| s.seq);
+        in_web
+            .into_iter()
+            .map(|s| (s.signal.clone(), processed.contains_key(&s.signal.id)))
+            .collect()
     }
 }
 
@@ -62,6 +307,7 @@ impl WebStore for InMemoryStore {
     fn create_web(&self, web: Web) -> Result<()> {
         let mut webs = self.webs.write().unwrap();
         webs.insert(web.id, web);
+        self.mark_dirty();
         Ok(())
     }
 
@@ -73,12 +319,14 @@ impl WebStore for InMemoryStore {
     fn update_web(&self, web: Web) -> Result<()> {
         let mut webs = self.webs.write().unwrap();
         webs.insert(web.id, web);
+        self.mark_dirty();
         Ok(())
     }
 
     fn add_agent(&self, agent: Agent) -> Result<()> {
         let mut agents = self.agents.write().unwrap();
         agents.insert(agent.id, agent);
+        self.mark_dirty();
         Ok(())
     }
 
@@ -90,6 +338,7 @@ impl WebStore for InMemoryStore {
     fn update_agent(&self, agent: Agent) -> Result<()> {
         let mut agents = self.agents.write().unwrap();
         agents.insert(agent.id, agent);
+        self.mark_dirty();
         Ok(())
     }
 
@@ -150,39 +399,45 @@ impl WebStore for InMemoryStore {
     }
 
     fn add_signal(&self, signal: Signal) -> Result<()> {
-        let mut signals = self.signals.write().unwrap();
-        signals.insert(signal.id, signal);
+        self.insert_signal(signal);
         Ok(())
     }
 
     fn get_signal(&self, signal_id: &SignalId) -> Result<Option<Signal>> {
         let signals = self.signals.read().unwrap();
-        Ok(signals.get(signal_id).cloned())
+        Ok(signals.get(signal_id).map(|s| s.signal.clone()))
     }
 
     fn get_pending_signals(&self, web_id: &WebId) -> Result<Vec<Signal>> {
-        let signals = self.signals.read().unwrap();
-        let agents = self.agents.read().unwrap();
-        let processed = self.processed_signals.read().unwrap();
+        Ok(self.pending_signals(web_id))
+    }
 
-        Ok(signals
-            .values()
-            .filter(|s| {
-                if processed.contains_key(&s.id) {
-                    return false;
-                }
-                agents
-                    .get(&s.origin)
-                    .map(|a| &a.web_id == web_id)
-                    .unwrap_or(false)
-            })
-            .cloned()
-            .collect())
+    fn get_web_signals(&self, web_id: &WebId) -> Result<Vec<(Signal, bool)>> {
+        Ok(self.web_signals(web_id))
     }
 
     fn mark_signal_processed(&self, signal_id: &SignalId) -> Result<()> {
         let mut processed = self.processed_signals.write().unwrap();
         processed.insert(*signal_id, true);
+        self.mark_dirty();
+        Ok(())
+    }
+
+    fn add_failure_pattern(&self, pattern: FailurePattern) -> Result<()> {
+        let mut patterns = self.failure_patterns.write().unwrap();
+        patterns.insert(pattern.id, pattern);
+        Ok(())
+    }
+
+    fn save_web_analytics(&self, analytics: WebAnalytics) -> Result<()> {
+        let mut stored = self.analytics.write().unwrap();
+        stored.insert(analytics.web_id, analytics);
+        Ok(())
+    }
+
+    fn record_token_usage(&self, web_id: &WebId, usage: Usage) -> Result<()> {
+        let mut stored = self.token_usage.write().unwrap();
+        *stored.entry(*web_id).or_default() += usage;
         Ok(())
     }
 }
@@ -190,9 +445,14 @@ impl WebStore for InMemoryStore {
 // New Storage trait implementation
 #[async_trait]
 impl Storage for InMemoryStore {
+    async fn ping(&self) -> Result<()> {
+        Ok(())
+    }
+
     async fn create_web(&self, web: &Web) -> Result<()> {
         let mut webs = self.webs.write().unwrap();
         webs.insert(web.id, web.clone());
+        self.mark_dirty();
         Ok(())
     }
 
@@ -204,9 +464,22 @@ impl Storage for InMemoryStore {
     async fn update_web(&self, web: &Web) -> Result<()> {
         let mut webs = self.webs.write().unwrap();
         webs.insert(web.id, web.clone());
+        self.mark_dirty();
         Ok(())
     }
 
+    async fn update_web_config(&self, id: WebId, config: &WebConfig) -> Result<bool> {
+        let mut webs = self.webs.write().unwrap();
+        match webs.get_mut(&id) {
+            Some(web) if !web.state.is_terminal() => {
+                web.config = config.clone();
+                self.mark_dirty();
+                Ok(true)
+            }
+            _ => Ok(false),
+        }
+    }
+
     async fn list_webs(&self, state: Option<WebState>) -> Result<Vec<Web>> {
         let webs = self.webs.read().unwrap();
         Ok(webs
@@ -219,6 +492,7 @@ impl Storage for InMemoryStore {
     async fn create_agent(&self, agent: &Agent) -> Result<()> {
         let mut agents = self.agents.write().unwrap();
         agents.insert(agent.id, agent.clone());
+        self.mark_dirty();
         Ok(())
     }
 
@@ -230,6 +504,7 @@ impl Storage for InMemoryStore {
     async fn update_agent(&self, agent: &Agent) -> Result<()> {
         let mut agents = self.agents.write().unwrap();
         agents.insert(agent.id, agent.clone());
+        self.mark_dirty();
         Ok(())
     }
 
@@ -288,17 +563,40 @@ impl Storage for InMemoryStore {
         threshold: f32,
     ) -> Result<Vec<(Agent, f32)>> {
         let agents = self.agents.read().unwrap();
-        let mut results: Vec<(Agent, f32)> = agents
+        let candidates: Vec<&Agent> = agents
             .values()
             .filter(|a| {
                 a.web_id == web_id
-                    && !matches!(a.state, AgentState::Terminated | AgentState::WindingDown)
-            })
-            .map(|a| {
-                let similarity = cosine_similarity(&a.tuning, frequency);
-                (a.clone(), similarity)
+                    && !matches!(
+                        a.state,
+                        AgentState::Terminated | AgentState::WindingDown | AgentState::Paused
+                    )
             })
+            .collect();
+        // Vectors of different lengths can't be compared; say so rather than
+        // scoring them 0.
+        let (candidates, mismatched): (Vec<&Agent>, Vec<&Agent>) = candidates
+            .into_iter()
+            .partition(|a| a.tuning.len() == frequency.len());
+        if !mismatched.is_empty() {
+            tracing::warn!(
+                %web_id,
+                skipped = mismatched.len(),
+                frequency_dim = frequency.len(),
+                "Skipping agents whose tuning dimension differs from the frequency"
+            );
+        }
+        let tunings: Vec<(&[f32], f32)> = candidates
+            .iter()
+            .map(|a| (a.tuning.as_slice(), a.tuning_norm()))
+            .collect();
+        let similarities = cosine_similarity_batch_with_norms(frequency, &tunings);
+
+        let mut results: Vec<(Agent, f32)> = candidates
+            .into_iter()
+            .zip(similarities)
             .filter(|(_, similarity)| *similarity > threshold)
+            .map(|(a, similarity)| (a.clone(), similarity))
             .collect();
 
         results.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal));
@@ -306,32 +604,18 @@ impl Storage for InMemoryStore {
     }
 
     async fn create_signal(&self, signal: &Signal) -> Result<()> {
-        let mut signals = self.signals.write().unwrap();
-        signals.insert(signal.id, signal.clone());
+        self.insert_signal(signal.clone());
         Ok(())
     }
 
     async fn get_pending_signals(&self, web_id: WebId) -> Result<Vec<Signal>> {
-        let signals = self.signals.read().unwrap();
-        let agents = self.agents.read().unwrap();
-        let processed = self.processed_signals.read().unwrap();
-
-        Ok(signals
-            .values()
-            .filter(|s| {
-                !processed.contains_key(&s.id)
-                    && agents
-                        .get(&s.origin)
-                        .map(|a| a.web_id == web_id)
-                        .unwrap_or(false)
-            })
-            .cloned()
-            .collect())
+        Ok(self.pending_signals(&web_id))
     }
 
     async fn mark_signal_processed(&self, id: SignalId) -> Result<()> {
         let mut processed = self.processed_signals.write().unwrap();
         processed.insert(id, true);
+        self.mark_dirty();
         Ok(())
     }
 
@@ -350,9 +634,156 @@ impl Storage for InMemoryStore {
             .collect())
     }
 
+    async fn record_tool_execution(&self, execution: &ToolExecution) -> Result<()> {
+        let mut execution = execution.clone();
+        execution.output = truncate_output(execution.output);
+        let mut executions = self.tool_executions.write().unwrap();
+        executions.push(execution);
+        Ok(())
+    }
+
+    async fn get_tool_executions(&self, agent_id: AgentId) -> Result<Vec<ToolExecution>> {
+        let executions = self.tool_executions.read().unwrap();
+        Ok(executions
+            .iter()
+            .filter(|e| e.agent_id == agent_id)
+            .cloned()
+            .collect())
+    }
+
+    async fn enqueue_job(&self, job: &WebRunJob) -> Result<()> {
+        let mut jobs = self.jobs.write().unwrap();
+        jobs.push(job.clone());
+        Ok(())
+    }
+
+    async fn get_job(&self, id: JobId) -> Result<Option<WebRunJob>> {
+        let jobs = self.jobs.read().unwrap();
+        Ok(jobs.iter().find(|j| j.id == id).cloned())
+    }
+
+    async fn get_web_jobs(&self, web_id: WebId) -> Result<Vec<WebRunJob>> {
+        let jobs = self.jobs.read().unwrap();
+        Ok(jobs
+            .iter()
+            .filter(|j| j.web_id == web_id)
+            .cloned()
+            .collect())
+    }
+
+    async fn claim_job(&self, worker_id: &str, policy: &ClaimPolicy) -> Result<Option<WebRunJob>> {
+        let now = chrono::Utc::now();
+        let mut jobs = self.jobs.write().unwrap();
+
+        let mut claims_by_owner: HashMap<String, usize> = HashMap::new();
+        for job in jobs.iter() {
+            if job.status == JobStatus::Claimed && job.visible_at > now {
+                if let Some(owner) = &job.owner {
+                    *claims_by_owner.entry(owner.clone()).or_default() += 1;
+                }
+            }
+        }
+        let owner_at_cap = |job: &WebRunJob| match (&job.owner, policy.max_claims_per_owner) {
+            (Some(owner), Some(cap)) => claims_by_owner.get(owner).copied().unwrap_or(0) >= cap,
+            _ => false,
+        };
+
+        let Some(job) = jobs
+            .iter_mut()
+            .filter(|j| j.is_claimable(now) && !owner_at_cap(j))
+            .max_by(|a, b| {
+                let a_priority = a.effective_priority(now, policy.aging_interval);
+                let b_priority = b.effective_priority(now, policy.aging_interval);
+                a_priority
+                    .total_cmp(&b_priority)
+                    .then(b.created_at.cmp(&a.created_at))
+            })
+        else {
+            return Ok(None);
+        };
+
+        job.status = JobStatus::Claimed;
+        job.claimed_by = Some(worker_id.to_string());
+        job.attempts += 1;
+        job.visible_at = now + chrono::Duration::from_std(policy.lease)?;
+        Ok(Some(job.clone()))
+    }
+
+    async fn renew_job(
+        &self,
+        id: JobId,
+        worker_id: &str,
+        lease: std::time::Duration,
+    ) -> Result<bool> {
+        let mut jobs = self.jobs.write().unwrap();
+        match jobs.iter_mut().find(|j| j.id == id) {
+            Some(job)
+                if job.status == JobStatus::Claimed
+                    && job.claimed_by.as_deref() == Some(worker_id) =>
+            {
+                job.visible_at = chrono::Utc::now() + chrono::Duration::from_std(lease)?;
+                Ok(true)
+            }
+            _ => Ok(false),
+        }
+    }
+
+    async fn finish_job(
+        &self,
+        id: JobId,
+        worker_id: &str,
+        status: JobStatus,
+        error: Option<&str>,
+    ) -> Result<bool> {
+        let mut jobs = self.jobs.write().unwrap();
+        match jobs.iter_mut().find(|j| j.id == id) {
+            Some(job)
+                if job.status == JobStatus::Claimed
+                    && job.claimed_by.as_deref() == Some(worker_id) =>
+            {
+                job.status = status;
+                job.last_error = error.map(str::to_string);
+                Ok(true)
+            }
+            _ => Ok(false),
+        }
+    }
+
+    async fn save_checkpoint(&self, checkpoint: &WebCheckpoint) -> Result<()> {
+        let mut checkpoints = self.checkpoints.write().unwrap();
+        checkpoints.insert(checkpoint.web.id, checkpoint.clone());
+        Ok(())
+    }
+
+    async fn get_checkpoint(&self, web_id: WebId) -> Result<Option<WebCheckpoint>> {
+        let checkpoints = self.checkpoints.read().unwrap();
+        Ok(checkpoints.get(&web_id).cloned())
+    }
+
+    async fn save_web_analytics(&self, analytics: &WebAnalytics) -> Result<()> {
+        WebStore::save_web_analytics(self, analytics.clone())
+    }
+
+    async fn get_web_analytics(&self, web_id: WebId) -> Result<Option<WebAnalytics>> {
+        let stored = self.analytics.read().unwrap();
+        Ok(stored.get(&web_id).cloned())
+    }
+
+    async fn record_token_usage(&self, web_id: WebId, usage: &Usage) -> Result<()> {
+        WebStore::record_token_usage(self, &web_id, *usage)
+    }
+
+    async fn get_token_usage(&self, web_id: WebId) -> Result<Usage> {
+        let stored = self.token_usage.read().unwrap();
+        Ok(stored.get(&web_id).copied().unwrap_or_default())
+    }
+
     async fn create_definition(&self, definition: &AgentDefinition) -> Result<()> {
+        let mut definition = definition.clone();
+        definition.normalize();
         let mut definitions = self.definitions.write().unwrap();
-        definitions.insert(definition.id, definition.clone());
+        definitions.insert(definition.id, definition);
+        self.mark_dirty();
         Ok(())
     }
 
@@ -363,12 +794,16 @@ impl Storage for InMemoryStore {
 
     async fn get_definition_by_name(&self, name: &str) -> Result<Option<AgentDefinition>> {
         let definitions = self.definitions.read().unwrap();
-        Ok(definitions.values().find(|d| d.name == name).cloned())
+        Ok(definitions
+            .values()
+            .find(|d| d.name == name && !d.is_deleted())
+            .cloned())
     }
 
     async fn update_definition(&self, definition: &AgentDefinition) -> Result<()> {
         let mut definitions = self.definitions.write().unwrap();
         definitions.insert(definition.id, definition.clone());
+        self.mark_dirty();
         Ok(())
     }
 
@@ -379,13 +814,33 @@ impl Storage for InMemoryStore {
         let definitions = self.definitions.read().unwrap();
         let mut result: Vec<AgentDefinition> = definitions
             .values()
-            .filter(|d| source.is_none() || source == Some(d.source))
+            .filter(|d| !d.is_deleted() && (source.is_none() || source == Some(d.source)))
             .cloned()
             .collect();
-        result.sort_by(|a, b| b.use_count.cmp(&a.use_count));
+        result.sort_by_key(|d| std::cmp::Reverse(d.use_count));
         Ok(result)
     }
 
+    async fn list_definitions_paged(
+        &self,
+        source: Option<DefinitionSource>,
+        limit: usize,
+        offset: usize,
+    ) -> Result<Vec<AgentDefinition>> {
+        let definitions = self.definitions.read().unwrap();
+        let mut result: Vec<&AgentDefinition> = definitions
+            .values()
+            .filter(|d| !d.is_deleted() && source.is_none_or(|s| d.source == s))
+            .collect();
+        result.sort_by_key(|d| (std::cmp::Reverse(d.use_count), d.id));
+        Ok(result
+            .into_iter()
+            .skip(offset)
+            .take(limit)
+            .cloned()
+            .collect())
+    }
+
     async fn find_definitions_by_similarity(
         &self,
         embedding: &[f32],
@@ -396,7 +851,9 @@ impl Storage for InMemoryStore {
         let definitions = self.definitions.read().unwrap();
         let mut results: Vec<(AgentDefinition, f32)> = definitions
             .values()
-            .filter(|d| sources.contains(&d.source) && !d.tuning_embedding.is_empty())
+            .filter(|d| {
+                !d.is_deleted() && sources.contains(&d.source) && !d.tuning_embedding.is_empty()
+            })
             .map(|d| {
                 let similarity = cosine_similarity(&d.tuning_embedding, embedding);
                 (d.clone(), similarity)
@@ -409,11 +866,69 @@ impl Storage for InMemoryStore {
         Ok(results)
     }
 
+    async fn get_definitions_by_health(
+        &self,
+        max_health: f32,
+        source: Option<DefinitionSource>,
+    ) -> Result<Vec<AgentDefinition>> {
+        let definitions = self.definitions.read().unwrap();
+        let mut result: Vec<AgentDefinition> = definitions
+            .values()
+            .filter(|d| {
+                !d.is_deleted()
+                    && d.health_score <= max_health
+                    && source.is_none_or(|s| d.source == s)
+            })
+            .cloned()
+            .collect();
+        result.sort_by(|a, b| {
+            a.health_score
+                .total_cmp(&b.health_score)
+                .then_with(|| a.name.cmp(&b.name))
+        });
+        Ok(result)
+    }
+
+    async fn soft_delete_definition(&self, id: DefinitionId) -> Result<bool> {
+        let mut definitions = self.definitions.write().unwrap();
+        let Some(def) = definitions.get_mut(&id).filter(|d| !d.is_deleted()) else {
+            return Ok(false);
+        };
+        def.deleted_at = Some(chrono::Utc::now());
+        self.mark_dirty();
+        Ok(true)
+    }
+
+    async fn restore_definition(&self, id: DefinitionId) -> Result<bool> {
+        let mut definitions = self.definitions.write().unwrap();
+        let Some(def) = definitions.get_mut(&id).filter(|d| d.is_deleted()) else {
+            return Ok(false);
+        };
+        def.deleted_at = None;
+        self.mark_dirty();
+        Ok(true)
+    }
+
+    async fn delete_definition(&self, id: DefinitionId) -> Result<bool> {
+        let mut definitions = self.definitions.write().unwrap();
+        match definitions.get(&id) {
+            None => return Ok(false),
+            Some(def) if def.source == DefinitionSource::BuiltIn => {
+                anyhow::bail!("Cannot delete built-in definition {}", def.name)
+            }
+            Some(_) => {}
+        }
+        definitions.remove(&id);
+        self.mark_dirty();
+        Ok(true)
+    }
+
     async fn increment_definition_use_count(&self, id: DefinitionId) -> Result<()> {
         let mut definitions = self.definitions.write().unwrap();
         if let Some(def) = definitions.get_mut(&id) {
             def.use_count += 1;
         }
+        self.mark_dirty();
         Ok(())
     }
 
@@ -422,6 +937,7 @@ impl Storage for InMemoryStore {
         if let Some(def) = definitions.get_mut(&id) {
             def.health_score = (def.health_score + health_delta).clamp(0.0, 1.0);
         }
+        self.mark_dirty();
         Ok(())
     }
 }
@@ -440,6 +956,9 @@ mod tests {
             task: "test task".to_string(),
             state: WebState::Running,
             config: WebConfig::default(),
+            priority: 0,
+            metadata: Default::default(),
+            report: None,
         }
     }
 
@@ -463,6 +982,45 @@ mod tests {
         )
     }
 
+    #[tokio::test]
+    async fn test_tool_execution_output_is_truncated() {
+        use crate::definitions::ToolType;
+        use crate::storage::traits::MAX_STORED_OUTPUT_BYTES;
+
+        let store = InMemoryStore::new();
+        let agent = create_test_agent(Uuid::new_v4(), None);
+        let big = "x".repeat(MAX_STORED_OUTPUT_BYTES * 2);
+
+        let execution = ToolExecution::new(
+            agent.id,
+            agent.web_id,
+            ToolType::ReadFile,
+            serde_json::json!({ "path": "big.txt" }),
+            serde_json::json!({ "content": big }),
+            true,
+        );
+        store.record_tool_execution(&execution).await.unwrap();
+
+        let stored = store.get_tool_executions(agent.id).await.unwrap();
+        assert_eq!(stored.len(), 1);
+        assert_eq!(stored[0].output["truncated"], true);
+        assert!(stored[0].output["preview"].as_str().unwrap().len() <= MAX_STORED_OUTPUT_BYTES);
+        assert!(store
+            .get_tool_executions(Uuid::new_v4())
+            .await
+            .unwrap()
+            .is_empty());
+
+        // Built without `new`, so only the store can truncate it.
+        let unbounded = ToolExecution {
+            output: serde_json::json!({ "content": "y".repeat(MAX_STORED_OUTPUT_BYTES * 2) }),
+            ..execution
+        };
+        store.record_tool_execution(&unbounded).await.unwrap();
+        let stored = store.get_tool_executions(agent.id).await.unwrap();
+        assert_eq!(stored[1].output["truncated"], true);
+    }
+
     #[tokio::test]
     async fn test_web_operations() {
         let store = InMemoryStore::new();
@@ -476,6 +1034,40 @@ mod tests {
         assert_eq!(retrieved.unwrap().id, web_id);
     }
 
+    #[tokio::test]
+    async fn test_update_web_config_leaves_state_alone() {
+        let store = InMemoryStore::new();
+        let web = create_test_web();
+        Storage::create_web(&store, &web).await.unwrap();
+
+        let config = WebConfig {
+            max_agents: 7,
+            ..web.config.clone()
+        };
+        assert!(Storage::update_web_config(&store, web.id, &config)
+            .await
+            .unwrap());
+
+        // The run converges after a config update read the web.
+        let mut converged = Storage::get_web(&store, web.id).await.unwrap().unwrap();
+        converged.state = WebState::Converged;
+        Storage::update_web(&store, &converged).await.unwrap();
+        let late = WebConfig {
+            max_agents: 3,
+            ..config.clone()
+        };
+        assert!(!Storage::update_web_config(&store, web.id, &late)
+            .await
+            .unwrap());
+
+        let stored = Storage::get_web(&store, web.id).await.unwrap().unwrap();
+        assert_eq!(stored.state, WebState::Converged);
+        assert_eq!(stored.config.max_agents, 7);
+        assert!(!Storage::update_web_config(&store, Uuid::new_v4(), &config)
+            .await
+            .unwrap());
+    }
+
     #[tokio::test]
     async fn test_agent_operations() {
         let store = InMemoryStore::new();
@@ -567,4 +1159,274 @@ mod tests {
         let pending_after = Storage::get_pending_signals(&store, web.id).await.unwrap();
         assert_eq!(pending_after.len(), 0);
     }
+
+    #[tokio::test]
+    async fn test_pending_signals_are_fifo() {
+        let store = InMemoryStore::new();
+        let web = create_test_web();
+        let agent = create_test_agent(web.id, None);
+        store.add_agent(agent.clone()).unwrap();
+
+        let signals: Vec<Signal> = (0..20).map(|_| create_test_signal(agent.id)).collect();
+        for (i, signal) in signals.iter().enumerate() {
+            if i % 2 == 0 {
+                store.add_signal(signal.clone()).unwrap();
+            } else {
+                Storage::create_signal(&store, signal).await.unwrap();
+            }
+        }
+        // Re-adding keeps the original position.
+        store.add_signal(signals[0].clone()).unwrap();
+        WebStore::mark_signal_processed(&store, &signals[3].id).unwrap();
+
+        let expected: Vec<SignalId> = signals
+            .iter()
+            .map(|s| s.id)
+            .filter(|id| *id != signals[3].id)
+            .collect();
+        let ids = |pending: Vec<Signal>| pending.iter().map(|s| s.id).collect::<Vec<_>>();
+        assert_eq!(
+            ids(WebStore::get_pending_signals(&store, &web.id).unwrap()),
+            expected
+        );
+        assert_eq!(
+            ids(Storage::get_pending_signals(&store, web.id).await.unwrap()),
+            expected
+        );
+    }
+
+    #[tokio::test]
+    async fn test_find_definitions_by_similarity() {
+        use crate::definitions::builtin::task_coordinator_definition;
+
+        let store = InMemoryStore::new();
+        for (name, source, embedding) in [
+            ("close", DefinitionSource::UserCustom, vec![1.0, 0.1, 0.0]),
+            ("closest", DefinitionSource::UserCustom, vec![1.0, 0.0, 0.0]),
+            ("far", DefinitionSource::UserCustom, vec![0.0, 1.0, 0.0]),
+            (
+                "generated",
+                DefinitionSource::Generated,
+                vec![1.0, 0.0, 0.0],
+            ),
+            ("unembedded", DefinitionSource::UserCustom, vec![]),
+        ] {
+            let mut definition = task_coordinator_definition();
+            definition.id = Uuid::new_v4();
+            definition.name = name.to_string();
+            definition.source = source;
+            definition.tuning_embedding = embedding;
+            store.create_definition(&definition).await.unwrap();
+        }
+
+        let query = [1.0, 0.0, 0.0];
+        let names = |results: Vec<(AgentDefinition, f32)>| {
+            results.into_iter().map(|(d, _)| d.name).collect::<Vec<_>>()
+        };
+
+        let matches = store
+            .find_definitions_by_similarity(&query, 0.5, &[DefinitionSource::UserCustom], 10)
+            .await
+            .unwrap();
+        assert!(matches[0].1 > matches[1].1);
+        assert_eq!(names(matches), ["closest", "close"]);
+
+        let limited = store
+            .find_definitions_by_similarity(&query, 0.5, &[DefinitionSource::UserCustom], 1)
+            .await
+            .unwrap();
+        assert_eq!(names(limited), ["closest"]);
+
+        // The threshold is exclusive, as in Postgres.
+        let exact = store
+            .find_definitions_by_similarity(&query, 1.0, &[DefinitionSource::UserCustom], 10)
+            .await
+            .unwrap();
+        assert!(exact.is_empty());
+
+        let generated = store
+            .find_definitions_by_similarity(&query, 0.5, &[DefinitionSource::Generated], 10)
+            .await
+            .unwrap();
+        assert_eq!(names(generated), ["generated"]);
+    }
+
+    #[tokio::test]
+    async fn test_save_and_load_preserves_entities() {
+        use crate::definitions::builtin::task_coordinator_definition;
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("store.json");
+
+        let store = InMemoryStore::new();
+        let web = create_test_web();
+        let root = create_test_agent(web.id, None);
+        let child = create_test_agent(web.id, Some(root.id));
+        WebStore::create_web(&store, web.clone()).unwrap();
+        store.add_agent(root.clone()).unwrap();
+        store.add_agent(child.clone()).unwrap();
+        let signals: Vec<Signal> = (0..3).map(|_| create_test_signal(root.id)).collect();
+        for signal in &signals {
+            store.add_signal(signal.clone()).unwrap();
+        }
+        WebStore::mark_signal_processed(&store, &signals[1].id).unwrap();
+        let mut definition = task_coordinator_definition();
+        definition.tuning_embedding = vec![0.5, 0.5];
+        store.create_definition(&definition).await.unwrap();
+
+        store.save_to(&path).unwrap();
+        drop(store);
+        let loaded = InMemoryStore::load_from(&path).unwrap();
+
+        let loaded_web = WebStore::get_web(&loaded, &web.id).unwrap().unwrap();
+        assert_eq!(loaded_web.task, web.task);
+        assert_eq!(loaded_web.root_agent, web.root_agent);
+        let loaded_child = WebStore::get_agent(&loaded, &child.id).unwrap().unwrap();
+        assert_eq!(loaded_child.parent_id, Some(root.id));
+        assert_eq!(loaded_child.tuning, child.tuning);
+        assert_eq!(
+            WebStore::get_agents_by_web(&loaded, &web.id).unwrap().len(),
+            2
+        );
+
+        let pending: Vec<SignalId> = WebStore::get_pending_signals(&loaded, &web.id)
+            .unwrap()
+            .iter()
+            .map(|s| s.id)
+            .collect();
+        assert_eq!(pending, [signals[0].id, signals[2].id]);
+        assert!(loaded.get_signal(&signals[1].id).unwrap().is_some());
+
+        let loaded_definition = loaded.get_definition(definition.id).await.unwrap().unwrap();
+        assert_eq!(loaded_definition.name, definition.name);
+        assert_eq!(loaded_definition.tuning_embedding, vec![0.5, 0.5]);
+    }
+
+    #[test]
+    fn test_auto_persist_writes_after_mutation() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("store.json");
+        let store = InMemoryStore::new().with_auto_persist(&path, Duration::from_millis(10));
+
+        let web = create_test_web();
+        WebStore::create_web(&store, web.clone()).unwrap();
+
+        let deadline = std::time::Instant::now() + Duration::from_secs(5);
+        while !path.exists() && std::time::Instant::now() < deadline {
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        let loaded = InMemoryStore::load_from(&path).unwrap();
+        assert!(WebStore::get_web(&loaded, &web.id).unwrap().is_some());
+    }
+
+    #[tokio::test]
+    async fn test_get_definitions_by_health() {
+        use crate::definitions::builtin::task_coordinator_definition;
+
+        let store = InMemoryStore::new();
+        for (name, source, health) in [
+            ("healthy", DefinitionSource::Generated, 0.9),
+            ("borderline", DefinitionSource::Generated, 0.5),
+            ("sick", DefinitionSource::Generated, 0.1),
+            ("custom-sick", DefinitionSource::UserCustom, 0.2),
+        ] {
+            let mut definition = task_coordinator_definition();
+            definition.id = Uuid::new_v4();
+            definition.name = name.to_string();
+            definition.source = source;
+            definition.health_score = health;
+            store.create_definition(&definition).await.unwrap();
+        }
+        let names = |definitions: Vec<AgentDefinition>| {
+            definitions.into_iter().map(|d| d.name).collect::<Vec<_>>()
+        };
+
+        let all = store.get_definitions_by_health(0.5, None).await.unwrap();
+        assert_eq!(names(all), ["sick", "custom-sick", "borderline"]);
+
+        let generated = store
+            .get_definitions_by_health(0.5, Some(DefinitionSource::Generated))
+            .await
+            .unwrap();
+        assert_eq!(names(generated), ["sick", "borderline"]);
+
+        assert!(store
+            .get_definitions_by_health(0.05, None)
+            .await
+            .unwrap()
+            .is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_list_definitions_paged() {
+        use crate::definitions::builtin::task_coordinator_definition;
+
+        let store = InMemoryStore::new();
+        for (name, source, use_count) in [
+            ("a", DefinitionSource::Generated, 3),
+            ("b", DefinitionSource::Generated, 1),
+            ("c", DefinitionSource::UserCustom, 2),
+            ("d", DefinitionSource::Generated, 1),
+            ("e", DefinitionSource::Generated, 0),
+        ] {
+            let mut definition = task_coordinator_definition();
+            definition.id = Uuid::new_v4();
+            definition.name = name.to_string();
+            definition.source = source;
+            definition.use_count = use_count;
+            store.create_definition(&definition).await.unwrap();
+        }
+
+        let mut pages = Vec::new();
+        for offset in (0..6).step_by(2) {
+            let page = store.list_definitions_paged(None, 2, offset).await.unwrap();
+            assert!(page.len() <= 2);
+            pages.extend(page);
+        }
+        let all = store.list_definitions(None).await.unwrap();
+        assert_eq!(pages.len(), all.len());
+        assert_eq!(pages[0].name, "a");
+        assert_eq!(pages[1].name, "c");
+        assert_eq!(pages[4].name, "e");
+        // "b" and "d" tie on use_count and are ordered by id.
+        assert!(pages[2].id < pages[3].id);
+
+        let generated = store
+            .list_definitions_paged(Some(DefinitionSource::Generated), 10, 1)
+            .await
+            .unwrap();
+        assert_eq!(generated.len(), 3);
+        assert!(generated
+            .iter()
+            .all(|d| d.source == DefinitionSource::Generated && d.name != "a"));
+        assert!(store
+            .list_definitions_paged(None, 10, 5)
+            .await
+            .unwrap()
+            .is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_find_resonating_agents_skips_mismatched_dimensions() {
+        let store = InMemoryStore::new();
+        let web_id = Uuid::new_v4();
+        for tuning in [vec![1.0, 0.0, 0.0], vec![1.0, 0.0], vec![0.9, 0.1, 0.0]] {
+            let agent = Agent::new(
+                web_id,
+                None,
+                "agent".to_string(),
+                tuning,
+                CapabilityType::Search,
+                0.5,
+            );
+            Storage::create_agent(&store, &agent).await.unwrap();
+        }
+
+        let resonating = store
+            .find_resonating_agents(web_id, &[1.0, 0.0, 0.0], 0.5)
+            .await
+            .unwrap();
+        assert_eq!(resonating.len(), 2);
+        assert!(resonating.iter().all(|(a, _)| a.tuning.len() == 3));
+    }
 }
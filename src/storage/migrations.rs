@@ -0,0 +1,293 @@
+use anyhow::{anyhow, bail, Result};
+use chrono::{DateTime, Utc};
+use sha2::{Digest, Sha256};
+
+/// Records which migrations have been applied, one row per version.
+pub const TRACKING_TABLE_SQL: &str = r#"
+CREATE TABLE IF NOT EXISTS _arachnid_migrations (
+    version BIGINT PRIMARY KEY,
+    name TEXT NOT NULL,
+    checksum BYTEA NOT NULL,
+    applied_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+)
+"#;
+
+/// A schema migration compiled into the binary from
+/// `migrations/<name>.sql`, reversed by `migrations/<name>.down.sql`.
+#[derive(Debug, Clone, Copy)]
+pub struct Migration {
+    /// File stem, e.g. `V001__initial_schema`.
+    pub name: &'static str,
+    pub up: &'static str,
+    /// `None` for migrations that can't be reversed.
+    pub down: Option<&'static str>,
+}
+
+macro_rules! migration {
+    ($name:literal) => {
+        Migration {
+            name: $name,
+            up: include_str!(concat!("../../migrations/", $name, ".sql")),
+            down: Some(include_str!(concat!(
+                "../../migrations/",
+                $name,
+                ".down.sql"
+            ))),
+        }
+    };
+}
+
+/// Every migration, in version order. Add new files here.
+pub static MIGRATIONS: &[Migration] = &[
+    migration!("V001__initial_schema"),
+    migration!("V002__add_validations"),
+    migration!("V010__agent_definitions"),
+    migration!("V011__tool_executions"),
+    migration!("V012__job_queue"),
+    migration!("V013__web_priority"),
+    migration!("V014__web_checkpoints"),
+    migration!("V015__web_analytics"),
+    migration!("V016__hnsw_vector_indexes"),
+    migration!("V017__definition_soft_delete"),
+    migration!("V018__agent_tuning_norm"),
+    migration!("V019__web_token_usage"),
+    migration!("V020__definition_max_tokens"),
+    migration!("V021__tool_execution_effects"),
+    migration!("V022__web_reports"),
+];
+
+/// Migrations a database may have without a tracking row, each with a table
+/// it creates: the runner before tracking applied V001 on every start, and
+/// V002 and V010 were applied by hand.
+const UNTRACKED_MARKERS: &[(&str, &str)] = &[
+    ("V001__initial_schema", "webs"),
+    ("V002__add_validations", "validations"),
+    ("V010__agent_definitions", "agent_definitions"),
+];
+
+/// The migrations an untracked schema with `tables` already has applied.
+pub fn untracked_migrations(tables: &[String]) -> Vec<&'static Migration> {
+    if !tables.iter().any(|t| t == "webs") {
+        return Vec::new();
+    }
+    UNTRACKED_MARKERS
+        .iter()
+        .filter(|(_, table)| tables.iter().any(|t| t == table))
+        .filter_map(|(name, _)| MIGRATIONS.iter().find(|m| m.name == *name))
+        .collect()
+}
+
+impl Migration {
+    /// The number after the `V`, e.g. 16 for `V016__hnsw_vector_indexes`.
+    pub fn version(&self) -> i64 {
+        self.name
+            .strip_prefix('V')
+            .and_then(|rest| rest.split("__").next())
+            .and_then(|digits| digits.parse().ok())
+            .unwrap_or_else(|| panic!("migration {} has no Vxxx__ version prefix", self.name))
+    }
+
+    /// SHA-256 of the up script, to notice files edited after being applied.
+    pub fn checksum(&self) -> Vec<u8> {
+        Sha256::digest(self.up.as_bytes()).to_vec()
+    }
+
+    pub fn down_sql(&self) -> Result<&'static str> {
+        self.down.ok_or_else(|| {
+            anyhow!(
+                "Cannot roll back {}: migrations/{}.down.sql does not exist",
+                self.name,
+                self.name
+            )
+        })
+    }
+}
+
+pub fn find_migration(version: i64) -> Option<&'static Migration> {
+    MIGRATIONS.iter().find(|m| m.version() == version)
+}
+
+/// A row of the tracking table.
+#[derive(Debug, Clone)]
+pub struct AppliedMigration {
+    pub version: i64,
+    pub checksum: Vec<u8>,
+    pub applied_at: DateTime<Utc>,
+}
+
+/// Whether a migration has been applied, as shown by `migrate --status`.
+#[derive(Debug, Clone)]
+pub struct MigrationStatus {
+    pub migration: &'static Migration,
+    pub applied_at: Option<DateTime<Utc>>,
+    /// The file no longer matches what was applied.
+    pub checksum_changed: bool,
+}
+
+pub fn migration_status(
+    migrations: &'static [Migration],
+    applied: &[AppliedMigration],
+) -> Vec<MigrationStatus> {
+    migrations
+        .iter()
+        .map(|migration| {
+            let row = applied.iter().find(|a| a.version == migration.version());
+            MigrationStatus {
+                migration,
+                applied_at: row.map(|a| a.applied_at),
+                checksum_changed: row.is_some_and(|a| a.checksum != migration.checksum()),
+            }
+        })
+        .collect()
+}
+
+/// Migrations still to apply, in version order. Fails if an applied
+/// migration's file was edited afterwards, since the database no longer
+/// matches what the files describe.
+pub fn pending_migrations(
+    migrations: &'static [Migration],
+    applied: &[AppliedMigration],
+) -> Result<Vec<&'static Migration>> {
+    let statuses = migration_status(migrations, applied);
+    if let Some(changed) = statuses.iter().find(|s| s.checksum_changed) {
+        bail!(
+            "migrations/{}.sql changed after it was applied; restore it and add a new migration instead",
+            changed.migration.name
+        );
+    }
+    Ok(statuses
+        .into_iter()
+        .filter(|s| s.applied_at.is_none())
+        .map(|s| s.migration)
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_untracked_migrations() {
+        let tables = |names: &[&str]| names.iter().map(|n| n.to_string()).collect::<Vec<_>>();
+
+        assert!(untracked_migrations(&tables(&[])).is_empty());
+        // Without the initial schema, nothing else counts.
+        assert!(untracked_migrations(&tables(&["validations"])).is_empty());
+
+        let names =
+            |migrations: Vec<&Migration>| migrations.iter().map(|m| m.name).collect::<Vec<_>>();
+        assert_eq!(
+            names(untracked_migrations(&tables(&[
+                "webs", "agents", "signals"
+            ]))),
+            vec!["V001__initial_schema"]
+        );
+        assert_eq!(
+            names(untracked_migrations(&tables(&[
+                "webs",
+                "agent_definitions",
+                "validations"
+            ]))),
+            vec![
+                "V001__initial_schema",
+                "V002__add_validations",
+                "V010__agent_definitions"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_migrations_match_files() {
+        let mut files: Vec<String> =
+            std::fs::read_dir(concat!(env!("CARGO_MANIFEST_DIR"), "/migrations"))
+                .unwrap()
+                .map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+                .filter(|name| name.starts_with('V') && !name.ends_with(".down.sql"))
+                .filter_map(|name| name.strip_suffix(".sql").map(str::to_string))
+                .collect();
+        files.sort();
+
+        let listed: Vec<&str> = MIGRATIONS.iter().map(|m| m.name).collect();
+        assert_eq!(
+            listed, files,
+            "MIGRATIONS must list every migrations/V*.sql"
+        );
+
+        let versions: Vec<i64> = MIGRATIONS.iter().map(Migration::version).collect();
+        assert!(versions.windows(2).all(|w| w[0] < w[1]));
+        assert_eq!(
+            find_migration(16).unwrap().name,
+            "V016__hnsw_vector_indexes"
+        );
+    }
+
+    static TEST_MIGRATIONS: &[Migration] = &[
+        Migration {
+            name: "V001__first",
+            up: "CREATE TABLE a (id INT)",
+            down: None,
+        },
+        Migration {
+            name: "V002__second",
+            up: "CREATE TABLE b (id INT)",
+            down: None,
+        },
+        Migration {
+            name: "V003__third",
+            up: "CREATE TABLE c (id INT)",
+            down: None,
+        },
+    ];
+
+    fn applied(migration: &Migration) -> AppliedMigration {
+        AppliedMigration {
+            version: migration.version(),
+            checksum: migration.checksum(),
+            applied_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_pending_migrations() {
+        let all = pending_migrations(TEST_MIGRATIONS, &[]).unwrap();
+        assert_eq!(all.len(), 3);
+
+        // Gaps are filled in version order.
+        let rows = [applied(&TEST_MIGRATIONS[0]), applied(&TEST_MIGRATIONS[2])];
+        let pending = pending_migrations(TEST_MIGRATIONS, &rows).unwrap();
+        let names: Vec<&str> = pending.iter().map(|m| m.name).collect();
+        assert_eq!(names, vec!["V002__second"]);
+
+        let status = migration_status(TEST_MIGRATIONS, &rows);
+        assert!(status[0].applied_at.is_some() && !status[0].checksum_changed);
+        assert!(status[1].applied_at.is_none());
+
+        let every = TEST_MIGRATIONS.iter().map(applied).collect::<Vec<_>>();
+        assert!(pending_migrations(TEST_MIGRATIONS, &every)
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_changed_migration_is_rejected() {
+        let mut row = applied(&TEST_MIGRATIONS[0]);
+        row.checksum = Sha256::digest(b"CREATE TABLE a (id BIGINT)").to_vec();
+
+        assert!(migration_status(TEST_MIGRATIONS, std::slice::from_ref(&row))[0].checksum_changed);
+        let err = pending_migrations(TEST_MIGRATIONS, &[row])
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("migrations/V001__first.sql changed"));
+    }
+
+    #[test]
+    fn test_missing_down_script_names_the_file() {
+        let irreversible = Migration {
+            name: "V099__irreversible",
+            up: "SELECT 1",
+            down: None,
+        };
+        let err = irreversible.down_sql().unwrap_err().to_string();
+        assert!(err.contains("migrations/V099__irreversible.down.sql"));
+    }
+}
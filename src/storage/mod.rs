@@ -1,5 +1,13 @@
 pub mod memory;
+pub mod migrations;
 pub mod postgres;
+pub mod queue;
+pub mod stats;
 pub mod traits;
 
-pub use traits::{FailurePattern, FailurePatternType, Storage};
+pub use queue::WebRunQueue;
+pub use stats::StorageStats;
+pub use traits::{
+    ClaimPolicy, FailurePattern, FailurePatternType, JobId, JobStatus, Storage, WebAnalytics,
+    WebCheckpoint, WebRunJob,
+};
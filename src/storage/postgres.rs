@@ -1,11 +1,22 @@
-use anyhow::Result;
+use anyhow::{Context, Result};
 use async_trait::async_trait;
 use pgvector::Vector;
-use sqlx::postgres::PgPoolOptions;
-use sqlx::{PgPool, Row};
+use sqlx::postgres::{PgConnection, PgPoolOptions};
+use sqlx::{Connection, PgPool, Row};
+use std::future::Future;
+use std::time::Duration;
 
 use crate::definitions::{AgentDefinition, DefinitionId, DefinitionSource, ToolType};
-use crate::storage::traits::{FailurePattern, FailurePatternType, Storage};
+use crate::engine::resonance::magnitude;
+use crate::providers::usage::Usage;
+use crate::storage::migrations::{
+    find_migration, migration_status, pending_migrations, untracked_migrations, AppliedMigration,
+    Migration, MigrationStatus, MIGRATIONS, TRACKING_TABLE_SQL,
+};
+use crate::storage::traits::{
+    truncate_output, ClaimPolicy, FailurePattern, FailurePatternType, JobId, JobStatus, Storage,
+    ToolExecution, WebAnalytics, WebCheckpoint, WebRunJob,
+};
 use crate::types::{
     Agent, AgentContext, AgentId, AgentState, CapabilityType, Signal, SignalDirection, SignalId,
     Web, WebConfig, WebId, WebState,
@@ -15,12 +26,182 @@ pub struct PostgresStorage {
     pool: PgPool,
 }
 
+/// Width of the `vector(1536)` tuning and frequency columns. The HNSW
+/// indexes need a fixed width, so webs with another `embedding_dim` can't
+/// be stored in PostgreSQL.
+pub const POSTGRES_EMBEDDING_DIM: usize = 1536;
+
+fn check_embedding_dim(dim: usize) -> Result<()> {
+    if dim != POSTGRES_EMBEDDING_DIM {
+        anyhow::bail!(
+            "Embedding dimension {} isn't supported with PostgreSQL storage, which stores {}-dimensional vectors",
+            dim,
+            POSTGRES_EMBEDDING_DIM
+        );
+    }
+    Ok(())
+}
+
+/// Query-time accuracy of the pgvector indexes, set on every pooled
+/// connection. Higher values find more of the true nearest neighbours but
+/// read more of the index. Unset values keep pgvector's defaults.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct VectorSearchConfig {
+    /// `hnsw.ef_search` (pgvector default 40). Also caps how many rows one
+    /// index scan returns, so keep it at or above the largest similarity
+    /// search limit.
+    pub hnsw_ef_search: Option<u32>,
+    /// `ivfflat.probes` (pgvector default 1), for deployments that rebuilt
+    /// the indexes as ivfflat.
+    pub ivfflat_probes: Option<u32>,
+}
+
+impl VectorSearchConfig {
+    /// Reads `ARACHNID_HNSW_EF_SEARCH` and `ARACHNID_IVFFLAT_PROBES`.
+    pub fn from_env() -> Self {
+        let env = |var: &str| std::env::var(var).ok().and_then(|v| v.parse().ok());
+        Self {
+            hnsw_ef_search: env("ARACHNID_HNSW_EF_SEARCH").filter(|n| *n > 0),
+            ivfflat_probes: env("ARACHNID_IVFFLAT_PROBES").filter(|n| *n > 0),
+        }
+    }
+
+    fn statements(&self) -> Vec<String> {
+        let mut statements = Vec::new();
+        if let Some(ef_search) = self.hnsw_ef_search {
+            statements.push(format!("SET hnsw.ef_search = {}", ef_search));
+        }
+        if let Some(probes) = self.ivfflat_probes {
+            statements.push(format!("SET ivfflat.probes = {}", probes));
+        }
+        statements
+    }
+}
+
+/// How `PostgresStorage` retries while the database isn't accepting
+/// connections yet, e.g. while its container starts. Each wait doubles, up to
+/// `max_backoff`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConnectRetry {
+    /// Connection attempts in total, including the first.
+    pub attempts: u32,
+    pub initial_backoff: Duration,
+    pub max_backoff: Duration,
+}
+
+impl Default for ConnectRetry {
+    fn default() -> Self {
+        Self {
+            attempts: 5,
+            initial_backoff: Duration::from_millis(500),
+            max_backoff: Duration::from_secs(5),
+        }
+    }
+}
+
+impl ConnectRetry {
+    /// Reads `ARACHNID_DB_CONNECT_ATTEMPTS` and `ARACHNID_DB_CONNECT_BACKOFF_MS`.
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+        let env = |var: &str| std::env::var(var).ok().and_then(|v| v.parse::<u64>().ok());
+
+        Self {
+            attempts: env("ARACHNID_DB_CONNECT_ATTEMPTS")
+                .map(|n| n.clamp(1, u32::MAX as u64) as u32)
+                .unwrap_or(defaults.attempts),
+            initial_backoff: env("ARACHNID_DB_CONNECT_BACKOFF_MS")
+                .map(Duration::from_millis)
+                .unwrap_or(defaults.initial_backoff),
+            ..defaults
+        }
+    }
+
+    /// Run `connect` until it succeeds, fails with an error retrying won't
+    /// fix, or the attempts run out.
+    pub async fn run<T, F, Fut>(&self, mut connect: F) -> Result<T>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T, sqlx::Error>>,
+    {
+        let mut backoff = self.initial_backoff;
+        let mut attempt = 1;
+        loop {
+            match connect().await {
+                Ok(value) => return Ok(value),
+                Err(e) if attempt < self.attempts && is_retryable(&e) => {
+                    tracing::warn!(
+                        attempt,
+                        attempts = self.attempts,
+                        error = %e,
+                        "Database not ready, retrying in {:?}",
+                        backoff
+                    );
+                    tokio::time::sleep(backoff).await;
+                    backoff = (backoff * 2).min(self.max_backoff);
+                    attempt += 1;
+                }
+                Err(e) => return Err(e.into()),
+            }
+        }
+    }
+}
+
+/// Whether a connect error may go away by waiting: the server is down,
+/// refusing connections or still starting. Bad credentials, a missing
+/// database or a malformed URL are not.
+fn is_retryable(error: &sqlx::Error) -> bool {
+    match error {
+        sqlx::Error::Io(_) | sqlx::Error::PoolTimedOut => true,
+        // 57P03: cannot_connect_now, sent while the server starts up.
+        sqlx::Error::Database(e) => e.code().as_deref() == Some("57P03"),
+        _ => false,
+    }
+}
+
+/// Connection settings for `PostgresStorage::with_config`.
+#[derive(Debug, Clone, Default)]
+pub struct PostgresConfig {
+    pub vector_search: VectorSearchConfig,
+    pub connect_retry: ConnectRetry,
+}
+
+impl PostgresConfig {
+    pub fn from_env() -> Self {
+        Self {
+            vector_search: VectorSearchConfig::from_env(),
+            connect_retry: ConnectRetry::from_env(),
+        }
+    }
+}
+
 impl PostgresStorage {
+    /// Connect with the settings from the environment.
     pub async fn new(database_url: &str) -> Result<Self> {
-        let pool = PgPoolOptions::new()
+        Self::with_config(database_url, PostgresConfig::from_env()).await
+    }
+
+    pub async fn with_config(database_url: &str, config: PostgresConfig) -> Result<Self> {
+        let statements = config.vector_search.statements();
+        let options = PgPoolOptions::new()
             .max_connections(10)
-            .connect(database_url)
+            .after_connect(move |conn, _meta| {
+                let statements = statements.clone();
+                Box::pin(async move {
+                    for statement in &statements {
+                        sqlx::query(statement).execute(&mut *conn).await?;
+                    }
+                    Ok(())
+                })
+            });
+
+        // The pool retries refused connections on its own until its acquire
+        // timeout, then reports only a timeout. Probe with single
+        // connections instead, which fail fast with the real error.
+        config
+            .connect_retry
+            .run(|| async { PgConnection::connect(database_url).await?.close().await })
             .await?;
+        let pool = options.connect(database_url).await?;
         Ok(Self { pool })
     }
 
@@ -28,21 +209,160 @@ impl PostgresStorage {
         Self { pool }
     }
 
-    pub async fn run_migrations(&self) -> Result<()> {
-        sqlx::query(include_str!("../../migrations/V001__initial_schema.sql"))
+    /// Apply every pending migration in version order, in one transaction,
+    /// and return them. Concurrent callers wait for each other.
+    pub async fn run_migrations(&self) -> Result<Vec<&'static Migration>> {
+        sqlx::raw_sql(TRACKING_TABLE_SQL)
             .execute(&self.pool)
             .await?;
-        Ok(())
+
+        let mut tx = self.pool.begin().await?;
+        sqlx::query("SELECT pg_advisory_xact_lock($1)")
+            .bind(MIGRATION_LOCK_ID)
+            .execute(&mut *tx)
+            .await?;
+        let mut applied = applied_migrations(&mut tx).await?;
+        if applied.is_empty() {
+            // A schema from before migrations were tracked: record what it
+            // already has rather than running it again.
+            let tables: Vec<String> = sqlx::query_scalar(
+                "SELECT table_name::text FROM information_schema.tables WHERE table_schema = current_schema()",
+            )
+            .fetch_all(&mut *tx)
+            .await?;
+            for migration in untracked_migrations(&tables) {
+                record_migration(&mut tx, migration).await?;
+            }
+            applied = applied_migrations(&mut tx).await?;
+        }
+        let pending = pending_migrations(MIGRATIONS, &applied)?;
+
+        for migration in &pending {
+            sqlx::raw_sql(migration.up)
+                .execute(&mut *tx)
+                .await
+                .with_context(|| format!("Failed to apply {}", migration.name))?;
+            record_migration(&mut tx, migration).await?;
+        }
+        tx.commit().await?;
+        Ok(pending)
+    }
+
+    /// Every known migration and whether it has been applied.
+    pub async fn migration_status(&self) -> Result<Vec<MigrationStatus>> {
+        sqlx::raw_sql(TRACKING_TABLE_SQL)
+            .execute(&self.pool)
+            .await?;
+        let mut conn = self.pool.acquire().await?;
+        let applied = applied_migrations(&mut conn).await?;
+        Ok(migration_status(MIGRATIONS, &applied))
+    }
+
+    /// Run the down script of the latest applied migration and forget it,
+    /// in one transaction. Returns the migration, or `None` if none was
+    /// applied.
+    pub async fn rollback_last_migration(&self) -> Result<Option<&'static Migration>> {
+        sqlx::raw_sql(TRACKING_TABLE_SQL)
+            .execute(&self.pool)
+            .await?;
+
+        let mut tx = self.pool.begin().await?;
+        sqlx::query("SELECT pg_advisory_xact_lock($1)")
+            .bind(MIGRATION_LOCK_ID)
+            .execute(&mut *tx)
+            .await?;
+        let Some(version) = applied_migrations(&mut tx).await?.pop().map(|a| a.version) else {
+            return Ok(None);
+        };
+        let migration = find_migration(version).ok_or_else(|| {
+            anyhow::anyhow!(
+                "Migration version {} was applied but isn't known to this build",
+                version
+            )
+        })?;
+        let down = migration.down_sql()?;
+
+        sqlx::raw_sql(down)
+            .execute(&mut *tx)
+            .await
+            .with_context(|| format!("Failed to roll back {}", migration.name))?;
+        sqlx::query("DELETE FROM _arachnid_migrations WHERE version = $1")
+            .bind(version)
+            .execute(&mut *tx)
+            .await?;
+        tx.commit().await?;
+        Ok(Some(migration))
     }
 }
 
+/// Advisory lock held while migrating, so two processes starting together
+/// don't both apply the same migration.
+const MIGRATION_LOCK_ID: i64 = 0x6172_6163_686e_6964;
+
+async fn record_migration(conn: &mut PgConnection, migration: &Migration) -> Result<()> {
+    sqlx::query("INSERT INTO _arachnid_migrations (version, name, checksum) VALUES ($1, $2, $3)")
+        .bind(migration.version())
+        .bind(migration.name)
+        .bind(migration.checksum())
+        .execute(conn)
+        .await?;
+    Ok(())
+}
+
+async fn applied_migrations(conn: &mut PgConnection) -> Result<Vec<AppliedMigration>> {
+    let rows = sqlx::query(
+        "SELECT version, checksum, applied_at FROM _arachnid_migrations ORDER BY version",
+    )
+    .fetch_all(conn)
+    .await?;
+    Ok(rows
+        .iter()
+        .map(|r| AppliedMigration {
+            version: r.get("version"),
+            checksum: r.get("checksum"),
+            applied_at: r.get("applied_at"),
+        })
+        .collect())
+}
+
+const JOB_COLUMNS: &str =
+    "id, web_id, priority, owner, status, attempts, claimed_by, visible_at, last_error, created_at";
+
+fn job_from_row(r: &sqlx::postgres::PgRow) -> Result<WebRunJob> {
+    let status_str: String = r.get("status");
+    let status = JobStatus::parse(&status_str)
+        .ok_or_else(|| anyhow::anyhow!("Unknown job status: {}", status_str))?;
+    let attempts: i32 = r.get("attempts");
+
+    Ok(WebRunJob {
+        id: r.get("id"),
+        web_id: r.get("web_id"),
+        priority: r.get("priority"),
+        owner: r.get("owner"),
+        status,
+        attempts: attempts as u32,
+        claimed_by: r.get("claimed_by"),
+        visible_at: r.get("visible_at"),
+        last_error: r.get("last_error"),
+        created_at: r.get("created_at"),
+    })
+}
+
 #[async_trait]
 impl Storage for PostgresStorage {
+    async fn ping(&self) -> Result<()> {
+        sqlx::query("SELECT 1").execute(&self.pool).await?;
+        Ok(())
+    }
+
     async fn create_web(&self, web: &Web) -> Result<()> {
+        check_embedding_dim(web.config.embedding_dim)?;
         sqlx::query(
             r#"
-            INSERT INTO webs (id, task, state, root_agent_id, config, created_at, updated_at)
-            VALUES ($1, $2, $3, $4, $5, NOW(), NOW())
+            INSERT INTO webs
+                (id, task, state, root_agent_id, config, priority, metadata, report,
+                 created_at, updated_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, NOW(), NOW())
             "#,
         )
         .bind(web.id)
@@ -50,6 +370,9 @@ impl Storage for PostgresStorage {
         .bind(web.state.as_str())
         .bind(web.root_agent)
         .bind(serde_json::to_value(&web.config)?)
+        .bind(web.priority)
+        .bind(serde_json::to_value(&web.metadata)?)
+        .bind(web.report.as_ref().map(serde_json::to_value).transpose()?)
         .execute(&self.pool)
         .await?;
         Ok(())
@@ -58,7 +381,7 @@ impl Storage for PostgresStorage {
     async fn get_web(&self, id: WebId) -> Result<Option<Web>> {
         let row = sqlx::query(
             r#"
-            SELECT id, task, state, root_agent_id, config
+            SELECT id, task, state, root_agent_id, config, priority, metadata, report
             FROM webs
             WHERE id = $1
             "#,
@@ -84,6 +407,12 @@ impl Storage for PostgresStorage {
                     state,
                     root_agent: r.get("root_agent_id"),
                     config,
+                    priority: r.get("priority"),
+                    metadata: serde_json::from_value(r.get("metadata"))?,
+                    report: r
+                        .get::<Option<serde_json::Value>, _>("report")
+                        .map(serde_json::from_value)
+                        .transpose()?,
                 }))
             }
             None => Ok(None),
@@ -94,7 +423,8 @@ impl Storage for PostgresStorage {
         sqlx::query(
             r#"
             UPDATE webs
-            SET task = $2, state = $3, root_agent_id = $4, config = $5, updated_at = NOW()
+            SET task = $2, state = $3, root_agent_id = $4, config = $5,
+                priority = $6, metadata = $7, report = $8, updated_at = NOW()
             WHERE id = $1
             "#,
         )
@@ -103,17 +433,35 @@ impl Storage for PostgresStorage {
         .bind(web.state.as_str())
         .bind(web.root_agent)
         .bind(serde_json::to_value(&web.config)?)
+        .bind(web.priority)
+        .bind(serde_json::to_value(&web.metadata)?)
+        .bind(web.report.as_ref().map(serde_json::to_value).transpose()?)
         .execute(&self.pool)
         .await?;
         Ok(())
     }
 
+    async fn update_web_config(&self, id: WebId, config: &WebConfig) -> Result<bool> {
+        let result = sqlx::query(
+            r#"
+            UPDATE webs
+            SET config = $2, updated_at = NOW()
+            WHERE id = $1 AND state NOT IN ('Converged', 'Failed')
+            "#,
+        )
+        .bind(id)
+        .bind(serde_json::to_value(config)?)
+        .execute(&self.pool)
+        .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
     async fn list_webs(&self, state: Option<WebState>) -> Result<Vec<Web>> {
         let rows = match state {
             Some(s) => {
                 sqlx::query(
                     r#"
-                    SELECT id, task, state, root_agent_id, config
+                    SELECT id, task, state, root_agent_id, config, priority, metadata, report
                     FROM webs
                     WHERE state = $1
                     ORDER BY created_at DESC
@@ -126,7 +474,7 @@ impl Storage for PostgresStorage {
             None => {
                 sqlx::query(
                     r#"
-                    SELECT id, task, state, root_agent_id, config
+                    SELECT id, task, state, root_agent_id, config, priority, metadata, report
                     FROM webs
                     ORDER BY created_at DESC
                     "#,
@@ -153,12 +501,19 @@ impl Storage for PostgresStorage {
                     state,
                     root_agent: r.get("root_agent_id"),
                     config,
+                    priority: r.get("priority"),
+                    metadata: serde_json::from_value(r.get("metadata"))?,
+                    report: r
+                        .get::<Option<serde_json::Value>, _>("report")
+                        .map(serde_json::from_value)
+                        .transpose()?,
                 })
             })
             .collect()
     }
 
     async fn create_agent(&self, agent: &Agent) -> Result<()> {
+        check_embedding_dim(agent.tuning.len())?;
         let tuning_vec = Vector::from(agent.tuning.clone());
 
         sqlx::query(
@@ -166,9 +521,9 @@ impl Storage for PostgresStorage {
             INSERT INTO agents (
                 id, web_id, parent_id, purpose, tuning, capability, state, health,
                 activation_threshold, context, probation_remaining, created_at,
-                last_active_at, dormant_since, definition_id
+                last_active_at, dormant_since, definition_id, tuning_norm
             )
-            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16)
             "#,
         )
         .bind(agent.id)
@@ -186,6 +541,7 @@ impl Storage for PostgresStorage {
         .bind(agent.last_active_at)
         .bind(agent.dormant_since)
         .bind(agent.definition_id)
+        .bind(agent.tuning_norm())
         .execute(&self.pool)
         .await?;
         Ok(())
@@ -196,7 +552,7 @@ impl Storage for PostgresStorage {
             r#"
             SELECT id, web_id, parent_id, purpose, tuning, capability, state, health,
                    activation_threshold, context, probation_remaining, created_at,
-                   last_active_at, dormant_since, definition_id
+                   last_active_at, dormant_since, definition_id, tuning_norm
             FROM agents
             WHERE id = $1
             "#,
@@ -212,6 +568,7 @@ impl Storage for PostgresStorage {
     }
 
     async fn update_agent(&self, agent: &Agent) -> Result<()> {
+        check_embedding_dim(agent.tuning.len())?;
         let tuning_vec = Vector::from(agent.tuning.clone());
 
         sqlx::query(
@@ -220,7 +577,7 @@ impl Storage for PostgresStorage {
             SET web_id = $2, parent_id = $3, purpose = $4, tuning = $5, capability = $6,
                 state = $7, health = $8, activation_threshold = $9, context = $10,
                 probation_remaining = $11, last_active_at = $12, dormant_since = $13,
-                definition_id = $14
+                definition_id = $14, tuning_norm = $15
             WHERE id = $1
             "#,
         )
@@ -238,6 +595,7 @@ impl Storage for PostgresStorage {
         .bind(agent.last_active_at)
         .bind(agent.dormant_since)
         .bind(agent.definition_id)
+        .bind(agent.tuning_norm())
         .execute(&self.pool)
         .await?;
         Ok(())
@@ -248,7 +606,7 @@ impl Storage for PostgresStorage {
             r#"
             SELECT id, web_id, parent_id, purpose, tuning, capability, state, health,
                    activation_threshold, context, probation_remaining, created_at,
-                   last_active_at, dormant_since, definition_id
+                   last_active_at, dormant_since, definition_id, tuning_norm
             FROM agents
             WHERE parent_id = $1
             "#,
@@ -274,7 +632,7 @@ impl Storage for PostgresStorage {
             )
             SELECT id, web_id, parent_id, purpose, tuning, capability, state, health,
                    activation_threshold, context, probation_remaining, created_at,
-                   last_active_at, dormant_since, definition_id
+                   last_active_at, dormant_since, definition_id, tuning_norm
             FROM ancestors
             WHERE level > 0
             ORDER BY level ASC
@@ -292,7 +650,7 @@ impl Storage for PostgresStorage {
             r#"
             SELECT id, web_id, parent_id, purpose, tuning, capability, state, health,
                    activation_threshold, context, probation_remaining, created_at,
-                   last_active_at, dormant_since, definition_id
+                   last_active_at, dormant_since, definition_id, tuning_norm
             FROM agents
             WHERE web_id = $1 AND state = $2
             "#,
@@ -310,7 +668,7 @@ impl Storage for PostgresStorage {
             r#"
             SELECT id, web_id, parent_id, purpose, tuning, capability, state, health,
                    activation_threshold, context, probation_remaining, created_at,
-                   last_active_at, dormant_since, definition_id
+                   last_active_at, dormant_since, definition_id, tuning_norm
             FROM agents
             WHERE web_id = $1
             "#,
@@ -335,11 +693,11 @@ impl Storage for PostgresStorage {
             SELECT
                 id, web_id, parent_id, purpose, tuning, capability, state, health,
                 activation_threshold, context, probation_remaining, created_at,
-                last_active_at, dormant_since, definition_id,
+                last_active_at, dormant_since, definition_id, tuning_norm,
                 1 - (tuning <=> $2::vector) as similarity
             FROM agents
             WHERE web_id = $1
-              AND state NOT IN ('Terminated', 'WindingDown')
+              AND state NOT IN ('Terminated', 'WindingDown', 'Paused')
               AND 1 - (tuning <=> $2::vector) > $3
             ORDER BY similarity DESC
             "#,
@@ -360,6 +718,7 @@ impl Storage for PostgresStorage {
     }
 
     async fn create_signal(&self, signal: &Signal) -> Result<()> {
+        check_embedding_dim(signal.frequency.len())?;
         let frequency_vec = Vector::from(signal.frequency.clone());
 
         sqlx::query(
@@ -371,6 +730,7 @@ impl Storage for PostgresStorage {
             SELECT $1, a.web_id, $2, $3, $4, $5, $6, $7, $8, false, NOW()
             FROM agents a
             WHERE a.id = $2
+            ON CONFLICT (id) DO NOTHING
             "#,
         )
         .bind(signal.id)
@@ -487,7 +847,290 @@ impl Storage for PostgresStorage {
             .collect()
     }
 
+    async fn record_tool_execution(&self, execution: &ToolExecution) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO tool_executions
+                (id, agent_id, web_id, tool_type, params, output, success,
+                 artifacts, side_effects, created_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+            "#,
+        )
+        .bind(execution.id)
+        .bind(execution.agent_id)
+        .bind(execution.web_id)
+        .bind(execution.tool_type.as_str())
+        .bind(&execution.params)
+        .bind(truncate_output(execution.output.clone()))
+        .bind(execution.success)
+        .bind(serde_json::Value::from(execution.artifacts.clone()))
+        .bind(serde_json::Value::from(execution.side_effects.clone()))
+        .bind(execution.created_at)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    async fn get_tool_executions(&self, agent_id: AgentId) -> Result<Vec<ToolExecution>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT id, agent_id, web_id, tool_type, params, output, success,
+                   artifacts, side_effects, created_at
+            FROM tool_executions
+            WHERE agent_id = $1
+            ORDER BY created_at ASC
+            "#,
+        )
+        .bind(agent_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.iter()
+            .map(|r| {
+                let type_str: String = r.get("tool_type");
+                let tool_type = ToolType::parse(&type_str)
+                    .ok_or_else(|| anyhow::anyhow!("Unknown tool type: {}", type_str))?;
+
+                Ok(ToolExecution {
+                    id: r.get("id"),
+                    agent_id: r.get("agent_id"),
+                    web_id: r.get("web_id"),
+                    tool_type,
+                    params: r.get("params"),
+                    output: r.get("output"),
+                    success: r.get("success"),
+                    artifacts: serde_json::from_value(r.get("artifacts"))?,
+                    side_effects: serde_json::from_value(r.get("side_effects"))?,
+                    created_at: r.get("created_at"),
+                })
+            })
+            .collect()
+    }
+
+    async fn enqueue_job(&self, job: &WebRunJob) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO job_queue
+                (id, web_id, priority, owner, status, attempts, claimed_by, visible_at,
+                 last_error, created_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+            "#,
+        )
+        .bind(job.id)
+        .bind(job.web_id)
+        .bind(job.priority)
+        .bind(&job.owner)
+        .bind(job.status.as_str())
+        .bind(job.attempts as i32)
+        .bind(&job.claimed_by)
+        .bind(job.visible_at)
+        .bind(&job.last_error)
+        .bind(job.created_at)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    async fn get_job(&self, id: JobId) -> Result<Option<WebRunJob>> {
+        let row = sqlx::query(&format!(
+            "SELECT {} FROM job_queue WHERE id = $1",
+            JOB_COLUMNS
+        ))
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await?;
+        row.as_ref().map(job_from_row).transpose()
+    }
+
+    async fn get_web_jobs(&self, web_id: WebId) -> Result<Vec<WebRunJob>> {
+        let rows = sqlx::query(&format!(
+            "SELECT {} FROM job_queue WHERE web_id = $1 ORDER BY created_at",
+            JOB_COLUMNS
+        ))
+        .bind(web_id)
+        .fetch_all(&self.pool)
+        .await?;
+        rows.iter().map(job_from_row).collect()
+    }
+
+    async fn claim_job(&self, worker_id: &str, policy: &ClaimPolicy) -> Result<Option<WebRunJob>> {
+        // SKIP LOCKED lets concurrent workers each take a different row
+        // instead of queueing behind the same one. The owner cap is checked
+        // without locking, so racing workers may briefly exceed it.
+        let aging_secs = policy.aging_interval.as_secs_f64();
+        let row = sqlx::query(&format!(
+            r#"
+            UPDATE job_queue
+            SET status = 'claimed',
+                claimed_by = $1,
+                attempts = attempts + 1,
+                visible_at = NOW() + make_interval(secs => $2),
+                updated_at = NOW()
+            WHERE id = (
+                SELECT id FROM job_queue
+                WHERE status IN ('queued', 'claimed') AND visible_at <= NOW()
+                  AND ($4::BIGINT IS NULL OR owner IS NULL OR owner NOT IN (
+                      SELECT owner FROM job_queue
+                      WHERE status = 'claimed' AND visible_at > NOW() AND owner IS NOT NULL
+                      GROUP BY owner
+                      HAVING COUNT(*) >= $4
+                  ))
+                ORDER BY priority + CASE WHEN $3 > 0
+                    THEN EXTRACT(EPOCH FROM NOW() - created_at) / $3
+                    ELSE 0 END DESC,
+                    created_at
+                LIMIT 1
+                FOR UPDATE SKIP LOCKED
+            )
+            RETURNING {}
+            "#,
+            JOB_COLUMNS
+        ))
+        .bind(worker_id)
+        .bind(policy.lease.as_secs_f64())
+        .bind(aging_secs)
+        .bind(policy.max_claims_per_owner.map(|cap| cap as i64))
+        .fetch_optional(&self.pool)
+        .await?;
+        row.as_ref().map(job_from_row).transpose()
+    }
+
+    async fn renew_job(
+        &self,
+        id: JobId,
+        worker_id: &str,
+        lease: std::time::Duration,
+    ) -> Result<bool> {
+        let result = sqlx::query(
+            r#"
+            UPDATE job_queue
+            SET visible_at = NOW() + make_interval(secs => $3), updated_at = NOW()
+            WHERE id = $1 AND claimed_by = $2 AND status = 'claimed'
+            "#,
+        )
+        .bind(id)
+        .bind(worker_id)
+        .bind(lease.as_secs_f64())
+        .execute(&self.pool)
+        .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn finish_job(
+        &self,
+        id: JobId,
+        worker_id: &str,
+        status: JobStatus,
+        error: Option<&str>,
+    ) -> Result<bool> {
+        let result = sqlx::query(
+            r#"
+            UPDATE job_queue
+            SET status = $3, last_error = $4, updated_at = NOW()
+            WHERE id = $1 AND claimed_by = $2 AND status = 'claimed'
+            "#,
+        )
+        .bind(id)
+        .bind(worker_id)
+        .bind(status.as_str())
+        .bind(error)
+        .execute(&self.pool)
+        .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn save_checkpoint(&self, checkpoint: &WebCheckpoint) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO web_checkpoints (web_id, iteration, snapshot, created_at)
+            VALUES ($1, $2, $3, $4)
+            ON CONFLICT (web_id) DO UPDATE
+            SET iteration = $2, snapshot = $3, created_at = $4
+            "#,
+        )
+        .bind(checkpoint.web.id)
+        .bind(checkpoint.iteration as i64)
+        .bind(serde_json::to_value(checkpoint)?)
+        .bind(checkpoint.created_at)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    async fn get_checkpoint(&self, web_id: WebId) -> Result<Option<WebCheckpoint>> {
+        let row = sqlx::query("SELECT snapshot FROM web_checkpoints WHERE web_id = $1")
+            .bind(web_id)
+            .fetch_optional(&self.pool)
+            .await?;
+        row.map(|r| Ok(serde_json::from_value(r.get("snapshot"))?))
+            .transpose()
+    }
+
+    async fn save_web_analytics(&self, analytics: &WebAnalytics) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO web_analytics (web_id, analytics, completed_at)
+            VALUES ($1, $2, $3)
+            ON CONFLICT (web_id) DO UPDATE
+            SET analytics = $2, completed_at = $3
+            "#,
+        )
+        .bind(analytics.web_id)
+        .bind(serde_json::to_value(analytics)?)
+        .bind(analytics.completed_at)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    async fn get_web_analytics(&self, web_id: WebId) -> Result<Option<WebAnalytics>> {
+        let row = sqlx::query("SELECT analytics FROM web_analytics WHERE web_id = $1")
+            .bind(web_id)
+            .fetch_optional(&self.pool)
+            .await?;
+        row.map(|r| Ok(serde_json::from_value(r.get("analytics"))?))
+            .transpose()
+    }
+
+    async fn record_token_usage(&self, web_id: WebId, usage: &Usage) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO web_token_usage (web_id, input_tokens, output_tokens, cost_usd)
+            VALUES ($1, $2, $3, $4)
+            ON CONFLICT (web_id) DO UPDATE
+            SET input_tokens = web_token_usage.input_tokens + $2,
+                output_tokens = web_token_usage.output_tokens + $3,
+                cost_usd = web_token_usage.cost_usd + $4,
+                updated_at = NOW()
+            "#,
+        )
+        .bind(web_id)
+        .bind(usage.input_tokens as i64)
+        .bind(usage.output_tokens as i64)
+        .bind(usage.cost_usd)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    async fn get_token_usage(&self, web_id: WebId) -> Result<Usage> {
+        let row = sqlx::query(
+            "SELECT input_tokens, output_tokens, cost_usd FROM web_token_usage WHERE web_id = $1",
+        )
+        .bind(web_id)
+        .fetch_optional(&self.pool)
+        .await?;
+        Ok(row.map_or_else(Usage::default, |r| Usage {
+            input_tokens: r.get::<i64, _>("input_tokens") as u64,
+            output_tokens: r.get::<i64, _>("output_tokens") as u64,
+            cost_usd: r.get("cost_usd"),
+        }))
+    }
+
     async fn create_definition(&self, definition: &AgentDefinition) -> Result<()> {
+        let mut definition = definition.clone();
+        definition.normalize();
+
         let tuning_vec = if definition.tuning_embedding.is_empty() {
             None
         } else {
@@ -504,9 +1147,9 @@ impl Storage for PostgresStorage {
             INSERT INTO agent_definitions (
                 id, name, tuning_keywords, tuning_embedding, system_prompt,
                 temperature, tools, source, health_score, use_count,
-                version, created_at, updated_at
+                version, created_at, max_tokens, updated_at
             )
-            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, NOW())
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, NOW())
             "#,
         )
         .bind(definition.id)
@@ -521,6 +1164,7 @@ impl Storage for PostgresStorage {
         .bind(definition.use_count as i32)
         .bind(&definition.version)
         .bind(definition.created_at)
+        .bind(definition.max_tokens.map(|t| t as i32))
         .execute(&self.pool)
         .await?;
         Ok(())
@@ -531,7 +1175,7 @@ impl Storage for PostgresStorage {
             r#"
             SELECT id, name, tuning_keywords, tuning_embedding, system_prompt,
                    temperature, tools, source, health_score, use_count,
-                   version, created_at
+                   version, created_at, deleted_at, max_tokens
             FROM agent_definitions
             WHERE id = $1
             "#,
@@ -551,9 +1195,9 @@ impl Storage for PostgresStorage {
             r#"
             SELECT id, name, tuning_keywords, tuning_embedding, system_prompt,
                    temperature, tools, source, health_score, use_count,
-                   version, created_at
+                   version, created_at, deleted_at, max_tokens
             FROM agent_definitions
-            WHERE name = $1
+            WHERE name = $1 AND deleted_at IS NULL
             "#,
         )
         .bind(name)
@@ -583,7 +1227,7 @@ impl Storage for PostgresStorage {
             UPDATE agent_definitions
             SET name = $2, tuning_keywords = $3, tuning_embedding = $4, system_prompt = $5,
                 temperature = $6, tools = $7, source = $8, health_score = $9, use_count = $10,
-                version = $11, updated_at = NOW()
+                version = $11, max_tokens = $12, updated_at = NOW()
             WHERE id = $1
             "#,
         )
@@ -598,6 +1242,7 @@ impl Storage for PostgresStorage {
         .bind(definition.health_score)
         .bind(definition.use_count as i32)
         .bind(&definition.version)
+        .bind(definition.max_tokens.map(|t| t as i32))
         .execute(&self.pool)
         .await?;
         Ok(())
@@ -613,9 +1258,9 @@ impl Storage for PostgresStorage {
                     r#"
                     SELECT id, name, tuning_keywords, tuning_embedding, system_prompt,
                            temperature, tools, source, health_score, use_count,
-                           version, created_at
+                           version, created_at, deleted_at, max_tokens
                     FROM agent_definitions
-                    WHERE source = $1
+                    WHERE source = $1 AND deleted_at IS NULL
                     ORDER BY use_count DESC, created_at DESC
                     "#,
                 )
@@ -628,8 +1273,9 @@ impl Storage for PostgresStorage {
                     r#"
                     SELECT id, name, tuning_keywords, tuning_embedding, system_prompt,
                            temperature, tools, source, health_score, use_count,
-                           version, created_at
+                           version, created_at, deleted_at, max_tokens
                     FROM agent_definitions
+                    WHERE deleted_at IS NULL
                     ORDER BY use_count DESC, created_at DESC
                     "#,
                 )
@@ -641,6 +1287,33 @@ impl Storage for PostgresStorage {
         rows.iter().map(row_to_definition).collect()
     }
 
+    async fn list_definitions_paged(
+        &self,
+        source: Option<DefinitionSource>,
+        limit: usize,
+        offset: usize,
+    ) -> Result<Vec<AgentDefinition>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT id, name, tuning_keywords, tuning_embedding, system_prompt,
+                   temperature, tools, source, health_score, use_count,
+                   version, created_at, deleted_at, max_tokens
+            FROM agent_definitions
+            WHERE ($1::text IS NULL OR source = $1)
+              AND deleted_at IS NULL
+            ORDER BY use_count DESC, id
+            LIMIT $2 OFFSET $3
+            "#,
+        )
+        .bind(source.as_ref().map(source_to_str))
+        .bind(limit as i64)
+        .bind(offset as i64)
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.iter().map(row_to_definition).collect()
+    }
+
     async fn find_definitions_by_similarity(
         &self,
         embedding: &[f32],
@@ -651,18 +1324,24 @@ impl Storage for PostgresStorage {
         let embedding_vec = Vector::from(embedding.to_vec());
         let source_strs: Vec<String> = sources.iter().map(source_to_str).collect();
 
+        // Nearest first with a LIMIT, so the HNSW index can serve the inner
+        // query; the threshold only applies to what it returns.
         let rows = sqlx::query(
             r#"
-            SELECT id, name, tuning_keywords, tuning_embedding, system_prompt,
-                   temperature, tools, source, health_score, use_count,
-                   version, created_at,
-                   1 - (tuning_embedding <=> $1::vector) as similarity
-            FROM agent_definitions
-            WHERE tuning_embedding IS NOT NULL
-              AND source = ANY($2)
-              AND 1 - (tuning_embedding <=> $1::vector) > $3
+            SELECT * FROM (
+                SELECT id, name, tuning_keywords, tuning_embedding, system_prompt,
+                       temperature, tools, source, health_score, use_count,
+                       version, created_at, deleted_at, max_tokens,
+                       1 - (tuning_embedding <=> $1::vector) as similarity
+                FROM agent_definitions
+                WHERE tuning_embedding IS NOT NULL
+                  AND deleted_at IS NULL
+                  AND source = ANY($2)
+                ORDER BY tuning_embedding <=> $1::vector
+                LIMIT $4
+            ) nearest
+            WHERE similarity > $3
             ORDER BY similarity DESC
-            LIMIT $4
             "#,
         )
         .bind(embedding_vec)
@@ -681,6 +1360,92 @@ impl Storage for PostgresStorage {
             .collect()
     }
 
+    async fn get_definitions_by_health(
+        &self,
+        max_health: f32,
+        source: Option<DefinitionSource>,
+    ) -> Result<Vec<AgentDefinition>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT id, name, tuning_keywords, tuning_embedding, system_prompt,
+                   temperature, tools, source, health_score, use_count,
+                   version, created_at, deleted_at, max_tokens
+            FROM agent_definitions
+            WHERE health_score <= $1
+              AND ($2::text IS NULL OR source = $2)
+              AND deleted_at IS NULL
+            ORDER BY health_score ASC, name ASC
+            "#,
+        )
+        .bind(max_health)
+        .bind(source.as_ref().map(source_to_str))
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.iter().map(row_to_definition).collect()
+    }
+
+    async fn soft_delete_definition(&self, id: DefinitionId) -> Result<bool> {
+        let result = sqlx::query(
+            r#"
+            UPDATE agent_definitions
+            SET deleted_at = NOW(), updated_at = NOW()
+            WHERE id = $1 AND deleted_at IS NULL
+            "#,
+        )
+        .bind(id)
+        .execute(&self.pool)
+        .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn restore_definition(&self, id: DefinitionId) -> Result<bool> {
+        let result = sqlx::query(
+            r#"
+            UPDATE agent_definitions
+            SET deleted_at = NULL, updated_at = NOW()
+            WHERE id = $1 AND deleted_at IS NOT NULL
+            "#,
+        )
+        .bind(id)
+        .execute(&self.pool)
+        .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn delete_definition(&self, id: DefinitionId) -> Result<bool> {
+        let mut tx = self.pool.begin().await?;
+
+        let row =
+            sqlx::query("SELECT name, source FROM agent_definitions WHERE id = $1 FOR UPDATE")
+                .bind(id)
+                .fetch_optional(&mut *tx)
+                .await?;
+        let Some(row) = row else {
+            return Ok(false);
+        };
+        if str_to_source(row.get("source")) == DefinitionSource::BuiltIn {
+            let name: String = row.get("name");
+            return Err(anyhow::anyhow!(
+                "Cannot delete built-in definition {}",
+                name
+            ));
+        }
+
+        // Unlink agents first so the foreign key doesn't block the delete.
+        sqlx::query("UPDATE agents SET definition_id = NULL WHERE definition_id = $1")
+            .bind(id)
+            .execute(&mut *tx)
+            .await?;
+        sqlx::query("DELETE FROM agent_definitions WHERE id = $1")
+            .bind(id)
+            .execute(&mut *tx)
+            .await?;
+
+        tx.commit().await?;
+        Ok(true)
+    }
+
     async fn increment_definition_use_count(&self, id: DefinitionId) -> Result<()> {
         sqlx::query(
             r#"
@@ -713,7 +1478,13 @@ impl Storage for PostgresStorage {
 }
 
 fn row_to_agent(r: &sqlx::postgres::PgRow) -> Result<Agent> {
-    let tuning_vec: Vector = r.get("tuning");
+    let tuning: Vec<f32> = r.get::<Vector, _>("tuning").to_vec();
+    // NULL for rows written before V018.
+    let tuning_norm = r
+        .try_get::<Option<f32>, _>("tuning_norm")
+        .ok()
+        .flatten()
+        .unwrap_or_else(|| magnitude(&tuning));
     let cap_str: String = r.get("capability");
     let state_str: String = r.get("state");
 
@@ -723,6 +1494,7 @@ fn row_to_agent(r: &sqlx::postgres::PgRow) -> Result<Agent> {
         "CodeWriter" => CapabilityType::CodeWriter,
         "CodeReviewer" => CapabilityType::CodeReviewer,
         "Analyst" => CapabilityType::Analyst,
+        "Planner" => CapabilityType::Planner,
         s => CapabilityType::Custom(s.to_string()),
     };
 
@@ -730,6 +1502,7 @@ fn row_to_agent(r: &sqlx::postgres::PgRow) -> Result<Agent> {
         "Active" => AgentState::Active,
         "Listening" => AgentState::Listening,
         "Dormant" => AgentState::Dormant,
+        "Paused" => AgentState::Paused,
         "Quarantine" => AgentState::Quarantine,
         "Isolated" => AgentState::Isolated,
         "WindingDown" => AgentState::WindingDown,
@@ -745,7 +1518,8 @@ fn row_to_agent(r: &sqlx::postgres::PgRow) -> Result<Agent> {
         web_id: r.get("web_id"),
         parent_id: r.get("parent_id"),
         purpose: r.get("purpose"),
-        tuning: tuning_vec.to_vec(),
+        tuning,
+        tuning_norm,
         capability,
         state,
         health: r.get("health"),
@@ -766,6 +1540,7 @@ fn capability_to_str(capability: &CapabilityType) -> String {
         CapabilityType::CodeWriter => "CodeWriter".to_string(),
         CapabilityType::CodeReviewer => "CodeReviewer".to_string(),
         CapabilityType::Analyst => "Analyst".to_string(),
+        CapabilityType::Planner => "Planner".to_string(),
         CapabilityType::Custom(s) => s.clone(),
     }
 }
@@ -813,12 +1588,14 @@ fn row_to_definition(r: &sqlx::postgres::PgRow) -> Result<AgentDefinition> {
         tuning_embedding,
         system_prompt: r.get("system_prompt"),
         temperature: r.get("temperature"),
+        max_tokens: r.get::<Option<i32>, _>("max_tokens").map(|t| t as u32),
         tools,
         source,
         health_score: r.get("health_score"),
         use_count: r.get::<i32, _>("use_count") as u32,
         created_at: r.get("created_at"),
         version: r.get("version"),
+        deleted_at: r.get("deleted_at"),
     })
 }
 
@@ -831,3 +1608,62 @@ impl WebState {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io;
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    fn quick_retry(attempts: u32) -> ConnectRetry {
+        ConnectRetry {
+            attempts,
+            initial_backoff: Duration::from_millis(1),
+            max_backoff: Duration::from_millis(4),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_connect_retry_waits_for_database() {
+        let calls = AtomicU32::new(0);
+        // Refuses the first three connections, as a database still starting does.
+        let result = quick_retry(5)
+            .run(|| async {
+                if calls.fetch_add(1, Ordering::SeqCst) < 3 {
+                    Err(sqlx::Error::Io(io::ErrorKind::ConnectionRefused.into()))
+                } else {
+                    Ok("connected")
+                }
+            })
+            .await;
+
+        assert_eq!(result.unwrap(), "connected");
+        assert_eq!(calls.load(Ordering::SeqCst), 4);
+    }
+
+    #[tokio::test]
+    async fn test_connect_retry_gives_up() {
+        let calls = AtomicU32::new(0);
+        let result: Result<()> = quick_retry(3)
+            .run(|| async {
+                calls.fetch_add(1, Ordering::SeqCst);
+                Err(sqlx::Error::Io(io::ErrorKind::ConnectionRefused.into()))
+            })
+            .await;
+        assert!(result.is_err());
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+
+        // Configuration errors such as a bad URL or credentials fail at once.
+        let calls = AtomicU32::new(0);
+        let result: Result<()> = quick_retry(3)
+            .run(|| async {
+                calls.fetch_add(1, Ordering::SeqCst);
+                Err(sqlx::Error::Configuration(
+                    "password authentication failed".into(),
+                ))
+            })
+            .await;
+        assert!(result.is_err());
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+}
@@ -0,0 +1,231 @@
+use anyhow::Result;
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::storage::traits::{ClaimPolicy, JobStatus, Storage, WebRunJob};
+use crate::types::Web;
+
+/// How long a claim lasts before another worker may take the job over.
+pub const DEFAULT_VISIBILITY_TIMEOUT: Duration = Duration::from_secs(60);
+/// Claims after which a job that keeps getting abandoned is failed.
+pub const DEFAULT_MAX_ATTEMPTS: u32 = 3;
+/// Waiting this long raises a queued web's priority by one.
+pub const DEFAULT_PRIORITY_AGING: Duration = Duration::from_secs(60);
+
+/// Web runs queued in `Storage`, so any worker sharing the storage can pick
+/// them up and a job survives the worker that queued it.
+#[derive(Clone)]
+pub struct WebRunQueue {
+    storage: Arc<dyn Storage>,
+    worker_id: String,
+    visibility_timeout: Duration,
+    max_attempts: u32,
+    priority_aging: Duration,
+    max_claims_per_owner: Option<usize>,
+}
+
+impl WebRunQueue {
+    pub fn new(storage: Arc<dyn Storage>, worker_id: impl Into<String>) -> Self {
+        Self {
+            storage,
+            worker_id: worker_id.into(),
+            visibility_timeout: DEFAULT_VISIBILITY_TIMEOUT,
+            max_attempts: DEFAULT_MAX_ATTEMPTS,
+            priority_aging: DEFAULT_PRIORITY_AGING,
+            max_claims_per_owner: None,
+        }
+    }
+
+    /// Applies `ARACHNID_PRIORITY_AGING_SECS` and `ARACHNID_MAX_WEBS_PER_OWNER`.
+    pub fn from_env(storage: Arc<dyn Storage>, worker_id: impl Into<String>) -> Self {
+        let env = |var: &str| std::env::var(var).ok().and_then(|v| v.parse::<u64>().ok());
+
+        let mut queue = Self::new(storage, worker_id);
+        if let Some(secs) = env("ARACHNID_PRIORITY_AGING_SECS") {
+            queue = queue.with_priority_aging(Duration::from_secs(secs));
+        }
+        queue.with_max_claims_per_owner(env("ARACHNID_MAX_WEBS_PER_OWNER").map(|n| n as usize))
+    }
+
+    pub fn with_visibility_timeout(mut self, timeout: Duration) -> Self {
+        self.visibility_timeout = timeout;
+        self
+    }
+
+    pub fn with_max_attempts(mut self, max_attempts: u32) -> Self {
+        self.max_attempts = max_attempts.max(1);
+        self
+    }
+
+    /// Zero turns aging off, so jobs run strictly by priority.
+    pub fn with_priority_aging(mut self, aging: Duration) -> Self {
+        self.priority_aging = aging;
+        self
+    }
+
+    /// Cap on webs one owner may have running at once across all workers.
+    pub fn with_max_claims_per_owner(mut self, cap: Option<usize>) -> Self {
+        self.max_claims_per_owner = cap;
+        self
+    }
+
+    pub fn worker_id(&self) -> &str {
+        &self.worker_id
+    }
+
+    pub fn visibility_timeout(&self) -> Duration {
+        self.visibility_timeout
+    }
+
+    pub async fn enqueue_web_run(&self, web: &Web) -> Result<WebRunJob> {
+        let job = WebRunJob::new(web);
+        self.storage.enqueue_job(&job).await?;
+        Ok(job)
+    }
+
+    /// Claim the available job with the highest aged priority, skipping
+    /// owners at their cap. Jobs that have already been claimed
+    /// `max_attempts` times are failed and skipped.
+    pub async fn claim_next(&self) -> Result<Option<WebRunJob>> {
+        let policy = ClaimPolicy {
+            lease: self.visibility_timeout,
+            aging_interval: self.priority_aging,
+            max_claims_per_owner: self.max_claims_per_owner,
+        };
+        loop {
+            let Some(job) = self.storage.claim_job(&self.worker_id, &policy).await? else {
+                return Ok(None);
+            };
+
+            if job.attempts <= self.max_attempts {
+                return Ok(Some(job));
+            }
+            let error = format!("Abandoned after {} attempts", self.max_attempts);
+            self.fail(&job, &error).await?;
+        }
+    }
+
+    /// Extend the claim on a job still being worked on.
+    pub async fn renew(&self, job: &WebRunJob) -> Result<bool> {
+        self.storage
+            .renew_job(job.id, &self.worker_id, self.visibility_timeout)
+            .await
+    }
+
+    pub async fn complete(&self, job: &WebRunJob) -> Result<bool> {
+        self.storage
+            .finish_job(job.id, &self.worker_id, JobStatus::Completed, None)
+            .await
+    }
+
+    pub async fn fail(&self, job: &WebRunJob, error: &str) -> Result<bool> {
+        self.storage
+            .finish_job(job.id, &self.worker_id, JobStatus::Failed, Some(error))
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::memory::InMemoryStore;
+    use crate::types::WebConfig;
+    use std::collections::HashMap;
+
+    fn web(priority: i32, owner: Option<&str>) -> Web {
+        let metadata = owner
+            .map(|o| HashMap::from([("owner".to_string(), o.to_string())]))
+            .unwrap_or_default();
+        Web::new(
+            uuid::Uuid::new_v4(),
+            "task".to_string(),
+            WebConfig::default(),
+        )
+        .with_priority(priority)
+        .with_metadata(metadata)
+    }
+
+    #[tokio::test]
+    async fn test_workers_do_not_claim_the_same_job() {
+        let storage: Arc<dyn Storage> = Arc::new(InMemoryStore::new());
+        let first = WebRunQueue::new(storage.clone(), "worker-1");
+        let second = WebRunQueue::new(storage.clone(), "worker-2");
+
+        let job = first.enqueue_web_run(&web(0, None)).await.unwrap();
+
+        let (a, b) = tokio::join!(first.claim_next(), second.claim_next());
+        let claimed: Vec<_> = [a.unwrap(), b.unwrap()].into_iter().flatten().collect();
+        assert_eq!(claimed.len(), 1);
+        assert_eq!(claimed[0].id, job.id);
+
+        // Only the holder can finish it.
+        let holder = if claimed[0].claimed_by.as_deref() == Some("worker-1") {
+            (&first, &second)
+        } else {
+            (&second, &first)
+        };
+        assert!(!holder.1.complete(&job).await.unwrap());
+        assert!(holder.0.complete(&job).await.unwrap());
+
+        let job = storage.get_job(job.id).await.unwrap().unwrap();
+        assert_eq!(job.status, JobStatus::Completed);
+        assert!(first.claim_next().await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_expired_claim_is_reclaimed() {
+        let storage: Arc<dyn Storage> = Arc::new(InMemoryStore::new());
+        let crashed = WebRunQueue::new(storage.clone(), "crashed")
+            .with_visibility_timeout(Duration::from_millis(20));
+        let survivor = WebRunQueue::new(storage.clone(), "survivor").with_max_attempts(2);
+
+        let job = crashed.enqueue_web_run(&web(0, None)).await.unwrap();
+        assert!(crashed.claim_next().await.unwrap().is_some());
+        assert!(survivor.claim_next().await.unwrap().is_none());
+
+        tokio::time::sleep(Duration::from_millis(40)).await;
+        let reclaimed = survivor.claim_next().await.unwrap().unwrap();
+        assert_eq!(reclaimed.id, job.id);
+        assert_eq!(reclaimed.attempts, 2);
+        assert!(!crashed.renew(&job).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_claims_by_priority_with_aging() {
+        let storage: Arc<dyn Storage> = Arc::new(InMemoryStore::new());
+        let queue = WebRunQueue::new(storage.clone(), "worker")
+            .with_priority_aging(Duration::from_millis(10));
+
+        let low = queue.enqueue_web_run(&web(0, None)).await.unwrap();
+        let high = queue.enqueue_web_run(&web(5, None)).await.unwrap();
+        assert_eq!(queue.claim_next().await.unwrap().unwrap().id, high.id);
+
+        // After waiting, the old low-priority job outranks a fresh one at 1.
+        tokio::time::sleep(Duration::from_millis(60)).await;
+        let fresh = queue.enqueue_web_run(&web(1, None)).await.unwrap();
+        assert_eq!(queue.claim_next().await.unwrap().unwrap().id, low.id);
+        assert_eq!(queue.claim_next().await.unwrap().unwrap().id, fresh.id);
+    }
+
+    #[tokio::test]
+    async fn test_owner_cap_lets_other_owners_run() {
+        let storage: Arc<dyn Storage> = Arc::new(InMemoryStore::new());
+        let queue = WebRunQueue::new(storage.clone(), "worker")
+            .with_priority_aging(Duration::ZERO)
+            .with_max_claims_per_owner(Some(1));
+
+        let busy_first = queue.enqueue_web_run(&web(9, Some("busy"))).await.unwrap();
+        let busy_second = queue.enqueue_web_run(&web(9, Some("busy"))).await.unwrap();
+        let quiet = queue.enqueue_web_run(&web(0, Some("quiet"))).await.unwrap();
+
+        assert_eq!(queue.claim_next().await.unwrap().unwrap().id, busy_first.id);
+        assert_eq!(queue.claim_next().await.unwrap().unwrap().id, quiet.id);
+        assert!(queue.claim_next().await.unwrap().is_none());
+
+        queue.complete(&busy_first).await.unwrap();
+        assert_eq!(
+            queue.claim_next().await.unwrap().unwrap().id,
+            busy_second.id
+        );
+    }
+}
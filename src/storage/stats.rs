@@ -0,0 +1,181 @@
+use anyhow::Result;
+use serde::Serialize;
+
+use crate::definitions::DefinitionSource;
+use crate::storage::traits::Storage;
+use crate::types::WebState;
+
+/// System-wide counts, as shown by `arachnid stats`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct StorageStats {
+    pub webs: WebCounts,
+    pub agents: AgentCounts,
+    pub definitions: DefinitionCounts,
+    /// Mean time to convergence over converged webs that have analytics.
+    pub avg_convergence_ms: Option<f64>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct WebCounts {
+    pub running: usize,
+    pub converged: usize,
+    pub failed: usize,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct AgentCounts {
+    pub total: usize,
+    /// Agents of webs that are still running.
+    pub active: usize,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct DefinitionCounts {
+    pub built_in: usize,
+    pub user_custom: usize,
+    pub generated: usize,
+}
+
+impl StorageStats {
+    pub async fn collect(storage: &dyn Storage) -> Result<Self> {
+        let mut stats = Self::default();
+        let mut convergence_times = Vec::new();
+
+        for web in storage.list_webs(None).await? {
+            let agents = storage.get_web_agents(web.id).await?.len();
+            stats.agents.total += agents;
+            match web.state {
+                WebState::Running => {
+                    stats.webs.running += 1;
+                    stats.agents.active += agents;
+                }
+                WebState::Converged => {
+                    stats.webs.converged += 1;
+                    if let Some(ms) = storage
+                        .get_web_analytics(web.id)
+                        .await?
+                        .and_then(|a| a.time_to_convergence_ms)
+                    {
+                        convergence_times.push(ms as f64);
+                    }
+                }
+                WebState::Failed => stats.webs.failed += 1,
+            }
+        }
+
+        for definition in storage.list_definitions(None).await? {
+            match definition.source {
+                DefinitionSource::BuiltIn => stats.definitions.built_in += 1,
+                DefinitionSource::UserCustom => stats.definitions.user_custom += 1,
+                DefinitionSource::Generated => stats.definitions.generated += 1,
+            }
+        }
+
+        if !convergence_times.is_empty() {
+            stats.avg_convergence_ms =
+                Some(convergence_times.iter().sum::<f64>() / convergence_times.len() as f64);
+        }
+        Ok(stats)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::definitions::builtin::task_coordinator_definition;
+    use crate::storage::memory::InMemoryStore;
+    use crate::storage::traits::WebAnalytics;
+    use crate::types::{Agent, CapabilityType, Web, WebConfig};
+
+    async fn seed_web(storage: &InMemoryStore, state: WebState, agents: usize) -> Web {
+        let mut web = Web::new(
+            uuid::Uuid::new_v4(),
+            "task".to_string(),
+            WebConfig::default(),
+        );
+        web.state = state;
+        storage.create_web(&web).await.unwrap();
+        for _ in 0..agents {
+            let agent = Agent::new(
+                web.id,
+                None,
+                "agent".to_string(),
+                vec![1.0, 0.0],
+                CapabilityType::Search,
+                0.5,
+            );
+            storage.create_agent(&agent).await.unwrap();
+        }
+        web
+    }
+
+    async fn converged_in(storage: &InMemoryStore, web: &Web, ms: u64) {
+        storage
+            .save_web_analytics(&WebAnalytics {
+                web_id: web.id,
+                state: WebState::Converged,
+                time_to_convergence_ms: Some(ms),
+                max_depth: 1,
+                peak_active_agents: 1,
+                iterations: 3,
+                completed_at: chrono::Utc::now(),
+            })
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_collect_stats() {
+        let storage = InMemoryStore::new();
+        assert_eq!(
+            StorageStats::collect(&storage).await.unwrap(),
+            StorageStats::default()
+        );
+
+        seed_web(&storage, WebState::Running, 3).await;
+        let fast = seed_web(&storage, WebState::Converged, 2).await;
+        let slow = seed_web(&storage, WebState::Converged, 1).await;
+        seed_web(&storage, WebState::Converged, 1).await;
+        seed_web(&storage, WebState::Failed, 4).await;
+        converged_in(&storage, &fast, 1000).await;
+        converged_in(&storage, &slow, 3000).await;
+
+        for (name, source) in [
+            ("a", DefinitionSource::BuiltIn),
+            ("b", DefinitionSource::BuiltIn),
+            ("c", DefinitionSource::Generated),
+        ] {
+            let mut definition = task_coordinator_definition();
+            definition.id = uuid::Uuid::new_v4();
+            definition.name = name.to_string();
+            definition.source = source;
+            storage.create_definition(&definition).await.unwrap();
+        }
+
+        let stats = StorageStats::collect(&storage).await.unwrap();
+        assert_eq!(
+            stats.webs,
+            WebCounts {
+                running: 1,
+                converged: 3,
+                failed: 1,
+            }
+        );
+        assert_eq!(
+            stats.agents,
+            AgentCounts {
+                total: 11,
+                active: 3,
+            }
+        );
+        assert_eq!(
+            stats.definitions,
+            DefinitionCounts {
+                built_in: 2,
+                user_custom: 0,
+                generated: 1,
+            }
+        );
+        assert_eq!(stats.avg_convergence_ms, Some(2000.0));
+    }
+}
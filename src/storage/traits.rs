@@ -1,8 +1,10 @@
 use anyhow::Result;
 use async_trait::async_trait;
 
-use crate::definitions::{AgentDefinition, DefinitionId, DefinitionSource};
-use crate::types::{Agent, AgentId, AgentState, Signal, SignalId, Web, WebId, WebState};
+use crate::definitions::{AgentDefinition, DefinitionId, DefinitionSource, ToolType};
+use crate::providers::usage::Usage;
+use crate::types::{Agent, AgentId, AgentState, Signal, SignalId, Web, WebConfig, WebId, WebState};
+use std::time::Duration;
 
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct FailurePattern {
@@ -32,12 +34,219 @@ impl FailurePatternType {
     }
 }
 
+/// Serialized output beyond this many bytes is replaced by a truncated preview.
+pub const MAX_STORED_OUTPUT_BYTES: usize = 8 * 1024;
+
+/// Audit record of a single tool call made by an agent.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct ToolExecution {
+    pub id: uuid::Uuid,
+    pub agent_id: AgentId,
+    pub web_id: WebId,
+    pub tool_type: ToolType,
+    pub params: serde_json::Value,
+    pub output: serde_json::Value,
+    pub success: bool,
+    /// Summaries of the files and data the call produced.
+    #[serde(default)]
+    pub artifacts: Vec<serde_json::Value>,
+    /// Summaries of what the call changed: signals, spawns, files, runs.
+    #[serde(default)]
+    pub side_effects: Vec<serde_json::Value>,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
+
+impl ToolExecution {
+    pub fn new(
+        agent_id: AgentId,
+        web_id: WebId,
+        tool_type: ToolType,
+        params: serde_json::Value,
+        output: serde_json::Value,
+        success: bool,
+    ) -> Self {
+        Self {
+            id: uuid::Uuid::new_v4(),
+            agent_id,
+            web_id,
+            tool_type,
+            params,
+            output: truncate_output(output),
+            success,
+            artifacts: Vec::new(),
+            side_effects: Vec::new(),
+            created_at: chrono::Utc::now(),
+        }
+    }
+
+    pub fn with_effects(
+        mut self,
+        artifacts: Vec<serde_json::Value>,
+        side_effects: Vec<serde_json::Value>,
+    ) -> Self {
+        self.artifacts = artifacts;
+        self.side_effects = side_effects;
+        self
+    }
+}
+
+/// Backends apply this again when recording, for executions built without
+/// `ToolExecution::new`.
+pub(crate) fn truncate_output(output: serde_json::Value) -> serde_json::Value {
+    let serialized = output.to_string();
+    if serialized.len() <= MAX_STORED_OUTPUT_BYTES {
+        return output;
+    }
+
+    let mut end = MAX_STORED_OUTPUT_BYTES;
+    while !serialized.is_char_boundary(end) {
+        end -= 1;
+    }
+    serde_json::json!({
+        "truncated": true,
+        "size": serialized.len(),
+        "preview": &serialized[..end],
+    })
+}
+
+pub type JobId = uuid::Uuid;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub enum JobStatus {
+    Queued,
+    Claimed,
+    Completed,
+    Failed,
+}
+
+impl JobStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            JobStatus::Queued => "queued",
+            JobStatus::Claimed => "claimed",
+            JobStatus::Completed => "completed",
+            JobStatus::Failed => "failed",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "queued" => Some(JobStatus::Queued),
+            "claimed" => Some(JobStatus::Claimed),
+            "completed" => Some(JobStatus::Completed),
+            "failed" => Some(JobStatus::Failed),
+            _ => None,
+        }
+    }
+}
+
+/// A queued request to run a web.
+///
+/// A claimed job is leased to one worker until `visible_at`; if the worker
+/// doesn't finish or renew it by then, another worker may claim it.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct WebRunJob {
+    pub id: JobId,
+    pub web_id: WebId,
+    pub priority: i32,
+    pub owner: Option<String>,
+    pub status: JobStatus,
+    pub attempts: u32,
+    pub claimed_by: Option<String>,
+    pub visible_at: chrono::DateTime<chrono::Utc>,
+    pub last_error: Option<String>,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
+
+impl WebRunJob {
+    pub fn new(web: &Web) -> Self {
+        let now = chrono::Utc::now();
+        Self {
+            id: JobId::new_v4(),
+            web_id: web.id,
+            priority: web.priority,
+            owner: web.owner().map(str::to_string),
+            status: JobStatus::Queued,
+            attempts: 0,
+            claimed_by: None,
+            visible_at: now,
+            last_error: None,
+            created_at: now,
+        }
+    }
+
+    /// Whether a worker may claim the job at `now`.
+    pub fn is_claimable(&self, now: chrono::DateTime<chrono::Utc>) -> bool {
+        matches!(self.status, JobStatus::Queued | JobStatus::Claimed) && self.visible_at <= now
+    }
+
+    /// Priority raised by one for every `aging_interval` the job has waited,
+    /// so low-priority jobs can't be starved indefinitely.
+    pub fn effective_priority(
+        &self,
+        now: chrono::DateTime<chrono::Utc>,
+        aging_interval: Duration,
+    ) -> f64 {
+        let waited = (now - self.created_at).num_milliseconds().max(0) as f64 / 1000.0;
+        let aging = aging_interval.as_secs_f64();
+        if aging > 0.0 {
+            self.priority as f64 + waited / aging
+        } else {
+            self.priority as f64
+        }
+    }
+}
+
+/// Snapshot of a web's in-flight state, enough to pick the run back up
+/// after the process holding it crashed.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct WebCheckpoint {
+    pub web: Web,
+    pub agents: Vec<Agent>,
+    pub pending_signals: Vec<Signal>,
+    /// Coordination loop iterations completed when the snapshot was taken.
+    pub iteration: u64,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// How a web's coordination loop went, computed once the web finishes.
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct WebAnalytics {
+    pub web_id: WebId,
+    pub state: WebState,
+    /// Loop run time, for webs that converged.
+    pub time_to_convergence_ms: Option<u64>,
+    /// Deepest agent below the root, from parent links.
+    pub max_depth: usize,
+    /// Most agents activated within a single loop iteration.
+    pub peak_active_agents: usize,
+    pub iterations: u64,
+    pub completed_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// How `Storage::claim_job` picks among available jobs.
+#[derive(Debug, Clone)]
+pub struct ClaimPolicy {
+    /// How long the claim lasts before the job can be taken over.
+    pub lease: Duration,
+    /// See [`WebRunJob::effective_priority`]; zero disables aging.
+    pub aging_interval: Duration,
+    /// Owners already holding this many claims are skipped.
+    pub max_claims_per_owner: Option<usize>,
+}
+
 #[async_trait]
 pub trait Storage: Send + Sync {
+    /// Fails when the backing store can't be reached.
+    async fn ping(&self) -> Result<()>;
+
     // Web operations
     async fn create_web(&self, web: &Web) -> Result<()>;
     async fn get_web(&self, id: WebId) -> Result<Option<Web>>;
     async fn update_web(&self, web: &Web) -> Result<()>;
+    /// Replace only the config of a web that hasn't finished, leaving its
+    /// state alone. Returns `false` if the web is missing or finished.
+    async fn update_web_config(&self, id: WebId, config: &WebConfig) -> Result<bool>;
     async fn list_webs(&self, state: Option<WebState>) -> Result<Vec<Web>>;
 
     // Agent operations
@@ -56,6 +265,7 @@ pub trait Storage: Send + Sync {
     ) -> Result<Vec<(Agent, f32)>>;
 
     // Signal operations
+    /// A no-op for a signal that already exists.
     async fn create_signal(&self, signal: &Signal) -> Result<()>;
     async fn get_pending_signals(&self, web_id: WebId) -> Result<Vec<Signal>>;
     async fn mark_signal_processed(&self, id: SignalId) -> Result<()>;
@@ -64,8 +274,51 @@ pub trait Storage: Send + Sync {
     async fn record_failure_pattern(&self, web_id: WebId, pattern: &FailurePattern) -> Result<()>;
     async fn get_failure_patterns(&self, web_id: WebId) -> Result<Vec<FailurePattern>>;
 
+    // Tool audit
+    async fn record_tool_execution(&self, execution: &ToolExecution) -> Result<()>;
+    /// Executions for an agent, oldest first.
+    async fn get_tool_executions(&self, agent_id: AgentId) -> Result<Vec<ToolExecution>>;
+
+    // Job queue
+    async fn enqueue_job(&self, job: &WebRunJob) -> Result<()>;
+    async fn get_job(&self, id: JobId) -> Result<Option<WebRunJob>>;
+    /// Every job queued for `web_id`, oldest first.
+    async fn get_web_jobs(&self, web_id: WebId) -> Result<Vec<WebRunJob>>;
+    /// Atomically lease the claimable job with the highest effective
+    /// priority (oldest first on ties) to `worker_id`.
+    async fn claim_job(&self, worker_id: &str, policy: &ClaimPolicy) -> Result<Option<WebRunJob>>;
+    /// Extend a lease; returns `false` if the worker no longer holds the job.
+    async fn renew_job(&self, id: JobId, worker_id: &str, lease: Duration) -> Result<bool>;
+    /// Mark a job held by `worker_id` completed or failed; returns `false`
+    /// if the worker no longer holds it.
+    async fn finish_job(
+        &self,
+        id: JobId,
+        worker_id: &str,
+        status: JobStatus,
+        error: Option<&str>,
+    ) -> Result<bool>;
+
+    // Checkpoint operations
+    /// Replaces any earlier checkpoint of the same web.
+    async fn save_checkpoint(&self, checkpoint: &WebCheckpoint) -> Result<()>;
+    async fn get_checkpoint(&self, web_id: WebId) -> Result<Option<WebCheckpoint>>;
+
+    // Analytics operations
+    /// Replaces any earlier analytics of the same web.
+    async fn save_web_analytics(&self, analytics: &WebAnalytics) -> Result<()>;
+    async fn get_web_analytics(&self, web_id: WebId) -> Result<Option<WebAnalytics>>;
+
+    // Token usage operations
+    /// Adds `usage` to the web's running total.
+    async fn record_token_usage(&self, web_id: WebId, usage: &Usage) -> Result<()>;
+    /// Zero for a web that has made no LLM calls.
+    async fn get_token_usage(&self, web_id: WebId) -> Result<Usage>;
+
     // Definition operations
     async fn create_definition(&self, definition: &AgentDefinition) -> Result<()>;
+    /// Resolves soft-deleted definitions too, so agents spawned from one
+    /// keep it. Every other lookup leaves them out.
     async fn get_definition(&self, id: DefinitionId) -> Result<Option<AgentDefinition>>;
     async fn get_definition_by_name(&self, name: &str) -> Result<Option<AgentDefinition>>;
     async fn update_definition(&self, definition: &AgentDefinition) -> Result<()>;
@@ -73,6 +326,16 @@ pub trait Storage: Send + Sync {
         &self,
         source: Option<DefinitionSource>,
     ) -> Result<Vec<AgentDefinition>>;
+    /// One page of `list_definitions`, most used first with ties broken by
+    /// id, so consecutive pages neither repeat nor skip definitions.
+    async fn list_definitions_paged(
+        &self,
+        source: Option<DefinitionSource>,
+        limit: usize,
+        offset: usize,
+    ) -> Result<Vec<AgentDefinition>>;
+    /// Definitions from `sources` whose tuning embedding has a cosine
+    /// similarity above `threshold`, most similar first, at most `limit`.
     async fn find_definitions_by_similarity(
         &self,
         embedding: &[f32],
@@ -80,6 +343,23 @@ pub trait Storage: Send + Sync {
         sources: &[DefinitionSource],
         limit: usize,
     ) -> Result<Vec<(AgentDefinition, f32)>>;
+    /// Definitions with a health score at or below `max_health`, least
+    /// healthy first, for finding ones worth pruning.
+    async fn get_definitions_by_health(
+        &self,
+        max_health: f32,
+        source: Option<DefinitionSource>,
+    ) -> Result<Vec<AgentDefinition>>;
+    /// Hide a definition from listings and matching. Returns whether a
+    /// definition that wasn't already deleted was found.
+    async fn soft_delete_definition(&self, id: DefinitionId) -> Result<bool>;
+    /// Undo `soft_delete_definition`. Returns whether a deleted definition
+    /// was found.
+    async fn restore_definition(&self, id: DefinitionId) -> Result<bool>;
+    /// Permanently remove a definition. Returns whether one was found, and
+    /// fails for built-in definitions. Agents spawned from it fall back to
+    /// the executor's legacy definition.
+    async fn delete_definition(&self, id: DefinitionId) -> Result<bool>;
     async fn increment_definition_use_count(&self, id: DefinitionId) -> Result<()>;
     async fn update_definition_health(&self, id: DefinitionId, health_delta: f32) -> Result<()>;
 }
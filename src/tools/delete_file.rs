@@ -0,0 +1,255 @@
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use serde_json::{json, Value};
+use std::path::{Path, PathBuf};
+use tokio::fs;
+
+use super::impresario_client::ImpresarioClient;
+use super::sandbox;
+use super::{SideEffect, Tool, ToolContext, ToolResult};
+use crate::definitions::ToolType;
+
+pub enum DeleteFileMode {
+    Local,
+    Remote(ImpresarioClient),
+}
+
+pub struct DeleteFileTool {
+    mode: DeleteFileMode,
+    sandbox_root: PathBuf,
+}
+
+impl DeleteFileTool {
+    pub fn new_local(sandbox_root: PathBuf) -> Self {
+        Self {
+            mode: DeleteFileMode::Local,
+            sandbox_root,
+        }
+    }
+
+    pub fn new_remote(client: ImpresarioClient, sandbox_root: PathBuf) -> Self {
+        Self {
+            mode: DeleteFileMode::Remote(client),
+            sandbox_root,
+        }
+    }
+
+    fn validate_path(&self, path: &str) -> Result<PathBuf> {
+        let normalized = sandbox::resolve_path(&self.sandbox_root, path)?;
+
+        // The sandbox itself is never a valid target
+        if normalized == sandbox::resolve_path(&self.sandbox_root, ".")? {
+            return Err(anyhow!("Refusing to delete the sandbox root"));
+        }
+
+        Ok(normalized)
+    }
+
+    async fn delete_local(&self, path: &Path, recursive: bool) -> Result<bool> {
+        // A link as the last component is deleted itself, but one further up
+        // would carry the delete out of the sandbox.
+        sandbox::check_symlinks(&self.sandbox_root, path, false).await?;
+        let metadata = fs::symlink_metadata(path).await?;
+
+        if metadata.is_dir() {
+            if recursive {
+                fs::remove_dir_all(path).await?;
+            } else {
+                fs::remove_dir(path).await.map_err(|e| {
+                    anyhow!(
+                        "Cannot delete directory {} ({}); pass recursive: true to delete its contents",
+                        path.display(),
+                        e
+                    )
+                })?;
+            }
+        } else {
+            fs::remove_file(path).await?;
+        }
+
+        Ok(metadata.is_dir())
+    }
+}
+
+#[async_trait]
+impl Tool for DeleteFileTool {
+    fn tool_type(&self) -> ToolType {
+        ToolType::DeleteFile
+    }
+
+    fn name(&self) -> &str {
+        "delete_file"
+    }
+
+    fn description(&self) -> &str {
+        "Delete a file or empty directory within the sandbox. Non-empty directories are only removed when recursive is set."
+    }
+
+    fn parameters_schema(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "path": {
+                    "type": "string",
+                    "description": "Path to delete (relative to sandbox or absolute)"
+                },
+                "recursive": {
+                    "type": "boolean",
+                    "description": "Delete a directory and everything in it (default: false)",
+                    "default": false
+                }
+            },
+            "required": ["path"]
+        })
+    }
+
+    async fn execute(&self, params: Value, _context: &ToolContext) -> Result<ToolResult> {
+        let path = params["path"]
+            .as_str()
+            .ok_or_else(|| anyhow!("Missing path parameter"))?;
+        let recursive = params["recursive"].as_bool().unwrap_or(false);
+
+        let validated_path = self.validate_path(path)?;
+
+        // Only known for local deletes; the remote side doesn't report it.
+        let was_dir = match &self.mode {
+            DeleteFileMode::Local => Some(self.delete_local(&validated_path, recursive).await?),
+            DeleteFileMode::Remote(client) => {
+                client
+                    .remove_path(validated_path.to_str().unwrap(), recursive)
+                    .await?;
+                None
+            }
+        };
+
+        Ok(ToolResult {
+            success: true,
+            output: json!({
+                "path": path,
+                "deleted": true,
+                "directory": was_dir,
+            }),
+            artifacts: vec![],
+            side_effects: vec![SideEffect::FileDeleted(validated_path)],
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn test_context(dir: &Path) -> ToolContext {
+        ToolContext {
+            agent_id: uuid::Uuid::new_v4(),
+            web_id: uuid::Uuid::new_v4(),
+            sandbox_path: dir.to_path_buf(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_delete_file_local() {
+        let temp_dir = TempDir::new().unwrap();
+        let target = temp_dir.path().join("scratch.txt");
+        fs::write(&target, "temp").await.unwrap();
+
+        let tool = DeleteFileTool::new_local(temp_dir.path().to_path_buf());
+        let result = tool
+            .execute(
+                json!({ "path": "scratch.txt" }),
+                &test_context(temp_dir.path()),
+            )
+            .await
+            .unwrap();
+
+        assert!(result.success);
+        assert!(!target.exists());
+        assert!(matches!(
+            &result.side_effects[0],
+            SideEffect::FileDeleted(p) if p == &target
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_delete_directory_requires_recursive() {
+        let temp_dir = TempDir::new().unwrap();
+        let dir = temp_dir.path().join("build");
+        fs::create_dir(&dir).await.unwrap();
+        fs::write(dir.join("out.o"), "").await.unwrap();
+
+        let tool = DeleteFileTool::new_local(temp_dir.path().to_path_buf());
+        let context = test_context(temp_dir.path());
+
+        assert!(tool
+            .execute(json!({ "path": "build" }), &context)
+            .await
+            .is_err());
+        assert!(dir.exists());
+
+        tool.execute(json!({ "path": "build", "recursive": true }), &context)
+            .await
+            .unwrap();
+        assert!(!dir.exists());
+    }
+
+    #[tokio::test]
+    async fn test_delete_rejects_escape() {
+        let outer = TempDir::new().unwrap();
+        let sandbox = outer.path().join("sandbox");
+        fs::create_dir(&sandbox).await.unwrap();
+        let outside = outer.path().join("keep.txt");
+        fs::write(&outside, "precious").await.unwrap();
+
+        let tool = DeleteFileTool::new_local(sandbox.clone());
+        let context = test_context(&sandbox);
+
+        let err = tool
+            .execute(json!({ "path": "../keep.txt" }), &context)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("escapes sandbox"));
+        assert!(outside.exists());
+
+        assert!(tool
+            .execute(json!({ "path": ".", "recursive": true }), &context)
+            .await
+            .is_err());
+        assert!(sandbox.exists());
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_delete_through_symlinked_directory_is_rejected() {
+        let sandbox = TempDir::new().unwrap();
+        let outside = TempDir::new().unwrap();
+        fs::create_dir(outside.path().join("victim")).await.unwrap();
+        fs::write(outside.path().join("victim/data.txt"), "precious")
+            .await
+            .unwrap();
+        fs::write(outside.path().join("file.txt"), "precious")
+            .await
+            .unwrap();
+        std::os::unix::fs::symlink(outside.path(), sandbox.path().join("link")).unwrap();
+
+        let tool = DeleteFileTool::new_local(sandbox.path().to_path_buf());
+        let context = test_context(sandbox.path());
+
+        for params in [
+            json!({ "path": "link/victim", "recursive": true }),
+            json!({ "path": "link/file.txt" }),
+        ] {
+            let err = tool.execute(params, &context).await.unwrap_err();
+            assert!(err.to_string().contains("escapes sandbox"), "{}", err);
+        }
+        assert!(outside.path().join("victim/data.txt").exists());
+        assert!(outside.path().join("file.txt").exists());
+
+        // Deleting the link itself leaves its target alone.
+        tool.execute(json!({ "path": "link" }), &context)
+            .await
+            .unwrap();
+        assert!(!sandbox.path().join("link").exists());
+        assert!(outside.path().join("file.txt").exists());
+    }
+}
@@ -0,0 +1,50 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use serde_json::{json, Value};
+
+use super::{Tool, ToolContext, ToolResult};
+use crate::definitions::ToolType;
+use crate::providers::DryRunLog;
+
+/// Stands in for any tool during a dry run: records the call and succeeds
+/// without touching the network, filesystem or sandbox.
+pub struct DryRunTool {
+    tool_type: ToolType,
+    log: DryRunLog,
+}
+
+impl DryRunTool {
+    pub fn new(tool_type: ToolType, log: DryRunLog) -> Self {
+        Self { tool_type, log }
+    }
+}
+
+#[async_trait]
+impl Tool for DryRunTool {
+    fn tool_type(&self) -> ToolType {
+        self.tool_type
+    }
+
+    fn name(&self) -> &str {
+        self.tool_type.as_str()
+    }
+
+    fn description(&self) -> &str {
+        "Dry run: records the call without performing it"
+    }
+
+    fn parameters_schema(&self) -> Value {
+        json!({ "type": "object" })
+    }
+
+    async fn execute(&self, params: Value, _context: &ToolContext) -> Result<ToolResult> {
+        self.log
+            .record("tool", format!("{} {}", self.tool_type.as_str(), params));
+        Ok(ToolResult {
+            success: true,
+            output: json!({ "dry_run": true, "tool": self.tool_type.as_str() }),
+            artifacts: vec![],
+            side_effects: vec![],
+        })
+    }
+}
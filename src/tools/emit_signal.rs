@@ -1,12 +1,16 @@
 use anyhow::{anyhow, Result};
 use async_trait::async_trait;
 use serde_json::{json, Value};
+use std::sync::Arc;
 
 use super::{SideEffect, Tool, ToolContext, ToolResult};
 use crate::definitions::ToolType;
+use crate::providers::EmbeddingProvider;
 use crate::types::{Signal, SignalDirection};
 
-pub struct EmitSignalTool {}
+pub struct EmitSignalTool {
+    embedding_provider: Option<Arc<dyn EmbeddingProvider>>,
+}
 
 impl Default for EmitSignalTool {
     fn default() -> Self {
@@ -16,7 +20,16 @@ impl Default for EmitSignalTool {
 
 impl EmitSignalTool {
     pub fn new() -> Self {
-        Self {}
+        Self {
+            embedding_provider: None,
+        }
+    }
+
+    /// Embed signal content so emitted signals carry a real frequency. Without a
+    /// provider the frequency is left empty for the engine to fill in.
+    pub fn with_embedding_provider(mut self, provider: Arc<dyn EmbeddingProvider>) -> Self {
+        self.embedding_provider = Some(provider);
+        self
     }
 }
 
@@ -48,6 +61,13 @@ impl Tool for EmitSignalTool {
                     "description": "Signal direction: 'upward' for results to parents, 'downward' for needs to children",
                     "default": "upward"
                 },
+                "amplitude": {
+                    "type": "number",
+                    "minimum": 0.0,
+                    "maximum": 1.0,
+                    "description": "Signal strength between 0 and 1 (default: 1.0)",
+                    "default": 1.0
+                },
                 "payload": {
                     "type": "object",
                     "description": "Optional structured data payload",
@@ -62,22 +82,39 @@ impl Tool for EmitSignalTool {
         let content = params["content"]
             .as_str()
             .ok_or_else(|| anyhow!("Missing content parameter"))?;
+        if content.trim().is_empty() {
+            return Err(anyhow!("Signal content must not be empty"));
+        }
 
-        let direction_str = params["direction"].as_str().unwrap_or("upward");
+        let direction_str = match &params["direction"] {
+            Value::Null => "upward",
+            value => value
+                .as_str()
+                .ok_or_else(|| anyhow!("Invalid direction: {}", value))?,
+        };
         let direction = match direction_str {
             "upward" => SignalDirection::Upward,
             "downward" => SignalDirection::Downward,
             _ => return Err(anyhow!("Invalid direction: {}", direction_str)),
         };
 
+        let amplitude = match &params["amplitude"] {
+            Value::Null => 1.0,
+            value => value
+                .as_f64()
+                .ok_or_else(|| anyhow!("amplitude must be a number"))?
+                .clamp(0.0, 1.0) as f32,
+        };
+
         let payload = params.get("payload").cloned();
 
-        let mut signal = Signal::new(
-            context.agent_id,
-            vec![], // Frequency will be calculated by the engine
-            content.to_string(),
-            direction,
-        );
+        let frequency = match &self.embedding_provider {
+            Some(provider) => provider.embed(content).await?,
+            None => vec![], // Frequency will be calculated by the engine
+        };
+
+        let mut signal = Signal::new(context.agent_id, frequency, content.to_string(), direction);
+        signal.amplitude = amplitude;
 
         if let Some(p) = payload {
             signal = signal.with_payload(p);
@@ -89,6 +126,7 @@ impl Tool for EmitSignalTool {
                 "signal_id": signal.id,
                 "content": content,
                 "direction": direction_str,
+                "amplitude": amplitude,
                 "agent_id": context.agent_id,
             }),
             artifacts: vec![],
@@ -166,6 +204,77 @@ mod tests {
         }
     }
 
+    struct KeywordEmbedding;
+
+    #[async_trait]
+    impl EmbeddingProvider for KeywordEmbedding {
+        async fn embed(&self, text: &str) -> Result<Vec<f32>> {
+            Ok(vec![text.len() as f32, 1.0])
+        }
+
+        async fn embed_batch(&self, texts: &[String]) -> Result<Vec<Vec<f32>>> {
+            Ok(texts.iter().map(|t| vec![t.len() as f32, 1.0]).collect())
+        }
+    }
+
+    fn test_context() -> ToolContext {
+        ToolContext {
+            agent_id: uuid::Uuid::new_v4(),
+            web_id: uuid::Uuid::new_v4(),
+            sandbox_path: PathBuf::from("/tmp"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_emit_signal_rejects_invalid_input() {
+        let tool = EmitSignalTool::new();
+
+        let err = tool
+            .execute(
+                json!({ "content": "hello", "direction": "sideways" }),
+                &test_context(),
+            )
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("Invalid direction"));
+
+        assert!(tool
+            .execute(json!({ "content": "   " }), &test_context())
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_emit_signal_clamps_amplitude_and_embeds_content() {
+        let tool = EmitSignalTool::new().with_embedding_provider(Arc::new(KeywordEmbedding));
+
+        let result = tool
+            .execute(
+                json!({ "content": "found it", "amplitude": 3.5 }),
+                &test_context(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(result.output["amplitude"], 1.0);
+        match &result.side_effects[0] {
+            SideEffect::SignalEmitted(signal) => {
+                assert_eq!(signal.amplitude, 1.0);
+                assert_eq!(signal.frequency, vec![8.0, 1.0]);
+            }
+            _ => panic!("Expected SignalEmitted side effect"),
+        }
+
+        let result = tool
+            .execute(
+                json!({ "content": "faint", "amplitude": -0.2 }),
+                &test_context(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(result.output["amplitude"], 0.0);
+    }
+
     #[tokio::test]
     async fn test_emit_signal_default_direction() {
         let tool = EmitSignalTool::new();
@@ -3,13 +3,178 @@ use async_trait::async_trait;
 use serde_json::{json, Value};
 use uuid::Uuid;
 
-use super::impresario_client::{ExecResult, ImpresarioClient};
-use super::{SideEffect, Tool, ToolContext, ToolResult};
+use super::impresario_client::{shell_quote, ExecResult, ImpresarioClient};
+use super::{Artifact, SideEffect, Tool, ToolContext, ToolResult};
 use crate::definitions::ToolType;
 
+/// Total bytes of output files returned inline; larger files are reported by size only.
+const MAX_CAPTURED_BYTES: u64 = 1024 * 1024;
+
+/// Seconds a program gets to exit after the wall timeout's TERM before it is
+/// killed.
+const WALL_TIMEOUT_GRACE_SECS: u64 = 5;
+
+/// Caps applied to every program `execute_code` runs.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ResourceLimits {
+    /// Caps the data segment (`ulimit -d`): heap and other writable private
+    /// memory. Address space a runtime only reserves, as JITs do, doesn't
+    /// count. Uncapped by default.
+    pub max_memory_mb: Option<u64>,
+    pub max_cpu_secs: Option<u64>,
+    pub wall_timeout_secs: Option<u64>,
+}
+
+impl Default for ResourceLimits {
+    fn default() -> Self {
+        Self {
+            max_memory_mb: None,
+            max_cpu_secs: Some(60),
+            wall_timeout_secs: Some(120),
+        }
+    }
+}
+
+impl ResourceLimits {
+    /// Reads `EXECUTE_CODE_MAX_MEMORY_MB`, `EXECUTE_CODE_MAX_CPU_SECS` and
+    /// `EXECUTE_CODE_WALL_TIMEOUT_SECS`; `0` disables a limit.
+    pub fn from_env() -> Self {
+        Self::from_env_with_prefix("EXECUTE_CODE", Self::default())
+    }
+
+    /// Reads `<prefix>_MAX_MEMORY_MB`, `<prefix>_MAX_CPU_SECS` and
+    /// `<prefix>_WALL_TIMEOUT_SECS` over `defaults`; `0` disables a limit.
+    pub fn from_env_with_prefix(prefix: &str, defaults: Self) -> Self {
+        let read =
+            |name: &str, default: Option<u64>| match std::env::var(format!("{}_{}", prefix, name))
+                .ok()
+                .and_then(|v| v.parse::<u64>().ok())
+            {
+                Some(0) => None,
+                Some(v) => Some(v),
+                None => default,
+            };
+
+        Self {
+            max_memory_mb: read("MAX_MEMORY_MB", defaults.max_memory_mb),
+            max_cpu_secs: read("MAX_CPU_SECS", defaults.max_cpu_secs),
+            wall_timeout_secs: read("WALL_TIMEOUT_SECS", defaults.wall_timeout_secs),
+        }
+    }
+
+    /// Wrap a shell command so it runs under these limits.
+    pub(crate) fn wrap(&self, command: &str) -> String {
+        let mut prefix = Vec::new();
+        if let Some(cpu) = self.max_cpu_secs {
+            // Keep the hard limit a second above the soft one so the kernel sends
+            // SIGXCPU (reported as exit 152) rather than going straight to SIGKILL.
+            prefix.push(format!("ulimit -Ht {}; ulimit -St {};", cpu + 1, cpu));
+        }
+        if let Some(memory) = self.max_memory_mb {
+            prefix.push(format!("ulimit -d {};", memory * 1024));
+        }
+        let timeout = self
+            .wall_timeout_secs
+            .map(|secs| format!("timeout -k {} {} ", WALL_TIMEOUT_GRACE_SECS, secs))
+            .unwrap_or_default();
+
+        format!(
+            "({} {}sh -c '{}')",
+            prefix.join(" "),
+            timeout,
+            command.replace('\'', "'\\''")
+        )
+    }
+
+    /// Which limit, if any, explains how a run ended.
+    pub(crate) fn exceeded(&self, result: &ExecResult) -> Option<&'static str> {
+        match result.exit_code {
+            // SIGXCPU
+            152 if self.max_cpu_secs.is_some() => Some("cpu_time"),
+            // `timeout` ran out and the program exited on its TERM
+            124 if self.wall_timeout_secs.is_some() => Some("wall_time"),
+            // SIGKILL: the OOM killer, or a program that ignored the TERM
+            137 => Some("killed"),
+            code if code != 0 && self.max_memory_mb.is_some() => {
+                let stderr = result.stderr.to_lowercase();
+                ["memoryerror", "cannot allocate memory", "out of memory"]
+                    .iter()
+                    .any(|m| stderr.contains(m))
+                    .then_some("memory")
+            }
+            _ => None,
+        }
+    }
+}
+
+/// The command that runs `code` directly, for languages that need no build step.
+fn interpreter_command(language: &str, code: &str) -> Option<String> {
+    let safe_code = code.replace('\'', "'\\''");
+    match language {
+        "python" => Some(format!("python3 -c '{}'", safe_code)),
+        "javascript" | "typescript" => Some(format!("bun -e '{}'", safe_code)),
+        "ruby" => Some(format!("ruby -e '{}'", safe_code)),
+        "shell" => Some(code.to_string()),
+        _ => None,
+    }
+}
+
+/// Languages whose code is written to a temporary file before it runs.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum SourceLanguage {
+    Rust,
+    Go,
+}
+
+/// Temporary paths for one program that has to be written to disk first.
+/// Each gets its own UUID so concurrent runs never share files.
+struct TempProgram {
+    language: SourceLanguage,
+    source: String,
+    binary: String,
+}
+
+impl TempProgram {
+    fn new(language: &str, dir: &str) -> Option<Self> {
+        let (language, name, extension) = match language {
+            "rust" => (SourceLanguage::Rust, "rust", "rs"),
+            "go" => (SourceLanguage::Go, "go", "go"),
+            _ => return None,
+        };
+        let temp_id = Uuid::new_v4();
+        Some(Self {
+            language,
+            source: format!("{}/arachnid_{}_{}.{}", dir, name, temp_id, extension),
+            binary: format!("{}/arachnid_{}_{}", dir, name, temp_id),
+        })
+    }
+
+    /// Build step run outside the resource limits, if the language has one.
+    fn compile_command(&self) -> Option<String> {
+        match self.language {
+            SourceLanguage::Rust => Some(format!("rustc {} -o {}", self.source, self.binary)),
+            SourceLanguage::Go => None,
+        }
+    }
+
+    fn run_command(&self) -> String {
+        match self.language {
+            SourceLanguage::Rust => self.binary.clone(),
+            SourceLanguage::Go => format!("go run {}", self.source),
+        }
+    }
+
+    fn cleanup_command(&self) -> String {
+        format!("rm -f {} {}", self.source, self.binary)
+    }
+}
+
 pub struct ExecuteCodeTool {
     client: ImpresarioClient,
     enable_checkpoints: bool,
+    limits: ResourceLimits,
+    /// Directory on the sandbox host for compiled languages' temporary files.
+    temp_dir: String,
 }
 
 impl ExecuteCodeTool {
@@ -21,54 +186,160 @@ impl ExecuteCodeTool {
         Self {
             client,
             enable_checkpoints,
+            limits: ResourceLimits::from_env(),
+            temp_dir: "/tmp".to_string(),
         }
     }
 
-    async fn execute_python(&self, code: &str) -> Result<ExecResult> {
-        let safe_code = code.replace('\'', "'\\''");
-        let command = format!("python3 -c '{}'", safe_code);
-        self.client.exec(&command).await
+    pub fn with_limits(mut self, limits: ResourceLimits) -> Self {
+        self.limits = limits;
+        self
     }
 
-    async fn execute_javascript(&self, code: &str) -> Result<ExecResult> {
-        let safe_code = code.replace('\'', "'\\''");
-        let command = format!("bun -e '{}'", safe_code);
-        self.client.exec(&command).await
+    pub fn with_temp_dir(mut self, temp_dir: impl Into<String>) -> Self {
+        self.temp_dir = temp_dir.into();
+        self
     }
 
-    async fn execute_rust(&self, code: &str) -> Result<ExecResult> {
-        let temp_id = Uuid::new_v4();
-        let temp_file = format!("/tmp/arachnid_rust_{}.rs", temp_id);
-        let temp_bin = format!("/tmp/arachnid_rust_{}", temp_id);
-
-        self.client.write_file(&temp_file, code).await?;
-
-        let compile_result = self
-            .client
-            .exec(&format!("rustc {} -o {}", temp_file, temp_bin))
-            .await?;
-
-        if !compile_result.success {
-            return Ok(ExecResult {
-                stdout: compile_result.stdout,
-                stderr: format!("Compilation failed:\n{}", compile_result.stderr),
-                exit_code: compile_result.exit_code,
-                success: false,
-            });
+    /// The shell command that runs `command` under the configured limits,
+    /// piping `stdin` into it if given.
+    fn limited_command(&self, command: &str, stdin: Option<&str>) -> String {
+        let limited = self.limits.wrap(command);
+        match stdin {
+            Some(input) => format!(
+                "printf '%s' '{}' | {}",
+                input.replace('\'', "'\\''"),
+                limited
+            ),
+            None => limited,
         }
+    }
 
-        let exec_result = self.client.exec(&temp_bin).await?;
+    async fn run_limited(&self, command: &str, stdin: Option<&str>) -> Result<ExecResult> {
+        self.client
+            .exec(&self.limited_command(command, stdin))
+            .await
+    }
 
-        let _ = self
-            .client
-            .exec(&format!("rm {} {}", temp_file, temp_bin))
-            .await;
+    /// The command that would run the program, and every command `execute`
+    /// would send to the sandbox for this code, in order. Compiled languages'
+    /// source is written to `TempProgram::source` before the first.
+    fn planned_commands(
+        &self,
+        language: &str,
+        code: &str,
+        stdin: Option<&str>,
+    ) -> Result<(String, Vec<String>)> {
+        if let Some(program) = TempProgram::new(language, &self.temp_dir) {
+            let run = self.limited_command(&program.run_command(), stdin);
+            let commands = program
+                .compile_command()
+                .into_iter()
+                .chain([run.clone(), program.cleanup_command()])
+                .collect();
+            return Ok((run, commands));
+        }
+
+        let command = interpreter_command(language, code)
+            .ok_or_else(|| anyhow!("Unsupported language: {}", language))?;
+        let run = self.limited_command(&command, stdin);
+        Ok((run.clone(), vec![run]))
+    }
+
+    /// Write `program` to the sandbox, build and run it, then remove its
+    /// temporary files whether or not any step failed.
+    async fn execute_program(
+        &self,
+        program: TempProgram,
+        code: &str,
+        stdin: Option<&str>,
+    ) -> Result<ExecResult> {
+        let result = self.build_and_run(&program, code, stdin).await;
 
-        Ok(exec_result)
+        if let Err(e) = self.client.exec(&program.cleanup_command()).await {
+            log::warn!("Failed to remove {}: {}", program.source, e);
+        }
+
+        result
     }
 
-    async fn execute_shell(&self, code: &str) -> Result<ExecResult> {
-        self.client.exec(code).await
+    async fn build_and_run(
+        &self,
+        program: &TempProgram,
+        code: &str,
+        stdin: Option<&str>,
+    ) -> Result<ExecResult> {
+        self.client.write_file(&program.source, code).await?;
+
+        if let Some(compile) = program.compile_command() {
+            let compile_result = self.client.exec(&compile).await?;
+
+            if !compile_result.success {
+                return Ok(ExecResult {
+                    stdout: compile_result.stdout,
+                    stderr: format!("Compilation failed:\n{}", compile_result.stderr),
+                    exit_code: compile_result.exit_code,
+                    success: false,
+                });
+            }
+        }
+
+        self.run_limited(&program.run_command(), stdin).await
+    }
+
+    /// Fetch declared output files from the sandbox.
+    ///
+    /// Files are returned inline until `MAX_CAPTURED_BYTES` is used up; the rest
+    /// are reported by path and size only. Paths that don't exist are skipped.
+    async fn capture_outputs(&self, paths: &[String]) -> (Vec<Artifact>, Vec<Value>) {
+        let mut artifacts = Vec::new();
+        let mut summary = Vec::new();
+        let mut captured_bytes = 0u64;
+
+        for path in paths {
+            let size = match self
+                .client
+                .exec(&format!("wc -c < {}", shell_quote(path)))
+                .await
+            {
+                Ok(result) if result.success => result.stdout.trim().parse::<u64>().ok(),
+                _ => None,
+            };
+
+            let Some(size) = size else {
+                summary.push(json!({ "path": path, "found": false }));
+                continue;
+            };
+
+            let inline = captured_bytes + size <= MAX_CAPTURED_BYTES;
+            let content = if inline {
+                self.client.read_bytes(path).await.ok()
+            } else {
+                None
+            };
+
+            match content {
+                Some(content) => {
+                    captured_bytes += content.len() as u64;
+                    artifacts.push(Artifact::Data {
+                        name: path.clone(),
+                        content,
+                    });
+                }
+                None => artifacts.push(Artifact::File {
+                    path: path.into(),
+                    size,
+                }),
+            }
+            summary.push(json!({
+                "path": path,
+                "found": true,
+                "size": size,
+                "captured": inline,
+            }));
+        }
+
+        (artifacts, summary)
     }
 }
 
@@ -83,7 +354,7 @@ impl Tool for ExecuteCodeTool {
     }
 
     fn description(&self) -> &str {
-        "Execute code in a sandboxed environment on Dais. Supports Python, JavaScript/TypeScript (via Bun), Rust, and shell commands. Automatically creates checkpoint before execution and can rollback on failure."
+        "Execute code in a sandboxed environment on Dais. Supports Python, JavaScript/TypeScript (via Bun), Rust, Go, Ruby, and shell commands. Automatically creates checkpoint before execution and can rollback on failure. With dry_run, returns the commands it would run without running them."
     }
 
     fn parameters_schema(&self) -> Value {
@@ -92,17 +363,31 @@ impl Tool for ExecuteCodeTool {
             "properties": {
                 "language": {
                     "type": "string",
-                    "enum": ["python", "javascript", "typescript", "rust", "shell"],
+                    "enum": ["python", "javascript", "typescript", "rust", "go", "ruby", "shell"],
                     "description": "Programming language of the code"
                 },
                 "code": {
                     "type": "string",
                     "description": "The code to execute"
                 },
+                "stdin": {
+                    "type": "string",
+                    "description": "Input fed to the program on stdin"
+                },
                 "rollback_on_error": {
                     "type": "boolean",
                     "description": "Rollback to checkpoint if execution fails (default: true)",
                     "default": true
+                },
+                "output_paths": {
+                    "type": "array",
+                    "items": { "type": "string" },
+                    "description": "Sandbox files the code produces; their contents are returned as artifacts"
+                },
+                "dry_run": {
+                    "type": "boolean",
+                    "description": "Return the shell commands that would run instead of running them; no checkpoint is created (default: false)",
+                    "default": false
                 }
             },
             "required": ["language", "code"]
@@ -116,7 +401,32 @@ impl Tool for ExecuteCodeTool {
         let code = params["code"]
             .as_str()
             .ok_or_else(|| anyhow!("Missing code parameter"))?;
+        let stdin = params["stdin"].as_str();
         let rollback_on_error = params["rollback_on_error"].as_bool().unwrap_or(true);
+        let output_paths: Vec<String> = params["output_paths"]
+            .as_array()
+            .map(|paths| {
+                paths
+                    .iter()
+                    .filter_map(|p| p.as_str().map(str::to_string))
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        if params["dry_run"].as_bool().unwrap_or(false) {
+            let (command, commands) = self.planned_commands(language, code, stdin)?;
+            return Ok(ToolResult {
+                success: true,
+                output: json!({
+                    "language": language,
+                    "dry_run": true,
+                    "command": command,
+                    "commands": commands,
+                }),
+                artifacts: vec![],
+                side_effects: vec![],
+            });
+        }
 
         let checkpoint_name = if self.enable_checkpoints {
             let name = format!("arachnid_exec_{}", Uuid::new_v4());
@@ -128,14 +438,17 @@ impl Tool for ExecuteCodeTool {
             None
         };
 
-        let result = match language {
-            "python" => self.execute_python(code).await?,
-            "javascript" | "typescript" => self.execute_javascript(code).await?,
-            "rust" => self.execute_rust(code).await?,
-            "shell" => self.execute_shell(code).await?,
-            _ => return Err(anyhow!("Unsupported language: {}", language)),
+        let result = match TempProgram::new(language, &self.temp_dir) {
+            Some(program) => self.execute_program(program, code, stdin).await?,
+            None => match interpreter_command(language, code) {
+                Some(command) => self.run_limited(&command, stdin).await?,
+                None => return Err(anyhow!("Unsupported language: {}", language)),
+            },
         };
 
+        // Capture before any rollback so a failed run's outputs are still visible.
+        let (artifacts, outputs) = self.capture_outputs(&output_paths).await;
+
         if !result.success && rollback_on_error {
             if let Some(checkpoint) = &checkpoint_name {
                 if let Err(e) = self.client.restore_checkpoint(checkpoint).await {
@@ -153,8 +466,10 @@ impl Tool for ExecuteCodeTool {
                 "exit_code": result.exit_code,
                 "checkpoint_created": checkpoint_name.is_some(),
                 "rolled_back": !result.success && rollback_on_error && checkpoint_name.is_some(),
+                "outputs": outputs,
+                "limit_exceeded": self.limits.exceeded(&result),
             }),
-            artifacts: vec![],
+            artifacts,
             side_effects: vec![SideEffect::CodeExecuted {
                 language: language.to_string(),
                 exit_code: result.exit_code,
@@ -166,6 +481,7 @@ impl Tool for ExecuteCodeTool {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::tools::impresario_client::test_support::local_client;
 
     #[test]
     fn test_tool_schema() {
@@ -175,6 +491,8 @@ mod tests {
             user: "test".to_string(),
             key_path: None,
             timeout_secs: 60,
+            control_persist_secs: None,
+            max_retries: 0,
         });
 
         let tool = ExecuteCodeTool::new(client);
@@ -185,4 +503,279 @@ mod tests {
         assert_eq!(schema["required"][0], "language");
         assert_eq!(schema["required"][1], "code");
     }
+
+    #[test]
+    fn test_limits_wrap_command() {
+        let limits = ResourceLimits {
+            max_memory_mb: Some(256),
+            max_cpu_secs: Some(5),
+            wall_timeout_secs: Some(10),
+        };
+        let wrapped = limits.wrap("echo 'hi'");
+
+        assert!(wrapped.contains("ulimit -Ht 6; ulimit -St 5;"));
+        assert!(wrapped.contains("ulimit -d 262144;"));
+        assert!(wrapped.contains("timeout -k 5 10 sh -c 'echo '\\''hi'\\'''"));
+
+        let unlimited = ResourceLimits {
+            max_memory_mb: None,
+            max_cpu_secs: None,
+            wall_timeout_secs: None,
+        };
+        assert!(!unlimited.wrap("true").contains("ulimit"));
+    }
+
+    /// Runs the loop through a local stand-in for the sandbox, so this needs a
+    /// POSIX shell with `ulimit` and coreutils `timeout`, and spins a CPU for
+    /// the limit's length.
+    #[tokio::test]
+    #[ignore = "burns CPU time; run with --ignored"]
+    async fn test_cpu_limit_stops_infinite_loop() {
+        let dir = tempfile::tempdir().unwrap();
+        let tool = ExecuteCodeTool::new(local_client(dir.path())).with_limits(ResourceLimits {
+            max_memory_mb: None,
+            max_cpu_secs: Some(1),
+            wall_timeout_secs: Some(30),
+        });
+        let context = ToolContext {
+            agent_id: Uuid::new_v4(),
+            web_id: Uuid::new_v4(),
+            sandbox_path: dir.path().to_path_buf(),
+        };
+
+        let started = std::time::Instant::now();
+        let result = tool
+            .execute(
+                json!({
+                    "language": "shell",
+                    "code": "while :; do :; done",
+                    "rollback_on_error": false,
+                }),
+                &context,
+            )
+            .await
+            .unwrap();
+
+        assert!(!result.success);
+        assert_eq!(result.output["limit_exceeded"], "cpu_time");
+        assert!(started.elapsed() < std::time::Duration::from_secs(20));
+    }
+
+    #[tokio::test]
+    async fn test_wall_timeout_is_told_apart_from_kill() {
+        let dir = tempfile::tempdir().unwrap();
+        let limits = ResourceLimits {
+            max_memory_mb: None,
+            max_cpu_secs: None,
+            wall_timeout_secs: Some(1),
+        };
+        let tool = ExecuteCodeTool::new(local_client(dir.path())).with_limits(limits.clone());
+        let context = ToolContext {
+            agent_id: Uuid::new_v4(),
+            web_id: Uuid::new_v4(),
+            sandbox_path: dir.path().to_path_buf(),
+        };
+
+        let result = tool
+            .execute(
+                json!({
+                    "language": "shell",
+                    "code": "sleep 30",
+                    "rollback_on_error": false,
+                }),
+                &context,
+            )
+            .await
+            .unwrap();
+        assert_eq!(result.output["limit_exceeded"], "wall_time");
+
+        let killed = ExecResult {
+            stdout: String::new(),
+            stderr: String::new(),
+            exit_code: 137,
+            success: false,
+        };
+        assert_eq!(limits.exceeded(&killed), Some("killed"));
+    }
+
+    #[tokio::test]
+    async fn test_stdin_is_piped_to_program() {
+        let dir = tempfile::tempdir().unwrap();
+        let tool = ExecuteCodeTool::new(local_client(dir.path()));
+        let context = ToolContext {
+            agent_id: Uuid::new_v4(),
+            web_id: Uuid::new_v4(),
+            sandbox_path: dir.path().to_path_buf(),
+        };
+
+        let result = tool
+            .execute(
+                json!({
+                    "language": "shell",
+                    "code": "read line; echo \"got: $line\"",
+                    "stdin": "it's here\nignored\n",
+                    "rollback_on_error": false,
+                }),
+                &context,
+            )
+            .await
+            .unwrap();
+
+        assert!(result.success);
+        assert_eq!(
+            result.output["stdout"].as_str().unwrap().trim(),
+            "got: it's here"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_output_files_become_artifacts() {
+        let dir = tempfile::tempdir().unwrap();
+        let tool = ExecuteCodeTool::new(local_client(dir.path()));
+        let context = ToolContext {
+            agent_id: Uuid::new_v4(),
+            web_id: Uuid::new_v4(),
+            sandbox_path: dir.path().to_path_buf(),
+        };
+
+        let out = dir.path().join("report.txt");
+        let missing = dir.path().join("missing.txt");
+        let result = tool
+            .execute(
+                json!({
+                    "language": "shell",
+                    "code": format!("printf 'done' > '{}'", out.display()),
+                    "rollback_on_error": false,
+                    "output_paths": [out.to_str().unwrap(), missing.to_str().unwrap()],
+                }),
+                &context,
+            )
+            .await
+            .unwrap();
+
+        assert!(result.success);
+        assert_eq!(result.artifacts.len(), 1);
+        match &result.artifacts[0] {
+            Artifact::Data { name, content } => {
+                assert_eq!(name, out.to_str().unwrap());
+                assert_eq!(content, b"done");
+            }
+            other => panic!("expected inline artifact, got {:?}", other),
+        }
+        assert_eq!(result.output["outputs"][0]["size"], 4);
+        assert_eq!(result.output["outputs"][1]["found"], false);
+    }
+
+    #[tokio::test]
+    async fn test_dry_run_returns_command_without_running() {
+        let dir = tempfile::tempdir().unwrap();
+        let tool = ExecuteCodeTool::new(local_client(dir.path()));
+        let context = ToolContext {
+            agent_id: Uuid::new_v4(),
+            web_id: Uuid::new_v4(),
+            sandbox_path: dir.path().to_path_buf(),
+        };
+
+        let marker = dir.path().join("ran");
+        let code = format!("touch '{}'", marker.display());
+        let result = tool
+            .execute(
+                json!({ "language": "shell", "code": code, "dry_run": true }),
+                &context,
+            )
+            .await
+            .unwrap();
+
+        assert!(result.success);
+        assert!(!marker.exists());
+        assert!(result.side_effects.is_empty());
+        assert_eq!(result.output["dry_run"], true);
+        assert_eq!(result.output["command"], tool.limited_command(&code, None));
+        assert!(result.output.get("checkpoint_created").is_none());
+
+        let rust = tool
+            .execute(
+                json!({ "language": "rust", "code": "fn main() {}", "dry_run": true }),
+                &context,
+            )
+            .await
+            .unwrap();
+        let commands = rust.output["commands"].as_array().unwrap();
+        assert_eq!(commands.len(), 3);
+        assert!(commands[0].as_str().unwrap().starts_with("rustc "));
+        assert_eq!(commands[1], rust.output["command"]);
+
+        let go = tool
+            .execute(
+                json!({ "language": "go", "code": "package main", "dry_run": true }),
+                &context,
+            )
+            .await
+            .unwrap();
+        let commands = go.output["commands"].as_array().unwrap();
+        assert_eq!(commands.len(), 2);
+        assert!(commands[0]
+            .as_str()
+            .unwrap()
+            .contains("go run /tmp/arachnid_go_"));
+        assert!(commands[1].as_str().unwrap().starts_with("rm -f "));
+
+        let ruby = tool
+            .execute(
+                json!({ "language": "ruby", "code": "puts 'hi'", "dry_run": true }),
+                &context,
+            )
+            .await
+            .unwrap();
+        assert!(ruby.output["command"].as_str().unwrap().contains("ruby -e"));
+
+        assert!(tool
+            .execute(
+                json!({ "language": "cobol", "code": "", "dry_run": true }),
+                &context,
+            )
+            .await
+            .is_err());
+    }
+
+    #[test]
+    fn test_temp_programs_are_unique() {
+        let first = TempProgram::new("go", "/tmp").unwrap();
+        let second = TempProgram::new("go", "/tmp").unwrap();
+        assert_ne!(first.source, second.source);
+        assert!(first.source.ends_with(".go"));
+        assert!(TempProgram::new("python", "/tmp").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_temp_files_removed_after_compile_failure() {
+        let dir = tempfile::tempdir().unwrap();
+        let temp_dir = tempfile::tempdir().unwrap();
+        let tool = ExecuteCodeTool::new(local_client(dir.path()))
+            .with_temp_dir(temp_dir.path().to_str().unwrap());
+        let context = ToolContext {
+            agent_id: Uuid::new_v4(),
+            web_id: Uuid::new_v4(),
+            sandbox_path: dir.path().to_path_buf(),
+        };
+
+        let result = tool
+            .execute(
+                json!({
+                    "language": "rust",
+                    "code": "fn main() {",
+                    "rollback_on_error": false,
+                }),
+                &context,
+            )
+            .await
+            .unwrap();
+
+        assert!(!result.success);
+        assert!(result.output["stderr"]
+            .as_str()
+            .unwrap()
+            .starts_with("Compilation failed"));
+        assert_eq!(std::fs::read_dir(temp_dir.path()).unwrap().count(), 0);
+    }
 }
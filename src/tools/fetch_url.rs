@@ -5,12 +5,52 @@ use serde_json::{json, Value};
 use super::{Tool, ToolContext, ToolResult};
 use crate::definitions::ToolType;
 
+pub(crate) const DEFAULT_MAX_REDIRECTS: usize = 5;
+pub(crate) const DEFAULT_MAX_BYTES: usize = 10 * 1024 * 1024;
+
+/// Read a response body chunk by chunk, giving up as soon as it passes `max_bytes`.
+pub(crate) async fn read_body_capped(
+    mut response: reqwest::Response,
+    max_bytes: usize,
+) -> Result<Vec<u8>> {
+    let too_large = || anyhow!("Response too large: exceeds {} bytes", max_bytes);
+
+    if response
+        .content_length()
+        .is_some_and(|len| len as usize > max_bytes)
+    {
+        return Err(too_large());
+    }
+
+    let mut body = Vec::new();
+    while let Some(chunk) = response.chunk().await? {
+        if body.len() + chunk.len() > max_bytes {
+            return Err(too_large());
+        }
+        body.extend_from_slice(&chunk);
+    }
+
+    Ok(body)
+}
+
 pub struct FetchUrlTool {
     client: reqwest::Client,
+    max_redirects: usize,
+    max_bytes: usize,
 }
 
 impl FetchUrlTool {
+    /// Reads `FETCH_TIMEOUT_SECS`, `FETCH_MAX_REDIRECTS` and `FETCH_MAX_BYTES`.
     pub fn new() -> Result<Self> {
+        let env = |var: &str| std::env::var(var).ok().and_then(|s| s.parse().ok());
+
+        Self::with_limits(
+            env("FETCH_MAX_REDIRECTS").unwrap_or(DEFAULT_MAX_REDIRECTS),
+            env("FETCH_MAX_BYTES").unwrap_or(DEFAULT_MAX_BYTES),
+        )
+    }
+
+    pub fn with_limits(max_redirects: usize, max_bytes: usize) -> Result<Self> {
         let timeout_secs = std::env::var("FETCH_TIMEOUT_SECS")
             .ok()
             .and_then(|s| s.parse().ok())
@@ -18,14 +58,25 @@ impl FetchUrlTool {
 
         let client = reqwest::Client::builder()
             .timeout(std::time::Duration::from_secs(timeout_secs))
+            .redirect(reqwest::redirect::Policy::limited(max_redirects))
             .user_agent("Arachnid/1.0")
             .build()?;
 
-        Ok(Self { client })
+        Ok(Self {
+            client,
+            max_redirects,
+            max_bytes,
+        })
     }
 
     async fn fetch_and_extract(&self, url: &str) -> Result<FetchedContent> {
-        let response = self.client.get(url).send().await?;
+        let response = self.client.get(url).send().await.map_err(|e| {
+            if e.is_redirect() {
+                anyhow!("Too many redirects: stopped after {}", self.max_redirects)
+            } else {
+                e.into()
+            }
+        })?;
 
         let status = response.status();
         let headers = response.headers().clone();
@@ -35,7 +86,8 @@ impl FetchUrlTool {
             .unwrap_or("")
             .to_string();
 
-        let body = response.text().await?;
+        let body = String::from_utf8_lossy(&read_body_capped(response, self.max_bytes).await?)
+            .into_owned();
 
         let extracted = if content_type.contains("html") {
             extract_text_from_html(&body)
@@ -183,6 +235,70 @@ mod tests {
         assert!(!text.contains(".hidden"));
     }
 
+    async fn serve(router: axum::Router) -> String {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, router).await.unwrap() });
+        format!("http://{}", addr)
+    }
+
+    fn test_context() -> ToolContext {
+        ToolContext {
+            agent_id: uuid::Uuid::new_v4(),
+            web_id: uuid::Uuid::new_v4(),
+            sandbox_path: std::env::temp_dir(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_redirect_loop_stops_at_cap() {
+        use axum::{response::Redirect, routing::get};
+
+        let base = serve(
+            axum::Router::new().route("/loop", get(|| async { Redirect::temporary("/loop") })),
+        )
+        .await;
+        let tool = FetchUrlTool::with_limits(3, DEFAULT_MAX_BYTES).unwrap();
+
+        let err = tool
+            .execute(json!({ "url": format!("{}/loop", base) }), &test_context())
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("Too many redirects"), "{}", err);
+    }
+
+    #[tokio::test]
+    async fn test_oversized_body_is_rejected() {
+        use axum::routing::get;
+
+        let base = serve(
+            axum::Router::new()
+                // Streamed without a content-length, so the cap is hit mid-body.
+                .route(
+                    "/big",
+                    get(|| async {
+                        let chunks = (0..64).map(|_| Ok::<_, std::io::Error>("x".repeat(1024)));
+                        axum::body::Body::from_stream(futures::stream::iter(chunks))
+                    }),
+                )
+                .route("/small", get(|| async { "ok" })),
+        )
+        .await;
+        let tool = FetchUrlTool::with_limits(DEFAULT_MAX_REDIRECTS, 1024).unwrap();
+
+        let err = tool
+            .execute(json!({ "url": format!("{}/big", base) }), &test_context())
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("Response too large"), "{}", err);
+
+        let result = tool
+            .execute(json!({ "url": format!("{}/small", base) }), &test_context())
+            .await
+            .unwrap();
+        assert_eq!(result.output["text"], "ok");
+    }
+
     #[tokio::test]
     async fn test_fetch_url_tool_creation() {
         let tool = FetchUrlTool::new().unwrap();
@@ -0,0 +1,463 @@
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use reqwest::header::{
+    HeaderMap, HeaderName, HeaderValue, AUTHORIZATION, CONTENT_TYPE, COOKIE, LOCATION,
+};
+use reqwest::{Method, Url};
+use serde_json::{json, Value};
+use std::net::{IpAddr, SocketAddr};
+use std::time::Duration;
+
+use super::fetch_url::{read_body_capped, DEFAULT_MAX_BYTES, DEFAULT_MAX_REDIRECTS};
+use super::{Tool, ToolContext, ToolResult};
+use crate::definitions::ToolType;
+
+/// Where `http_request` may send requests.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HttpRequestConfig {
+    /// Hosts that may be contacted; subdomains of an entry also match. Empty
+    /// allows none, and `*` allows any host that passes the private-address
+    /// check.
+    pub allowed_hosts: Vec<String>,
+    /// Permit loopback, private and link-local destinations.
+    pub allow_private: bool,
+    pub max_redirects: usize,
+    pub max_bytes: usize,
+    pub timeout_secs: u64,
+}
+
+impl Default for HttpRequestConfig {
+    fn default() -> Self {
+        Self {
+            allowed_hosts: Vec::new(),
+            allow_private: false,
+            max_redirects: DEFAULT_MAX_REDIRECTS,
+            max_bytes: DEFAULT_MAX_BYTES,
+            timeout_secs: 30,
+        }
+    }
+}
+
+impl HttpRequestConfig {
+    /// Reads `HTTP_REQUEST_ALLOWED_HOSTS` (comma-separated, `*` for any host),
+    /// `HTTP_REQUEST_ALLOW_PRIVATE`, and the `FETCH_*` size/redirect/timeout caps.
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+        let env = |var: &str| std::env::var(var).ok();
+
+        Self {
+            allowed_hosts: env("HTTP_REQUEST_ALLOWED_HOSTS")
+                .unwrap_or_default()
+                .split(',')
+                .map(|h| h.trim().to_lowercase())
+                .filter(|h| !h.is_empty())
+                .collect(),
+            allow_private: env("HTTP_REQUEST_ALLOW_PRIVATE").is_some_and(|v| v == "true"),
+            max_redirects: env("FETCH_MAX_REDIRECTS")
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(defaults.max_redirects),
+            max_bytes: env("FETCH_MAX_BYTES")
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(defaults.max_bytes),
+            timeout_secs: env("FETCH_TIMEOUT_SECS")
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(defaults.timeout_secs),
+        }
+    }
+
+    fn host_allowed(&self, host: &str) -> bool {
+        self.allowed_hosts.iter().any(|allowed| {
+            allowed == "*" || host == allowed || host.ends_with(&format!(".{}", allowed))
+        })
+    }
+}
+
+/// Loopback, private, link-local and other addresses that shouldn't be reachable
+/// from an agent by default: anything that isn't a unicast public address.
+fn is_private(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(v4) => {
+            let [a, b, c, _] = v4.octets();
+            v4.is_loopback()
+                || v4.is_private()
+                || v4.is_link_local()
+                || v4.is_multicast()
+                || v4.is_documentation()
+                // "This network", 0.0.0.0/8
+                || a == 0
+                // Carrier-grade NAT, 100.64.0.0/10
+                || (a == 100 && (64..128).contains(&b))
+                // IETF protocol assignments, 192.0.0.0/24
+                || (a == 192 && b == 0 && c == 0)
+                // Benchmarking, 198.18.0.0/15
+                || (a == 198 && (b & 0xfe) == 18)
+                // Reserved 240.0.0.0/4, including broadcast
+                || a >= 240
+        }
+        IpAddr::V6(v6) => {
+            if let Some(v4) = v6.to_ipv4_mapped() {
+                return is_private(IpAddr::V4(v4));
+            }
+            let segments = v6.segments();
+            v6.is_loopback()
+                || v6.is_unspecified()
+                || v6.is_multicast()
+                // Unique local fc00::/7 and link-local fe80::/10
+                || (segments[0] & 0xfe00) == 0xfc00
+                || (segments[0] & 0xffc0) == 0xfe80
+                // NAT64 64:ff9b::/96, which reaches IPv4 addresses
+                || (segments[0] == 0x64 && segments[1] == 0xff9b)
+                // Discard-only 100::/64
+                || (segments[0] == 0x100 && segments[1..4] == [0, 0, 0])
+                // Documentation 2001:db8::/32
+                || (segments[0] == 0x2001 && segments[1] == 0xdb8)
+        }
+    }
+}
+
+pub struct HttpRequestTool {
+    config: HttpRequestConfig,
+}
+
+impl HttpRequestTool {
+    pub fn new(config: HttpRequestConfig) -> Self {
+        Self { config }
+    }
+
+    /// Check a URL against the allowlist and resolve it, refusing private
+    /// destinations. The returned addresses are pinned for the request so a
+    /// second DNS lookup can't swap in a different target.
+    async fn resolve(&self, url: &Url) -> Result<(String, Vec<SocketAddr>)> {
+        if url.scheme() != "http" && url.scheme() != "https" {
+            return Err(anyhow!("URL must start with http:// or https://"));
+        }
+        let host = url
+            .host_str()
+            .ok_or_else(|| anyhow!("URL has no host: {}", url))?
+            .trim_start_matches('[')
+            .trim_end_matches(']')
+            .to_lowercase();
+
+        if !self.config.host_allowed(&host) {
+            return Err(anyhow!("Host not in allowlist: {}", host));
+        }
+
+        let port = url.port_or_known_default().unwrap_or(80);
+        let addrs: Vec<SocketAddr> = tokio::net::lookup_host((host.as_str(), port))
+            .await?
+            .collect();
+        if addrs.is_empty() {
+            return Err(anyhow!("Could not resolve host: {}", host));
+        }
+
+        if !self.config.allow_private {
+            if let Some(addr) = addrs.iter().find(|a| is_private(a.ip())) {
+                return Err(anyhow!(
+                    "Refusing request to private address {} ({})",
+                    addr.ip(),
+                    host
+                ));
+            }
+        }
+
+        Ok((host, addrs))
+    }
+
+    /// Send the request, following redirects by hand so every hop is checked.
+    async fn send(
+        &self,
+        method: Method,
+        url: Url,
+        headers: HeaderMap,
+        body: Option<Vec<u8>>,
+    ) -> Result<(Url, reqwest::Response)> {
+        let mut method = method;
+        let mut url = url;
+        let mut headers = headers;
+        let mut body = body;
+
+        for _ in 0..=self.config.max_redirects {
+            let (host, addrs) = self.resolve(&url).await?;
+            let client = reqwest::Client::builder()
+                .timeout(Duration::from_secs(self.config.timeout_secs))
+                .redirect(reqwest::redirect::Policy::none())
+                .user_agent("Arachnid/1.0")
+                .resolve_to_addrs(&host, &addrs)
+                .build()?;
+
+            let mut request = client
+                .request(method.clone(), url.clone())
+                .headers(headers.clone());
+            if let Some(body) = &body {
+                request = request.body(body.clone());
+            }
+            let response = request.send().await?;
+
+            if !response.status().is_redirection() {
+                return Ok((url, response));
+            }
+
+            let location = response
+                .headers()
+                .get(LOCATION)
+                .and_then(|v| v.to_str().ok())
+                .ok_or_else(|| anyhow!("Redirect without a Location header"))?;
+            let next = url.join(location)?;
+            // Credentials are meant for the host they were given for.
+            if next.host_str() != url.host_str() {
+                headers.remove(AUTHORIZATION);
+                headers.remove(COOKIE);
+            }
+            url = next;
+
+            // 303, and 301/302 for POST, turn into a bodiless GET as browsers do.
+            let status = response.status().as_u16();
+            if status == 303 || (matches!(status, 301 | 302) && method == Method::POST) {
+                method = Method::GET;
+                body = None;
+            }
+        }
+
+        Err(anyhow!(
+            "Too many redirects: stopped after {}",
+            self.config.max_redirects
+        ))
+    }
+}
+
+#[async_trait]
+impl Tool for HttpRequestTool {
+    fn tool_type(&self) -> ToolType {
+        ToolType::HttpRequest
+    }
+
+    fn name(&self) -> &str {
+        "http_request"
+    }
+
+    fn description(&self) -> &str {
+        "Make an HTTP request (GET, POST, PUT, PATCH, DELETE) with custom headers and a body. JSON responses are parsed. Only allowed, public hosts can be reached."
+    }
+
+    fn parameters_schema(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "url": {
+                    "type": "string",
+                    "description": "The URL to request"
+                },
+                "method": {
+                    "type": "string",
+                    "enum": ["GET", "POST", "PUT", "PATCH", "DELETE", "HEAD"],
+                    "description": "HTTP method (default: GET)",
+                    "default": "GET"
+                },
+                "headers": {
+                    "type": "object",
+                    "description": "Request headers as name/value pairs",
+                    "additionalProperties": { "type": "string" }
+                },
+                "body": {
+                    "description": "Request body; objects and arrays are sent as JSON, strings as-is"
+                }
+            },
+            "required": ["url"]
+        })
+    }
+
+    async fn execute(&self, params: Value, _context: &ToolContext) -> Result<ToolResult> {
+        let url_str = params["url"]
+            .as_str()
+            .ok_or_else(|| anyhow!("Missing url parameter"))?;
+        let url = Url::parse(url_str).map_err(|e| anyhow!("Invalid URL {}: {}", url_str, e))?;
+
+        let method_str = params["method"].as_str().unwrap_or("GET").to_uppercase();
+        let method = match method_str.as_str() {
+            "GET" | "POST" | "PUT" | "PATCH" | "DELETE" | "HEAD" => {
+                Method::from_bytes(method_str.as_bytes())?
+            }
+            _ => return Err(anyhow!("Unsupported method: {}", method_str)),
+        };
+
+        let mut headers = HeaderMap::new();
+        if let Some(map) = params["headers"].as_object() {
+            for (name, value) in map {
+                let value = value
+                    .as_str()
+                    .ok_or_else(|| anyhow!("Header {} must be a string", name))?;
+                headers.insert(
+                    HeaderName::from_bytes(name.as_bytes())?,
+                    HeaderValue::from_str(value)?,
+                );
+            }
+        }
+
+        let body = match &params["body"] {
+            Value::Null => None,
+            Value::String(text) => Some(text.clone().into_bytes()),
+            value => {
+                if !headers.contains_key(CONTENT_TYPE) {
+                    headers.insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+                }
+                Some(serde_json::to_vec(value)?)
+            }
+        };
+
+        let (final_url, response) = self.send(method, url, headers, body).await?;
+
+        let status = response.status();
+        let content_type = response
+            .headers()
+            .get(CONTENT_TYPE)
+            .and_then(|v| v.to_str().ok())
+            .unwrap_or("")
+            .to_string();
+        let bytes = read_body_capped(response, self.config.max_bytes).await?;
+        let text = String::from_utf8_lossy(&bytes).into_owned();
+        let parsed = if content_type.contains("json") {
+            serde_json::from_str::<Value>(&text).ok()
+        } else {
+            None
+        };
+
+        Ok(ToolResult {
+            success: status.is_success(),
+            output: json!({
+                "url": final_url.as_str(),
+                "method": method_str,
+                "status_code": status.as_u16(),
+                "content_type": content_type,
+                "json": parsed,
+                "text": if parsed.is_some() { None } else { Some(text) },
+                "size": bytes.len(),
+            }),
+            artifacts: vec![],
+            side_effects: vec![],
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{http::HeaderMap as AxumHeaders, routing::post, Json, Router};
+
+    async fn serve(router: Router) -> String {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, router).await.unwrap() });
+        format!("http://{}", addr)
+    }
+
+    fn test_context() -> ToolContext {
+        ToolContext {
+            agent_id: uuid::Uuid::new_v4(),
+            web_id: uuid::Uuid::new_v4(),
+            sandbox_path: std::env::temp_dir(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_post_json_with_headers() {
+        let base = serve(Router::new().route(
+            "/items",
+            post(|headers: AxumHeaders, Json(body): Json<Value>| async move {
+                Json(json!({
+                    "auth": headers.get("authorization").and_then(|v| v.to_str().ok()),
+                    "received": body,
+                }))
+            }),
+        ))
+        .await;
+
+        let tool = HttpRequestTool::new(HttpRequestConfig {
+            allowed_hosts: vec!["127.0.0.1".to_string()],
+            allow_private: true,
+            ..HttpRequestConfig::default()
+        });
+
+        let result = tool
+            .execute(
+                json!({
+                    "url": format!("{}/items", base),
+                    "method": "POST",
+                    "headers": { "Authorization": "Bearer t0ken" },
+                    "body": { "name": "widget" },
+                }),
+                &test_context(),
+            )
+            .await
+            .unwrap();
+
+        assert!(result.success);
+        assert_eq!(result.output["status_code"], 200);
+        assert_eq!(result.output["json"]["auth"], "Bearer t0ken");
+        assert_eq!(result.output["json"]["received"]["name"], "widget");
+    }
+
+    #[tokio::test]
+    async fn test_private_and_unlisted_hosts_are_blocked() {
+        let base = serve(Router::new().route("/", post(|| async { "reached" }))).await;
+
+        // Nothing is allowed until the allowlist says so.
+        let tool = HttpRequestTool::new(HttpRequestConfig::default());
+        let err = tool
+            .execute(
+                json!({ "url": "https://example.com", "method": "POST" }),
+                &test_context(),
+            )
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("allowlist"), "{}", err);
+
+        let tool = HttpRequestTool::new(HttpRequestConfig {
+            allowed_hosts: vec!["*".to_string()],
+            ..HttpRequestConfig::default()
+        });
+        let err = tool
+            .execute(json!({ "url": base, "method": "POST" }), &test_context())
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("private address"), "{}", err);
+
+        let tool = HttpRequestTool::new(HttpRequestConfig {
+            allowed_hosts: vec!["api.example.com".to_string()],
+            allow_private: true,
+            ..HttpRequestConfig::default()
+        });
+        let err = tool
+            .execute(json!({ "url": base, "method": "POST" }), &test_context())
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("allowlist"), "{}", err);
+    }
+
+    #[test]
+    fn test_is_private() {
+        for ip in [
+            "127.0.0.1",
+            "10.1.2.3",
+            "192.168.0.1",
+            "169.254.169.254",
+            "::1",
+            "fd00::1",
+            "0.1.2.3",
+            "198.18.0.1",
+            "198.19.255.255",
+            "224.0.0.251",
+            "240.0.0.1",
+            "255.255.255.255",
+            "192.0.0.8",
+            "203.0.113.5",
+            "ff02::1",
+            "64:ff9b::a00:1",
+            "2001:db8::1",
+        ] {
+            assert!(is_private(ip.parse().unwrap()), "{}", ip);
+        }
+        for ip in ["93.184.216.34", "198.20.0.1", "2606:4700::1111"] {
+            assert!(!is_private(ip.parse().unwrap()), "{}", ip);
+        }
+    }
+}
@@ -1,7 +1,12 @@
 use anyhow::{anyhow, Result};
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
 use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
 use std::process::{Command, Stdio};
+use std::sync::{Arc, Mutex, OnceLock, Weak};
 use std::time::Duration;
 
 #[derive(Debug, Clone)]
@@ -11,6 +16,11 @@ pub struct ImpresarioConfig {
     pub user: String,
     pub key_path: Option<PathBuf>,
     pub timeout_secs: u64,
+    /// How long an idle multiplexed SSH connection is kept open.
+    /// `None` opens a fresh connection for every command.
+    pub control_persist_secs: Option<u64>,
+    /// Extra attempts after an SSH transport failure (not a command failure).
+    pub max_retries: u32,
 }
 
 impl Default for ImpresarioConfig {
@@ -24,13 +34,146 @@ impl Default for ImpresarioConfig {
             user: std::env::var("IMPRESARIO_USER").unwrap_or_else(|_| "ubuntu".to_string()),
             key_path: std::env::var("IMPRESARIO_KEY").ok().map(PathBuf::from),
             timeout_secs: 300,
+            control_persist_secs: match std::env::var("IMPRESARIO_CONTROL_PERSIST")
+                .ok()
+                .and_then(|p| p.parse::<u64>().ok())
+            {
+                Some(0) => None,
+                Some(secs) => Some(secs),
+                None => Some(60),
+            },
+            max_retries: std::env::var("IMPRESARIO_RETRIES")
+                .ok()
+                .and_then(|r| r.parse().ok())
+                .unwrap_or(2),
         }
     }
 }
 
+impl ImpresarioConfig {
+    /// Control socket shared by every client talking to the same user/host/port.
+    /// It lives under `~/.ssh/arachnid`, beside ssh's own `cm-*` sockets, so
+    /// no other user can plant or reach it; `None` without a home directory.
+    pub fn control_path(&self) -> Option<PathBuf> {
+        let home = std::env::var_os("HOME").filter(|home| !home.is_empty())?;
+        let mut hasher = DefaultHasher::new();
+        (&self.user, &self.host, self.port).hash(&mut hasher);
+        // Unix socket paths are length-limited, so use a short hash rather than the host name.
+        Some(
+            PathBuf::from(home)
+                .join(".ssh")
+                .join("arachnid")
+                .join(format!("cm-{:016x}", hasher.finish())),
+        )
+    }
+}
+
+/// Create `dir` (and `~/.ssh` if missing) so only this user can enter it.
+fn prepare_control_dir(dir: &Path) -> std::io::Result<()> {
+    use std::os::unix::fs::{DirBuilderExt, PermissionsExt};
+
+    std::fs::DirBuilder::new()
+        .recursive(true)
+        .mode(0o700)
+        .create(dir)?;
+    std::fs::set_permissions(dir, std::fs::Permissions::from_mode(0o700))
+}
+
+/// Masters still in use, by socket path, so every client for a destination
+/// shares one.
+fn control_masters() -> &'static Mutex<HashMap<PathBuf, Weak<ControlMaster>>> {
+    static MASTERS: OnceLock<Mutex<HashMap<PathBuf, Weak<ControlMaster>>>> = OnceLock::new();
+    MASTERS.get_or_init(Default::default)
+}
+
+/// Raw bytes per remote write command; base64 output stays well under ARG_MAX.
+const WRITE_CHUNK_BYTES: usize = 48 * 1024;
+
+const SSH_TRANSPORT_EXIT_CODE: i32 = 255;
+const RETRY_BASE_DELAY_MS: u64 = 200;
+
+/// Upper bound on commands `exec_many` runs at once.
+const MAX_PARALLEL_EXECS: usize = 4;
+
 #[derive(Debug, Clone)]
 pub struct ImpresarioClient {
     config: ImpresarioConfig,
+    control: Option<Arc<ControlMaster>>,
+    ssh_program: PathBuf,
+}
+
+/// Owns the multiplexed master connection and closes it once the last client
+/// sharing it is dropped. Clients for the same destination get the same one
+/// from [`ControlMaster::shared`].
+#[derive(Debug)]
+struct ControlMaster {
+    path: PathBuf,
+    destination: String,
+    port: u16,
+}
+
+impl ControlMaster {
+    /// The master every client for `config`'s destination shares, or `None`
+    /// when no private socket directory is available.
+    fn shared(config: &ImpresarioConfig) -> Option<Arc<Self>> {
+        let path = config.control_path()?;
+        if let Err(e) = prepare_control_dir(path.parent()?) {
+            log::warn!(
+                "Not multiplexing SSH to {}: cannot create {}: {}",
+                config.host,
+                path.display(),
+                e
+            );
+            return None;
+        }
+
+        let mut masters = control_masters().lock().unwrap();
+        if let Some(master) = masters.get(&path).and_then(Weak::upgrade) {
+            return Some(master);
+        }
+        let master = Arc::new(Self {
+            path: path.clone(),
+            destination: format!("{}@{}", config.user, config.host),
+            port: config.port,
+        });
+        masters.insert(path, Arc::downgrade(&master));
+        Some(master)
+    }
+}
+
+impl Drop for ControlMaster {
+    fn drop(&mut self) {
+        {
+            let mut masters = control_masters().lock().unwrap();
+            // A client created since the last one dropped may already have
+            // registered a new master here.
+            if masters
+                .get(&self.path)
+                .is_some_and(|master| master.strong_count() == 0)
+            {
+                masters.remove(&self.path);
+            }
+        }
+        if !self.path.exists() {
+            return;
+        }
+
+        let mut exit = Command::new("ssh");
+        exit.args(["-O", "exit", "-o"])
+            .arg(format!("ControlPath={}", self.path.display()))
+            .args(["-p", &self.port.to_string(), &self.destination])
+            .stdout(Stdio::null())
+            .stderr(Stdio::null());
+        // Keep the wait off the async runtime's worker threads.
+        match tokio::runtime::Handle::try_current() {
+            Ok(runtime) => {
+                runtime.spawn_blocking(move || exit.status());
+            }
+            Err(_) => {
+                let _ = exit.status();
+            }
+        }
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -41,9 +184,31 @@ pub struct ExecResult {
     pub success: bool,
 }
 
+/// An entry found by [`ImpresarioClient::list_dir_entries`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct RemoteDirEntry {
+    /// Path relative to the listed directory.
+    pub path: String,
+    pub is_dir: bool,
+    pub size: u64,
+}
+
 impl ImpresarioClient {
     pub fn new(config: ImpresarioConfig) -> Self {
-        Self { config }
+        let control = config
+            .control_persist_secs
+            .and_then(|_| ControlMaster::shared(&config));
+        Self {
+            config,
+            control,
+            ssh_program: PathBuf::from("ssh"),
+        }
+    }
+
+    /// Use a different `ssh` binary (e.g. a wrapper script).
+    pub fn with_ssh_program(mut self, program: impl Into<PathBuf>) -> Self {
+        self.ssh_program = program.into();
+        self
     }
 
     pub fn from_env() -> Result<Self> {
@@ -58,13 +223,38 @@ impl ImpresarioClient {
         Ok(Self::new(config))
     }
 
+    /// Run a command remotely, retrying SSH transport failures with backoff.
+    ///
+    /// The remote command's own exit code is passed through untouched.
     pub async fn exec(&self, command: &str) -> Result<ExecResult> {
+        let mut attempt = 0;
+        loop {
+            let result = self.exec_once(command).await?;
+            if !is_transport_failure(&result) || attempt >= self.config.max_retries {
+                return Ok(result);
+            }
+
+            let delay = RETRY_BASE_DELAY_MS * 2u64.pow(attempt);
+            log::warn!(
+                "SSH transport failure to {} (attempt {}), retrying in {}ms: {}",
+                self.connection_info(),
+                attempt + 1,
+                delay,
+                result.stderr.trim()
+            );
+            tokio::time::sleep(Duration::from_millis(delay)).await;
+            attempt += 1;
+        }
+    }
+
+    async fn exec_once(&self, command: &str) -> Result<ExecResult> {
         let ssh_command = self.build_ssh_command(command);
+        let program = self.ssh_program.clone();
 
         let output = tokio::time::timeout(
             Duration::from_secs(self.config.timeout_secs),
             tokio::task::spawn_blocking(move || {
-                Command::new("ssh")
+                Command::new(program)
                     .args(&ssh_command)
                     .stdout(Stdio::piped())
                     .stderr(Stdio::piped())
@@ -83,6 +273,57 @@ impl ImpresarioClient {
         })
     }
 
+    /// Run independent commands concurrently, returning results in input order.
+    ///
+    /// A command that cannot be run at all (spawn failure, timeout) is reported
+    /// as a failed `ExecResult` rather than aborting the rest.
+    pub async fn exec_many(&self, commands: &[String]) -> Result<Vec<ExecResult>> {
+        use futures::stream::{self, StreamExt};
+
+        let results = stream::iter(commands.iter().map(|command| async move {
+            self.exec(command).await.unwrap_or_else(|e| ExecResult {
+                stdout: String::new(),
+                stderr: e.to_string(),
+                exit_code: -1,
+                success: false,
+            })
+        }))
+        .buffered(MAX_PARALLEL_EXECS)
+        .collect()
+        .await;
+
+        Ok(results)
+    }
+
+    /// Whether a multiplexed master connection is currently up.
+    pub async fn has_master_connection(&self) -> bool {
+        let Some(control) = &self.control else {
+            return false;
+        };
+        let args = vec![
+            "-O".to_string(),
+            "check".to_string(),
+            "-o".to_string(),
+            format!("ControlPath={}", control.path.display()),
+            "-p".to_string(),
+            control.port.to_string(),
+            control.destination.clone(),
+        ];
+
+        let program = self.ssh_program.clone();
+        tokio::task::spawn_blocking(move || {
+            Command::new(program)
+                .args(&args)
+                .stdout(Stdio::null())
+                .stderr(Stdio::null())
+                .status()
+                .map(|s| s.success())
+                .unwrap_or(false)
+        })
+        .await
+        .unwrap_or(false)
+    }
+
     pub async fn read_file(&self, path: &str) -> Result<String> {
         let result = self.exec(&format!("cat {}", shell_quote(path))).await?;
 
@@ -93,18 +334,60 @@ impl ImpresarioClient {
         Ok(result.stdout)
     }
 
+    /// Read a file byte-for-byte (transferred as base64).
+    pub async fn read_bytes(&self, path: &str) -> Result<Vec<u8>> {
+        let result = self
+            .exec(&format!("base64 < {}", shell_quote(path)))
+            .await?;
+
+        if !result.success {
+            return Err(anyhow!("Failed to read file: {}", result.stderr));
+        }
+
+        let encoded: String = result.stdout.split_whitespace().collect();
+        Ok(BASE64.decode(encoded)?)
+    }
+
     pub async fn write_file(&self, path: &str, content: &str) -> Result<()> {
-        let safe_content = content.replace('\'', "'\\''");
-        let command = format!(
-            "cat > {} <<'ARACHNID_EOF'\n{}\nARACHNID_EOF",
-            shell_quote(path),
-            safe_content
-        );
+        self.write_bytes(path, content.as_bytes()).await
+    }
 
-        let result = self.exec(&command).await?;
+    /// Write arbitrary bytes to a remote file.
+    ///
+    /// Content is sent base64-encoded and decoded remotely, in chunks so large
+    /// files stay under the command-line length limit.
+    pub async fn write_bytes(&self, path: &str, content: &[u8]) -> Result<()> {
+        let target = shell_quote(path);
+        let mut chunks = content.chunks(WRITE_CHUNK_BYTES).peekable();
+
+        if chunks.peek().is_none() {
+            let result = self.exec(&format!(": > {}", target)).await?;
+            if !result.success {
+                return Err(anyhow!("Failed to write file: {}", result.stderr));
+            }
+            return Ok(());
+        }
 
-        if !result.success {
-            return Err(anyhow!("Failed to write file: {}", result.stderr));
+        for (i, chunk) in chunks.enumerate() {
+            let redirect = if i == 0 { ">" } else { ">>" };
+            let command = format!(
+                "printf '%s' '{}' | base64 -d {} {}",
+                BASE64.encode(chunk),
+                redirect,
+                target
+            );
+
+            // An append may have landed before the connection dropped, so
+            // only the first, truncating chunk is safe to retry.
+            let result = if i == 0 {
+                self.exec(&command).await?
+            } else {
+                self.exec_once(&command).await?
+            };
+
+            if !result.success {
+                return Err(anyhow!("Failed to write file: {}", result.stderr));
+            }
         }
 
         Ok(())
@@ -125,6 +408,58 @@ impl ImpresarioClient {
             .collect())
     }
 
+    /// List entries under `path` down to `max_depth` levels, with type and size.
+    pub async fn list_dir_entries(
+        &self,
+        path: &str,
+        max_depth: usize,
+    ) -> Result<Vec<RemoteDirEntry>> {
+        let result = self
+            .exec(&format!(
+                "find {} -mindepth 1 -maxdepth {} -printf '%y\\t%s\\t%P\\n'",
+                shell_quote(path),
+                max_depth.max(1)
+            ))
+            .await?;
+
+        if !result.success {
+            return Err(anyhow!("Failed to list directory: {}", result.stderr));
+        }
+
+        Ok(result
+            .stdout
+            .lines()
+            .filter_map(|line| {
+                let mut fields = line.splitn(3, '\t');
+                let kind = fields.next()?;
+                let size = fields.next()?.parse().ok()?;
+                let path = fields.next()?.to_string();
+                Some(RemoteDirEntry {
+                    path,
+                    is_dir: kind == "d",
+                    size,
+                })
+            })
+            .collect())
+    }
+
+    /// Remove a file or empty directory; `recursive` also removes non-empty directories.
+    pub async fn remove_path(&self, path: &str, recursive: bool) -> Result<()> {
+        let path = shell_quote(path);
+        let remove_dir = if recursive { "rm -r" } else { "rmdir" };
+        let result = self
+            .exec(&format!(
+                "if [ -d {path} ] && [ ! -L {path} ]; then {remove_dir} {path}; else rm {path}; fi"
+            ))
+            .await?;
+
+        if !result.success {
+            return Err(anyhow!("Failed to delete: {}", result.stderr.trim()));
+        }
+
+        Ok(())
+    }
+
     pub async fn file_exists(&self, path: &str) -> Result<bool> {
         let result = self
             .exec(&format!("test -f {} && echo exists", shell_quote(path)))
@@ -169,6 +504,17 @@ impl ImpresarioClient {
             self.config.port.to_string(),
         ];
 
+        if let (Some(control), Some(persist)) = (&self.control, self.config.control_persist_secs) {
+            args.extend([
+                "-o".to_string(),
+                "ControlMaster=auto".to_string(),
+                "-o".to_string(),
+                format!("ControlPath={}", control.path.display()),
+                "-o".to_string(),
+                format!("ControlPersist={}", persist),
+            ]);
+        }
+
         if let Some(key_path) = &self.config.key_path {
             args.push("-i".to_string());
             args.push(key_path.to_string_lossy().to_string());
@@ -188,16 +534,69 @@ impl ImpresarioClient {
     }
 }
 
-fn shell_quote(s: &str) -> String {
-    if s.contains('\'') {
-        format!("\"{}\"", s.replace('"', "\\\""))
-    } else {
-        format!("'{}'", s)
+/// `ssh` exits 255 when the connection itself fails, but so can the remote
+/// command, so only a 255 with one of ssh's own messages counts. A silent 255
+/// is the command's and is never retried.
+fn is_transport_failure(result: &ExecResult) -> bool {
+    if result.exit_code != SSH_TRANSPORT_EXIT_CODE {
+        return false;
+    }
+    let stderr = result.stderr.to_lowercase();
+    [
+        "ssh:",
+        "kex_exchange_identification",
+        "client_loop:",
+        "packet_write_wait",
+        "closed by remote host",
+        "mux_client",
+        "control socket",
+    ]
+    .iter()
+    .any(|marker| stderr.contains(marker))
+}
+
+/// Quote `s` as a single POSIX shell word. Inside single quotes nothing is
+/// special, so each `'` closes the quote, adds an escaped quote and reopens.
+pub(crate) fn shell_quote(s: &str) -> String {
+    format!("'{}'", s.replace('\'', "'\\''"))
+}
+
+#[cfg(test)]
+pub(crate) mod test_support {
+    use super::*;
+
+    /// Stand-in for `ssh` that runs the remote command locally.
+    pub(crate) fn local_ssh(dir: &std::path::Path) -> PathBuf {
+        use std::os::unix::fs::PermissionsExt;
+
+        let path = dir.join("fake-ssh");
+        std::fs::write(
+            &path,
+            "#!/bin/sh\nfor last; do :; done\nexec sh -c \"$last\"\n",
+        )
+        .unwrap();
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755)).unwrap();
+        path
+    }
+
+    /// Client whose "remote" commands run on this machine.
+    pub(crate) fn local_client(dir: &std::path::Path) -> ImpresarioClient {
+        let config = ImpresarioConfig {
+            host: "local.test".to_string(),
+            port: 22,
+            user: "test".to_string(),
+            key_path: None,
+            timeout_secs: 60,
+            control_persist_secs: None,
+            max_retries: 0,
+        };
+        ImpresarioClient::new(config).with_ssh_program(local_ssh(dir))
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use super::test_support::local_ssh;
     use super::*;
 
     #[test]
@@ -212,7 +611,19 @@ mod tests {
 
     #[test]
     fn test_shell_quote_with_single_quote() {
-        assert_eq!(shell_quote("it's"), "\"it's\"");
+        assert_eq!(shell_quote("it's"), "'it'\\''s'");
+    }
+
+    #[test]
+    fn test_shell_quote_keeps_substitutions_literal() {
+        for path in ["a'$(touch pwned)", "b'`touch pwned`'", "c\"$(id)\""] {
+            let output = std::process::Command::new("sh")
+                .arg("-c")
+                .arg(format!("printf %s {}", shell_quote(path)))
+                .output()
+                .unwrap();
+            assert_eq!(String::from_utf8(output.stdout).unwrap(), path);
+        }
     }
 
     #[test]
@@ -231,4 +642,258 @@ mod tests {
         std::env::remove_var("IMPRESARIO_PORT");
         std::env::remove_var("IMPRESARIO_USER");
     }
+
+    fn test_config(port: u16) -> ImpresarioConfig {
+        ImpresarioConfig {
+            host: "sandbox.example.com".to_string(),
+            port,
+            user: "agent".to_string(),
+            key_path: None,
+            timeout_secs: 60,
+            control_persist_secs: Some(60),
+            max_retries: 2,
+        }
+    }
+
+    #[test]
+    fn test_control_path_per_destination() {
+        assert_eq!(
+            test_config(22).control_path(),
+            test_config(22).control_path()
+        );
+        assert_ne!(
+            test_config(22).control_path(),
+            test_config(2222).control_path()
+        );
+        assert!(test_config(22)
+            .control_path()
+            .unwrap()
+            .starts_with(PathBuf::from(std::env::var_os("HOME").unwrap()).join(".ssh")));
+    }
+
+    #[test]
+    fn test_clients_share_one_master() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let config = test_config(2200);
+        let path = config.control_path().unwrap();
+        let first = ImpresarioClient::new(config.clone());
+        let second = ImpresarioClient::new(config);
+        let master = first.control.clone().unwrap();
+        assert!(Arc::ptr_eq(&master, second.control.as_ref().unwrap()));
+
+        let dir = path.parent().unwrap();
+        let mode = std::fs::metadata(dir).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o700);
+
+        // Dropping one client leaves the master to the other.
+        drop(first);
+        drop(master);
+        assert!(control_masters()
+            .lock()
+            .unwrap()
+            .get(&path)
+            .and_then(Weak::upgrade)
+            .is_some());
+
+        drop(second);
+        assert!(!control_masters().lock().unwrap().contains_key(&path));
+    }
+
+    #[test]
+    fn test_ssh_command_uses_control_master() {
+        let config = test_config(22);
+        let control_path = format!("ControlPath={}", config.control_path().unwrap().display());
+        let args = ImpresarioClient::new(config).build_ssh_command("true");
+
+        assert!(args.contains(&"ControlMaster=auto".to_string()));
+        assert!(args.contains(&control_path));
+        assert!(args.contains(&"ControlPersist=60".to_string()));
+
+        let mut config = test_config(22);
+        config.control_persist_secs = None;
+        let args = ImpresarioClient::new(config).build_ssh_command("true");
+        assert!(!args.iter().any(|a| a.starts_with("ControlPath")));
+    }
+
+    #[tokio::test]
+    async fn test_exec_many_keeps_order_and_isolates_failures() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut config = test_config(22);
+        config.control_persist_secs = None;
+        let client = ImpresarioClient::new(config).with_ssh_program(local_ssh(dir.path()));
+
+        let commands = vec![
+            "sleep 0.2; echo first".to_string(),
+            "echo broken >&2; exit 3".to_string(),
+            "echo third".to_string(),
+        ];
+        let results = client.exec_many(&commands).await.unwrap();
+
+        assert_eq!(results.len(), 3);
+        assert!(results[0].success);
+        assert_eq!(results[0].stdout.trim(), "first");
+        assert!(!results[1].success);
+        assert_eq!(results[1].exit_code, 3);
+        assert_eq!(results[1].stderr.trim(), "broken");
+        assert!(results[2].success);
+        assert_eq!(results[2].stdout.trim(), "third");
+    }
+
+    #[tokio::test]
+    async fn test_write_file_round_trips_awkward_content() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut config = test_config(22);
+        config.control_persist_secs = None;
+        let client = ImpresarioClient::new(config).with_ssh_program(local_ssh(dir.path()));
+
+        let path = dir.path().join("out.txt");
+        let path = path.to_str().unwrap();
+        let content = "line one\nARACHNID_EOF\nit's a 'quoted' \"string\"\0after null\n$HOME `id`";
+
+        client.write_file(path, content).await.unwrap();
+        assert_eq!(client.read_file(path).await.unwrap(), content);
+        assert_eq!(std::fs::read(path).unwrap(), content.as_bytes());
+    }
+
+    #[tokio::test]
+    async fn test_write_bytes_binary_and_chunked() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut config = test_config(22);
+        config.control_persist_secs = None;
+        let client = ImpresarioClient::new(config).with_ssh_program(local_ssh(dir.path()));
+
+        let path = dir.path().join("blob.bin");
+        let path = path.to_str().unwrap();
+        let content: Vec<u8> = (0..WRITE_CHUNK_BYTES * 2 + 17)
+            .map(|i| (i % 256) as u8)
+            .collect();
+
+        client.write_bytes(path, &content).await.unwrap();
+        assert_eq!(client.read_bytes(path).await.unwrap(), content);
+
+        client.write_bytes(path, &[]).await.unwrap();
+        assert!(client.read_bytes(path).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_transport_failure_is_retried() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempfile::tempdir().unwrap();
+        let marker = dir.path().join("attempted");
+        let ssh = dir.path().join("flaky-ssh");
+        std::fs::write(
+            &ssh,
+            format!(
+                "#!/bin/sh\nfor last; do :; done\n\
+                 if [ ! -f '{0}' ]; then touch '{0}'; echo 'kex_exchange_identification: read: Connection reset by peer' >&2; exit 255; fi\n\
+                 exec sh -c \"$last\"\n",
+                marker.display()
+            ),
+        )
+        .unwrap();
+        std::fs::set_permissions(&ssh, std::fs::Permissions::from_mode(0o755)).unwrap();
+
+        let mut config = test_config(22);
+        config.control_persist_secs = None;
+        let client = ImpresarioClient::new(config).with_ssh_program(&ssh);
+
+        let result = client.exec("echo recovered").await.unwrap();
+        assert!(result.success);
+        assert_eq!(result.stdout.trim(), "recovered");
+
+        // Command failures pass straight through without a retry.
+        let result = client.exec("exit 7").await.unwrap();
+        assert_eq!(result.exit_code, 7);
+    }
+
+    #[test]
+    fn test_is_transport_failure() {
+        let result = |exit_code, stderr: &str| ExecResult {
+            stdout: String::new(),
+            stderr: stderr.to_string(),
+            exit_code,
+            success: exit_code == 0,
+        };
+
+        assert!(is_transport_failure(&result(
+            255,
+            "ssh: connect to host x port 22: Connection refused"
+        )));
+        assert!(is_transport_failure(&result(
+            255,
+            "client_loop: send disconnect: Broken pipe"
+        )));
+        assert!(!is_transport_failure(&result(1, "Connection refused")));
+        assert!(!is_transport_failure(&result(255, "my script chose 255")));
+        assert!(!is_transport_failure(&result(255, "")));
+    }
+
+    #[tokio::test]
+    async fn test_append_chunk_is_not_retried() {
+        use std::os::unix::fs::PermissionsExt;
+
+        // Runs the first append, then reports the connection dropping.
+        let dir = tempfile::tempdir().unwrap();
+        let marker = dir.path().join("dropped");
+        let ssh = dir.path().join("dropping-ssh");
+        std::fs::write(
+            &ssh,
+            format!(
+                "#!/bin/sh\nfor last; do :; done\n\
+                 case \"$last\" in *'>>'*) if [ ! -f '{0}' ]; then touch '{0}'; sh -c \"$last\"; \
+                 echo 'client_loop: send disconnect: Broken pipe' >&2; exit 255; fi;; esac\n\
+                 exec sh -c \"$last\"\n",
+                marker.display()
+            ),
+        )
+        .unwrap();
+        std::fs::set_permissions(&ssh, std::fs::Permissions::from_mode(0o755)).unwrap();
+
+        let mut config = test_config(22);
+        config.control_persist_secs = None;
+        let client = ImpresarioClient::new(config).with_ssh_program(&ssh);
+
+        let path = dir.path().join("blob.bin");
+        let content = vec![7u8; WRITE_CHUNK_BYTES + 10];
+        assert!(client
+            .write_bytes(path.to_str().unwrap(), &content)
+            .await
+            .is_err());
+        // The append ran once, not once per attempt.
+        assert_eq!(std::fs::read(&path).unwrap().len(), content.len());
+    }
+
+    /// Needs a reachable sandbox: set IMPRESARIO_TEST_HOST (and optionally
+    /// IMPRESARIO_TEST_USER / IMPRESARIO_KEY).
+    #[tokio::test]
+    async fn test_second_exec_reuses_connection() {
+        let Ok(host) = std::env::var("IMPRESARIO_TEST_HOST") else {
+            return;
+        };
+        let config = ImpresarioConfig {
+            host,
+            user: std::env::var("IMPRESARIO_TEST_USER").unwrap_or_else(|_| "ubuntu".to_string()),
+            control_persist_secs: Some(30),
+            ..ImpresarioConfig::default()
+        };
+        let client = ImpresarioClient::new(config);
+
+        let started = std::time::Instant::now();
+        assert!(client.exec("true").await.unwrap().success);
+        let first = started.elapsed();
+        assert!(client.has_master_connection().await);
+
+        let started = std::time::Instant::now();
+        assert!(client.exec("true").await.unwrap().success);
+        let second = started.elapsed();
+
+        assert!(
+            second < first,
+            "second exec ({:?}) should skip the handshake ({:?})",
+            second,
+            first
+        );
+    }
 }
@@ -0,0 +1,317 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use serde_json::{json, Value};
+use std::path::{Path, PathBuf};
+use tokio::fs;
+
+use super::impresario_client::ImpresarioClient;
+use super::sandbox;
+use super::{Tool, ToolContext, ToolResult};
+use crate::definitions::ToolType;
+
+/// Deepest recursion a caller may ask for.
+const MAX_DEPTH: usize = 10;
+/// Entries returned before the listing is cut off.
+const MAX_ENTRIES: usize = 1000;
+
+/// Translate a shell glob (`*`, `**`, `?`) into an anchored regex.
+fn glob_to_regex(glob: &str) -> Result<regex::Regex> {
+    let mut pattern = String::from("^");
+    let mut chars = glob.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '*' if chars.peek() == Some(&'*') => {
+                chars.next();
+                pattern.push_str(".*");
+            }
+            '*' => pattern.push_str("[^/]*"),
+            '?' => pattern.push_str("[^/]"),
+            c => pattern.push_str(&regex::escape(&c.to_string())),
+        }
+    }
+    pattern.push('$');
+    Ok(regex::Regex::new(&pattern)?)
+}
+
+#[derive(Debug, Clone)]
+struct DirEntry {
+    path: String,
+    is_dir: bool,
+    size: u64,
+}
+
+pub enum ListDirectoryMode {
+    Local,
+    Remote(ImpresarioClient),
+}
+
+pub struct ListDirectoryTool {
+    mode: ListDirectoryMode,
+    sandbox_root: PathBuf,
+}
+
+impl ListDirectoryTool {
+    pub fn new_local(sandbox_root: PathBuf) -> Self {
+        Self {
+            mode: ListDirectoryMode::Local,
+            sandbox_root,
+        }
+    }
+
+    pub fn new_remote(client: ImpresarioClient, sandbox_root: PathBuf) -> Self {
+        Self {
+            mode: ListDirectoryMode::Remote(client),
+            sandbox_root,
+        }
+    }
+
+    async fn list_local(&self, root: &Path, depth: usize) -> Result<Vec<DirEntry>> {
+        let mut entries = Vec::new();
+        let mut pending = vec![(root.to_path_buf(), 1)];
+
+        while let Some((dir, level)) = pending.pop() {
+            let mut read_dir = fs::read_dir(&dir).await?;
+            while let Some(entry) = read_dir.next_entry().await? {
+                let metadata = entry.metadata().await?;
+                let relative = entry
+                    .path()
+                    .strip_prefix(root)
+                    .unwrap_or(&entry.path())
+                    .to_string_lossy()
+                    .to_string();
+
+                if metadata.is_dir() && level < depth {
+                    pending.push((entry.path(), level + 1));
+                }
+                entries.push(DirEntry {
+                    path: relative,
+                    is_dir: metadata.is_dir(),
+                    size: metadata.len(),
+                });
+            }
+        }
+
+        Ok(entries)
+    }
+}
+
+#[async_trait]
+impl Tool for ListDirectoryTool {
+    fn tool_type(&self) -> ToolType {
+        ToolType::ListDirectory
+    }
+
+    fn name(&self) -> &str {
+        "list_directory"
+    }
+
+    fn description(&self) -> &str {
+        "List files and directories within the sandbox, with their type and size. Can recurse and filter by a glob pattern."
+    }
+
+    fn parameters_schema(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "path": {
+                    "type": "string",
+                    "description": "Directory to list (relative to sandbox or absolute; default: sandbox root)",
+                    "default": "."
+                },
+                "depth": {
+                    "type": "integer",
+                    "description": "How many levels to descend; 1 lists only direct entries (default: 1, max: 10)",
+                    "default": 1
+                },
+                "pattern": {
+                    "type": "string",
+                    "description": "Optional glob filter (e.g., '*.rs', 'src/**/*.ts'); matched against the name, or the relative path if it contains '/'"
+                }
+            }
+        })
+    }
+
+    async fn execute(&self, params: Value, _context: &ToolContext) -> Result<ToolResult> {
+        let path = params["path"].as_str().unwrap_or(".");
+        let depth = (params["depth"].as_u64().unwrap_or(1) as usize).clamp(1, MAX_DEPTH);
+        let pattern = params["pattern"].as_str();
+        let matcher = pattern.map(glob_to_regex).transpose()?;
+
+        let validated_path = sandbox::resolve_path(&self.sandbox_root, path)?;
+
+        let mut entries = match &self.mode {
+            ListDirectoryMode::Local => {
+                sandbox::check_symlinks(&self.sandbox_root, &validated_path, true).await?;
+                self.list_local(&validated_path, depth).await?
+            }
+            ListDirectoryMode::Remote(client) => client
+                .list_dir_entries(validated_path.to_str().unwrap(), depth)
+                .await?
+                .into_iter()
+                .map(|e| DirEntry {
+                    path: e.path,
+                    is_dir: e.is_dir,
+                    size: e.size,
+                })
+                .collect(),
+        };
+
+        if let (Some(matcher), Some(pattern)) = (&matcher, pattern) {
+            entries.retain(|e| {
+                let subject = if pattern.contains('/') {
+                    e.path.as_str()
+                } else {
+                    e.path.rsplit('/').next().unwrap_or(&e.path)
+                };
+                matcher.is_match(subject)
+            });
+        }
+
+        entries.sort_by(|a, b| a.path.cmp(&b.path));
+        let truncated = entries.len() > MAX_ENTRIES;
+        entries.truncate(MAX_ENTRIES);
+
+        let listed: Vec<Value> = entries
+            .iter()
+            .map(|e| {
+                json!({
+                    "path": e.path,
+                    "name": e.path.rsplit('/').next().unwrap_or(&e.path),
+                    "type": if e.is_dir { "dir" } else { "file" },
+                    "size": e.size,
+                })
+            })
+            .collect();
+
+        Ok(ToolResult {
+            success: true,
+            output: json!({
+                "path": path,
+                "depth": depth,
+                "pattern": pattern,
+                "num_entries": listed.len(),
+                "truncated": truncated,
+                "entries": listed,
+            }),
+            artifacts: vec![],
+            side_effects: vec![],
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tools::impresario_client::test_support::local_client;
+    use tempfile::TempDir;
+
+    async fn nested_sandbox() -> TempDir {
+        let temp_dir = TempDir::new().unwrap();
+        fs::create_dir_all(temp_dir.path().join("src/nested"))
+            .await
+            .unwrap();
+        fs::write(temp_dir.path().join("README.md"), "hello")
+            .await
+            .unwrap();
+        fs::write(temp_dir.path().join("src/lib.rs"), "fn a() {}")
+            .await
+            .unwrap();
+        fs::write(temp_dir.path().join("src/nested/mod.rs"), "")
+            .await
+            .unwrap();
+        temp_dir
+    }
+
+    fn paths(result: &ToolResult) -> Vec<String> {
+        result.output["entries"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|e| e["path"].as_str().unwrap().to_string())
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_list_directory_nested() {
+        let temp_dir = nested_sandbox().await;
+        let tool = ListDirectoryTool::new_local(temp_dir.path().to_path_buf());
+        let context = ToolContext {
+            agent_id: uuid::Uuid::new_v4(),
+            web_id: uuid::Uuid::new_v4(),
+            sandbox_path: temp_dir.path().to_path_buf(),
+        };
+
+        let result = tool.execute(json!({}), &context).await.unwrap();
+        assert_eq!(paths(&result), vec!["README.md", "src"]);
+        assert_eq!(result.output["entries"][0]["type"], "file");
+        assert_eq!(result.output["entries"][0]["size"], 5);
+        assert_eq!(result.output["entries"][1]["type"], "dir");
+
+        let result = tool.execute(json!({ "depth": 3 }), &context).await.unwrap();
+        assert_eq!(
+            paths(&result),
+            vec![
+                "README.md",
+                "src",
+                "src/lib.rs",
+                "src/nested",
+                "src/nested/mod.rs"
+            ]
+        );
+
+        let result = tool
+            .execute(json!({ "depth": 3, "pattern": "*.rs" }), &context)
+            .await
+            .unwrap();
+        assert_eq!(paths(&result), vec!["src/lib.rs", "src/nested/mod.rs"]);
+
+        assert!(tool
+            .execute(json!({ "path": "../" }), &context)
+            .await
+            .is_err());
+
+        #[cfg(unix)]
+        {
+            let outside = TempDir::new().unwrap();
+            std::os::unix::fs::symlink(outside.path(), temp_dir.path().join("out")).unwrap();
+            let err = tool
+                .execute(json!({ "path": "out" }), &context)
+                .await
+                .unwrap_err();
+            assert!(err.to_string().contains("escapes sandbox"), "{}", err);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_list_directory_remote() {
+        let temp_dir = nested_sandbox().await;
+        let ssh_dir = TempDir::new().unwrap();
+        let tool = ListDirectoryTool::new_remote(
+            local_client(ssh_dir.path()),
+            temp_dir.path().to_path_buf(),
+        );
+        let context = ToolContext {
+            agent_id: uuid::Uuid::new_v4(),
+            web_id: uuid::Uuid::new_v4(),
+            sandbox_path: temp_dir.path().to_path_buf(),
+        };
+
+        let result = tool
+            .execute(json!({ "path": "src", "depth": 2 }), &context)
+            .await
+            .unwrap();
+        assert_eq!(paths(&result), vec!["lib.rs", "nested", "nested/mod.rs"]);
+        assert_eq!(result.output["entries"][0]["size"], 9);
+        assert_eq!(result.output["entries"][1]["type"], "dir");
+    }
+
+    #[test]
+    fn test_glob_to_regex() {
+        assert!(glob_to_regex("*.rs").unwrap().is_match("lib.rs"));
+        assert!(!glob_to_regex("*.rs").unwrap().is_match("src/lib.rs"));
+        assert!(glob_to_regex("src/**/*.rs")
+            .unwrap()
+            .is_match("src/a/b/mod.rs"));
+        assert!(glob_to_regex("?.txt").unwrap().is_match("a.txt"));
+    }
+}
@@ -1,9 +1,16 @@
+pub mod delete_file;
+pub mod dry_run;
 pub mod emit_signal;
 pub mod execute_code;
 pub mod fetch_url;
+pub mod http_request;
 pub mod impresario_client;
+pub mod list_directory;
+pub mod query_database;
 pub mod read_file;
+pub mod run_tests;
 pub mod runtime;
+pub mod sandbox;
 pub mod search_codebase;
 pub mod spawn_agent;
 pub mod web_search;
@@ -11,7 +18,7 @@ pub mod write_file;
 
 use anyhow::Result;
 use async_trait::async_trait;
-use serde_json::Value;
+use serde_json::{json, Value};
 use std::path::PathBuf;
 
 use crate::definitions::ToolType;
@@ -42,9 +49,43 @@ pub enum SideEffect {
     SignalEmitted(Signal),
     AgentSpawned(AgentId),
     FileWritten(PathBuf),
+    FileDeleted(PathBuf),
     CodeExecuted { language: String, exit_code: i32 },
 }
 
+impl Artifact {
+    /// What the audit log keeps. Data artifacts are recorded by size, not
+    /// content.
+    pub fn summary(&self) -> Value {
+        match self {
+            Artifact::File { path, size } => json!({ "type": "file", "path": path, "size": size }),
+            Artifact::Data { name, content } => {
+                json!({ "type": "data", "name": name, "size": content.len() })
+            }
+        }
+    }
+}
+
+impl SideEffect {
+    /// What the audit log keeps. Signals are recorded by id.
+    pub fn summary(&self) -> Value {
+        match self {
+            SideEffect::SignalEmitted(signal) => {
+                json!({ "type": "signal_emitted", "signal_id": signal.id })
+            }
+            SideEffect::AgentSpawned(agent_id) => {
+                json!({ "type": "agent_spawned", "agent_id": agent_id })
+            }
+            SideEffect::FileWritten(path) => json!({ "type": "file_written", "path": path }),
+            SideEffect::FileDeleted(path) => json!({ "type": "file_deleted", "path": path }),
+            SideEffect::CodeExecuted {
+                language,
+                exit_code,
+            } => json!({ "type": "code_executed", "language": language, "exit_code": exit_code }),
+        }
+    }
+}
+
 #[async_trait]
 pub trait Tool: Send + Sync {
     fn tool_type(&self) -> ToolType;
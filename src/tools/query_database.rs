@@ -0,0 +1,296 @@
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use serde_json::{json, Value};
+use sqlx::postgres::PgPoolOptions;
+use sqlx::{PgPool, Row};
+use std::time::Duration;
+
+use super::{Tool, ToolContext, ToolResult};
+use crate::definitions::ToolType;
+
+/// Where `query_database` connects and how much it may read.
+#[derive(Debug, Clone, PartialEq)]
+pub struct QueryDatabaseConfig {
+    /// Ideally a role that can only read. Unset leaves the tool unregistered.
+    pub database_url: Option<String>,
+    pub max_rows: usize,
+    pub timeout_secs: u64,
+}
+
+impl Default for QueryDatabaseConfig {
+    fn default() -> Self {
+        Self {
+            database_url: None,
+            max_rows: 100,
+            timeout_secs: 5,
+        }
+    }
+}
+
+impl QueryDatabaseConfig {
+    /// Reads `QUERY_DATABASE_URL`, `QUERY_DATABASE_MAX_ROWS` and
+    /// `QUERY_DATABASE_TIMEOUT_SECS`. Arachnid's own DATABASE_URL is never
+    /// used, so agents can't read the runtime's tables by default.
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+        let env = |var: &str| std::env::var(var).ok();
+
+        Self {
+            database_url: env("QUERY_DATABASE_URL").filter(|url| !url.is_empty()),
+            max_rows: env("QUERY_DATABASE_MAX_ROWS")
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(defaults.max_rows),
+            timeout_secs: env("QUERY_DATABASE_TIMEOUT_SECS")
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(defaults.timeout_secs),
+        }
+    }
+}
+
+/// Words that let a `SELECT` or `WITH` do more than read: data-modifying
+/// CTEs, `SELECT ... INTO` (which creates a table) and `FOR UPDATE` locks.
+/// The read-only transaction refuses these as well.
+const FORBIDDEN_KEYWORDS: &[&str] = &["insert", "update", "delete", "merge", "into"];
+
+/// Check `sql` is a single `SELECT` (or `WITH ... SELECT`) and return it
+/// without a trailing semicolon. String literals, quoted identifiers and
+/// comments are skipped, so a `;` or keyword inside them is harmless.
+pub fn validate_select(sql: &str) -> Result<String> {
+    let sql = sql.trim();
+    let sql = sql.strip_suffix(';').unwrap_or(sql).trim_end();
+
+    let mut words: Vec<String> = Vec::new();
+    let chars: Vec<char> = sql.chars().collect();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        match c {
+            '\'' | '"' => {
+                i += 1;
+                loop {
+                    match chars.get(i) {
+                        None => return Err(anyhow!("Unterminated quote in query")),
+                        // A doubled quote is an escaped one.
+                        Some(&q) if q == c && chars.get(i + 1) == Some(&c) => i += 2,
+                        Some(&q) if q == c => break,
+                        Some(_) => i += 1,
+                    }
+                }
+                i += 1;
+            }
+            '-' if chars.get(i + 1) == Some(&'-') => {
+                while i < chars.len() && chars[i] != '\n' {
+                    i += 1;
+                }
+            }
+            '/' if chars.get(i + 1) == Some(&'*') => {
+                let rest: String = chars[i + 2..].iter().collect();
+                let end = rest
+                    .find("*/")
+                    .ok_or_else(|| anyhow!("Unterminated comment in query"))?;
+                i += 2 + rest[..end].chars().count() + 2;
+            }
+            '$' => {
+                // Dollar quoting: $$...$$ or $tag$...$tag$. A bare $1 is a
+                // parameter, which can't be bound here anyway.
+                let tag_end = chars[i + 1..]
+                    .iter()
+                    .position(|&ch| !(ch.is_alphanumeric() || ch == '_'))
+                    .map(|p| i + 1 + p);
+                match tag_end {
+                    Some(end) if chars[end] == '$' => {
+                        let tag: String = chars[i..=end].iter().collect();
+                        let rest: String = chars[end + 1..].iter().collect();
+                        let close = rest
+                            .find(&tag)
+                            .ok_or_else(|| anyhow!("Unterminated dollar quote in query"))?;
+                        i = end + 1 + rest[..close].chars().count() + tag.chars().count();
+                    }
+                    _ => return Err(anyhow!("Query parameters are not supported")),
+                }
+            }
+            ';' => return Err(anyhow!("Only a single statement is allowed")),
+            c if c.is_alphabetic() || c == '_' => {
+                let start = i;
+                while i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == '_') {
+                    i += 1;
+                }
+                words.push(chars[start..i].iter().collect::<String>().to_lowercase());
+            }
+            _ => i += 1,
+        }
+    }
+
+    match words.first().map(String::as_str) {
+        Some("select") | Some("with") => {}
+        Some(first) => {
+            return Err(anyhow!(
+                "Only SELECT queries are allowed, not {}",
+                first.to_uppercase()
+            ))
+        }
+        None => return Err(anyhow!("Query is empty")),
+    }
+    if let Some(word) = words
+        .iter()
+        .find(|w| FORBIDDEN_KEYWORDS.contains(&w.as_str()))
+    {
+        return Err(anyhow!(
+            "Only read-only SELECT queries are allowed; found {}",
+            word.to_uppercase()
+        ));
+    }
+
+    Ok(sql.to_string())
+}
+
+pub struct QueryDatabaseTool {
+    pool: PgPool,
+    config: QueryDatabaseConfig,
+}
+
+impl QueryDatabaseTool {
+    /// Connects lazily, on the first query.
+    pub fn new(database_url: &str, config: QueryDatabaseConfig) -> Result<Self> {
+        let pool = PgPoolOptions::new()
+            .max_connections(2)
+            .acquire_timeout(Duration::from_secs(config.timeout_secs.max(1)))
+            .connect_lazy(database_url)?;
+        Ok(Self { pool, config })
+    }
+
+    /// Run `sql` in a read-only transaction, so the database refuses writes
+    /// even if validation missed one, and return up to `max_rows + 1` rows.
+    async fn fetch(&self, sql: &str) -> Result<Vec<Value>> {
+        let mut tx = self.pool.begin().await?;
+        sqlx::query("SET TRANSACTION READ ONLY")
+            .execute(&mut *tx)
+            .await?;
+        // SET can't take bind parameters; the value is a number we control.
+        sqlx::query(&format!(
+            "SET LOCAL statement_timeout = {}",
+            self.config.timeout_secs.saturating_mul(1000)
+        ))
+        .execute(&mut *tx)
+        .await?;
+
+        let rows = sqlx::query(&format!(
+            "SELECT to_jsonb(q) AS row FROM ({}\n) q LIMIT $1",
+            sql
+        ))
+        .bind(self.config.max_rows as i64 + 1)
+        .fetch_all(&mut *tx)
+        .await?;
+        tx.rollback().await?;
+
+        Ok(rows.iter().map(|r| r.get::<Value, _>("row")).collect())
+    }
+}
+
+#[async_trait]
+impl Tool for QueryDatabaseTool {
+    fn tool_type(&self) -> ToolType {
+        ToolType::QueryDatabase
+    }
+
+    fn name(&self) -> &str {
+        "query_database"
+    }
+
+    fn description(&self) -> &str {
+        "Run a single read-only SELECT query against the configured PostgreSQL database and get the rows back as JSON objects."
+    }
+
+    fn parameters_schema(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "sql": {
+                    "type": "string",
+                    "description": "A single SELECT statement (WITH ... SELECT is allowed)"
+                }
+            },
+            "required": ["sql"]
+        })
+    }
+
+    async fn execute(&self, params: Value, _context: &ToolContext) -> Result<ToolResult> {
+        let sql = params["sql"]
+            .as_str()
+            .ok_or_else(|| anyhow!("Missing sql parameter"))?;
+        let sql = validate_select(sql)?;
+
+        // A little slack over the server-side timeout, for a stuck connection.
+        let timeout = Duration::from_secs(self.config.timeout_secs + 1);
+        let mut rows = tokio::time::timeout(timeout, self.fetch(&sql))
+            .await
+            .map_err(|_| anyhow!("Query timed out after {}s", self.config.timeout_secs))??;
+
+        let truncated = rows.len() > self.config.max_rows;
+        rows.truncate(self.config.max_rows);
+
+        Ok(ToolResult {
+            success: true,
+            output: json!({
+                "row_count": rows.len(),
+                "truncated": truncated,
+                "rows": rows,
+            }),
+            artifacts: vec![],
+            side_effects: vec![],
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_accepts_single_select() {
+        assert_eq!(validate_select("SELECT 1;").unwrap(), "SELECT 1");
+        assert!(validate_select("  select * from users where id = 3  ").is_ok());
+        assert!(
+            validate_select("WITH recent AS (SELECT * FROM orders) SELECT * FROM recent").is_ok()
+        );
+        // Semicolons and keywords inside literals, identifiers and comments.
+        assert!(validate_select("SELECT 'a; drop table users' AS note").is_ok());
+        assert!(validate_select(r#"SELECT "update" FROM "delete""#).is_ok());
+        assert!(validate_select("SELECT 'it''s; fine' -- ; delete\nFROM t").is_ok());
+        assert!(validate_select("SELECT /* ; insert */ 1").is_ok());
+        assert!(validate_select("SELECT $tag$; drop$tag$").is_ok());
+    }
+
+    #[test]
+    fn test_rejects_anything_but_one_select() {
+        for sql in [
+            "",
+            ";",
+            "DELETE FROM users",
+            "UPDATE users SET admin = true",
+            "SELECT 1; DROP TABLE users",
+            "SELECT 1;;",
+            "select * into backup from users",
+            "SELECT * FROM users FOR UPDATE",
+            "WITH gone AS (DELETE FROM users RETURNING *) SELECT * FROM gone",
+            "SELECT 'unterminated",
+            "SELECT * FROM users WHERE id = $1",
+            "EXPLAIN ANALYZE SELECT 1",
+            "SHOW search_path",
+        ] {
+            assert!(
+                validate_select(sql).is_err(),
+                "{:?} should be rejected",
+                sql
+            );
+        }
+    }
+
+    #[test]
+    fn test_config_defaults() {
+        let config = QueryDatabaseConfig::default();
+        assert_eq!(config.database_url, None);
+        assert_eq!(config.max_rows, 100);
+        assert_eq!(config.timeout_secs, 5);
+    }
+}
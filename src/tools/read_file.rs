@@ -1,27 +1,37 @@
 use anyhow::{anyhow, Result};
 use async_trait::async_trait;
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
 use serde_json::{json, Value};
-use std::path::{Component, Path, PathBuf};
+use std::path::PathBuf;
 use tokio::fs;
+use tokio::io::AsyncReadExt;
 
 use super::impresario_client::ImpresarioClient;
+use super::sandbox;
 use super::{Tool, ToolContext, ToolResult};
 use crate::definitions::ToolType;
 
-fn normalize_path(path: &Path) -> PathBuf {
-    let mut components = Vec::new();
-    for component in path.components() {
-        match component {
-            Component::ParentDir => {
-                if !components.is_empty() {
-                    components.pop();
-                }
-            }
-            Component::CurDir => {}
-            _ => components.push(component),
+/// How much of a file is returned unless the call asks for less or more.
+pub const DEFAULT_READ_MAX_BYTES: usize = 256 * 1024;
+/// Upper bound on `max_bytes`, so one read can't swamp the context.
+pub const READ_MAX_BYTES_LIMIT: usize = 4 * 1024 * 1024;
+
+/// Text unless it has NUL bytes or isn't UTF-8. A multi-byte character cut
+/// off by truncation doesn't count; it is dropped instead.
+fn decode_text(mut bytes: Vec<u8>, truncated: bool) -> Option<String> {
+    if bytes.contains(&0) {
+        return None;
+    }
+    match String::from_utf8(bytes) {
+        Ok(text) => Some(text),
+        Err(e) if truncated && e.utf8_error().error_len().is_none() => {
+            let valid = e.utf8_error().valid_up_to();
+            bytes = e.into_bytes();
+            bytes.truncate(valid);
+            String::from_utf8(bytes).ok()
         }
+        Err(_) => None,
     }
-    components.iter().collect()
 }
 
 pub enum ReadFileMode {
@@ -48,24 +58,6 @@ impl ReadFileTool {
             sandbox_root,
         }
     }
-
-    fn validate_path(&self, path: &str) -> Result<PathBuf> {
-        let full_path = if path.starts_with('/') {
-            PathBuf::from(path)
-        } else {
-            self.sandbox_root.join(path)
-        };
-
-        // Normalize the path by resolving .. and . components
-        let normalized = normalize_path(&full_path);
-
-        // Check if the normalized path is within the sandbox
-        if !normalized.starts_with(&self.sandbox_root) {
-            return Err(anyhow!("Path escapes sandbox: {}", path));
-        }
-
-        Ok(full_path)
-    }
 }
 
 #[async_trait]
@@ -79,7 +71,7 @@ impl Tool for ReadFileTool {
     }
 
     fn description(&self) -> &str {
-        "Read contents of a file within the sandbox. Path must be relative to sandbox root or absolute within sandbox."
+        "Read contents of a file within the sandbox. Path must be relative to sandbox root or absolute within sandbox. Large files are truncated to max_bytes; binary files are reported without content unless read as base64."
     }
 
     fn parameters_schema(&self) -> Value {
@@ -89,6 +81,19 @@ impl Tool for ReadFileTool {
                 "path": {
                     "type": "string",
                     "description": "Path to the file (relative to sandbox or absolute)"
+                },
+                "encoding": {
+                    "type": "string",
+                    "enum": ["utf8", "base64"],
+                    "description": "How to return the content; use base64 for binary files (default: utf8)",
+                    "default": "utf8"
+                },
+                "max_bytes": {
+                    "type": "integer",
+                    "description": "Return at most this many bytes from the start of the file",
+                    "default": DEFAULT_READ_MAX_BYTES,
+                    "minimum": 1,
+                    "maximum": READ_MAX_BYTES_LIMIT
                 }
             },
             "required": ["path"]
@@ -100,24 +105,76 @@ impl Tool for ReadFileTool {
             .as_str()
             .ok_or_else(|| anyhow!("Missing path parameter"))?;
 
-        let validated_path = self.validate_path(path)?;
+        let encoding = params["encoding"].as_str().unwrap_or("utf8");
+        if encoding != "utf8" && encoding != "base64" {
+            return Err(anyhow!("Unsupported encoding: {}", encoding));
+        }
+
+        let max_bytes = params["max_bytes"]
+            .as_u64()
+            .map_or(DEFAULT_READ_MAX_BYTES, |n| n as usize)
+            .clamp(1, READ_MAX_BYTES_LIMIT);
 
-        let content = match &self.mode {
-            ReadFileMode::Local => fs::read_to_string(&validated_path).await?,
+        let validated_path = sandbox::resolve_path(&self.sandbox_root, path)?;
+
+        let (mut bytes, size) = match &self.mode {
+            ReadFileMode::Local => {
+                sandbox::check_symlinks(&self.sandbox_root, &validated_path, true).await?;
+                let file = fs::File::open(&validated_path)
+                    .await
+                    .map_err(|e| anyhow!("Cannot read {}: {}", path, e))?;
+                let size = file.metadata().await?.len() as usize;
+                let mut bytes = Vec::with_capacity(size.min(max_bytes));
+                file.take(max_bytes as u64).read_to_end(&mut bytes).await?;
+                (bytes, size)
+            }
             ReadFileMode::Remote(client) => {
-                client.read_file(validated_path.to_str().unwrap()).await?
+                let bytes = client.read_bytes(validated_path.to_str().unwrap()).await?;
+                let size = bytes.len();
+                (bytes, size)
+            }
+        };
+        let truncated = size > max_bytes;
+        bytes.truncate(max_bytes);
+
+        let content = if encoding == "base64" {
+            BASE64.encode(&bytes)
+        } else {
+            match decode_text(bytes, truncated) {
+                Some(text) => text,
+                None => {
+                    return Ok(ToolResult {
+                        success: true,
+                        output: json!({
+                            "path": path,
+                            "binary": true,
+                            "size": size,
+                            "notice": "Binary file; read it with encoding \"base64\" for the bytes",
+                        }),
+                        artifacts: vec![],
+                        side_effects: vec![],
+                    })
+                }
             }
         };
 
-        let size = content.len();
+        let mut output = json!({
+            "path": path,
+            "content": content,
+            "encoding": encoding,
+            "size": size,
+            "truncated": truncated,
+        });
+        if truncated {
+            output["notice"] = json!(format!(
+                "Showing the first {} of {} bytes; pass a larger max_bytes to read more",
+                max_bytes, size
+            ));
+        }
 
         Ok(ToolResult {
             success: true,
-            output: json!({
-                "path": path,
-                "content": content,
-                "size": size,
-            }),
+            output,
             artifacts: vec![],
             side_effects: vec![],
         })
@@ -152,16 +209,137 @@ mod tests {
 
         assert!(result.success);
         assert_eq!(result.output["content"], "Hello, World!");
+        assert_eq!(result.output["encoding"], "utf8");
         assert_eq!(result.output["size"], 13);
     }
 
-    #[test]
-    fn test_path_validation() {
+    #[tokio::test]
+    async fn test_read_binary_file_as_base64() {
         let temp_dir = TempDir::new().unwrap();
+        let blob = [0x89u8, b'P', b'N', b'G', 0x00, 0xff, 0xfe];
+        fs::write(temp_dir.path().join("image.png"), blob)
+            .await
+            .unwrap();
+
         let tool = ReadFileTool::new_local(temp_dir.path().to_path_buf());
+        let context = ToolContext {
+            agent_id: uuid::Uuid::new_v4(),
+            web_id: uuid::Uuid::new_v4(),
+            sandbox_path: temp_dir.path().to_path_buf(),
+        };
+
+        let result = tool
+            .execute(json!({ "path": "image.png" }), &context)
+            .await
+            .unwrap();
+        assert_eq!(result.output["binary"], true);
+        assert_eq!(result.output["size"], blob.len());
+        assert!(result.output.get("content").is_none());
+
+        let result = tool
+            .execute(
+                json!({ "path": "image.png", "encoding": "base64" }),
+                &context,
+            )
+            .await
+            .unwrap();
+        assert_eq!(result.output["encoding"], "base64");
+        assert_eq!(result.output["size"], blob.len());
+        let decoded = BASE64
+            .decode(result.output["content"].as_str().unwrap())
+            .unwrap();
+        assert_eq!(decoded, blob);
+    }
+
+    #[tokio::test]
+    async fn test_large_file_is_truncated() {
+        let temp_dir = TempDir::new().unwrap();
+        // 'é' is two bytes, so a 5-byte cut lands inside the third one.
+        fs::write(temp_dir.path().join("big.txt"), "éééé")
+            .await
+            .unwrap();
+        fs::write(
+            temp_dir.path().join("huge.txt"),
+            "x".repeat(DEFAULT_READ_MAX_BYTES + 10),
+        )
+        .await
+        .unwrap();
+
+        let tool = ReadFileTool::new_local(temp_dir.path().to_path_buf());
+        let context = ToolContext {
+            agent_id: uuid::Uuid::new_v4(),
+            web_id: uuid::Uuid::new_v4(),
+            sandbox_path: temp_dir.path().to_path_buf(),
+        };
+
+        let result = tool
+            .execute(json!({ "path": "big.txt", "max_bytes": 5 }), &context)
+            .await
+            .unwrap();
+        assert_eq!(result.output["content"], "éé");
+        assert_eq!(result.output["size"], 8);
+        assert_eq!(result.output["truncated"], true);
+        assert!(result.output["notice"]
+            .as_str()
+            .unwrap()
+            .contains("first 5 of 8 bytes"));
+
+        let result = tool
+            .execute(json!({ "path": "huge.txt" }), &context)
+            .await
+            .unwrap();
+        assert_eq!(
+            result.output["content"].as_str().unwrap().len(),
+            DEFAULT_READ_MAX_BYTES
+        );
+        assert_eq!(result.output["truncated"], true);
+
+        let result = tool
+            .execute(json!({ "path": "big.txt" }), &context)
+            .await
+            .unwrap();
+        assert_eq!(result.output["content"], "éééé");
+        assert_eq!(result.output["truncated"], false);
+        assert!(result.output.get("notice").is_none());
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_symlink_out_of_sandbox_is_rejected() {
+        let sandbox = TempDir::new().unwrap();
+        let outside = TempDir::new().unwrap();
+        let secret = outside.path().join("secret.txt");
+        fs::write(&secret, "top secret").await.unwrap();
+        fs::write(sandbox.path().join("notes.txt"), "notes")
+            .await
+            .unwrap();
+        std::os::unix::fs::symlink(&secret, sandbox.path().join("leak.txt")).unwrap();
+        std::os::unix::fs::symlink(outside.path(), sandbox.path().join("outside")).unwrap();
+        std::os::unix::fs::symlink(
+            sandbox.path().join("notes.txt"),
+            sandbox.path().join("alias.txt"),
+        )
+        .unwrap();
 
-        assert!(tool.validate_path("safe.txt").is_ok());
+        let tool = ReadFileTool::new_local(sandbox.path().to_path_buf());
+        let context = ToolContext {
+            agent_id: uuid::Uuid::new_v4(),
+            web_id: uuid::Uuid::new_v4(),
+            sandbox_path: sandbox.path().to_path_buf(),
+        };
+
+        for path in ["leak.txt", "outside/secret.txt"] {
+            let err = tool
+                .execute(json!({ "path": path }), &context)
+                .await
+                .unwrap_err();
+            assert!(err.to_string().contains("escapes sandbox"), "{}", err);
+        }
 
-        assert!(tool.validate_path("../escape.txt").is_err());
+        let result = tool
+            .execute(json!({ "path": "alias.txt" }), &context)
+            .await
+            .unwrap();
+        assert_eq!(result.output["content"], "notes");
     }
 }
@@ -0,0 +1,364 @@
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use regex::Regex;
+use serde_json::{json, Value};
+use std::path::{Path, PathBuf};
+
+use super::execute_code::ResourceLimits;
+use super::impresario_client::ImpresarioClient;
+use super::sandbox;
+use super::{SideEffect, Tool, ToolContext, ToolResult};
+use crate::definitions::ToolType;
+
+/// Failure names returned before the list is cut off.
+const MAX_REPORTED_FAILURES: usize = 50;
+/// Trailing output returned alongside the summary.
+const OUTPUT_TAIL_CHARS: usize = 4000;
+
+/// Limits unless `RUN_TESTS_*` overrides them. A test run builds first, and
+/// compilers and bundlers need far more memory, address space and CPU time
+/// than one `execute_code` program, so only the wall clock is capped.
+fn default_limits() -> ResourceLimits {
+    ResourceLimits {
+        max_memory_mb: None,
+        max_cpu_secs: None,
+        wall_timeout_secs: Some(1800),
+    }
+}
+
+/// Test frameworks the tool knows how to run and parse.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Runner {
+    Cargo,
+    Pytest,
+    Npm,
+}
+
+impl Runner {
+    fn parse(s: &str) -> Option<Self> {
+        match s {
+            "cargo" => Some(Runner::Cargo),
+            "pytest" => Some(Runner::Pytest),
+            "npm" => Some(Runner::Npm),
+            _ => None,
+        }
+    }
+
+    fn as_str(&self) -> &'static str {
+        match self {
+            Runner::Cargo => "cargo",
+            Runner::Pytest => "pytest",
+            Runner::Npm => "npm",
+        }
+    }
+
+    fn command(&self) -> &'static str {
+        match self {
+            Runner::Cargo => "cargo test --color never 2>&1",
+            Runner::Pytest => "python3 -m pytest -rf --color=no 2>&1",
+            Runner::Npm => "npm test --silent 2>&1",
+        }
+    }
+
+    /// Marker files that identify a project, checked in order.
+    const MARKERS: [(&'static str, Runner); 6] = [
+        ("Cargo.toml", Runner::Cargo),
+        ("pyproject.toml", Runner::Pytest),
+        ("pytest.ini", Runner::Pytest),
+        ("setup.py", Runner::Pytest),
+        ("requirements.txt", Runner::Pytest),
+        ("package.json", Runner::Npm),
+    ];
+}
+
+#[derive(Debug, Default, PartialEq)]
+struct TestSummary {
+    passed: u64,
+    failed: u64,
+    ignored: u64,
+    failures: Vec<String>,
+}
+
+fn parse_cargo(output: &str) -> TestSummary {
+    let counts =
+        Regex::new(r"test result: \w+\. (\d+) passed; (\d+) failed; (\d+) ignored").unwrap();
+    let failure = Regex::new(r"^test (\S+) \.\.\. FAILED").unwrap();
+
+    let mut summary = TestSummary::default();
+    for line in output.lines() {
+        if let Some(c) = counts.captures(line) {
+            summary.passed += c[1].parse::<u64>().unwrap_or(0);
+            summary.failed += c[2].parse::<u64>().unwrap_or(0);
+            summary.ignored += c[3].parse::<u64>().unwrap_or(0);
+        } else if let Some(c) = failure.captures(line) {
+            summary.failures.push(c[1].to_string());
+        }
+    }
+    summary
+}
+
+/// Counts from the last line mentioning passed/failed tests, which covers the
+/// pytest (`1 failed, 2 passed in 0.1s`) and jest (`Tests: 1 failed, 2 passed`)
+/// summaries.
+fn parse_counts(output: &str) -> TestSummary {
+    let count = |word: &str, line: &str| {
+        Regex::new(&format!(r"(\d+) {}", word))
+            .unwrap()
+            .captures(line)
+            .and_then(|c| c[1].parse::<u64>().ok())
+            .unwrap_or(0)
+    };
+
+    let summary_line = Regex::new(r"\d+ (passed|failed)").unwrap();
+
+    let mut summary = TestSummary::default();
+    if let Some(line) = output.lines().rev().find(|l| summary_line.is_match(l)) {
+        summary.passed = count("passed", line);
+        summary.failed = count("failed", line);
+        summary.ignored = count("skipped", line);
+    }
+
+    let failure = Regex::new(r"^(?:FAILED|✕)\s+(\S+)").unwrap();
+    summary.failures = output
+        .lines()
+        .filter_map(|l| failure.captures(l.trim()).map(|c| c[1].to_string()))
+        .collect();
+    summary
+}
+
+pub struct RunTestsTool {
+    client: ImpresarioClient,
+    sandbox_root: PathBuf,
+    limits: ResourceLimits,
+}
+
+impl RunTestsTool {
+    pub fn new(client: ImpresarioClient, sandbox_root: PathBuf) -> Self {
+        Self {
+            client,
+            sandbox_root,
+            limits: ResourceLimits::from_env_with_prefix("RUN_TESTS", default_limits()),
+        }
+    }
+
+    pub fn with_limits(mut self, limits: ResourceLimits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    async fn detect_runner(&self, dir: &Path) -> Result<Runner> {
+        for (marker, runner) in Runner::MARKERS {
+            let candidate = dir.join(marker);
+            if self.client.file_exists(candidate.to_str().unwrap()).await? {
+                return Ok(runner);
+            }
+        }
+        Err(anyhow!(
+            "Could not detect a test runner in {}; pass runner or command",
+            dir.display()
+        ))
+    }
+}
+
+#[async_trait]
+impl Tool for RunTestsTool {
+    fn tool_type(&self) -> ToolType {
+        ToolType::RunTests
+    }
+
+    fn name(&self) -> &str {
+        "run_tests"
+    }
+
+    fn description(&self) -> &str {
+        "Run a project's test suite in the sandbox (cargo test, pytest or npm test, auto-detected) and return pass/fail counts and the names of failing tests."
+    }
+
+    fn parameters_schema(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "path": {
+                    "type": "string",
+                    "description": "Project directory (relative to sandbox or absolute; default: sandbox root)",
+                    "default": "."
+                },
+                "runner": {
+                    "type": "string",
+                    "enum": ["cargo", "pytest", "npm"],
+                    "description": "Test runner to use; detected from project files when omitted"
+                },
+                "command": {
+                    "type": "string",
+                    "description": "Custom test command; output is parsed with the runner's format if one is given"
+                }
+            }
+        })
+    }
+
+    async fn execute(&self, params: Value, _context: &ToolContext) -> Result<ToolResult> {
+        let path = params["path"].as_str().unwrap_or(".");
+        let dir = sandbox::resolve_path(&self.sandbox_root, path)?;
+        let custom_command = params["command"].as_str();
+
+        let runner = match params["runner"].as_str() {
+            Some(name) => {
+                Some(Runner::parse(name).ok_or_else(|| anyhow!("Unknown runner: {}", name))?)
+            }
+            None if custom_command.is_some() => None,
+            None => Some(self.detect_runner(&dir).await?),
+        };
+
+        let command = match (custom_command, runner) {
+            (Some(command), _) => format!("{} 2>&1", command),
+            (None, Some(runner)) => runner.command().to_string(),
+            (None, None) => unreachable!("runner is detected when no command is given"),
+        };
+
+        let dir_str = dir.to_str().unwrap();
+        let result = self
+            .client
+            .exec(&format!(
+                "cd '{}' && {}",
+                dir_str.replace('\'', "'\\''"),
+                self.limits.wrap(&command)
+            ))
+            .await?;
+
+        let output = format!("{}{}", result.stdout, result.stderr);
+        let mut summary = match runner {
+            Some(Runner::Cargo) => parse_cargo(&output),
+            _ => parse_counts(&output),
+        };
+        let truncated_failures = summary.failures.len() > MAX_REPORTED_FAILURES;
+        summary.failures.truncate(MAX_REPORTED_FAILURES);
+
+        let tail_start = output
+            .char_indices()
+            .rev()
+            .nth(OUTPUT_TAIL_CHARS)
+            .map(|(i, _)| i)
+            .unwrap_or(0);
+
+        Ok(ToolResult {
+            success: result.success && summary.failed == 0,
+            output: json!({
+                "path": path,
+                "runner": runner.map(|r| r.as_str()),
+                "command": command,
+                "exit_code": result.exit_code,
+                "passed": summary.passed,
+                "failed": summary.failed,
+                "ignored": summary.ignored,
+                "failures": summary.failures,
+                "failures_truncated": truncated_failures,
+                "limit_exceeded": self.limits.exceeded(&result),
+                "output_tail": &output[tail_start..],
+            }),
+            artifacts: vec![],
+            side_effects: vec![SideEffect::CodeExecuted {
+                language: runner.map(|r| r.as_str()).unwrap_or("shell").to_string(),
+                exit_code: result.exit_code,
+            }],
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tools::impresario_client::test_support::local_client;
+
+    #[test]
+    fn test_parse_cargo_output() {
+        let output = "\
+running 3 tests
+test tests::adds ... ok
+test tests::subtracts ... FAILED
+test tests::slow ... ignored
+
+test result: FAILED. 1 passed; 1 failed; 1 ignored; 0 measured; 0 filtered out
+
+running 2 tests
+test result: ok. 2 passed; 0 failed; 0 ignored; 0 measured; 0 filtered out
+";
+        assert_eq!(
+            parse_cargo(output),
+            TestSummary {
+                passed: 3,
+                failed: 1,
+                ignored: 1,
+                failures: vec!["tests::subtracts".to_string()],
+            }
+        );
+    }
+
+    #[test]
+    fn test_parse_pytest_output() {
+        let output = "\
+test_math.py .F.s
+=========================== short test summary info ============================
+FAILED test_math.py::test_divide - ZeroDivisionError: division by zero
+=================== 1 failed, 2 passed, 1 skipped in 0.04s ====================
+";
+        assert_eq!(
+            parse_counts(output),
+            TestSummary {
+                passed: 2,
+                failed: 1,
+                ignored: 1,
+                failures: vec!["test_math.py::test_divide".to_string()],
+            }
+        );
+    }
+
+    #[test]
+    fn test_default_limits_leave_builds_room() {
+        let limits = default_limits();
+        assert_eq!(limits.max_memory_mb, None);
+        assert_eq!(limits.max_cpu_secs, None);
+        assert!(limits.wrap("cargo test").contains("timeout"));
+        assert!(!limits.wrap("cargo test").contains("ulimit"));
+    }
+
+    /// Builds and tests a throwaway crate, so this needs a local cargo toolchain;
+    /// set RUN_TESTS_CARGO=1 to enable.
+    #[tokio::test]
+    async fn test_run_cargo_suite() {
+        if std::env::var("RUN_TESTS_CARGO").is_err() {
+            return;
+        }
+
+        let dir = tempfile::tempdir().unwrap();
+        let project = dir.path().join("demo");
+        std::fs::create_dir_all(project.join("src")).unwrap();
+        std::fs::write(
+            project.join("Cargo.toml"),
+            "[package]\nname = \"demo\"\nversion = \"0.1.0\"\nedition = \"2021\"\n\n[workspace]\n",
+        )
+        .unwrap();
+        std::fs::write(
+            project.join("src/lib.rs"),
+            "#[test]\nfn passes() {}\n\n#[test]\nfn fails() { panic!(\"boom\") }\n",
+        )
+        .unwrap();
+
+        let tool = RunTestsTool::new(local_client(dir.path()), dir.path().to_path_buf());
+        let context = ToolContext {
+            agent_id: uuid::Uuid::new_v4(),
+            web_id: uuid::Uuid::new_v4(),
+            sandbox_path: dir.path().to_path_buf(),
+        };
+
+        let result = tool
+            .execute(json!({ "path": "demo" }), &context)
+            .await
+            .unwrap();
+
+        assert!(!result.success);
+        assert_eq!(result.output["runner"], "cargo");
+        assert_eq!(result.output["passed"], 1);
+        assert_eq!(result.output["failed"], 1);
+        assert_eq!(result.output["failures"][0], "fails");
+    }
+}
@@ -3,14 +3,24 @@ use serde_json::{json, Value};
 use std::collections::HashMap;
 use std::path::PathBuf;
 use std::sync::Arc;
+use std::time::Duration;
 
+use super::dry_run::DryRunTool;
 use super::impresario_client::ImpresarioClient;
 use super::{Tool, ToolCall, ToolContext, ToolResult};
 use crate::definitions::ToolType;
+use crate::factory::AgentFactory;
 use crate::providers::search::SearchProvider;
+use crate::providers::{DryRunLog, EmbeddingProvider};
+
+/// How long a tool call may run unless `ToolConfig::tool_timeouts` says
+/// otherwise.
+pub const DEFAULT_TOOL_TIMEOUT_SECS: u64 = 120;
 
 pub struct ToolRuntime {
     tools: HashMap<ToolType, Box<dyn Tool>>,
+    default_timeout: Duration,
+    timeouts: HashMap<ToolType, Duration>,
     #[allow(dead_code)]
     sandbox_root: PathBuf,
 }
@@ -20,6 +30,15 @@ pub struct ToolConfig {
     pub search_provider: Option<Arc<dyn SearchProvider>>,
     pub impresario_client: Option<ImpresarioClient>,
     pub enable_remote_execution: bool,
+    /// When set, `spawn_agent` creates children through the factory instead of
+    /// only reporting the request.
+    pub agent_factory: Option<Arc<AgentFactory>>,
+    /// Used by `emit_signal` to give emitted signals a frequency.
+    pub embedding_provider: Option<Arc<dyn EmbeddingProvider>>,
+    /// Longest a tool call may run before it is reported as failed.
+    pub tool_timeout_secs: u64,
+    /// Per-tool overrides of `tool_timeout_secs`.
+    pub tool_timeouts: HashMap<ToolType, u64>,
 }
 
 impl ToolRuntime {
@@ -39,6 +58,12 @@ impl ToolRuntime {
             ToolType::FetchUrl,
             Box::new(super::fetch_url::FetchUrlTool::new()?),
         );
+        tools.insert(
+            ToolType::HttpRequest,
+            Box::new(super::http_request::HttpRequestTool::new(
+                super::http_request::HttpRequestConfig::from_env(),
+            )),
+        );
 
         // Register file operation tools
         if config.enable_remote_execution {
@@ -57,6 +82,28 @@ impl ToolRuntime {
                         config.sandbox_root.clone(),
                     )),
                 );
+                tools.insert(
+                    ToolType::ListDirectory,
+                    Box::new(super::list_directory::ListDirectoryTool::new_remote(
+                        client.clone(),
+                        config.sandbox_root.clone(),
+                    )),
+                );
+
+                tools.insert(
+                    ToolType::DeleteFile,
+                    Box::new(super::delete_file::DeleteFileTool::new_remote(
+                        client.clone(),
+                        config.sandbox_root.clone(),
+                    )),
+                );
+                tools.insert(
+                    ToolType::RunTests,
+                    Box::new(super::run_tests::RunTestsTool::new(
+                        client.clone(),
+                        config.sandbox_root.clone(),
+                    )),
+                );
                 tools.insert(
                     ToolType::ExecuteCode,
                     Box::new(super::execute_code::ExecuteCodeTool::new(client)),
@@ -75,6 +122,19 @@ impl ToolRuntime {
                         config.sandbox_root.clone(),
                     )),
                 );
+                tools.insert(
+                    ToolType::ListDirectory,
+                    Box::new(super::list_directory::ListDirectoryTool::new_local(
+                        config.sandbox_root.clone(),
+                    )),
+                );
+
+                tools.insert(
+                    ToolType::DeleteFile,
+                    Box::new(super::delete_file::DeleteFileTool::new_local(
+                        config.sandbox_root.clone(),
+                    )),
+                );
             }
         } else {
             // Local file operations
@@ -90,18 +150,51 @@ impl ToolRuntime {
                     config.sandbox_root.clone(),
                 )),
             );
+            tools.insert(
+                ToolType::ListDirectory,
+                Box::new(super::list_directory::ListDirectoryTool::new_local(
+                    config.sandbox_root.clone(),
+                )),
+            );
+
+            tools.insert(
+                ToolType::DeleteFile,
+                Box::new(super::delete_file::DeleteFileTool::new_local(
+                    config.sandbox_root.clone(),
+                )),
+            );
         }
 
         // Register coordination tools
         tools.insert(
             ToolType::EmitSignal,
-            Box::new(super::emit_signal::EmitSignalTool::new()),
+            Box::new(match config.embedding_provider {
+                Some(provider) => {
+                    super::emit_signal::EmitSignalTool::new().with_embedding_provider(provider)
+                }
+                None => super::emit_signal::EmitSignalTool::new(),
+            }),
         );
         tools.insert(
             ToolType::SpawnAgent,
-            Box::new(super::spawn_agent::SpawnAgentTool::new()),
+            Box::new(match config.agent_factory {
+                Some(factory) => super::spawn_agent::SpawnAgentTool::with_factory(factory),
+                None => super::spawn_agent::SpawnAgentTool::new(),
+            }),
         );
 
+        // Register query_database tool if a database is configured for it
+        let query_config = super::query_database::QueryDatabaseConfig::from_env();
+        if let Some(url) = query_config.database_url.clone() {
+            tools.insert(
+                ToolType::QueryDatabase,
+                Box::new(super::query_database::QueryDatabaseTool::new(
+                    &url,
+                    query_config,
+                )?),
+            );
+        }
+
         // Register search_codebase tool
         tools.insert(
             ToolType::SearchCodebase,
@@ -112,10 +205,34 @@ impl ToolRuntime {
 
         Ok(Self {
             tools,
+            default_timeout: Duration::from_secs(config.tool_timeout_secs),
+            timeouts: config
+                .tool_timeouts
+                .into_iter()
+                .map(|(tool_type, secs)| (tool_type, Duration::from_secs(secs)))
+                .collect(),
             sandbox_root: config.sandbox_root,
         })
     }
 
+    /// A runtime where every tool is a `DryRunTool` recording into `log`.
+    pub fn dry_run(log: DryRunLog) -> Self {
+        let tools = ToolType::all()
+            .into_iter()
+            .map(|tool_type| {
+                let tool: Box<dyn Tool> = Box::new(DryRunTool::new(tool_type, log.clone()));
+                (tool_type, tool)
+            })
+            .collect();
+
+        Self {
+            tools,
+            default_timeout: Duration::from_secs(DEFAULT_TOOL_TIMEOUT_SECS),
+            timeouts: HashMap::new(),
+            sandbox_root: PathBuf::new(),
+        }
+    }
+
     pub fn get_schemas(&self, allowed: &[ToolType]) -> Vec<Value> {
         allowed
             .iter()
@@ -136,7 +253,28 @@ impl ToolRuntime {
             .get(&tool_call.tool_type)
             .ok_or_else(|| anyhow!("Unknown tool: {:?}", tool_call.tool_type))?;
 
-        tool.execute(tool_call.params.clone(), context).await
+        // A hung tool fails the call, not the whole conversation.
+        let timeout = self
+            .timeouts
+            .get(&tool_call.tool_type)
+            .copied()
+            .unwrap_or(self.default_timeout);
+        match tokio::time::timeout(timeout, tool.execute(tool_call.params.clone(), context)).await {
+            Ok(result) => result,
+            Err(_) => Ok(ToolResult {
+                success: false,
+                output: json!({
+                    "error": format!(
+                        "{} timed out after {:?}",
+                        tool_call.tool_type.as_str(),
+                        timeout
+                    ),
+                    "timed_out": true,
+                }),
+                artifacts: vec![],
+                side_effects: vec![],
+            }),
+        }
     }
 }
 
@@ -151,9 +289,111 @@ mod tests {
             search_provider: None,
             impresario_client: None,
             enable_remote_execution: false,
+            agent_factory: None,
+            embedding_provider: None,
+            tool_timeout_secs: DEFAULT_TOOL_TIMEOUT_SECS,
+            tool_timeouts: HashMap::new(),
         };
 
         let runtime = ToolRuntime::new(config).unwrap();
         assert!(!runtime.tools.is_empty()); // Should have at least fetch_url tool
     }
+
+    #[tokio::test]
+    async fn test_dry_run_runtime_records_instead_of_executing() {
+        let log = DryRunLog::new();
+        let runtime = ToolRuntime::dry_run(log.clone());
+        assert_eq!(runtime.tools.len(), ToolType::all().len());
+
+        let call = ToolCall {
+            tool_type: ToolType::ExecuteCode,
+            params: json!({"language": "python", "code": "print(1)"}),
+        };
+        let context = ToolContext {
+            agent_id: uuid::Uuid::new_v4(),
+            web_id: uuid::Uuid::new_v4(),
+            sandbox_path: PathBuf::from("/nonexistent"),
+        };
+        let result = runtime.execute(&call, &context).await.unwrap();
+
+        assert!(result.success);
+        assert!(result.side_effects.is_empty());
+        let calls = log.calls();
+        assert_eq!(calls.len(), 1);
+        assert!(calls[0].detail.starts_with("execute_code"));
+    }
+
+    /// Never finishes on its own.
+    struct HangingTool;
+
+    #[async_trait::async_trait]
+    impl Tool for HangingTool {
+        fn tool_type(&self) -> ToolType {
+            ToolType::FetchUrl
+        }
+
+        fn name(&self) -> &str {
+            "fetch_url"
+        }
+
+        fn description(&self) -> &str {
+            "Hangs"
+        }
+
+        fn parameters_schema(&self) -> Value {
+            json!({ "type": "object" })
+        }
+
+        async fn execute(&self, _params: Value, _context: &ToolContext) -> Result<ToolResult> {
+            std::future::pending().await
+        }
+    }
+
+    #[tokio::test]
+    async fn test_hung_tool_times_out_as_failed_result() {
+        let mut runtime = ToolRuntime::new(ToolConfig {
+            sandbox_root: PathBuf::from("/tmp/test"),
+            search_provider: None,
+            impresario_client: None,
+            enable_remote_execution: false,
+            agent_factory: None,
+            embedding_provider: None,
+            tool_timeout_secs: DEFAULT_TOOL_TIMEOUT_SECS,
+            tool_timeouts: HashMap::from([(ToolType::FetchUrl, 30)]),
+        })
+        .unwrap();
+        assert_eq!(
+            runtime.timeouts[&ToolType::FetchUrl],
+            Duration::from_secs(30)
+        );
+        assert_eq!(
+            runtime.default_timeout,
+            Duration::from_secs(DEFAULT_TOOL_TIMEOUT_SECS)
+        );
+
+        runtime
+            .tools
+            .insert(ToolType::FetchUrl, Box::new(HangingTool));
+        runtime
+            .timeouts
+            .insert(ToolType::FetchUrl, Duration::from_millis(20));
+
+        let call = ToolCall {
+            tool_type: ToolType::FetchUrl,
+            params: json!({ "url": "https://example.com" }),
+        };
+        let context = ToolContext {
+            agent_id: uuid::Uuid::new_v4(),
+            web_id: uuid::Uuid::new_v4(),
+            sandbox_path: PathBuf::from("/tmp/test"),
+        };
+        let result = runtime.execute(&call, &context).await.unwrap();
+
+        assert!(!result.success);
+        assert_eq!(result.output["timed_out"], true);
+        assert!(result.output["error"]
+            .as_str()
+            .unwrap()
+            .starts_with("fetch_url timed out"));
+    }
 }
@@ -0,0 +1,144 @@
+use anyhow::{anyhow, Result};
+use std::io::ErrorKind;
+use std::path::{Component, Path, PathBuf};
+use tokio::fs;
+
+fn normalize_path(path: &Path) -> PathBuf {
+    let mut components = Vec::new();
+    for component in path.components() {
+        match component {
+            Component::ParentDir => {
+                if !components.is_empty() {
+                    components.pop();
+                }
+            }
+            Component::CurDir => {}
+            _ => components.push(component),
+        }
+    }
+    components.iter().collect()
+}
+
+/// Resolve `path` (relative to `root`, or absolute) with its `.` and `..`
+/// components removed, and check it stays inside `root`. This only looks at
+/// the text of the path; local tools also call [`check_symlinks`].
+pub fn resolve_path(root: &Path, path: &str) -> Result<PathBuf> {
+    let full_path = if path.starts_with('/') {
+        PathBuf::from(path)
+    } else {
+        root.join(path)
+    };
+
+    let normalized = normalize_path(&full_path);
+    if !normalized.starts_with(root) {
+        return Err(anyhow!("Path escapes sandbox: {}", path));
+    }
+
+    Ok(normalized)
+}
+
+/// Check that following the symlinks in a resolved local `path` can't lead
+/// out of `root`. The last component is only followed when `follow_last` is
+/// set, since deleting or replacing a link acts on the link itself. Missing
+/// components are fine, as they will be created under whatever exists above
+/// them, but a dangling link is refused because creating through it would
+/// land wherever it points.
+pub async fn check_symlinks(root: &Path, path: &Path, follow_last: bool) -> Result<()> {
+    let root = fs::canonicalize(root).await?;
+    let mut candidate = if follow_last {
+        Some(path)
+    } else {
+        path.parent()
+    };
+
+    while let Some(current) = candidate {
+        match fs::canonicalize(current).await {
+            Ok(resolved) if resolved.starts_with(&root) => return Ok(()),
+            Ok(_) => {
+                return Err(anyhow!(
+                    "Path escapes sandbox through a symlink: {}",
+                    path.display()
+                ))
+            }
+            Err(e) if e.kind() == ErrorKind::NotFound => {
+                if fs::symlink_metadata(current).await.is_ok() {
+                    return Err(anyhow!(
+                        "Path goes through a dangling symlink: {}",
+                        current.display()
+                    ));
+                }
+                candidate = current.parent();
+            }
+            Err(e) => return Err(e.into()),
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_resolve_path() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+
+        assert_eq!(
+            resolve_path(root, "safe.txt").unwrap(),
+            root.join("safe.txt")
+        );
+        assert_eq!(
+            resolve_path(root, "notes/../safe.txt").unwrap(),
+            root.join("safe.txt")
+        );
+        let inside = root.join("safe.txt");
+        assert!(resolve_path(root, inside.to_str().unwrap()).is_ok());
+
+        assert!(resolve_path(root, "../escape.txt").is_err());
+        assert!(resolve_path(root, "../../etc/passwd").is_err());
+        assert!(resolve_path(root, "/etc/passwd").is_err());
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_check_symlinks() {
+        let sandbox = TempDir::new().unwrap();
+        let outside = TempDir::new().unwrap();
+        let root = sandbox.path();
+        fs::create_dir(root.join("real")).await.unwrap();
+        std::os::unix::fs::symlink(outside.path(), root.join("link")).unwrap();
+        std::os::unix::fs::symlink(root.join("real"), root.join("alias")).unwrap();
+        std::os::unix::fs::symlink(outside.path().join("gone"), root.join("dangling")).unwrap();
+
+        for (path, follow_last) in [
+            ("real/new/file.txt", true),
+            ("alias/file.txt", true),
+            ("link", false),
+        ] {
+            assert!(
+                check_symlinks(root, &root.join(path), follow_last)
+                    .await
+                    .is_ok(),
+                "{} should be allowed",
+                path
+            );
+        }
+        for (path, follow_last) in [
+            ("link", true),
+            ("link/victim", false),
+            ("link/new/file.txt", true),
+            ("dangling/file.txt", true),
+        ] {
+            assert!(
+                check_symlinks(root, &root.join(path), follow_last)
+                    .await
+                    .is_err(),
+                "{} should be refused",
+                path
+            );
+        }
+    }
+}
@@ -7,6 +7,19 @@ use std::process::Command;
 use super::{Tool, ToolContext, ToolResult};
 use crate::definitions::ToolType;
 
+/// Which files ripgrep is allowed to look at.
+#[derive(Debug, Clone, Copy, Default)]
+struct SearchOptions {
+    /// Also search files excluded by `.gitignore`/`.ignore` (`rg --no-ignore`).
+    include_ignored: bool,
+    /// Also search hidden files and directories (`rg --hidden`).
+    hidden: bool,
+    /// Lines of context before each match (`rg -B`).
+    before: usize,
+    /// Lines of context after each match (`rg -A`).
+    after: usize,
+}
+
 pub struct SearchCodebaseTool {
     sandbox_root: PathBuf,
 }
@@ -20,9 +33,23 @@ impl SearchCodebaseTool {
         &self,
         pattern: &str,
         file_pattern: Option<&str>,
+        options: SearchOptions,
     ) -> Result<Vec<SearchMatch>> {
         let mut args = vec!["--json".to_string(), "-n".to_string(), pattern.to_string()];
 
+        if options.include_ignored {
+            args.push("--no-ignore".to_string());
+        }
+        if options.hidden {
+            args.push("--hidden".to_string());
+        }
+        if options.before > 0 {
+            args.push(format!("--before-context={}", options.before));
+        }
+        if options.after > 0 {
+            args.push(format!("--after-context={}", options.after));
+        }
+
         if let Some(fp) = file_pattern {
             args.push("--glob".to_string());
             args.push(fp.to_string());
@@ -48,18 +75,38 @@ impl SearchCodebaseTool {
 
         let stdout = String::from_utf8_lossy(&output.stdout);
         let mut matches = Vec::new();
+        let mut context_lines = Vec::new();
 
         for line in stdout.lines() {
-            if let Ok(match_data) = serde_json::from_str::<RipgrepMatch>(line) {
-                matches.push(SearchMatch {
-                    file_path: match_data.data.path.text,
-                    line_number: match_data.data.line_number,
-                    line_content: match_data.data.lines.text,
+            let Ok(message) = serde_json::from_str::<RipgrepMessage>(line) else {
+                continue;
+            };
+            let target = match message.kind.as_str() {
+                "match" => &mut matches,
+                "context" => &mut context_lines,
+                _ => continue,
+            };
+            if let Ok(data) = serde_json::from_value::<RipgrepData>(message.data) {
+                target.push(SearchMatch {
+                    file_path: data.path.text,
+                    line_number: data.line_number,
+                    line_content: data.lines.text,
                     score: 1.0,
+                    context: Vec::new(),
                 });
             }
         }
 
+        for m in &mut matches {
+            let first = m.line_number.saturating_sub(options.before as u64);
+            let last = m.line_number + options.after as u64;
+            m.context = context_lines
+                .iter()
+                .filter(|c| c.file_path == m.file_path && (first..=last).contains(&c.line_number))
+                .map(|c| (c.line_number, c.line_content.clone()))
+                .collect();
+        }
+
         Ok(matches)
     }
 
@@ -67,6 +114,7 @@ impl SearchCodebaseTool {
         &self,
         query: &str,
         file_pattern: Option<&str>,
+        options: SearchOptions,
     ) -> Result<Vec<SearchMatch>> {
         let pattern = query
             .split_whitespace()
@@ -74,7 +122,7 @@ impl SearchCodebaseTool {
             .collect::<Vec<_>>()
             .join("|");
 
-        self.search_regex(&pattern, file_pattern).await
+        self.search_regex(&pattern, file_pattern, options).await
     }
 }
 
@@ -110,6 +158,26 @@ impl Tool for SearchCodebaseTool {
                     "type": "string",
                     "description": "Optional file pattern filter (e.g., '*.rs', '*.py', 'src/**/*.ts')"
                 },
+                "include_ignored": {
+                    "type": "boolean",
+                    "description": "Also search files excluded by .gitignore (default: false, ignored files are skipped)",
+                    "default": false
+                },
+                "hidden": {
+                    "type": "boolean",
+                    "description": "Also search hidden files and directories (default: false)",
+                    "default": false
+                },
+                "before": {
+                    "type": "integer",
+                    "description": "Lines of context to include before each match (default: 0)",
+                    "default": 0
+                },
+                "after": {
+                    "type": "integer",
+                    "description": "Lines of context to include after each match (default: 0)",
+                    "default": 0
+                },
                 "max_results": {
                     "type": "integer",
                     "description": "Maximum number of results to return (default: 50)",
@@ -127,10 +195,16 @@ impl Tool for SearchCodebaseTool {
         let mode = params["mode"].as_str().unwrap_or("content");
         let file_pattern = params["file_pattern"].as_str();
         let max_results = params["max_results"].as_u64().unwrap_or(50) as usize;
+        let options = SearchOptions {
+            include_ignored: params["include_ignored"].as_bool().unwrap_or(false),
+            hidden: params["hidden"].as_bool().unwrap_or(false),
+            before: params["before"].as_u64().unwrap_or(0) as usize,
+            after: params["after"].as_u64().unwrap_or(0) as usize,
+        };
 
         let mut matches = match mode {
-            "regex" => self.search_regex(query, file_pattern).await?,
-            "content" => self.search_content(query, file_pattern).await?,
+            "regex" => self.search_regex(query, file_pattern, options).await?,
+            "content" => self.search_content(query, file_pattern, options).await?,
             _ => return Err(anyhow!("Invalid mode: {}", mode)),
         };
 
@@ -140,12 +214,20 @@ impl Tool for SearchCodebaseTool {
         let results: Vec<Value> = matches
             .iter()
             .map(|m| {
-                json!({
+                let mut result = json!({
                     "file": m.file_path,
                     "line": m.line_number,
                     "content": m.line_content,
                     "score": m.score,
-                })
+                });
+                if options.before > 0 || options.after > 0 {
+                    result["context"] = m
+                        .context
+                        .iter()
+                        .map(|(line, content)| json!({ "line": line, "content": content }))
+                        .collect();
+                }
+                result
             })
             .collect();
 
@@ -170,11 +252,15 @@ struct SearchMatch {
     line_number: u64,
     line_content: String,
     score: f32,
+    /// Surrounding `(line_number, text)` pairs, in file order.
+    context: Vec<(u64, String)>,
 }
 
 #[derive(Debug, serde::Deserialize)]
-struct RipgrepMatch {
-    data: RipgrepData,
+struct RipgrepMessage {
+    #[serde(rename = "type")]
+    kind: String,
+    data: Value,
 }
 
 #[derive(Debug, serde::Deserialize)]
@@ -200,6 +286,100 @@ mod tests {
     use tempfile::TempDir;
     use tokio::fs;
 
+    fn rg_available() -> bool {
+        Command::new("rg").arg("--version").output().is_ok()
+    }
+
+    #[tokio::test]
+    async fn test_search_respects_gitignore_by_default() {
+        if !rg_available() {
+            return;
+        }
+
+        let temp_dir = TempDir::new().unwrap();
+        // rg only honours .gitignore inside a git repository.
+        fs::create_dir(temp_dir.path().join(".git")).await.unwrap();
+        fs::write(temp_dir.path().join(".gitignore"), "generated.rs\n")
+            .await
+            .unwrap();
+        fs::write(temp_dir.path().join("lib.rs"), "const NEEDLE: u8 = 1;\n")
+            .await
+            .unwrap();
+        fs::write(
+            temp_dir.path().join("generated.rs"),
+            "const NEEDLE: u8 = 2;\n",
+        )
+        .await
+        .unwrap();
+
+        let tool = SearchCodebaseTool::new(temp_dir.path().to_path_buf());
+        let context = ToolContext {
+            agent_id: uuid::Uuid::new_v4(),
+            web_id: uuid::Uuid::new_v4(),
+            sandbox_path: temp_dir.path().to_path_buf(),
+        };
+
+        let result = tool
+            .execute(json!({ "query": "NEEDLE" }), &context)
+            .await
+            .unwrap();
+        assert_eq!(result.output["num_results"], 1);
+
+        let result = tool
+            .execute(
+                json!({ "query": "NEEDLE", "include_ignored": true }),
+                &context,
+            )
+            .await
+            .unwrap();
+        assert_eq!(result.output["num_results"], 2);
+    }
+
+    #[tokio::test]
+    async fn test_search_includes_context_lines() {
+        if !rg_available() {
+            return;
+        }
+
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(
+            temp_dir.path().join("lib.rs"),
+            "// one\n// two\nfn target() {}\n// four\n// five\n",
+        )
+        .await
+        .unwrap();
+
+        let tool = SearchCodebaseTool::new(temp_dir.path().to_path_buf());
+        let context = ToolContext {
+            agent_id: uuid::Uuid::new_v4(),
+            web_id: uuid::Uuid::new_v4(),
+            sandbox_path: temp_dir.path().to_path_buf(),
+        };
+
+        let result = tool
+            .execute(
+                json!({ "query": "target", "before": 1, "after": 2 }),
+                &context,
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(result.output["num_results"], 1);
+        let hit = &result.output["results"][0];
+        assert_eq!(hit["line"], 3);
+        let lines: Vec<u64> = hit["context"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|c| c["line"].as_u64().unwrap())
+            .collect();
+        assert_eq!(lines, vec![2, 4, 5]);
+        assert!(hit["context"][0]["content"]
+            .as_str()
+            .unwrap()
+            .contains("two"));
+    }
+
     #[tokio::test]
     async fn test_search_codebase_content() {
         let temp_dir = TempDir::new().unwrap();
@@ -1,11 +1,16 @@
 use anyhow::{anyhow, Result};
 use async_trait::async_trait;
 use serde_json::{json, Value};
+use std::sync::Arc;
 
-use super::{Tool, ToolContext, ToolResult};
+use super::{SideEffect, Tool, ToolContext, ToolResult};
 use crate::definitions::ToolType;
+use crate::factory::AgentFactory;
+use crate::types::{Agent, ContextItem};
 
-pub struct SpawnAgentTool {}
+pub struct SpawnAgentTool {
+    factory: Option<Arc<AgentFactory>>,
+}
 
 impl Default for SpawnAgentTool {
     fn default() -> Self {
@@ -14,8 +19,100 @@ impl Default for SpawnAgentTool {
 }
 
 impl SpawnAgentTool {
+    /// A tool that only reports the spawn request back to the caller.
     pub fn new() -> Self {
-        Self {}
+        Self { factory: None }
+    }
+
+    /// A tool that creates and persists the child agent through `factory`.
+    pub fn with_factory(factory: Arc<AgentFactory>) -> Self {
+        Self {
+            factory: Some(factory),
+        }
+    }
+
+    async fn spawn(
+        &self,
+        factory: &AgentFactory,
+        need: &str,
+        suggested_capability: Option<&str>,
+        agent_context: Option<&str>,
+        context: &ToolContext,
+    ) -> Result<ToolResult> {
+        let storage = factory.storage();
+        let web = storage
+            .get_web(context.web_id)
+            .await?
+            .ok_or_else(|| anyhow!("Web not found: {}", context.web_id))?;
+
+        if web.state.is_terminal() {
+            return Err(anyhow!("Web {} is no longer running", web.id));
+        }
+
+        let agent_count = storage.get_web_agents(web.id).await?.len();
+        if agent_count >= web.config.max_agents {
+            return Ok(ToolResult {
+                success: false,
+                output: json!({
+                    "spawned": false,
+                    "need": need,
+                    "reason": format!("web has reached max_agents ({})", web.config.max_agents),
+                }),
+                artifacts: vec![],
+                side_effects: vec![],
+            });
+        }
+
+        // A capability naming a known definition pins the child to it; otherwise
+        // the factory matches or generates one from the need.
+        let named = match suggested_capability {
+            Some(name) => storage.get_definition_by_name(name).await?,
+            None => None,
+        };
+        let mut agent: Agent = match named {
+            Some(definition) => {
+                factory
+                    .spawn_from_definition(
+                        &definition,
+                        Some(context.agent_id),
+                        web.id,
+                        &web.config,
+                        need,
+                    )
+                    .await?
+            }
+            None => {
+                factory
+                    .spawn_for_need(need, Some(context.agent_id), web.id, &web.config)
+                    .await?
+            }
+        };
+
+        if let Some(background) = agent_context {
+            agent.context.accumulated_knowledge.push(ContextItem {
+                source_agent: context.agent_id,
+                content: background.to_string(),
+                data: json!({ "type": "spawn_context" }),
+                frequency: Vec::new(),
+                amplitude: None,
+            });
+        }
+
+        storage.create_agent(&agent).await?;
+
+        Ok(ToolResult {
+            success: true,
+            output: json!({
+                "spawned": true,
+                "agent_id": agent.id,
+                "definition_id": agent.definition_id,
+                "need": need,
+                "parent_agent_id": context.agent_id,
+                "web_id": context.web_id,
+            }),
+            artifacts: vec![],
+            side_effects: vec![SideEffect::AgentSpawned(agent.id)],
+        })
     }
 }
 
@@ -62,6 +159,12 @@ impl Tool for SpawnAgentTool {
         let suggested_capability = params["suggested_capability"].as_str();
         let agent_context = params["context"].as_str();
 
+        if let Some(factory) = &self.factory {
+            return self
+                .spawn(factory, need, suggested_capability, agent_context, context)
+                .await;
+        }
+
         Ok(ToolResult {
             success: true,
             output: json!({
@@ -81,8 +184,128 @@ impl Tool for SpawnAgentTool {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::definitions::{task_coordinator_definition, DefinitionSource};
+    use crate::factory::FactoryConfig;
+    use crate::providers::llm::MockLLMProvider;
+    use crate::providers::EmbeddingProvider;
+    use crate::storage::memory::InMemoryStore;
+    use crate::storage::traits::Storage;
+    use crate::types::{Web, WebConfig};
     use std::path::PathBuf;
 
+    struct FixedEmbedding;
+
+    #[async_trait]
+    impl EmbeddingProvider for FixedEmbedding {
+        async fn embed(&self, _text: &str) -> Result<Vec<f32>> {
+            Ok(vec![1.0, 0.0, 0.0])
+        }
+
+        async fn embed_batch(&self, texts: &[String]) -> Result<Vec<Vec<f32>>> {
+            Ok(texts.iter().map(|_| vec![1.0, 0.0, 0.0]).collect())
+        }
+    }
+
+    async fn factory_with_web(
+        max_agents: usize,
+    ) -> (Arc<AgentFactory>, Arc<dyn Storage>, Web, Agent) {
+        let storage: Arc<dyn Storage> = Arc::new(InMemoryStore::new());
+
+        let mut definition = task_coordinator_definition();
+        definition.source = DefinitionSource::UserCustom;
+        definition.tuning_embedding = vec![1.0, 0.0, 0.0];
+        storage.create_definition(&definition).await.unwrap();
+
+        let web = Web::new(
+            uuid::Uuid::new_v4(),
+            "Audit the auth module".to_string(),
+            WebConfig {
+                max_agents,
+                ..WebConfig::default()
+            },
+        );
+        storage.create_web(&web).await.unwrap();
+
+        let mut parent = Agent::from_definition(
+            definition.id,
+            web.id,
+            None,
+            "root".to_string(),
+            vec![1.0, 0.0, 0.0],
+            0.5,
+        );
+        parent.id = web.root_agent;
+        storage.create_agent(&parent).await.unwrap();
+
+        let factory = Arc::new(AgentFactory::new(
+            storage.clone(),
+            Arc::new(MockLLMProvider::new()),
+            Arc::new(FixedEmbedding),
+            FactoryConfig::default(),
+        ));
+
+        (factory, storage, web, parent)
+    }
+
+    #[tokio::test]
+    async fn test_spawn_agent_creates_child() {
+        let (factory, storage, web, parent) = factory_with_web(10).await;
+        let tool = SpawnAgentTool::with_factory(factory);
+        let context = ToolContext {
+            agent_id: parent.id,
+            web_id: web.id,
+            sandbox_path: PathBuf::from("/tmp"),
+        };
+
+        let result = tool
+            .execute(
+                json!({ "need": "Review session handling", "context": "Start with login.rs" }),
+                &context,
+            )
+            .await
+            .unwrap();
+
+        assert!(result.success);
+        let child_id = match result.side_effects.as_slice() {
+            [SideEffect::AgentSpawned(id)] => *id,
+            other => panic!("unexpected side effects: {:?}", other),
+        };
+        assert_eq!(result.output["agent_id"], child_id.to_string());
+
+        let child = storage.get_agent(child_id).await.unwrap().unwrap();
+        assert_eq!(child.parent_id, Some(parent.id));
+        assert_eq!(child.web_id, web.id);
+        assert_eq!(child.purpose, "Review session handling");
+        assert_eq!(
+            child.context.accumulated_knowledge[0].content,
+            "Start with login.rs"
+        );
+
+        let children = storage.get_children(parent.id).await.unwrap();
+        assert_eq!(children.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_spawn_agent_respects_max_agents() {
+        let (factory, storage, web, parent) = factory_with_web(1).await;
+        let tool = SpawnAgentTool::with_factory(factory);
+        let context = ToolContext {
+            agent_id: parent.id,
+            web_id: web.id,
+            sandbox_path: PathBuf::from("/tmp"),
+        };
+
+        let result = tool
+            .execute(json!({ "need": "Review session handling" }), &context)
+            .await
+            .unwrap();
+
+        assert!(!result.success);
+        assert_eq!(result.output["spawned"], false);
+        assert!(result.side_effects.is_empty());
+        assert_eq!(storage.get_web_agents(web.id).await.unwrap().len(), 1);
+    }
+
     #[tokio::test]
     async fn test_spawn_agent_basic() {
         let tool = SpawnAgentTool::new();
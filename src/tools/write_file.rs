@@ -1,28 +1,39 @@
 use anyhow::{anyhow, Result};
 use async_trait::async_trait;
 use serde_json::{json, Value};
-use std::path::{Component, Path, PathBuf};
+use std::path::{Path, PathBuf};
 use tokio::fs::{self, OpenOptions};
 use tokio::io::AsyncWriteExt;
 
 use super::impresario_client::ImpresarioClient;
+use super::sandbox;
 use super::{Artifact, SideEffect, Tool, ToolContext, ToolResult};
 use crate::definitions::ToolType;
 
-fn normalize_path(path: &Path) -> PathBuf {
-    let mut components = Vec::new();
-    for component in path.components() {
-        match component {
-            Component::ParentDir => {
-                if !components.is_empty() {
-                    components.pop();
-                }
-            }
-            Component::CurDir => {}
-            _ => components.push(component),
-        }
+/// Write `content` to a temp file beside `path`, then rename it into place so
+/// readers see either the old file or the complete new one.
+async fn write_atomic(path: &Path, content: &[u8]) -> Result<()> {
+    let file_name = path
+        .file_name()
+        .ok_or_else(|| anyhow!("Not a file path: {}", path.display()))?;
+    let temp_path = path.with_file_name(format!(
+        ".{}.{}.tmp",
+        file_name.to_string_lossy(),
+        uuid::Uuid::new_v4()
+    ));
+
+    let result = async {
+        let mut file = fs::File::create(&temp_path).await?;
+        file.write_all(content).await?;
+        file.sync_all().await?;
+        fs::rename(&temp_path, path).await
+    }
+    .await;
+
+    if result.is_err() {
+        let _ = fs::remove_file(&temp_path).await;
     }
-    components.iter().collect()
+    Ok(result?)
 }
 
 pub enum WriteFileMode {
@@ -49,24 +60,6 @@ impl WriteFileTool {
             sandbox_root,
         }
     }
-
-    fn validate_path(&self, path: &str) -> Result<PathBuf> {
-        let full_path = if path.starts_with('/') {
-            PathBuf::from(path)
-        } else {
-            self.sandbox_root.join(path)
-        };
-
-        // Normalize the path by resolving .. and . components
-        let normalized = normalize_path(&full_path);
-
-        // Check if the normalized path is within the sandbox
-        if !normalized.starts_with(&self.sandbox_root) {
-            return Err(anyhow!("Path escapes sandbox: {}", path));
-        }
-
-        Ok(full_path)
-    }
 }
 
 #[async_trait]
@@ -114,10 +107,13 @@ impl Tool for WriteFileTool {
             .ok_or_else(|| anyhow!("Missing content parameter"))?;
         let append = params["append"].as_bool().unwrap_or(false);
 
-        let validated_path = self.validate_path(path)?;
+        let validated_path = sandbox::resolve_path(&self.sandbox_root, path)?;
 
         match &self.mode {
             WriteFileMode::Local => {
+                // An overwrite replaces a link at the path; an append
+                // writes through it.
+                sandbox::check_symlinks(&self.sandbox_root, &validated_path, append).await?;
                 if let Some(parent) = validated_path.parent() {
                     fs::create_dir_all(parent).await?;
                 }
@@ -130,7 +126,7 @@ impl Tool for WriteFileTool {
                         .await?;
                     file.write_all(content.as_bytes()).await?;
                 } else {
-                    fs::write(&validated_path, content).await?;
+                    write_atomic(&validated_path, content.as_bytes()).await?;
                 }
             }
             WriteFileMode::Remote(client) => {
@@ -244,12 +240,66 @@ mod tests {
         assert!(temp_dir.path().join("nested/dir/test.txt").exists());
     }
 
-    #[test]
-    fn test_path_validation() {
+    #[tokio::test]
+    async fn test_overwrite_is_atomic() {
+        let temp_dir = TempDir::new().unwrap();
+        let target = temp_dir.path().join("test.txt");
+        fs::write(&target, "old content").await.unwrap();
+
+        let tool = WriteFileTool::new_local(temp_dir.path().to_path_buf());
+        let context = ToolContext {
+            agent_id: uuid::Uuid::new_v4(),
+            web_id: uuid::Uuid::new_v4(),
+            sandbox_path: temp_dir.path().to_path_buf(),
+        };
+
+        tool.execute(
+            json!({ "path": "test.txt", "content": "new content" }),
+            &context,
+        )
+        .await
+        .unwrap();
+        assert_eq!(fs::read_to_string(&target).await.unwrap(), "new content");
+
+        // A write that fails before the rename leaves the old file untouched and
+        // no temp file behind.
+        fs::create_dir(temp_dir.path().join("blocked"))
+            .await
+            .unwrap();
+        assert!(write_atomic(&temp_dir.path().join("blocked"), b"partial")
+            .await
+            .is_err());
+        assert_eq!(fs::read_to_string(&target).await.unwrap(), "new content");
+
+        let mut entries = fs::read_dir(temp_dir.path()).await.unwrap();
+        let mut names = Vec::new();
+        while let Some(entry) = entries.next_entry().await.unwrap() {
+            names.push(entry.file_name().to_string_lossy().to_string());
+        }
+        names.sort();
+        assert_eq!(names, vec!["blocked", "test.txt"]);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_path_validation() {
         let temp_dir = TempDir::new().unwrap();
+        let outside = TempDir::new().unwrap();
+        std::os::unix::fs::symlink(outside.path(), temp_dir.path().join("link")).unwrap();
         let tool = WriteFileTool::new_local(temp_dir.path().to_path_buf());
+        let context = ToolContext {
+            agent_id: uuid::Uuid::new_v4(),
+            web_id: uuid::Uuid::new_v4(),
+            sandbox_path: temp_dir.path().to_path_buf(),
+        };
 
-        assert!(tool.validate_path("safe.txt").is_ok());
-        assert!(tool.validate_path("../escape.txt").is_err());
+        for path in ["../escape.txt", "link/escape.txt"] {
+            let err = tool
+                .execute(json!({ "path": path, "content": "x" }), &context)
+                .await
+                .unwrap_err();
+            assert!(err.to_string().contains("escapes sandbox"), "{}", err);
+        }
+        assert!(!outside.path().join("escape.txt").exists());
     }
 }
@@ -2,8 +2,10 @@ use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
-use super::{AgentId, AgentState, CapabilityType, WebId};
+use super::{AgentId, AgentState, CapabilityType, ContextEvictionPolicy, WebId};
 use crate::definitions::DefinitionId;
+use crate::engine::resonance::{cosine_similarity, magnitude};
+use crate::providers::embedding::DEFAULT_EMBEDDING_DIM;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Agent {
@@ -11,7 +13,12 @@ pub struct Agent {
     pub web_id: WebId,
     pub parent_id: Option<AgentId>,
     pub purpose: String,
+    /// Change with `set_tuning`, which keeps `tuning_norm` in step.
     pub tuning: Vec<f32>,
+    /// Cached magnitude of `tuning`; 0 when unknown (e.g. older snapshots).
+    /// Read it through `tuning_norm()`.
+    #[serde(default)]
+    pub tuning_norm: f32,
     pub capability: CapabilityType,
     pub state: AgentState,
     pub health: f32,
@@ -30,6 +37,33 @@ pub struct Agent {
 pub struct AgentContext {
     pub purpose: String,
     pub accumulated_knowledge: Vec<ContextItem>,
+    /// Tuning before any drift, recorded the first time it drifts.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub purpose_tuning: Option<Vec<f32>>,
+    /// Length of signal frequencies in the agent's web, i.e. its
+    /// `embedding_dim`. The engine sets it before each execution.
+    #[serde(skip, default = "default_embedding_dim")]
+    pub embedding_dim: usize,
+    /// State the agent's capability keeps between executions. Unlike
+    /// knowledge items it is never evicted.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub capability_state: Option<Value>,
+}
+
+fn default_embedding_dim() -> usize {
+    DEFAULT_EMBEDDING_DIM
+}
+
+impl AgentContext {
+    pub fn new(purpose: String, embedding_dim: usize) -> Self {
+        Self {
+            purpose,
+            accumulated_knowledge: Vec::new(),
+            purpose_tuning: None,
+            embedding_dim,
+            capability_state: None,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -37,6 +71,14 @@ pub struct ContextItem {
     pub source_agent: AgentId,
     pub content: String,
     pub data: Value,
+    /// Frequency of the signal that carried this item; empty if it didn't
+    /// arrive as a signal.
+    #[serde(default)]
+    pub frequency: Vec<f32>,
+    /// Amplitude of the signal that carried this item when it arrived; `None`
+    /// if it didn't arrive as a signal.
+    #[serde(default)]
+    pub amplitude: Option<f32>,
 }
 
 impl Agent {
@@ -54,15 +96,13 @@ impl Agent {
             web_id,
             parent_id,
             purpose: purpose.clone(),
+            tuning_norm: magnitude(&tuning),
+            context: AgentContext::new(purpose, tuning.len()),
             tuning,
             capability,
             state: AgentState::Listening,
             health: 1.0,
             activation_threshold,
-            context: AgentContext {
-                purpose,
-                accumulated_knowledge: Vec::new(),
-            },
             probation_remaining: 5, // Default probation period
             created_at: now,
             last_active_at: now,
@@ -86,15 +126,13 @@ impl Agent {
             web_id,
             parent_id,
             purpose: purpose.clone(),
+            tuning_norm: magnitude(&tuning),
+            context: AgentContext::new(purpose, tuning.len()),
             tuning,
             capability: CapabilityType::Custom("definition-based".to_string()),
             state: AgentState::Listening,
             health: 1.0,
             activation_threshold,
-            context: AgentContext {
-                purpose,
-                accumulated_knowledge: Vec::new(),
-            },
             probation_remaining: 5,
             created_at: now,
             last_active_at: now,
@@ -103,6 +141,20 @@ impl Agent {
         }
     }
 
+    pub fn set_tuning(&mut self, tuning: Vec<f32>) {
+        self.tuning_norm = magnitude(&tuning);
+        self.tuning = tuning;
+    }
+
+    /// Magnitude of `tuning`, computed only if it wasn't cached.
+    pub fn tuning_norm(&self) -> f32 {
+        if self.tuning_norm > 0.0 {
+            self.tuning_norm
+        } else {
+            magnitude(&self.tuning)
+        }
+    }
+
     pub fn is_root(&self) -> bool {
         self.parent_id.is_none()
     }
@@ -117,4 +169,50 @@ impl Agent {
             self.probation_remaining -= 1;
         }
     }
+
+    /// Evict knowledge items under `policy` until at most `limit` remain.
+    /// Items that didn't arrive as signals (the agent's own findings, spawn
+    /// context) count as full strength and fully on-purpose; ties go to the
+    /// oldest.
+    pub fn trim_knowledge(&mut self, limit: usize, policy: ContextEvictionPolicy) {
+        let knowledge = &mut self.context.accumulated_knowledge;
+        if knowledge.len() <= limit {
+            return;
+        }
+
+        let scores: Vec<f32> = match policy {
+            ContextEvictionPolicy::Fifo => {
+                let overflow = knowledge.len() - limit;
+                knowledge.drain(0..overflow);
+                return;
+            }
+            ContextEvictionPolicy::LowestAmplitude => knowledge
+                .iter()
+                .map(|item| item.amplitude.unwrap_or(1.0))
+                .collect(),
+            ContextEvictionPolicy::LeastSimilarToPurpose => knowledge
+                .iter()
+                .map(|item| {
+                    if item.frequency.is_empty() {
+                        1.0
+                    } else {
+                        cosine_similarity(&item.frequency, &self.tuning)
+                    }
+                })
+                .collect(),
+        };
+
+        // Keep the `limit` best-scoring items, in their original order.
+        let mut ranked: Vec<usize> = (0..knowledge.len()).collect();
+        ranked.sort_by(|&a, &b| scores[b].total_cmp(&scores[a]).then(b.cmp(&a)));
+        let mut keep = vec![false; knowledge.len()];
+        for &index in &ranked[..limit] {
+            keep[index] = true;
+        }
+        let mut index = 0;
+        knowledge.retain(|_| {
+            index += 1;
+            keep[index - 1]
+        });
+    }
 }
@@ -4,7 +4,7 @@ pub mod web;
 
 pub use agent::{Agent, AgentContext, ContextItem};
 pub use signal::{Signal, SignalDraft};
-pub use web::{Web, WebConfig};
+pub use web::{ContextEvictionPolicy, FinalReport, Web, WebConfig};
 
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
@@ -18,6 +18,7 @@ pub enum AgentState {
     Active,      // Currently working
     Listening,   // Waiting for signals
     Dormant,     // Idle, can reactivate
+    Paused,      // Frozen by an operator, ignores signals
     Quarantine,  // Low health (< 0.6), signals marked suspect
     Isolated,    // Very low health (< 0.4), signals dampened
     WindingDown, // Terminal, transferring state
@@ -30,6 +31,7 @@ impl AgentState {
             AgentState::Active => "Active",
             AgentState::Listening => "Listening",
             AgentState::Dormant => "Dormant",
+            AgentState::Paused => "Paused",
             AgentState::Quarantine => "Quarantine",
             AgentState::Isolated => "Isolated",
             AgentState::WindingDown => "WindingDown",
@@ -45,6 +47,13 @@ pub enum WebState {
     Failed,
 }
 
+impl WebState {
+    /// Converged and failed webs no longer run and cannot be changed.
+    pub fn is_terminal(&self) -> bool {
+        matches!(self, WebState::Converged | WebState::Failed)
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum SignalDirection {
     Upward,
@@ -65,5 +74,6 @@ pub enum CapabilityType {
     CodeWriter,
     CodeReviewer,
     Analyst,
+    Planner,
     Custom(String),
 }
@@ -54,9 +54,16 @@ pub struct SignalDraft {
     pub frequency: Vec<f32>,
     pub content: String,
     pub direction: SignalDirection,
+    /// Amplitude the signal starts propagating with.
+    #[serde(default = "full_amplitude")]
+    pub amplitude: f32,
     pub payload: Option<Value>,
 }
 
+fn full_amplitude() -> f32 {
+    1.0
+}
+
 impl SignalDraft {
     pub fn into_signal(self, origin: AgentId) -> Signal {
         Signal {
@@ -64,7 +71,7 @@ impl SignalDraft {
             origin,
             frequency: self.frequency,
             content: self.content,
-            amplitude: 1.0,
+            amplitude: self.amplitude,
             direction: self.direction,
             hop_count: 0,
             payload: self.payload,
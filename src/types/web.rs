@@ -1,6 +1,9 @@
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 
 use super::{AgentId, WebId, WebState};
+use crate::lifecycle::tuning_drift::{KNOWLEDGE_DRIFT_STEP, MAX_TOTAL_DRIFT};
+use crate::providers::embedding::DEFAULT_EMBEDDING_DIM;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Web {
@@ -9,6 +12,24 @@ pub struct Web {
     pub task: String,
     pub state: WebState,
     pub config: WebConfig,
+    /// Higher-priority webs are run first when webs are queued.
+    #[serde(default)]
+    pub priority: i32,
+    /// Free-form labels; `owner` is used for fair scheduling.
+    #[serde(default)]
+    pub metadata: HashMap<String, String>,
+    /// Synthesized once the web converges.
+    #[serde(default)]
+    pub report: Option<FinalReport>,
+}
+
+/// What a converged web found, distilled from its root's knowledge.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FinalReport {
+    pub summary: String,
+    pub key_points: Vec<String>,
+    /// 0.0 to 1.0; 0 when no LLM judged the findings.
+    pub confidence: f32,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -20,6 +41,114 @@ pub struct WebConfig {
     pub max_depth: usize,
     pub idle_timeout_secs: u64,
     pub dormant_ttl_secs: u64,
+    /// Total LLM tokens the web may spend before it is failed.
+    #[serde(default)]
+    pub token_budget: Option<u64>,
+    /// Total LLM spend in dollars the web may reach before it is failed.
+    #[serde(default)]
+    pub cost_budget: Option<f64>,
+    /// Drift each parent's tuning toward the frequencies of the knowledge
+    /// its children report.
+    #[serde(default)]
+    pub tuning_drift: bool,
+    /// Fraction of the way toward its knowledge a parent's tuning moves
+    /// each time a child reports back.
+    #[serde(default = "default_tuning_drift_rate")]
+    pub tuning_drift_rate: f32,
+    /// Furthest a tuning may drift from its purpose, as the distance
+    /// between unit vectors.
+    #[serde(default = "default_max_tuning_drift")]
+    pub max_tuning_drift: f32,
+    /// Idle agents start losing health after this many seconds without work.
+    #[serde(default = "default_idle_decay_after_secs")]
+    pub idle_decay_after_secs: u64,
+    /// Health an idle agent loses per minute once decay has started.
+    #[serde(default = "default_idle_decay_per_min")]
+    pub idle_decay_per_min: f32,
+    /// Run lifecycle checks every this many loop iterations; 0 disables.
+    #[serde(default = "default_lifecycle_tick_iterations")]
+    pub lifecycle_tick_iterations: u64,
+    /// Run lifecycle checks at least this often; 0 disables.
+    #[serde(default = "default_lifecycle_tick_secs")]
+    pub lifecycle_tick_secs: u64,
+    /// Agents one loop iteration may spawn; needs beyond this are dropped.
+    #[serde(default = "default_max_spawns_per_iteration")]
+    pub max_spawns_per_iteration: usize,
+    /// Length of tunings and signal frequencies in this web; should match
+    /// the embedding provider.
+    #[serde(default = "default_embedding_dim")]
+    pub embedding_dim: usize,
+    /// Knowledge items each agent keeps; beyond this, items are evicted
+    /// according to `context_eviction`.
+    #[serde(default = "default_max_context_items")]
+    pub max_context_items: usize,
+    #[serde(default)]
+    pub context_eviction: ContextEvictionPolicy,
+    /// Agents below this health, or quarantined or isolated, pass their
+    /// needs upward instead of spawning children.
+    #[serde(default = "default_min_spawn_health")]
+    pub min_spawn_health: f32,
+    /// A dormant agent at least this similar to a need is woken to handle
+    /// it instead of spawning a new one.
+    #[serde(default = "default_dormant_reactivation_threshold")]
+    pub dormant_reactivation_threshold: f32,
+}
+
+/// Which knowledge item an agent drops when it holds too many.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ContextEvictionPolicy {
+    /// The oldest.
+    #[default]
+    Fifo,
+    /// The one whose signal arrived weakest.
+    LowestAmplitude,
+    /// The one whose frequency is furthest from the agent's tuning.
+    LeastSimilarToPurpose,
+}
+
+fn default_tuning_drift_rate() -> f32 {
+    KNOWLEDGE_DRIFT_STEP
+}
+
+fn default_max_tuning_drift() -> f32 {
+    MAX_TOTAL_DRIFT
+}
+
+fn default_idle_decay_after_secs() -> u64 {
+    300
+}
+
+fn default_idle_decay_per_min() -> f32 {
+    0.01
+}
+
+fn default_lifecycle_tick_iterations() -> u64 {
+    10
+}
+
+fn default_lifecycle_tick_secs() -> u64 {
+    5
+}
+
+fn default_max_spawns_per_iteration() -> usize {
+    10
+}
+
+fn default_embedding_dim() -> usize {
+    DEFAULT_EMBEDDING_DIM
+}
+
+fn default_max_context_items() -> usize {
+    10
+}
+
+fn default_min_spawn_health() -> f32 {
+    0.6
+}
+
+fn default_dormant_reactivation_threshold() -> f32 {
+    0.8
 }
 
 impl Default for WebConfig {
@@ -32,10 +161,34 @@ impl Default for WebConfig {
             max_depth: 10,
             idle_timeout_secs: 30,
             dormant_ttl_secs: 600,
+            token_budget: None,
+            cost_budget: None,
+            tuning_drift: false,
+            tuning_drift_rate: default_tuning_drift_rate(),
+            max_tuning_drift: default_max_tuning_drift(),
+            idle_decay_after_secs: default_idle_decay_after_secs(),
+            idle_decay_per_min: default_idle_decay_per_min(),
+            lifecycle_tick_iterations: default_lifecycle_tick_iterations(),
+            lifecycle_tick_secs: default_lifecycle_tick_secs(),
+            max_spawns_per_iteration: default_max_spawns_per_iteration(),
+            embedding_dim: default_embedding_dim(),
+            max_context_items: default_max_context_items(),
+            context_eviction: ContextEvictionPolicy::default(),
+            min_spawn_health: default_min_spawn_health(),
+            dormant_reactivation_threshold: default_dormant_reactivation_threshold(),
         }
     }
 }
 
+impl WebConfig {
+    /// Whether lifecycle checks are due, given the iterations run and time
+    /// passed since they last ran.
+    pub fn lifecycle_tick_due(&self, iterations: u64, elapsed: std::time::Duration) -> bool {
+        (self.lifecycle_tick_iterations > 0 && iterations >= self.lifecycle_tick_iterations)
+            || (self.lifecycle_tick_secs > 0 && elapsed.as_secs() >= self.lifecycle_tick_secs)
+    }
+}
+
 impl Web {
     pub fn new(root_agent: AgentId, task: String, config: WebConfig) -> Self {
         Self {
@@ -44,9 +197,27 @@ impl Web {
             task,
             state: WebState::Running,
             config,
+            priority: 0,
+            metadata: HashMap::new(),
+            report: None,
         }
     }
 
+    pub fn with_priority(mut self, priority: i32) -> Self {
+        self.priority = priority;
+        self
+    }
+
+    pub fn with_metadata(mut self, metadata: HashMap<String, String>) -> Self {
+        self.metadata = metadata;
+        self
+    }
+
+    /// Who the web runs for, from the `owner` metadata key.
+    pub fn owner(&self) -> Option<&str> {
+        self.metadata.get("owner").map(String::as_str)
+    }
+
     pub fn is_converged(&self) -> bool {
         self.state == WebState::Converged
     }
@@ -4,9 +4,17 @@ use serde::{Deserialize, Serialize};
 use std::sync::Arc;
 use uuid::Uuid;
 
+use crate::engine::events::{EngineEvent, EngineObserver};
+use crate::lifecycle::state_machine::AgentStateMachine;
+use crate::lifecycle::HealthChangeReason;
 use crate::providers::llm::{LLMProvider, Message};
+use crate::providers::usage::{MeteredLLMProvider, UsageMeter};
+use crate::storage::traits::Storage;
 use crate::types::{Agent, AgentId};
 
+/// Validation reasons are trimmed to this length when published as events.
+const MAX_EVENT_REASON_CHARS: usize = 200;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ValidationRequest {
     pub id: Uuid,
@@ -43,6 +51,7 @@ pub struct ValidationService {
     llm_provider: Arc<dyn LLMProvider>,
     #[allow(dead_code)]
     config: ValidationConfig,
+    observer: Option<Arc<dyn EngineObserver>>,
 }
 
 #[derive(Debug, Clone)]
@@ -67,9 +76,22 @@ impl ValidationService {
         Self {
             llm_provider,
             config,
+            observer: None,
         }
     }
 
+    pub fn with_observer(mut self, observer: Arc<dyn EngineObserver>) -> Self {
+        self.observer = Some(observer);
+        self
+    }
+
+    /// Record validation calls in `meter`, charged to the web whose
+    /// [`UsageMeter::scope`] the validation runs in.
+    pub fn with_usage_meter(mut self, meter: UsageMeter) -> Self {
+        self.llm_provider = Arc::new(MeteredLLMProvider::new(self.llm_provider, meter));
+        self
+    }
+
     pub fn should_validate(&self, agent: &Agent, priority: f32) -> bool {
         if priority > 0.8 {
             return true;
@@ -111,11 +133,15 @@ impl ValidationService {
         })
     }
 
+    /// Adjust the agent's health for the judgment and move it into (or out
+    /// of) quarantine or isolation as its new health calls for.
     pub fn apply_validation_result(
         &self,
         result: &ValidationResult,
         agent: &mut Agent,
     ) -> Result<()> {
+        let (old_state, old_health) = (agent.state, agent.health);
+
         match &result.judgment {
             ValidationJudgment::Confirm { confidence } => {
                 let boost = 0.05 * confidence;
@@ -137,9 +163,69 @@ impl ValidationService {
             agent.probation_remaining -= 1;
         }
 
+        AgentStateMachine::check_health_thresholds(agent)?;
+
+        if let Some(observer) = &self.observer {
+            let (judgment, confidence, reason) = match &result.judgment {
+                ValidationJudgment::Confirm { confidence } => ("confirm", Some(*confidence), ""),
+                ValidationJudgment::Challenge { reason, confidence } => {
+                    ("challenge", Some(*confidence), reason.as_str())
+                }
+                ValidationJudgment::Uncertain { reason } => ("uncertain", None, reason.as_str()),
+            };
+            observer.on_event(&EngineEvent::Validation {
+                web_id: agent.web_id,
+                agent_id: agent.id,
+                judgment: judgment.to_string(),
+                confidence,
+                reason: reason.chars().take(MAX_EVENT_REASON_CHARS).collect(),
+            });
+
+            let reason = match result.judgment {
+                ValidationJudgment::Confirm { .. } => Some(HealthChangeReason::ValidationConfirm),
+                ValidationJudgment::Challenge { .. } => {
+                    Some(HealthChangeReason::ValidationChallenge)
+                }
+                ValidationJudgment::Uncertain { .. } => None,
+            };
+            if let Some(reason) = reason.filter(|_| agent.health != old_health) {
+                observer.on_event(&EngineEvent::AgentHealthChanged {
+                    web_id: agent.web_id,
+                    agent_id: agent.id,
+                    old_health,
+                    new_health: agent.health,
+                    reason,
+                });
+            }
+
+            if agent.state != old_state {
+                observer.on_event(&EngineEvent::AgentStateChanged {
+                    web_id: agent.web_id,
+                    agent_id: agent.id,
+                    from: old_state,
+                    to: agent.state,
+                });
+            }
+        }
+
         Ok(())
     }
 
+    /// Apply `result` to the stored agent it judges and save it.
+    pub async fn apply_and_store(
+        &self,
+        result: &ValidationResult,
+        storage: &dyn Storage,
+    ) -> Result<Agent> {
+        let mut agent = storage
+            .get_agent(result.agent_id)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("Agent {} not found", result.agent_id))?;
+        self.apply_validation_result(result, &mut agent)?;
+        storage.update_agent(&agent).await?;
+        Ok(agent)
+    }
+
     fn build_validation_prompt(&self, request: &ValidationRequest) -> String {
         let trigger = request
             .context
@@ -293,4 +379,51 @@ mod tests {
             .unwrap();
         assert!(agent.health < original_health);
     }
+
+    #[tokio::test]
+    async fn test_challenges_quarantine_then_isolate() {
+        use crate::storage::memory::InMemoryStore;
+        use crate::types::AgentState;
+
+        let storage = InMemoryStore::new();
+        let mut agent = create_test_agent();
+        agent.probation_remaining = 0;
+        Storage::create_agent(&storage, &agent).await.unwrap();
+        let service = ValidationService::new(
+            Arc::new(crate::providers::llm::MockLLMProvider::new()),
+            ValidationConfig::default(),
+        );
+        let challenge = ValidationResult {
+            request_id: Uuid::new_v4(),
+            agent_id: agent.id,
+            judgment: ValidationJudgment::Challenge {
+                reason: "wrong".to_string(),
+                confidence: 0.9,
+            },
+            raw_response: String::new(),
+            validated_at: Utc::now(),
+        };
+
+        let mut states = Vec::new();
+        for _ in 0..5 {
+            service.apply_and_store(&challenge, &storage).await.unwrap();
+            let stored = Storage::get_agent(&storage, agent.id)
+                .await
+                .unwrap()
+                .unwrap();
+            states.push(stored.state);
+        }
+
+        // 1.0 -> 0.865 -> 0.73 -> 0.595 -> 0.46 -> 0.325
+        assert_eq!(
+            states,
+            vec![
+                AgentState::Listening,
+                AgentState::Listening,
+                AgentState::Quarantine,
+                AgentState::Quarantine,
+                AgentState::Isolated,
+            ]
+        );
+    }
 }
@@ -0,0 +1,204 @@
+//! A need that a dormant agent elsewhere in the web can handle wakes that
+//! agent instead of spawning a new one.
+
+use anyhow::Result;
+use async_trait::async_trait;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use arachnid::capabilities::{Capability, Providers};
+use arachnid::engine::coordination::{CoordinationEngine, ExecutionResult, Need};
+use arachnid::providers::EmbeddingProvider;
+use arachnid::storage::memory::{InMemoryStore, WebStore};
+use arachnid::types::{
+    Agent, AgentContext, AgentState, CapabilityType, ExecutionStatus, Signal, SignalDirection,
+    SignalDraft, Web, WebConfig, WebState,
+};
+
+const PLANNING: [f32; 2] = [1.0, 0.0];
+const LOOKUP: [f32; 2] = [0.0, 1.0];
+/// Resonates with neither of the above, so signals at these frequencies
+/// only wake who they are meant to.
+const ROOT: [f32; 2] = [-1.0, 0.0];
+const FINDING: [f32; 2] = [0.0, -1.0];
+
+/// Puts "look it up" on its own axis, away from everything else.
+struct TwoAxisEmbedding;
+
+#[async_trait]
+impl EmbeddingProvider for TwoAxisEmbedding {
+    async fn embed(&self, text: &str) -> Result<Vec<f32>> {
+        Ok(if text == "look it up" {
+            LOOKUP
+        } else {
+            PLANNING
+        }
+        .to_vec())
+    }
+
+    async fn embed_batch(&self, texts: &[String]) -> Result<Vec<Vec<f32>>> {
+        let mut embeddings = Vec::new();
+        for text in texts {
+            embeddings.push(self.embed(text).await?);
+        }
+        Ok(embeddings)
+    }
+
+    fn dimension(&self) -> usize {
+        2
+    }
+}
+
+/// Asks for a lookup.
+struct NeedyCapability;
+
+#[async_trait]
+impl Capability for NeedyCapability {
+    fn name(&self) -> &str {
+        "needy"
+    }
+
+    fn description(&self) -> &str {
+        "Needs something looked up"
+    }
+
+    async fn execute(
+        &self,
+        _context: &AgentContext,
+        _trigger: Option<&Signal>,
+        _providers: &Providers,
+    ) -> Result<ExecutionResult> {
+        Ok(ExecutionResult {
+            status: ExecutionStatus::Complete,
+            output: serde_json::json!({}),
+            signals_to_emit: vec![],
+            needs: vec![Need {
+                description: "look it up".to_string(),
+                suggested_capability: Some(CapabilityType::Search),
+            }],
+            context_updates: vec![],
+            capability_state: None,
+        })
+    }
+}
+
+/// Reports what it was asked for back up to its parent, at a frequency
+/// that wakes no one.
+struct LookupCapability;
+
+#[async_trait]
+impl Capability for LookupCapability {
+    fn name(&self) -> &str {
+        "lookup"
+    }
+
+    fn description(&self) -> &str {
+        "Looks things up"
+    }
+
+    async fn execute(
+        &self,
+        _context: &AgentContext,
+        trigger: Option<&Signal>,
+        _providers: &Providers,
+    ) -> Result<ExecutionResult> {
+        let trigger = trigger.expect("woken by a signal");
+        Ok(ExecutionResult {
+            status: ExecutionStatus::Complete,
+            output: serde_json::json!({}),
+            signals_to_emit: vec![SignalDraft {
+                frequency: FINDING.to_vec(),
+                content: format!("found: {}", trigger.content),
+                direction: SignalDirection::Upward,
+                amplitude: 1.0,
+                payload: None,
+            }],
+            needs: vec![],
+            context_updates: vec![],
+            capability_state: None,
+        })
+    }
+}
+
+#[tokio::test]
+async fn test_dormant_agent_is_reused_for_a_matching_need() {
+    let store = Arc::new(InMemoryStore::new());
+    let root = Agent::new(
+        uuid::Uuid::new_v4(),
+        None,
+        "root".to_string(),
+        ROOT.to_vec(),
+        CapabilityType::Planner,
+        0.5,
+    );
+    // The agent with the need, and a dormant sibling that did lookups before.
+    let planner = Agent::new(
+        root.web_id,
+        Some(root.id),
+        "plan".to_string(),
+        PLANNING.to_vec(),
+        CapabilityType::Analyst,
+        0.5,
+    );
+    let mut searcher = Agent::new(
+        root.web_id,
+        Some(root.id),
+        "lookups".to_string(),
+        LOOKUP.to_vec(),
+        CapabilityType::Search,
+        0.5,
+    );
+    searcher.state = AgentState::Dormant;
+    searcher.dormant_since = Some(chrono::Utc::now());
+
+    let mut web = Web::new(
+        root.id,
+        "Reuse".to_string(),
+        WebConfig {
+            embedding_dim: 2,
+            ..WebConfig::default()
+        },
+    );
+    web.id = root.web_id;
+    store.create_web(web.clone()).unwrap();
+    for agent in [&root, &planner, &searcher] {
+        store.add_agent(agent.clone()).unwrap();
+    }
+    // Wakes the planner only.
+    store
+        .add_signal(Signal::new(
+            root.id,
+            PLANNING.to_vec(),
+            "plan it".to_string(),
+            SignalDirection::Downward,
+        ))
+        .unwrap();
+
+    let mut capabilities: HashMap<CapabilityType, Box<dyn Capability>> = HashMap::new();
+    capabilities.insert(CapabilityType::Analyst, Box::new(NeedyCapability));
+    capabilities.insert(CapabilityType::Search, Box::new(LookupCapability));
+    let providers = Providers {
+        embedding: Some(Box::new(TwoAxisEmbedding)),
+        llm: None,
+        search: None,
+    };
+    let engine = CoordinationEngine::new(store.clone(), capabilities, providers);
+
+    engine.run_coordination_loop(&web.id).await.unwrap();
+
+    let agents = store.get_agents_by_web(&web.id).unwrap();
+    assert_eq!(agents.len(), 3, "no agent was spawned for the need");
+    let searcher = store.get_agent(&searcher.id).unwrap().unwrap();
+    assert!(searcher.last_active_at > searcher.created_at);
+
+    let root = store.get_agent(&root.id).unwrap().unwrap();
+    let findings: Vec<&str> = root
+        .context
+        .accumulated_knowledge
+        .iter()
+        .map(|item| item.content.as_str())
+        .collect();
+    assert_eq!(findings, vec!["found: look it up"]);
+    let web = store.get_web(&web.id).unwrap().unwrap();
+    assert_eq!(web.state, WebState::Converged);
+}
@@ -77,12 +77,14 @@ fn create_test_definition(name: &str, keywords: Vec<&str>) -> AgentDefinition {
         tuning_embedding: vec![],
         system_prompt: format!("You are a {} agent.", name),
         temperature: 0.4,
+        max_tokens: None,
         tools: vec![ToolType::EmitSignal, ToolType::WebSearch],
         source: DefinitionSource::UserCustom,
         health_score: 1.0,
         use_count: 0,
         created_at: chrono::Utc::now(),
         version: Some("1.0.0".to_string()),
+        deleted_at: None,
     }
 }
 
@@ -341,6 +343,10 @@ fn test_tool_type_conversions() {
     assert_eq!(ToolType::SpawnAgent.as_str(), "spawn_agent");
     assert_eq!(ToolType::SearchCodebase.as_str(), "search_codebase");
     assert_eq!(ToolType::QueryDatabase.as_str(), "query_database");
+    assert_eq!(ToolType::ListDirectory.as_str(), "list_directory");
+    assert_eq!(ToolType::DeleteFile.as_str(), "delete_file");
+    assert_eq!(ToolType::HttpRequest.as_str(), "http_request");
+    assert_eq!(ToolType::RunTests.as_str(), "run_tests");
 
     // Test parse
     assert_eq!(ToolType::parse("web_search"), Some(ToolType::WebSearch));
@@ -349,7 +355,7 @@ fn test_tool_type_conversions() {
 
     // Test all()
     let all_tools = ToolType::all();
-    assert_eq!(all_tools.len(), 9);
+    assert_eq!(all_tools.len(), 13);
 }
 
 // ============================================================================
@@ -0,0 +1,448 @@
+//! Round-trips through `PostgresStorage`. Needs `--features postgres-tests`
+//! and DATABASE_URL pointing at a database `arachnid migrate` has set up.
+#![cfg(feature = "postgres-tests")]
+
+use anyhow::Result;
+use async_trait::async_trait;
+use serde_json::json;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use arachnid::capabilities::{Capability, Providers};
+use arachnid::definitions::ToolType;
+use arachnid::engine::coordination::{CoordinationEngine, ExecutionResult};
+use arachnid::engine::scheduler::{StorageRunner, WebRunner};
+use arachnid::engine::CheckpointConfig;
+use arachnid::storage::memory::InMemoryStore;
+use arachnid::storage::postgres::PostgresStorage;
+use arachnid::storage::traits::{JobStatus, Storage, ToolExecution, MAX_STORED_OUTPUT_BYTES};
+use arachnid::storage::WebRunQueue;
+use arachnid::tools::query_database::{QueryDatabaseConfig, QueryDatabaseTool};
+use arachnid::tools::{Tool, ToolContext};
+use arachnid::types::{
+    Agent, AgentContext, AgentState, CapabilityType, ExecutionStatus, FinalReport, Signal,
+    SignalDirection, SignalDraft, Web, WebConfig, WebState,
+};
+
+fn database_url() -> String {
+    std::env::var("DATABASE_URL").expect("DATABASE_URL must be set")
+}
+
+async fn storage() -> PostgresStorage {
+    PostgresStorage::new(&database_url())
+        .await
+        .expect("Failed to connect to PostgreSQL")
+}
+
+#[tokio::test]
+async fn test_tool_execution_round_trip() {
+    let storage = storage().await;
+
+    let mut web = Web::new(
+        uuid::Uuid::new_v4(),
+        "tool execution round trip".to_string(),
+        WebConfig::default(),
+    );
+    let agent = Agent::new(
+        web.id,
+        None,
+        "reader".to_string(),
+        vec![0.1; 1536],
+        CapabilityType::Search,
+        0.6,
+    );
+    web.root_agent = agent.id;
+    storage.create_web(&web).await.unwrap();
+    storage.create_agent(&agent).await.unwrap();
+
+    let small = ToolExecution::new(
+        agent.id,
+        web.id,
+        ToolType::ReadFile,
+        json!({ "path": "notes.txt" }),
+        json!({ "content": "hello" }),
+        true,
+    );
+    let big = ToolExecution {
+        id: uuid::Uuid::new_v4(),
+        tool_type: ToolType::FetchUrl,
+        params: json!({ "url": "https://example.com" }),
+        output: json!({ "body": "x".repeat(MAX_STORED_OUTPUT_BYTES * 2) }),
+        success: false,
+        created_at: small.created_at + chrono::Duration::seconds(1),
+        ..small.clone()
+    };
+    storage.record_tool_execution(&small).await.unwrap();
+    storage.record_tool_execution(&big).await.unwrap();
+
+    let stored = storage.get_tool_executions(agent.id).await.unwrap();
+    assert_eq!(stored.len(), 2);
+
+    assert_eq!(stored[0].id, small.id);
+    assert_eq!(stored[0].web_id, web.id);
+    assert_eq!(stored[0].tool_type, ToolType::ReadFile);
+    assert_eq!(stored[0].params, small.params);
+    assert_eq!(stored[0].output, small.output);
+    assert!(stored[0].success);
+
+    assert_eq!(stored[1].id, big.id);
+    assert_eq!(stored[1].tool_type, ToolType::FetchUrl);
+    assert!(!stored[1].success);
+    assert_eq!(stored[1].output["truncated"], true);
+}
+
+#[tokio::test]
+async fn test_web_report_round_trip() {
+    let storage = storage().await;
+
+    let mut web = Web::new(
+        uuid::Uuid::new_v4(),
+        "report round trip".to_string(),
+        WebConfig::default(),
+    );
+    storage.create_web(&web).await.unwrap();
+    let stored = storage.get_web(web.id).await.unwrap().unwrap();
+    assert_eq!(stored.report, None);
+
+    web.state = WebState::Converged;
+    web.report = Some(FinalReport {
+        summary: "Found it".to_string(),
+        key_points: vec!["one".to_string(), "two".to_string()],
+        confidence: 0.5,
+    });
+    storage.update_web(&web).await.unwrap();
+
+    let stored = storage.get_web(web.id).await.unwrap().unwrap();
+    assert_eq!(stored.report, web.report);
+}
+
+#[tokio::test]
+async fn test_definition_similarity_search_uses_vector_index() {
+    let pool = sqlx::PgPool::connect(&database_url()).await.unwrap();
+    let mut conn = pool.acquire().await.unwrap();
+    // The table may be tiny, so make a sequential scan unattractive rather
+    // than impossible; the planner still picks it if the index can't serve
+    // the query at all.
+    sqlx::query("SET enable_seqscan = off")
+        .execute(&mut *conn)
+        .await
+        .unwrap();
+
+    let query = format!("[{}]", vec!["0.1"; 1536].join(","));
+    // Same shape as PostgresStorage::find_definitions_by_similarity.
+    let plan: Vec<String> = sqlx::query_scalar(
+        r#"
+        EXPLAIN
+        SELECT * FROM (
+            SELECT id, 1 - (tuning_embedding <=> $1::vector) as similarity
+            FROM agent_definitions
+            WHERE tuning_embedding IS NOT NULL
+              AND source = ANY($2)
+            ORDER BY tuning_embedding <=> $1::vector
+            LIMIT $3
+        ) nearest
+        WHERE similarity > $4
+        ORDER BY similarity DESC
+        "#,
+    )
+    .bind(&query)
+    .bind(vec!["user_custom".to_string()])
+    .bind(5_i64)
+    .bind(0.75_f32)
+    .fetch_all(&mut *conn)
+    .await
+    .unwrap();
+
+    let plan = plan.join("\n");
+    assert!(plan.contains("idx_definitions_tuning"), "{}", plan);
+}
+
+#[tokio::test]
+async fn test_query_database_tool() {
+    let config = QueryDatabaseConfig {
+        database_url: Some(database_url()),
+        max_rows: 2,
+        timeout_secs: 1,
+    };
+    let tool = QueryDatabaseTool::new(&database_url(), config).unwrap();
+    let context = ToolContext {
+        agent_id: uuid::Uuid::new_v4(),
+        web_id: uuid::Uuid::new_v4(),
+        sandbox_path: std::env::temp_dir(),
+    };
+
+    let result = tool
+        .execute(
+            json!({ "sql": "SELECT n, n * 2 AS doubled FROM generate_series(1, 5) n ORDER BY n;" }),
+            &context,
+        )
+        .await
+        .unwrap();
+    assert_eq!(result.output["row_count"], 2);
+    assert_eq!(result.output["truncated"], true);
+    assert_eq!(result.output["rows"][0], json!({ "n": 1, "doubled": 2 }));
+
+    let timed_out = tool
+        .execute(json!({ "sql": "SELECT pg_sleep(3)" }), &context)
+        .await;
+    assert!(timed_out.is_err());
+
+    let write = tool
+        .execute(json!({ "sql": "DELETE FROM webs" }), &context)
+        .await;
+    assert!(write.unwrap_err().to_string().contains("Only SELECT"));
+}
+
+/// A database the runner set up before migrations were tracked: V001 ran
+/// without a tracking row. Migrating adopts it instead of running V001 again.
+#[tokio::test]
+async fn test_untracked_schema_is_adopted() {
+    use sqlx::postgres::PgPoolOptions;
+    use sqlx::Executor;
+
+    let schema = format!("upgrade_{}", uuid::Uuid::new_v4().simple());
+    let admin = PgPoolOptions::new()
+        .max_connections(1)
+        .connect(&database_url())
+        .await
+        .unwrap();
+    admin
+        .execute(format!("CREATE SCHEMA {}", schema).as_str())
+        .await
+        .unwrap();
+
+    let search_path = format!("SET search_path TO {}, public", schema);
+    let pool = PgPoolOptions::new()
+        .max_connections(2)
+        .after_connect(move |conn, _meta| {
+            let search_path = search_path.clone();
+            Box::pin(async move {
+                conn.execute(search_path.as_str()).await?;
+                Ok(())
+            })
+        })
+        .connect(&database_url())
+        .await
+        .unwrap();
+    sqlx::raw_sql(include_str!("../migrations/V001__initial_schema.sql"))
+        .execute(&pool)
+        .await
+        .unwrap();
+
+    let storage = PostgresStorage::from_pool(pool);
+    let applied = storage.run_migrations().await;
+    let status = storage.migration_status().await;
+    admin
+        .execute(format!("DROP SCHEMA {} CASCADE", schema).as_str())
+        .await
+        .unwrap();
+
+    let applied = applied.unwrap();
+    assert!(applied.iter().all(|m| m.name != "V001__initial_schema"));
+    assert!(status.unwrap().iter().all(|s| s.applied_at.is_some()));
+}
+
+/// Two serve workers pulling from the same database never hold the same job.
+#[tokio::test]
+async fn test_workers_do_not_claim_the_same_job() {
+    let storage: Arc<dyn Storage> = Arc::new(storage().await);
+    let first = WebRunQueue::new(storage.clone(), "worker-1");
+    let second = WebRunQueue::new(storage.clone(), "worker-2");
+
+    // Outranks whatever other tests left queued.
+    let web = Web::new(
+        uuid::Uuid::new_v4(),
+        "queue claim race".to_string(),
+        WebConfig::default(),
+    )
+    .with_priority(i32::MAX);
+    storage.create_web(&web).await.unwrap();
+    let job = first.enqueue_web_run(&web).await.unwrap();
+
+    let (a, b) = tokio::join!(first.claim_next(), second.claim_next());
+    let claimed: Vec<_> = [a.unwrap(), b.unwrap()].into_iter().flatten().collect();
+    assert_eq!(claimed.iter().filter(|j| j.id == job.id).count(), 1);
+    if let [a, b] = claimed.as_slice() {
+        assert_ne!(a.id, b.id);
+    }
+
+    for (queue, claim) in [(&first, "worker-1"), (&second, "worker-2")] {
+        for job in claimed
+            .iter()
+            .filter(|j| j.claimed_by.as_deref() == Some(claim))
+        {
+            assert!(queue.complete(job).await.unwrap());
+        }
+    }
+    let job = storage.get_job(job.id).await.unwrap().unwrap();
+    assert_eq!(job.status, JobStatus::Completed);
+}
+
+/// The vector columns are 1536 wide, so other dimensions are refused up front.
+#[tokio::test]
+async fn test_other_embedding_dims_are_rejected() {
+    let storage = storage().await;
+
+    let config = WebConfig {
+        embedding_dim: 768,
+        ..WebConfig::default()
+    };
+    let web = Web::new(uuid::Uuid::new_v4(), "small vectors".to_string(), config);
+    let error = storage.create_web(&web).await.unwrap_err();
+    assert!(error.to_string().contains("768"));
+    assert!(storage.get_web(web.id).await.unwrap().is_none());
+
+    let web = Web::new(
+        uuid::Uuid::new_v4(),
+        "default vectors".to_string(),
+        WebConfig::default(),
+    );
+    storage.create_web(&web).await.unwrap();
+    let agent = Agent::new(
+        web.id,
+        None,
+        "short tuning".to_string(),
+        vec![0.1; 768],
+        CapabilityType::Search,
+        0.6,
+    );
+    assert!(storage.create_agent(&agent).await.is_err());
+}
+
+/// Updating the config never writes back a stale state.
+#[tokio::test]
+async fn test_update_web_config_leaves_state_alone() {
+    let storage = storage().await;
+
+    let mut web = Web::new(
+        uuid::Uuid::new_v4(),
+        "config update race".to_string(),
+        WebConfig::default(),
+    );
+    storage.create_web(&web).await.unwrap();
+    let config = WebConfig {
+        max_agents: 7,
+        ..WebConfig::default()
+    };
+    assert!(storage.update_web_config(web.id, &config).await.unwrap());
+
+    // The run converges after a config update read the web.
+    web.state = WebState::Converged;
+    web.config = config.clone();
+    storage.update_web(&web).await.unwrap();
+    let late = WebConfig {
+        max_agents: 3,
+        ..config
+    };
+    assert!(!storage.update_web_config(web.id, &late).await.unwrap());
+
+    let stored = storage.get_web(web.id).await.unwrap().unwrap();
+    assert_eq!(stored.state, WebState::Converged);
+    assert_eq!(stored.config.max_agents, 7);
+}
+
+/// Takes a while, then re-broadcasts the trigger so the web keeps running.
+struct SlowCapability;
+
+#[async_trait]
+impl Capability for SlowCapability {
+    fn name(&self) -> &str {
+        "slow"
+    }
+
+    fn description(&self) -> &str {
+        "Takes its time"
+    }
+
+    async fn execute(
+        &self,
+        _context: &AgentContext,
+        trigger: Option<&Signal>,
+        _providers: &Providers,
+    ) -> Result<ExecutionResult> {
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        let trigger = trigger.expect("woken by a signal");
+        Ok(ExecutionResult {
+            status: ExecutionStatus::NeedsMore,
+            output: json!({}),
+            signals_to_emit: vec![SignalDraft {
+                frequency: trigger.frequency.clone(),
+                content: trigger.content.clone(),
+                direction: SignalDirection::Downward,
+                amplitude: 1.0,
+                payload: None,
+            }],
+            needs: vec![],
+            context_updates: vec![],
+            capability_state: None,
+        })
+    }
+}
+
+/// A web run by `StorageRunner` shows up in storage while it runs, and a
+/// pause made there sticks.
+#[tokio::test]
+async fn test_running_web_is_written_through() {
+    let storage: Arc<dyn Storage> = Arc::new(storage().await);
+    let mut capabilities: HashMap<CapabilityType, Box<dyn Capability>> = HashMap::new();
+    capabilities.insert(CapabilityType::Synthesizer, Box::new(SlowCapability));
+    let providers = Providers {
+        embedding: None,
+        llm: None,
+        search: None,
+    };
+    let engine = CoordinationEngine::new(Arc::new(InMemoryStore::new()), capabilities, providers)
+        .with_checkpoints(
+            storage.clone(),
+            CheckpointConfig {
+                every_iterations: Some(1),
+                every: None,
+            },
+        )
+        .with_write_through();
+    let runner = Arc::new(StorageRunner::new(Arc::new(engine), storage.clone()));
+
+    let web = Web::new(
+        uuid::Uuid::new_v4(),
+        "write-through".to_string(),
+        WebConfig::default(),
+    );
+    storage.create_web(&web).await.unwrap();
+    runner.seed(&web.id).await.unwrap();
+    let run = tokio::spawn({
+        let runner = runner.clone();
+        async move { runner.run(&web.id).await }
+    });
+
+    let pending = tokio::time::timeout(Duration::from_secs(10), async {
+        loop {
+            let pending = storage.get_pending_signals(web.id).await.unwrap();
+            if !pending.is_empty() {
+                return pending;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    })
+    .await
+    .expect("no signal reached storage");
+    assert!(!run.is_finished());
+    assert_eq!(pending[0].content, "write-through");
+    let agents = storage.get_web_agents(web.id).await.unwrap();
+    assert_eq!(agents.len(), 1);
+    assert_eq!(agents[0].id, web.root_agent);
+
+    let mut root = agents[0].clone();
+    root.state = AgentState::Paused;
+    storage.update_agent(&root).await.unwrap();
+    tokio::time::sleep(Duration::from_millis(200)).await;
+    let mut stopped = storage.get_web(web.id).await.unwrap().unwrap();
+    stopped.state = WebState::Failed;
+    storage.update_web(&stopped).await.unwrap();
+    run.await.unwrap().unwrap();
+
+    let root = storage.get_agent(web.root_agent).await.unwrap().unwrap();
+    assert_eq!(root.state, AgentState::Paused);
+    let web = storage.get_web(web.id).await.unwrap().unwrap();
+    assert_eq!(web.state, WebState::Failed);
+}
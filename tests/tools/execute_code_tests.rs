@@ -11,6 +11,8 @@ fn test_execute_code_tool_metadata() {
         user: "test".to_string(),
         key_path: None,
         timeout_secs: 60,
+        control_persist_secs: None,
+        max_retries: 0,
     };
 
     let client = ImpresarioClient::new(config);
@@ -29,6 +31,8 @@ fn test_execute_code_schema_validates_languages() {
         user: "test".to_string(),
         key_path: None,
         timeout_secs: 60,
+        control_persist_secs: None,
+        max_retries: 0,
     };
 
     let client = ImpresarioClient::new(config);
@@ -58,6 +62,8 @@ fn test_execute_code_has_rollback_option() {
         user: "test".to_string(),
         key_path: None,
         timeout_secs: 60,
+        control_persist_secs: None,
+        max_retries: 0,
     };
 
     let client = ImpresarioClient::new(config);
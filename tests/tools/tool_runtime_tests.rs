@@ -1,5 +1,6 @@
 use arachnid::definitions::ToolType;
-use arachnid::tools::runtime::{ToolConfig, ToolRuntime};
+use arachnid::tools::runtime::{ToolConfig, ToolRuntime, DEFAULT_TOOL_TIMEOUT_SECS};
+use std::collections::HashMap;
 use std::path::PathBuf;
 
 #[test]
@@ -9,6 +10,10 @@ fn test_tool_runtime_creation_without_providers() {
         search_provider: None,
         impresario_client: None,
         enable_remote_execution: false,
+        agent_factory: None,
+        embedding_provider: None,
+        tool_timeout_secs: DEFAULT_TOOL_TIMEOUT_SECS,
+        tool_timeouts: HashMap::new(),
     };
 
     let runtime = ToolRuntime::new(config).unwrap();
@@ -33,6 +38,10 @@ fn test_tool_schemas_have_required_fields() {
         search_provider: None,
         impresario_client: None,
         enable_remote_execution: false,
+        agent_factory: None,
+        embedding_provider: None,
+        tool_timeout_secs: DEFAULT_TOOL_TIMEOUT_SECS,
+        tool_timeouts: HashMap::new(),
     };
 
     let runtime = ToolRuntime::new(config).unwrap();
@@ -58,6 +67,10 @@ fn test_tool_schemas_filtered_by_allowed() {
         search_provider: None,
         impresario_client: None,
         enable_remote_execution: false,
+        agent_factory: None,
+        embedding_provider: None,
+        tool_timeout_secs: DEFAULT_TOOL_TIMEOUT_SECS,
+        tool_timeouts: HashMap::new(),
     };
 
     let runtime = ToolRuntime::new(config).unwrap();